@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Aggregate function for bucketed history queries
+ */
+export type Aggregation = "min" | "max" | "avg" | "first" | "last" | "count" | "sum" | "time_weighted_avg";
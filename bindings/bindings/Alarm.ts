@@ -6,4 +6,25 @@ import type { PointValue } from "./PointValue";
 /**
  * An alarm instance
  */
-export type Alarm = { id: string, source: string, message: string, severity: AlarmSeverity, state: AlarmState, triggered_at: string, acknowledged_at: string | null, acknowledged_by: string | null, cleared_at: string | null, value_at_trigger: PointValue | null, };
+export type Alarm = { id: string, 
+/**
+ * Alarm configuration that raised the alarm
+ */
+config_id: string | null, source: string, message: string, severity: AlarmSeverity, state: AlarmState, triggered_at: string, acknowledged_at: string | null, acknowledged_by: string | null, 
+/**
+ * Comment given when the alarm was acknowledged
+ */
+ack_comment: string | null, 
+/**
+ * When the condition returned to normal while the alarm stays active
+ * (a latching alarm awaiting acknowledgement)
+ */
+returned_to_normal_at: string | null, cleared_at: string | null, value_at_trigger: PointValue | null, 
+/**
+ * Alarm flood the alarm was raised in, if any
+ */
+flood_id: string | null, 
+/**
+ * Whether the alarm was the first of its flood (the likely root cause)
+ */
+first_out: boolean, };
@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * How often an alarm (configuration and source) was raised
+ */
+export type AlarmCount = { config_id: string | null, source: string, 
+/**
+ * Message of the latest occurrence
+ */
+message: string, count: number, };
@@ -0,0 +1,20 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { AlarmSeverity } from "./AlarmSeverity";
+
+/**
+ * Which active alarms a bulk operation applies to (every field that is set
+ * must match)
+ */
+export type AlarmFilter = { 
+/**
+ * Glob pattern of alarm sources (e.g., "net1/AHU-1/*")
+ */
+source_pattern: string | null, 
+/**
+ * Only alarms of this severity or higher
+ */
+min_severity: AlarmSeverity | null, 
+/**
+ * Only alarms raised by this alarm configuration
+ */
+config_id: string | null, };
@@ -0,0 +1,18 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * A burst of alarms (more than the flood threshold within its window)
+ */
+export type AlarmFlood = { id: string, 
+/**
+ * When the first alarm of the flood was raised
+ */
+started_at: string, 
+/**
+ * When the last alarm of the flood was raised
+ */
+last_alarm_at: string, alarm_count: number, 
+/**
+ * First alarm of the flood (the likely root cause)
+ */
+first_out: string, first_out_source: string, };
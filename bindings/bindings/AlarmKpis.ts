@@ -0,0 +1,33 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Alarm } from "./Alarm";
+import type { AlarmCount } from "./AlarmCount";
+import type { AlarmFlood } from "./AlarmFlood";
+
+/**
+ * Operator alarm KPIs over a time range, following ISA-18.2
+ */
+export type AlarmKpis = { start: string, end: string, 
+/**
+ * Alarms raised in the range
+ */
+total_alarms: number, alarms_per_hour: number, 
+/**
+ * Most frequent alarms ("bad actors"), most frequent first (up to 10)
+ */
+top_alarms: Array<AlarmCount>, 
+/**
+ * Alarms raised 3 or more times within 60 seconds
+ */
+chattering_alarms: Array<AlarmCount>, 
+/**
+ * Alarms active for more than 24 hours (as of now)
+ */
+standing_alarms: Array<Alarm>, 
+/**
+ * Mean seconds from raise to acknowledgement, over the alarms acknowledged
+ */
+mean_time_to_acknowledge_secs: number | null, 
+/**
+ * Alarm floods with alarms raised in the range
+ */
+floods: Array<AlarmFlood>, };
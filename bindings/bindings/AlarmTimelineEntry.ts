@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { AlarmTimelineKind } from "./AlarmTimelineKind";
+
+/**
+ * A step in an alarm's lifecycle, or an operator comment
+ */
+export type AlarmTimelineEntry = { at: string, kind: AlarmTimelineKind, 
+/**
+ * Operator who acknowledged or commented
+ */
+user: string | null, comment: string | null, };
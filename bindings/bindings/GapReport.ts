@@ -0,0 +1,28 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { HistoryGap } from "./HistoryGap";
+import type { QualityPeriod } from "./QualityPeriod";
+
+/**
+ * Gaps and data quality of one point's history over a time range
+ */
+export type GapReport = { point: string, start: string, end: string, 
+/**
+ * Longest expected time between samples
+ */
+expected_interval_secs: bigint, 
+/**
+ * Samples in the range
+ */
+samples: bigint, 
+/**
+ * Periods longer than the expected interval without a sample
+ */
+gaps: Array<HistoryGap>, 
+/**
+ * Periods of Bad or Stale samples
+ */
+bad_quality: Array<QualityPeriod>, 
+/**
+ * Share of the range backed by good samples (0-100)
+ */
+coverage_percent: number, };
@@ -0,0 +1,13 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Aggregated samples for one time bucket
+ *
+ * Only the requested aggregates are set; numeric samples with Bad quality
+ * are excluded.
+ */
+export type HistoryBucket = { start: string, end: string, 
+/**
+ * Number of samples in the bucket
+ */
+count: bigint, min: number | null, max: number | null, avg: number | null, first: number | null, last: number | null, sum: number | null, time_weighted_avg: number | null, units: string | null, };
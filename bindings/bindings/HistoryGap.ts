@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * A period without history samples
+ */
+export type HistoryGap = { 
+/**
+ * Last sample before the gap (or the range start)
+ */
+start: string, 
+/**
+ * First sample after the gap (or the range end)
+ */
+end: string, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * One row of aligned history (None where a point has no value)
+ */
+export type HistoryRow = { timestamp: string, values: Array<number | null>, };
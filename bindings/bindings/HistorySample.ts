@@ -5,4 +5,4 @@ import type { PointValue } from "./PointValue";
 /**
  * A single historical data sample
  */
-export type HistorySample = { timestamp: string, value: PointValue, quality: PointQuality, };
+export type HistorySample = { timestamp: string, value: PointValue, quality: PointQuality, units: string | null, };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { HistorySample } from "./HistorySample";
+
+/**
+ * An item of a history subscription: stored samples first, then live ones
+ */
+export type HistoryStreamItem = { "type": "Backfill", point: string, samples: Array<HistorySample>, } | { "type": "BackfillComplete", live_from: string, } | { "type": "Live", point: string, sample: HistorySample, };
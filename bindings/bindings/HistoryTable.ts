@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { HistoryRow } from "./HistoryRow";
+
+/**
+ * Several points aligned on a common time grid
+ */
+export type HistoryTable = { 
+/**
+ * Column order of each row's values
+ */
+points: Array<string>, rows: Array<HistoryRow>, };
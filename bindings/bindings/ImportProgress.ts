@@ -0,0 +1,35 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ImportState } from "./ImportState";
+
+/**
+ * Progress of a history import
+ */
+export type ImportProgress = { import_id: string, file_name: string, state: ImportState, started_at: string, finished_at: string | null, 
+/**
+ * File size, and how much of it has been read
+ */
+total_bytes: bigint, bytes_read: bigint, 
+/**
+ * Data rows read
+ */
+rows_read: bigint, 
+/**
+ * Samples stored
+ */
+imported: bigint, 
+/**
+ * Samples already stored for the same point and timestamp
+ */
+duplicates: bigint, 
+/**
+ * Rows that could not be parsed, or whose point is not mapped
+ */
+skipped: bigint, 
+/**
+ * The first row errors (e.g., "line 12: Invalid value 'n/a'")
+ */
+errors: Array<string>, 
+/**
+ * Why the import stopped, when it failed
+ */
+error: string | null, };
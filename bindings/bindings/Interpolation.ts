@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * How aligned history fills rows between samples
+ */
+export type Interpolation = "previous" | "linear" | "none";
@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * An active command in a priority array
+ */
+export type PointCommand = { priority: number, value: unknown, 
+/**
+ * Who issued the command (plugin, blueprint, user)
+ */
+source: string, written_at: string, 
+/**
+ * When the command releases itself (None = until relinquished)
+ */
+expires_at: string | null, };
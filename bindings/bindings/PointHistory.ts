@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { HistorySample } from "./HistorySample";
+
+/**
+ * Historical samples for one point
+ */
+export type PointHistory = { point: string, samples: Array<HistorySample>, };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { PointQuality } from "./PointQuality";
+
+/**
+ * Current value of a point
+ */
+export type PointSnapshot = { path: string, value: unknown, quality: PointQuality, timestamp: string, units: string | null, };
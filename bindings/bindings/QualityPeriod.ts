@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { PointQuality } from "./PointQuality";
+
+/**
+ * A run of history samples of one bad quality
+ */
+export type QualityPeriod = { start: string, 
+/**
+ * Next sample of a different quality (or the range end)
+ */
+end: string, quality: PointQuality, samples: bigint, };
@@ -1,8 +1,96 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Aggregation } from "./Aggregation";
+import type { AlarmFilter } from "./AlarmFilter";
+import type { ExportFormat } from "./ExportFormat";
+import type { ImportFormat } from "./ImportFormat";
+import type { Interpolation } from "./Interpolation";
 import type { JsonValue } from "../serde_json/JsonValue";
 import type { Schedule } from "./Schedule";
 
 /**
  * Request types that can be sent to any service
  */
-export type ServiceRequest = { "type": "GetStatus" } | { "type": "GetConfig" } | { "type": "SetConfig", config: JsonValue, } | { "type": "QueryHistory", point: string, start: string, end: string, limit: number | null, } | { "type": "GetActiveAlarms" } | { "type": "AcknowledgeAlarm", alarm_id: string, comment: string | null, } | { "type": "GetAlarmHistory", start: string, end: string, source_filter: string | null, } | { "type": "GetSchedules" } | { "type": "CreateSchedule", schedule: Schedule, } | { "type": "UpdateSchedule", id: string, schedule: Schedule, } | { "type": "DeleteSchedule", id: string, } | { "type": "Custom", action: string, payload: JsonValue, };
+export type ServiceRequest = { "type": "GetStatus" } | { "type": "GetConfig" } | { "type": "SetConfig", config: JsonValue, } | { "type": "ReadPoint", path: string, } | { "type": "WritePoint", path: string, value: unknown, 
+/**
+ * Identifies the writer (plugin, blueprint, user) for write permissions
+ */
+source: string | null, 
+/**
+ * Command priority 1-16 (default 16); a Null value releases this priority
+ */
+priority: number | null, 
+/**
+ * Release the command automatically after this many seconds
+ */
+duration_secs: number | null, } | { "type": "GetPointCommands", path: string, } | { "type": "ListPoints", pattern: string | null, } | { "type": "QueryTags", filter: string, } | { "type": "GetPointTags", path: string, } | { "type": "SetPointTags", path: string, tags: Record<string, unknown>, } | { "type": "QueryHistory", point: string, start: string, end: string, limit: number | null, 
+/**
+ * Convert samples to these units (e.g., "degrees-celsius")
+ */
+units: string | null, 
+/**
+ * Downsample to at most this many samples, preserving the trend shape (LTTB)
+ */
+downsample: number | null, } | { "type": "AggregateHistory", point: string, start: string, end: string, 
+/**
+ * Bucket width (e.g., "1m", "15m", "1h", "1d")
+ */
+interval: string, 
+/**
+ * Aggregates to compute (all when empty)
+ */
+functions: Array<Aggregation>, 
+/**
+ * Convert values to these units before aggregating
+ */
+units: string | null, } | { "type": "QueryHistoryByTags", filter: string, start: string, end: string, limit: number | null, 
+/**
+ * Convert samples to these units (e.g., "degrees-celsius")
+ */
+units: string | null, } | { "type": "QueryHistoryTable", points: Array<string>, start: string, end: string, 
+/**
+ * Row spacing (e.g., "15m"); a row at every sample timestamp when omitted
+ */
+interval: string | null, interpolation: Interpolation, 
+/**
+ * Convert values to these units (e.g., "degrees-celsius")
+ */
+units: string | null, limit: number | null, } | { "type": "ExportHistory", points: Array<string>, start: string, end: string, interval: string | null, interpolation: Interpolation, units: string | null, format: ExportFormat, 
+/**
+ * File name within the export directory (e.g., "ahu-1-march.csv")
+ */
+file_name: string, } | { "type": "ImportHistory", 
+/**
+ * File name within the import directory (e.g., "old-bas-trends.csv")
+ */
+file_name: string, format: ImportFormat, 
+/**
+ * Source point names to point paths (e.g., "AHU1.SAT" -> "ahu-1/sat")
+ */
+point_map: { [key in string]?: string }, 
+/**
+ * Skip rows for points not in the point map
+ */
+mapped_only: boolean, 
+/**
+ * IANA time zone of timestamps without an offset (e.g., "America/Chicago");
+ * UTC when omitted
+ */
+timezone: string | null, 
+/**
+ * strftime format of timestamps (e.g., "%d.%m.%Y %H:%M"); common
+ * layouts are recognized when omitted
+ */
+timestamp_format: string | null, } | { "type": "GetImportProgress", import_id: string, } | { "type": "GetHistoryGaps", points: Array<string>, start: string, end: string, 
+/**
+ * Longest expected time between samples (e.g., "15m"); taken from
+ * each point's logging policy when omitted
+ */
+expected_interval: string | null, } | { "type": "GetActiveAlarms" } | { "type": "AcknowledgeAlarm", alarm_id: string, acknowledged_by: string | null, comment: string | null, } | { "type": "AcknowledgeAlarms", filter: AlarmFilter, acknowledged_by: string | null, comment: string | null, } | { "type": "CommentAlarm", alarm_id: string, comment: string, author: string | null, } | { "type": "GetAlarmTimeline", alarm_id: string, } | { "type": "GetAlarmHistory", start: string, end: string, source_filter: string | null, 
+/**
+ * Alarms per page (newest first)
+ */
+limit: number | null, 
+/**
+ * Continue after this alarm (the `next_cursor` of the previous page)
+ */
+cursor: string | null, } | { "type": "GetAlarmKpis", start: string, end: string, } | { "type": "GetSchedules" } | { "type": "CreateSchedule", schedule: Schedule, } | { "type": "UpdateSchedule", id: string, schedule: Schedule, } | { "type": "DeleteSchedule", id: string, } | { "type": "Custom", action: string, payload: JsonValue, };
@@ -1,11 +1,29 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
 import type { Alarm } from "./Alarm";
+import type { AlarmKpis } from "./AlarmKpis";
+import type { AlarmTimelineEntry } from "./AlarmTimelineEntry";
+import type { GapReport } from "./GapReport";
+import type { HistoryBucket } from "./HistoryBucket";
 import type { HistorySample } from "./HistorySample";
+import type { HistoryTable } from "./HistoryTable";
+import type { ImportProgress } from "./ImportProgress";
 import type { JsonValue } from "../serde_json/JsonValue";
+import type { PointCommand } from "./PointCommand";
+import type { PointHistory } from "./PointHistory";
+import type { PointSnapshot } from "./PointSnapshot";
 import type { Schedule } from "./Schedule";
 import type { ServiceState } from "./ServiceState";
+import type { TaggedPoint } from "./TaggedPoint";
 
 /**
  * Response types from services
  */
-export type ServiceResponse = { "type": "Status", id: string, name: string, state: ServiceState, uptime_seconds: bigint, extra: JsonValue | null, } | { "type": "Config", config: JsonValue, } | { "type": "Ok" } | { "type": "HistoryData", samples: Array<HistorySample>, } | { "type": "ActiveAlarms", alarms: Array<Alarm>, } | { "type": "AlarmHistory", alarms: Array<Alarm>, } | { "type": "AlarmAcknowledged", alarm_id: string, } | { "type": "Schedules", schedules: Array<Schedule>, } | { "type": "ScheduleCreated", id: string, } | { "type": "ScheduleUpdated", id: string, } | { "type": "ScheduleDeleted", id: string, } | { "type": "Custom", payload: JsonValue, } | { "type": "Error", code: string, message: string, };
+export type ServiceResponse = { "type": "Status", id: string, name: string, state: ServiceState, uptime_seconds: bigint, extra: JsonValue | null, } | { "type": "Config", config: JsonValue, } | { "type": "Ok" } | { "type": "PointValue", point: PointSnapshot, } | { "type": "PointWritten", path: string, } | { "type": "PointCommands", path: string, commands: Array<PointCommand>, } | { "type": "Points", points: Array<PointSnapshot>, } | { "type": "PointTags", point: TaggedPoint, } | { "type": "TaggedPoints", points: Array<TaggedPoint>, } | { "type": "HistoryData", samples: Array<HistorySample>, } | { "type": "HistorySeries", series: Array<PointHistory>, } | { "type": "HistoryBuckets", buckets: Array<HistoryBucket>, } | { "type": "HistoryTable", table: HistoryTable, } | { "type": "HistoryExported", path: string, rows: bigint, } | { "type": "ImportProgress", progress: ImportProgress, } | { "type": "HistoryGaps", reports: Array<GapReport>, } | { "type": "ActiveAlarms", alarms: Array<Alarm>, } | { "type": "AlarmHistory", alarms: Array<Alarm>, 
+/**
+ * Cursor for the next page, when more alarms match
+ */
+next_cursor: string | null, } | { "type": "AlarmAcknowledged", alarm_id: string, } | { "type": "AlarmsAcknowledged", acknowledged: Array<string>, 
+/**
+ * Matching alarms left unacknowledged, as they require a comment
+ */
+skipped: Array<string>, } | { "type": "AlarmCommented", alarm_id: string, } | { "type": "AlarmTimeline", alarm_id: string, entries: Array<AlarmTimelineEntry>, } | { "type": "AlarmKpis", kpis: AlarmKpis, } | { "type": "Schedules", schedules: Array<Schedule>, } | { "type": "ScheduleCreated", id: string, } | { "type": "ScheduleUpdated", id: string, } | { "type": "ScheduleDeleted", id: string, } | { "type": "Custom", payload: JsonValue, } | { "type": "Error", code: string, message: string, };
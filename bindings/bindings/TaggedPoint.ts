@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * A point and its semantic tags
+ */
+export type TaggedPoint = { path: string, tags: Record<string, unknown>, };
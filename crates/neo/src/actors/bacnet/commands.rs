@@ -0,0 +1,105 @@
+// BACnet Command Store - Neo's commands on device points, kept in redb
+//
+// Every point Neo holds a priority on is stored under its path with its
+// commands and the BACnet priority written to the device, and removed once
+// Neo holds nothing on it. A device actor restores its points' commands when
+// it is created, so overrides written before a restart still show in
+// GetPointCommands, and still expire or release the priority they hold.
+
+use std::path::Path;
+use std::sync::Arc;
+
+use redb::{Database, ReadableTable, TableDefinition};
+use serde::{Deserialize, Serialize};
+
+use crate::points::{CommandPriority, PointCommand};
+use crate::types::{Error, ObjectIdentifier, Result};
+
+// Key is the point path, value is the JSON-serialized StoredCommands
+const COMMANDS_TABLE: TableDefinition<&str, &[u8]> = TableDefinition::new("bacnet_commands");
+
+/// A point's commands and the priority Neo holds on the device for it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StoredCommands {
+    pub object_id: ObjectIdentifier,
+    pub commands: Vec<PointCommand>,
+    pub written: CommandPriority,
+}
+
+/// Persistent command arbitration for the devices of a network
+#[derive(Clone)]
+pub struct CommandStore {
+    db: Arc<Database>,
+}
+
+impl CommandStore {
+    /// Open (or create) a store file
+    pub fn open(path: &Path) -> Result<Self> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(Error::Io)?;
+        }
+        let db = Database::create(path)
+            .map_err(|e| Error::Database(format!("Failed to open database: {}", e)))?;
+
+        let write_txn = db.begin_write().map_err(|e| Error::Database(e.to_string()))?;
+        write_txn
+            .open_table(COMMANDS_TABLE)
+            .map_err(|e| Error::Database(e.to_string()))?;
+        write_txn
+            .commit()
+            .map_err(|e| Error::Database(e.to_string()))?;
+
+        Ok(Self { db: Arc::new(db) })
+    }
+
+    /// Stored commands of the points under a device path ("{network}/{device}")
+    pub fn load(&self, device_path: &str) -> Result<Vec<StoredCommands>> {
+        let prefix = format!("{}/", device_path);
+        let read_txn = self.db.begin_read().map_err(|e| Error::Database(e.to_string()))?;
+        let table = read_txn
+            .open_table(COMMANDS_TABLE)
+            .map_err(|e| Error::Database(e.to_string()))?;
+
+        let mut stored = Vec::new();
+        for entry in table.iter().map_err(|e| Error::Database(e.to_string()))? {
+            let (key, value) = entry.map_err(|e| Error::Database(e.to_string()))?;
+            if !key.value().starts_with(&prefix) {
+                continue;
+            }
+            match serde_json::from_slice(value.value()) {
+                Ok(commands) => stored.push(commands),
+                Err(e) => {
+                    tracing::warn!("Skipping unreadable commands for {}: {}", key.value(), e)
+                }
+            }
+        }
+        Ok(stored)
+    }
+
+    /// Store a point's commands, or remove them when Neo holds nothing on it
+    pub fn save(&self, point_path: &str, commands: Option<&StoredCommands>) -> Result<()> {
+        let write_txn = self.db.begin_write().map_err(|e| Error::Database(e.to_string()))?;
+        {
+            let mut table = write_txn
+                .open_table(COMMANDS_TABLE)
+                .map_err(|e| Error::Database(e.to_string()))?;
+            match commands {
+                Some(commands) => {
+                    let bytes =
+                        serde_json::to_vec(commands).map_err(|e| Error::Service(e.to_string()))?;
+                    table
+                        .insert(point_path, bytes.as_slice())
+                        .map_err(|e| Error::Database(e.to_string()))?;
+                }
+                None => {
+                    table
+                        .remove(point_path)
+                        .map_err(|e| Error::Database(e.to_string()))?;
+                }
+            }
+        }
+        write_txn
+            .commit()
+            .map_err(|e| Error::Database(e.to_string()))
+    }
+}
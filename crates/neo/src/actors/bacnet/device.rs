@@ -1,9 +1,12 @@
 use crate::actors::PubSubBroker;
+use crate::actors::bacnet::commands::{CommandStore, StoredCommands};
 use crate::actors::bacnet::io::BACnetIOActor;
 use crate::messages::{BACnetIOMsg, BACnetIOReply, DeviceMsg, Event};
+use crate::points::{CommandPriority, EngineeringUnit, PointCommand, PointIdentity, PriorityArray, TagSet, TagStore};
 use crate::types::{BACnetPoint, DeviceStatus, ObjectIdentifier, ObjectType, PropertyIdentifier, PointQuality, PropertyValue};
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use dashmap::DashMap;
+use std::collections::HashMap;
 use kameo_actors::pubsub::Publish;
 use std::time::Instant;
 use tracing::{debug, error, info, warn};
@@ -18,6 +21,11 @@ pub struct BACnetDeviceActor {
     pub points: DashMap<ObjectIdentifier, BACnetPoint>, // Points as simple structs, not actors
     pubsub: Option<kameo::actor::ActorRef<PubSubBroker>>,
     io_actor: kameo::actor::ActorRef<BACnetIOActor>,  // Direct reference to I/O actor
+    tag_store: Option<TagStore>,
+
+    // Command arbitration for the device's commandable points
+    commands: CommandArbitration,
+    command_store: Option<CommandStore>,
 
     // Health tracking
     pub last_seen: Instant,
@@ -42,6 +50,9 @@ impl BACnetDeviceActor {
             points: DashMap::new(),
             pubsub: Some(pubsub),
             io_actor,
+            tag_store: None,
+            commands: CommandArbitration::default(),
+            command_store: None,
             last_seen: Instant::now(),
             last_seen_utc: Utc::now(),
             consecutive_failures: 0,
@@ -49,6 +60,53 @@ impl BACnetDeviceActor {
         }
     }
 
+    /// Resolve semantic tags for new points from the given tag store
+    pub fn with_tag_store(mut self, tag_store: Option<TagStore>) -> Self {
+        self.tag_store = tag_store;
+        self
+    }
+
+    /// Keep point commands in the given store, restoring those stored
+    pub fn with_command_store(mut self, command_store: Option<CommandStore>) -> Self {
+        if let Some(store) = &command_store {
+            let device_path = format!("{}/{}", self.network_name, self.device_name);
+            match store.load(&device_path) {
+                Ok(stored) => {
+                    if !stored.is_empty() {
+                        info!("Restored commands on {} points of {}", stored.len(), device_path);
+                    }
+                    self.commands = CommandArbitration::restore(stored);
+                }
+                Err(e) => warn!("Failed to restore commands for {}: {}", device_path, e),
+            }
+        }
+        self.command_store = command_store;
+        self
+    }
+
+    /// Full point path as published on the bus (network/device/object)
+    fn point_path(&self, object_id: ObjectIdentifier) -> String {
+        format!("{}/{}/{}", self.network_name, self.device_name, object_id)
+    }
+
+    /// Register a point with the tag store and return its tags
+    fn resolve_tags(
+        &self,
+        object_id: ObjectIdentifier,
+        object_name: Option<&str>,
+        description: Option<&str>,
+    ) -> TagSet {
+        let Some(store) = &self.tag_store else {
+            return TagSet::default();
+        };
+        let path = self.point_path(object_id);
+        store.register_point(PointIdentity {
+            path: &path,
+            object_name,
+            description,
+        })
+    }
+
     /// Get a point from cache
     pub fn get_point(&self, object_id: ObjectIdentifier) -> Option<BACnetPoint> {
         self.points.get(&object_id).map(|p| p.clone())
@@ -92,14 +150,20 @@ impl BACnetDeviceActor {
                 "📊 {}/{} {} initialized: {:?}",
                 self.device_name, object_id, object_id, value
             );
+            let (object_name, description) = self.read_point_metadata(object_id).await;
             let point = BACnetPoint {
                 object_id,
                 present_value: value.clone(),
                 quality,
                 last_update: Instant::now(),
                 last_update_utc: Utc::now(),
-                object_name: None,
-                description: None,
+                tags: self.resolve_tags(
+                    object_id,
+                    object_name.as_deref(),
+                    description.as_deref(),
+                ),
+                object_name,
+                description,
                 units: None,
                 cov_increment: None,
             };
@@ -112,9 +176,10 @@ impl BACnetDeviceActor {
     async fn publish_point_change(&self, object_id: ObjectIdentifier, point: &BACnetPoint) {
         if let Some(pubsub) = &self.pubsub {
             let event = Event::PointValueChanged {
-                point: format!("{}/{}/{}", self.network_name, self.device_name, object_id),
+                point: self.point_path(object_id),
                 value: point.present_value.clone(),
                 quality: point.quality,
+                units: point.units,
                 timestamp: point.last_update,
                 timestamp_utc: point.last_update_utc,
             };
@@ -183,6 +248,130 @@ impl BACnetDeviceActor {
         }
     }
 
+    /// Write a property to the real BACnet device via the I/O actor
+    async fn write_property_real(
+        &self,
+        object_id: ObjectIdentifier,
+        property_id: PropertyIdentifier,
+        value: PropertyValue,
+        priority: Option<u8>,
+    ) -> crate::types::Result<()> {
+        debug!(
+            "Writing {}/{} = {:?} (priority {:?}) to device {} via I/O actor",
+            self.device_name, object_id, value, priority, self.device_instance
+        );
+
+        match self.io_actor
+            .ask(BACnetIOMsg::WriteProperty {
+                device_id: self.device_instance,
+                object_id,
+                property_id,
+                value,
+                priority,
+            })
+            .await
+        {
+            Ok(BACnetIOReply::PropertyWritten) => Ok(()),
+            Ok(BACnetIOReply::IoError(e)) => {
+                Err(crate::types::Error::Protocol(e))
+            }
+            Ok(other) => Err(crate::types::Error::Protocol(format!(
+                "Unexpected reply from I/O actor: {:?}",
+                other
+            ))),
+            Err(e) => Err(crate::types::Error::Protocol(format!(
+                "Failed to send message to I/O actor: {}",
+                e
+            ))),
+        }
+    }
+
+    /// Command a point at a Neo priority (Null releases the priority)
+    ///
+    /// The winning command is written to the device at its own BACnet
+    /// priority, so the device's native arbitration agrees with Neo's.
+    async fn command_point(
+        &mut self,
+        object_id: ObjectIdentifier,
+        value: PropertyValue,
+        priority: CommandPriority,
+        source: &str,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> crate::types::Result<()> {
+        if !is_commandable_object_type(object_id.object_type) {
+            return Err(crate::types::Error::Protocol(format!(
+                "{} is not commandable",
+                object_id
+            )));
+        }
+
+        let mut commands = self.commands.get(object_id);
+        commands.write(priority, value, source, expires_at);
+        self.apply_commands(object_id, commands).await
+    }
+
+    /// Write a point's commands to the device, then record them
+    ///
+    /// Neo's record is left unchanged if a write fails, so it stays in line
+    /// with what the device holds (and expired commands are retried).
+    async fn apply_commands(
+        &mut self,
+        object_id: ObjectIdentifier,
+        commands: PriorityArray,
+    ) -> crate::types::Result<()> {
+        for write in self.commands.writes(object_id, &commands) {
+            if write.value == PropertyValue::Null {
+                info!(
+                    "✍️  {}/{} released (priority {})",
+                    self.device_name, object_id, write.priority
+                );
+            } else {
+                info!(
+                    "✍️  {}/{} commanded to {:?} at priority {}",
+                    self.device_name, object_id, write.value, write.priority
+                );
+            }
+            self.write_property_real(
+                object_id,
+                PropertyIdentifier::PresentValue,
+                write.value,
+                Some(write.priority.level()),
+            )
+            .await?;
+        }
+        self.commands.commit(object_id, commands);
+        if let Some(store) = &self.command_store {
+            let path = self.point_path(object_id);
+            if let Err(e) = store.save(&path, self.commands.stored(object_id).as_ref()) {
+                warn!("Failed to store commands for {}: {}", path, e);
+            }
+        }
+
+        // Read back so subscribers see the value the device settled on
+        if let Ok(value) = self.read_property_real(object_id, PropertyIdentifier::PresentValue).await {
+            self.update_point_value(object_id, value, PointQuality::Good).await;
+        }
+        Ok(())
+    }
+
+    /// Release expired commands and write the resulting values
+    ///
+    /// Runs on each poll, so a command can stay in control for up to one poll
+    /// interval past its expiry.
+    async fn expire_commands(&mut self, now: DateTime<Utc>) {
+        for (object_id, commands, expired) in self.commands.expire(now) {
+            for command in &expired {
+                info!(
+                    "Command on {}/{} at priority {} from '{}' expired",
+                    self.device_name, object_id, command.priority, command.source
+                );
+            }
+            if let Err(e) = self.apply_commands(object_id, commands).await {
+                warn!("Failed to apply commands for {}/{}: {}", self.device_name, object_id, e);
+            }
+        }
+    }
+
     /// Poll all points from this device
     async fn poll_points(&mut self) {
         info!(
@@ -342,19 +531,26 @@ impl BACnetDeviceActor {
 
         // Step 3: Create a point for each object and try to read its present value
         for object_id in &object_identifiers {
+            // Name and description first, as tag rules match on them
+            let (object_name, description) = self.read_point_metadata(*object_id).await;
+            let tags =
+                self.resolve_tags(*object_id, object_name.as_deref(), description.as_deref());
+
             // Try to read the present value
             match self.read_property_real(*object_id, PropertyIdentifier::PresentValue).await {
                 Ok(value) => {
+                    let units = self.read_units(*object_id).await;
                     let point = BACnetPoint {
                         object_id: *object_id,
                         present_value: value,
                         quality: PointQuality::Good,
                         last_update: Instant::now(),
                         last_update_utc: Utc::now(),
-                        object_name: None,
-                        description: None,
-                        units: None,
+                        object_name,
+                        description,
+                        units,
                         cov_increment: None,
+                        tags,
                     };
 
                     self.points.insert(*object_id, point);
@@ -370,10 +566,11 @@ impl BACnetDeviceActor {
                         quality: PointQuality::Uncertain,
                         last_update: Instant::now(),
                         last_update_utc: Utc::now(),
-                        object_name: None,
-                        description: None,
+                        object_name,
+                        description,
                         units: None,
                         cov_increment: None,
+                        tags,
                     };
 
                     self.points.insert(*object_id, point);
@@ -390,19 +587,65 @@ impl BACnetDeviceActor {
         Ok(discovered_count)
     }
 
-    /// Read metadata for a specific point (object-name, description, units)
-    #[allow(dead_code)]
+    /// Read the units property of an analog object
+    ///
+    /// Returns None for objects without units or with proprietary unit values.
+    async fn read_units(&self, object_id: ObjectIdentifier) -> Option<EngineeringUnit> {
+        if !matches!(
+            object_id.object_type,
+            ObjectType::AnalogInput | ObjectType::AnalogOutput | ObjectType::AnalogValue
+        ) {
+            return None;
+        }
+
+        match self.read_property_real(object_id, PropertyIdentifier::Units).await {
+            Ok(PropertyValue::Enumerated(id)) => EngineeringUnit::from_bacnet_id(id),
+            Ok(other) => {
+                debug!("Unexpected units value for {}: {:?}", object_id, other);
+                None
+            }
+            Err(e) => {
+                debug!("Could not read units for {}: {}", object_id, e);
+                None
+            }
+        }
+    }
+
+    /// Read the object-name (77) and description (28) of a point
+    ///
+    /// Either is None when the object does not have it or it cannot be read.
     async fn read_point_metadata(
         &self,
-        _object_id: ObjectIdentifier,
-    ) -> crate::types::Result<(Option<String>, Option<String>, Option<String>)> {
-        // Property IDs from BACnet standard:
-        // 77 = object-name
-        // 28 = description
-        // 117 = units (for analog objects)
+        object_id: ObjectIdentifier,
+    ) -> (Option<String>, Option<String>) {
+        let object_name = self
+            .read_text(object_id, PropertyIdentifier::ObjectName, "object-name")
+            .await;
+        let description = self
+            .read_text(object_id, PropertyIdentifier::Description, "description")
+            .await;
+        (object_name, description)
+    }
 
-        // TODO: Implement using I/O actor ReadProperty with specific property IDs
-        Ok((None, None, None))
+    /// Read a character-string property, treating an empty string as absent
+    async fn read_text(
+        &self,
+        object_id: ObjectIdentifier,
+        property_id: PropertyIdentifier,
+        property_name: &str,
+    ) -> Option<String> {
+        match self.read_property_real(object_id, property_id).await {
+            Ok(PropertyValue::CharacterString(text)) if !text.is_empty() => Some(text),
+            Ok(PropertyValue::CharacterString(_)) => None,
+            Ok(other) => {
+                debug!("Unexpected {} value for {}: {:?}", property_name, object_id, other);
+                None
+            }
+            Err(e) => {
+                debug!("Could not read {} for {}: {}", property_name, object_id, e);
+                None
+            }
+        }
     }
 
     /// Attempt to reconnect to the device
@@ -430,6 +673,7 @@ impl kameo::message::Message<DeviceMsg> for BACnetDeviceActor {
             },
 
             DeviceMsg::Poll => {
+                self.expire_commands(Utc::now()).await;
                 self.poll_points().await;
                 DeviceReply::Polled
             }
@@ -457,14 +701,51 @@ impl kameo::message::Message<DeviceMsg> for BACnetDeviceActor {
             }
 
             DeviceMsg::WriteProperty {
-                object_id: _,
-                property_id: _,
-                value: _,
+                object_id,
+                property_id,
+                value,
             } => {
-                // Write to real BACnet device
-                // TODO: Implement write_property_real() method
-                error!("Write property not yet implemented for real BACnet devices");
-                DeviceReply::Failure("Write property not yet implemented".to_string())
+                // Present-value writes are commands, arbitrated like any other
+                let result = if matches!(property_id, PropertyIdentifier::PresentValue) {
+                    self.command_point(
+                        object_id,
+                        value,
+                        CommandPriority::default(),
+                        "write-property",
+                        None,
+                    )
+                    .await
+                } else {
+                    self.write_property_real(object_id, property_id, value, None).await
+                };
+                match result {
+                    Ok(_) => DeviceReply::PropertyWritten,
+                    Err(e) => {
+                        error!("Failed to write {} to device: {}", object_id, e);
+                        DeviceReply::Failure(format!("Failed to write property: {}", e))
+                    }
+                }
+            }
+
+            DeviceMsg::CommandPoint {
+                object_id,
+                value,
+                priority,
+                source,
+                expires_at,
+            } => match self
+                .command_point(object_id, value, priority, &source, expires_at)
+                .await
+            {
+                Ok(_) => DeviceReply::PropertyWritten,
+                Err(e) => {
+                    error!("Failed to command {}: {}", object_id, e);
+                    DeviceReply::Failure(format!("Failed to command point: {}", e))
+                }
+            },
+
+            DeviceMsg::GetCommands { object_id } => {
+                DeviceReply::Commands(self.commands.get(object_id).commands())
             }
 
             DeviceMsg::DiscoverPoints => match self.discover_points().await {
@@ -513,9 +794,130 @@ pub enum DeviceReply {
     },
     Points(Vec<BACnetPoint>),
     Point(Option<BACnetPoint>),
+    Commands(Vec<PointCommand>),
     Failure(String),
 }
 
+/// Neo's command arbitration for a device's commandable points
+///
+/// Keeps a priority array per point and the BACnet priority Neo holds on the
+/// device for it: the active command is written at its own priority, and the
+/// priority Neo held before is released when the active priority changes.
+/// Expired commands are released when the device is next polled.
+#[derive(Debug, Default)]
+struct CommandArbitration {
+    commands: HashMap<ObjectIdentifier, PriorityArray>,
+    written: HashMap<ObjectIdentifier, CommandPriority>,
+}
+
+/// A present-value write at a BACnet priority (Null releases the priority)
+#[derive(Debug, Clone, PartialEq)]
+struct PriorityWrite {
+    value: PropertyValue,
+    priority: CommandPriority,
+}
+
+impl CommandArbitration {
+    /// Arbitration as stored, keeping expired commands so their release is
+    /// written to the device
+    fn restore(stored: Vec<StoredCommands>) -> Self {
+        let mut arbitration = Self::default();
+        for point in stored {
+            let mut commands = PriorityArray::new();
+            for command in point.commands {
+                commands.command(command);
+            }
+            arbitration.commands.insert(point.object_id, commands);
+            arbitration.written.insert(point.object_id, point.written);
+        }
+        arbitration
+    }
+
+    /// A point's commands to store (None when Neo holds nothing on it)
+    fn stored(&self, object_id: ObjectIdentifier) -> Option<StoredCommands> {
+        let written = self.written.get(&object_id).copied()?;
+        Some(StoredCommands {
+            object_id,
+            commands: self.get(object_id).commands(),
+            written,
+        })
+    }
+
+    /// The commands of a point (empty if it has none)
+    fn get(&self, object_id: ObjectIdentifier) -> PriorityArray {
+        self.commands.get(&object_id).cloned().unwrap_or_default()
+    }
+
+    /// Writes that bring the device in line with a point's new commands
+    fn writes(&self, object_id: ObjectIdentifier, commands: &PriorityArray) -> Vec<PriorityWrite> {
+        let active = commands.active();
+        let mut writes: Vec<PriorityWrite> = active
+            .map(|command| PriorityWrite {
+                value: command.value.clone(),
+                priority: command.priority,
+            })
+            .into_iter()
+            .collect();
+
+        let held = self.written.get(&object_id).copied();
+        if let Some(held) = held.filter(|p| Some(*p) != active.map(|c| c.priority)) {
+            writes.push(PriorityWrite {
+                value: PropertyValue::Null,
+                priority: held,
+            });
+        }
+        writes
+    }
+
+    /// Record a point's commands once they are written to the device
+    fn commit(&mut self, object_id: ObjectIdentifier, commands: PriorityArray) {
+        match commands.active().map(|c| c.priority) {
+            Some(priority) => {
+                self.written.insert(object_id, priority);
+                self.commands.insert(object_id, commands);
+            }
+            None => {
+                self.written.remove(&object_id);
+                self.commands.remove(&object_id);
+            }
+        }
+    }
+
+    /// Points with commands expired at the given time, with their remaining
+    /// commands and the expired ones
+    fn expire(
+        &self,
+        now: DateTime<Utc>,
+    ) -> Vec<(ObjectIdentifier, PriorityArray, Vec<PointCommand>)> {
+        self.commands
+            .iter()
+            .filter_map(|(object_id, commands)| {
+                let mut commands = commands.clone();
+                let expired = commands.expire(now);
+                (!expired.is_empty()).then_some((*object_id, commands, expired))
+            })
+            .collect()
+    }
+}
+
+/// Check if an object type has a commandable present-value (a priority array)
+fn is_commandable_object_type(object_type: ObjectType) -> bool {
+    matches!(
+        object_type,
+        ObjectType::AnalogOutput
+            | ObjectType::AnalogValue
+            | ObjectType::BinaryOutput
+            | ObjectType::BinaryValue
+            | ObjectType::MultiStateOutput
+            | ObjectType::MultiStateValue
+            | ObjectType::IntegerValue
+            | ObjectType::PositiveIntegerValue
+            | ObjectType::LargeAnalogValue
+            | ObjectType::LightingOutput
+            | ObjectType::BinaryLightingOutput
+    )
+}
+
 /// Check if an object type has a present-value property that can be polled
 fn is_pollable_object_type(object_type: ObjectType) -> bool {
     matches!(
@@ -538,3 +940,182 @@ fn is_pollable_object_type(object_type: ObjectType) -> bool {
             | ObjectType::PulseConverter
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn object_id() -> ObjectIdentifier {
+        ObjectIdentifier::new(ObjectType::AnalogValue, 1).unwrap()
+    }
+
+    /// Apply a point's commands to a stand-in for the device's own priority
+    /// array, then record them
+    fn apply(
+        arbitration: &mut CommandArbitration,
+        device: &mut PriorityArray,
+        commands: PriorityArray,
+    ) {
+        for write in arbitration.writes(object_id(), &commands) {
+            device.write(write.priority, write.value, "neo", None);
+        }
+        arbitration.commit(object_id(), commands);
+    }
+
+    fn command(
+        arbitration: &mut CommandArbitration,
+        device: &mut PriorityArray,
+        value: PropertyValue,
+        priority: CommandPriority,
+        expires_at: Option<DateTime<Utc>>,
+    ) {
+        let mut commands = arbitration.get(object_id());
+        commands.write(priority, value, "test", expires_at);
+        apply(arbitration, device, commands);
+    }
+
+    #[test]
+    fn test_command_two_priorities() {
+        let mut arbitration = CommandArbitration::default();
+        let mut device = PriorityArray::new();
+
+        command(
+            &mut arbitration,
+            &mut device,
+            PropertyValue::Real(72.0),
+            CommandPriority::SCHEDULED,
+            None,
+        );
+        command(
+            &mut arbitration,
+            &mut device,
+            PropertyValue::Real(68.0),
+            CommandPriority::MANUAL_OPERATOR,
+            None,
+        );
+
+        // The device holds only the winning command, at its own priority
+        assert_eq!(device.effective_value(&PropertyValue::Null), PropertyValue::Real(68.0));
+        assert!(device.get(CommandPriority::SCHEDULED).is_none());
+        assert_eq!(arbitration.get(object_id()).commands().len(), 2);
+
+        // A lower priority write does not take over
+        command(
+            &mut arbitration,
+            &mut device,
+            PropertyValue::Real(75.0),
+            CommandPriority::SCHEDULED,
+            None,
+        );
+        assert_eq!(device.effective_value(&PropertyValue::Null), PropertyValue::Real(68.0));
+    }
+
+    #[test]
+    fn test_command_override_expires() {
+        let mut arbitration = CommandArbitration::default();
+        let mut device = PriorityArray::new();
+        let now = Utc::now();
+
+        command(
+            &mut arbitration,
+            &mut device,
+            PropertyValue::Real(72.0),
+            CommandPriority::SCHEDULED,
+            None,
+        );
+        command(
+            &mut arbitration,
+            &mut device,
+            PropertyValue::Real(68.0),
+            CommandPriority::MANUAL_OPERATOR,
+            Some(now + chrono::Duration::hours(2)),
+        );
+        assert!(arbitration.expire(now + chrono::Duration::hours(1)).is_empty());
+
+        let expired = arbitration.expire(now + chrono::Duration::hours(3));
+        assert_eq!(expired.len(), 1);
+        let (_, commands, released) = expired.into_iter().next().unwrap();
+        assert_eq!(released[0].priority, CommandPriority::MANUAL_OPERATOR);
+        apply(&mut arbitration, &mut device, commands);
+
+        // The schedule is back in control on the device
+        assert_eq!(device.effective_value(&PropertyValue::Null), PropertyValue::Real(72.0));
+        assert!(device.get(CommandPriority::MANUAL_OPERATOR).is_none());
+        assert_eq!(arbitration.get(object_id()).commands().len(), 1);
+    }
+
+    #[test]
+    fn test_command_relinquish() {
+        let mut arbitration = CommandArbitration::default();
+        let mut device = PriorityArray::new();
+
+        command(
+            &mut arbitration,
+            &mut device,
+            PropertyValue::Real(72.0),
+            CommandPriority::SCHEDULED,
+            None,
+        );
+        command(
+            &mut arbitration,
+            &mut device,
+            PropertyValue::Real(68.0),
+            CommandPriority::MANUAL_OPERATOR,
+            None,
+        );
+
+        // Releasing the override hands control back to the schedule
+        command(
+            &mut arbitration,
+            &mut device,
+            PropertyValue::Null,
+            CommandPriority::MANUAL_OPERATOR,
+            None,
+        );
+        assert_eq!(device.effective_value(&PropertyValue::Null), PropertyValue::Real(72.0));
+        assert!(device.get(CommandPriority::MANUAL_OPERATOR).is_none());
+
+        // Releasing the last command leaves nothing held on the device
+        command(
+            &mut arbitration,
+            &mut device,
+            PropertyValue::Null,
+            CommandPriority::SCHEDULED,
+            None,
+        );
+        assert!(device.active().is_none());
+        assert!(arbitration.get(object_id()).active().is_none());
+        assert!(arbitration.writes(object_id(), &PriorityArray::new()).is_empty());
+    }
+
+    #[test]
+    fn test_command_restored_after_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = CommandStore::open(&dir.path().join("commands.redb")).unwrap();
+        let path = format!("net/dev/{}", object_id());
+        let mut arbitration = CommandArbitration::default();
+        let mut device = PriorityArray::new();
+        let now = Utc::now();
+
+        command(
+            &mut arbitration,
+            &mut device,
+            PropertyValue::Real(68.0),
+            CommandPriority::MANUAL_OPERATOR,
+            Some(now + chrono::Duration::hours(1)),
+        );
+        store.save(&path, arbitration.stored(object_id()).as_ref()).unwrap();
+        assert!(store.load("net/other").unwrap().is_empty());
+
+        // The override is still known, and its expiry releases the priority held
+        let mut restored = CommandArbitration::restore(store.load("net/dev").unwrap());
+        assert_eq!(restored.get(object_id()), arbitration.get(object_id()));
+        let expired = restored.expire(now + chrono::Duration::hours(2));
+        let (_, commands, _) = expired.into_iter().next().unwrap();
+        apply(&mut restored, &mut device, commands);
+        assert!(device.active().is_none());
+
+        store.save(&path, restored.stored(object_id()).as_ref()).unwrap();
+        assert!(store.load("net/dev").unwrap().is_empty());
+    }
+}
@@ -1,8 +1,10 @@
 // BACnet protocol actors
+pub mod commands;
 pub mod device;
 pub mod network;
 pub mod io;
 
+pub use commands::CommandStore;
 pub use device::{BACnetDeviceActor, DeviceReply};
 pub use network::{BACnetNetworkActor, NetworkReply};
 pub use io::BACnetIOActor;
@@ -1,7 +1,13 @@
 use crate::actors::PubSubBroker;
-use crate::actors::bacnet::device::BACnetDeviceActor;
+use crate::actors::bacnet::commands::CommandStore;
+use crate::actors::bacnet::device::{BACnetDeviceActor, DeviceReply};
 use crate::actors::bacnet::io::BACnetIOActor;
 use crate::messages::{BACnetIOMsg, BACnetIOReply, DeviceMsg, Event, NetworkMsg};
+use crate::points::{CommandPriority, PointCommand, TagStore};
+use crate::services::actor::{ServiceMsg, ServiceReply, ServiceStateTracker};
+use crate::services::messages::{ServiceRequest, ServiceResponse};
+use crate::types::{Error, ObjectIdentifier, Result};
+use chrono::Utc;
 use dashmap::DashMap;
 use kameo::actor::{ActorRef, Spawn};
 use kameo::registry::ACTOR_REGISTRY;
@@ -21,6 +27,9 @@ pub struct BACnetNetworkActor {
     pub discovery_interval_secs: u64,
     pubsub: Option<kameo::actor::ActorRef<PubSubBroker>>,
     io_actor: kameo::actor::ActorRef<BACnetIOActor>, // Reference to I/O actor for BACnet operations
+    tag_store: Option<TagStore>, // Semantic tags applied to discovered points
+    command_store: Option<CommandStore>, // Point commands kept across restarts
+    state: ServiceStateTracker, // State as a service (point requests from the registry)
 }
 
 impl BACnetNetworkActor {
@@ -41,9 +50,24 @@ impl BACnetNetworkActor {
             discovery_interval_secs: 60, // Every 60 seconds
             pubsub: Some(pubsub),
             io_actor,
+            tag_store: None,
+            command_store: None,
+            state: ServiceStateTracker::new(),
         }
     }
 
+    /// Tag points discovered on this network's devices
+    pub fn with_tag_store(mut self, tag_store: TagStore) -> Self {
+        self.tag_store = Some(tag_store);
+        self
+    }
+
+    /// Keep the commands on this network's device points in a store
+    pub fn with_command_store(mut self, command_store: Option<CommandStore>) -> Self {
+        self.command_store = command_store;
+        self
+    }
+
     /// Generate a registry key for a device
     fn device_registry_key(&self, device_name: &str) -> String {
         format!("bacnet/{}/{}", self.network_name, device_name)
@@ -127,7 +151,9 @@ impl BACnetNetworkActor {
                 device_instance,
                 pubsub.clone(),
                 self.io_actor.clone(),
-            ));
+            )
+            .with_tag_store(self.tag_store.clone())
+            .with_command_store(self.command_store.clone()));
 
             // Link the device actor to the network actor (supervision tree)
             let _ = network_actor_ref.link(&device).await;
@@ -629,6 +655,215 @@ impl kameo::message::Message<NetworkMsg> for BACnetNetworkActor {
     }
 }
 
+// Point requests from the service registry (id "bacnet"), for points on this
+// network's devices. Writes are commands arbitrated by the device actor.
+impl kameo::message::Message<ServiceMsg> for BACnetNetworkActor {
+    type Reply = ServiceReply;
+
+    async fn handle(
+        &mut self,
+        msg: ServiceMsg,
+        _ctx: &mut kameo::message::Context<Self, Self::Reply>,
+    ) -> Self::Reply {
+        match msg {
+            ServiceMsg::Start => {
+                self.state.set_starting();
+                self.state.set_running();
+                ServiceReply::Started
+            }
+
+            ServiceMsg::Stop => {
+                self.state.set_stopping();
+                self.state.set_stopped();
+                ServiceReply::Stopped
+            }
+
+            ServiceMsg::GetStatus => ServiceReply::Status {
+                id: "bacnet".to_string(),
+                name: format!("BACnet network {}", self.network_name),
+                state: self.state.state(),
+                uptime_secs: self.state.uptime_secs(),
+                extra: None,
+            },
+
+            ServiceMsg::GetConfig => ServiceReply::Config {
+                config: self.config(),
+            },
+
+            ServiceMsg::SetConfig { .. } => {
+                ServiceReply::Failed("BACnet network configuration is read-only".to_string())
+            }
+
+            ServiceMsg::OnEvent { .. } => ServiceReply::EventHandled,
+
+            ServiceMsg::HandleRequest { request, reply } => {
+                match request {
+                    ServiceRequest::GetStatus => {
+                        let _ = reply.send(ServiceResponse::Status {
+                            id: "bacnet".to_string(),
+                            name: format!("BACnet network {}", self.network_name),
+                            state: self.state.state(),
+                            uptime_seconds: self.state.uptime_secs(),
+                            extra: None,
+                        });
+                    }
+                    ServiceRequest::GetConfig => {
+                        let _ = reply.send(ServiceResponse::Config {
+                            config: self.config(),
+                        });
+                    }
+                    request @ (ServiceRequest::WritePoint { .. }
+                    | ServiceRequest::GetPointCommands { .. }) => {
+                        // Devices can take seconds to answer; don't hold up the network
+                        let network_name = self.network_name.clone();
+                        tokio::spawn(async move {
+                            let _ = reply.send(point_request(&network_name, request).await);
+                        });
+                    }
+                    _ => {
+                        let _ = reply.send(ServiceResponse::Error {
+                            code: "UNSUPPORTED".to_string(),
+                            message: "Request not supported by BACnet network".to_string(),
+                        });
+                    }
+                }
+                ServiceReply::RequestHandled
+            }
+        }
+    }
+}
+
+impl BACnetNetworkActor {
+    fn config(&self) -> serde_json::Value {
+        serde_json::json!({
+            "network_name": self.network_name,
+            "poll_interval_secs": self.poll_interval_secs,
+            "auto_discovery": self.auto_discovery,
+            "discovery_interval_secs": self.discovery_interval_secs,
+        })
+    }
+}
+
+/// Answer a point request for a point on one of a network's devices
+async fn point_request(network_name: &str, request: ServiceRequest) -> ServiceResponse {
+    match request {
+        ServiceRequest::WritePoint {
+            path,
+            value,
+            source,
+            priority,
+            duration_secs,
+        } => {
+            let source = source.unwrap_or_else(|| "request".to_string());
+            match write_point(network_name, &path, value, source, priority, duration_secs).await {
+                Ok(()) => ServiceResponse::PointWritten { path },
+                Err(e) => error_response("WRITE_FAILED", e),
+            }
+        }
+        ServiceRequest::GetPointCommands { path } => {
+            match point_commands(network_name, &path).await {
+                Ok(commands) => ServiceResponse::PointCommands { path, commands },
+                Err(e) => error_response("COMMANDS_FAILED", e),
+            }
+        }
+        _ => ServiceResponse::Error {
+            code: "UNSUPPORTED".to_string(),
+            message: "Not a point request".to_string(),
+        },
+    }
+}
+
+fn error_response(code: &str, e: Error) -> ServiceResponse {
+    let code = match e {
+        Error::NotFound(_) => "NOT_FOUND",
+        Error::Config(_) => "INVALID_REQUEST",
+        _ => code,
+    };
+    ServiceResponse::Error {
+        code: code.to_string(),
+        message: e.to_string(),
+    }
+}
+
+/// Command a point through its device's priority array
+async fn write_point(
+    network_name: &str,
+    path: &str,
+    value: crate::types::PropertyValue,
+    source: String,
+    priority: Option<u8>,
+    duration_secs: Option<u64>,
+) -> Result<()> {
+    let priority = priority.map(CommandPriority::new).transpose()?.unwrap_or_default();
+    let expires_at = match duration_secs {
+        Some(secs) => Some(
+            i64::try_from(secs)
+                .ok()
+                .and_then(chrono::Duration::try_seconds)
+                .and_then(|duration| Utc::now().checked_add_signed(duration))
+                .ok_or_else(|| Error::Config(format!("Duration {}s is too long", secs)))?,
+        ),
+        None => None,
+    };
+    let (device, object_id) = resolve_point(network_name, path).await?;
+
+    match device
+        .ask(DeviceMsg::CommandPoint {
+            object_id,
+            value,
+            priority,
+            source,
+            expires_at,
+        })
+        .await
+    {
+        Ok(DeviceReply::PropertyWritten) => Ok(()),
+        Ok(DeviceReply::Failure(e)) => Err(Error::Protocol(e)),
+        Ok(other) => Err(Error::Protocol(format!("Unexpected reply from device: {:?}", other))),
+        Err(e) => Err(Error::Actor(format!("Failed to send to device: {}", e))),
+    }
+}
+
+/// Active commands of a point, highest priority first
+async fn point_commands(network_name: &str, path: &str) -> Result<Vec<PointCommand>> {
+    let (device, object_id) = resolve_point(network_name, path).await?;
+    match device.ask(DeviceMsg::GetCommands { object_id }).await {
+        Ok(DeviceReply::Commands(commands)) => Ok(commands),
+        Ok(other) => Err(Error::Protocol(format!("Unexpected reply from device: {:?}", other))),
+        Err(e) => Err(Error::Actor(format!("Failed to send to device: {}", e))),
+    }
+}
+
+/// Find the device actor and object of a point path (network/device/object)
+async fn resolve_point(
+    network_name: &str,
+    path: &str,
+) -> Result<(ActorRef<BACnetDeviceActor>, ObjectIdentifier)> {
+    let not_found = || Error::NotFound(format!("BACnet point '{}' not found", path));
+    let (device_name, object) = path
+        .strip_prefix(network_name)
+        .and_then(|rest| rest.strip_prefix('/'))
+        .and_then(|rest| rest.split_once('/'))
+        .ok_or_else(not_found)?;
+
+    let registry_key = format!("bacnet/{}/{}", network_name, device_name);
+    let device = ActorRef::<BACnetDeviceActor>::lookup(registry_key.as_str())
+        .ok()
+        .flatten()
+        .ok_or_else(not_found)?;
+
+    match device.ask(DeviceMsg::ListPoints).await {
+        Ok(DeviceReply::Points(points)) => points
+            .into_iter()
+            .map(|point| point.object_id)
+            .find(|object_id| object_id.to_string() == object)
+            .map(|object_id| (device, object_id))
+            .ok_or_else(not_found),
+        Ok(other) => Err(Error::Protocol(format!("Unexpected reply from device: {:?}", other))),
+        Err(e) => Err(Error::Actor(format!("Failed to send to device: {}", e))),
+    }
+}
+
 #[derive(Debug, kameo::Reply)]
 pub enum NetworkReply {
     Status {
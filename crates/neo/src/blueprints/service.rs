@@ -22,6 +22,7 @@ use tracing::{debug, error, info, warn};
 
 use crate::actors::PubSubBroker;
 use crate::messages::Event;
+use crate::points::{TagFilter, TagStore};
 use crate::services::actor::{ServiceActorRef, ServiceMetadata, ServiceType};
 use crate::services::registry::{RegistryMsg, RegistryReply, ServiceRegistry};
 
@@ -177,6 +178,8 @@ pub struct BlueprintService {
     service_registry: Option<ActorRef<ServiceRegistry>>,
     /// Registered blueprint service adapters (blueprint_id -> adapter actor)
     service_adapters: HashMap<String, ActorRef<BlueprintServiceAdapter>>,
+    /// Semantic tags for event node `tag_filter` config (optional)
+    tag_store: Option<TagStore>,
 }
 
 struct SuspendedExecution {
@@ -203,6 +206,7 @@ impl BlueprintService {
             pubsub: None,
             service_registry: None,
             service_adapters: HashMap::new(),
+            tag_store: None,
         }
     }
 
@@ -228,6 +232,11 @@ impl BlueprintService {
         self.service_registry = Some(registry);
     }
 
+    /// Set the tag store used for event node tag filters
+    pub fn set_tag_store(&mut self, tag_store: TagStore) {
+        self.tag_store = Some(tag_store);
+    }
+
     /// Publish an event to PubSub
     pub async fn publish_event(&self, event: Event) {
         if let Some(pubsub) = &self.pubsub {
@@ -423,7 +432,7 @@ impl BlueprintService {
     }
 
     /// Find blueprints that handle a specific event type
-    fn find_event_handlers(&self, event_type: &str) -> Vec<(Arc<Blueprint>, String)> {
+    fn find_event_handlers(&self, event_type: &str, data: &Value) -> Vec<(Arc<Blueprint>, String)> {
         let mut handlers = Vec::new();
 
         for blueprint in self.blueprints.values() {
@@ -438,7 +447,7 @@ impl BlueprintService {
                         .contains(&event_type.to_lowercase().replace('_', ""))
                 };
 
-                if matches {
+                if matches && self.matches_tag_filter(node.config.get("tag_filter"), data) {
                     handlers.push((Arc::clone(blueprint), node.id.clone()));
                 }
             }
@@ -447,6 +456,28 @@ impl BlueprintService {
         handlers
     }
 
+    /// Check an event node's `tag_filter` config against the event's point
+    ///
+    /// Nodes without a filter always match; events without a point never
+    /// match a filter.
+    fn matches_tag_filter(&self, filter: Option<&Value>, data: &Value) -> bool {
+        let Some(filter) = filter.and_then(|f| f.as_str()) else {
+            return true;
+        };
+        let point = data.get("point").and_then(|p| p.as_str());
+        let (Some(store), Some(point)) = (&self.tag_store, point) else {
+            return false;
+        };
+
+        match TagFilter::parse(filter) {
+            Ok(filter) => store.matches(point, &filter),
+            Err(e) => {
+                warn!(error = %e, "Invalid tag_filter on event node");
+                false
+            }
+        }
+    }
+
     /// Process pending latent executions
     async fn tick_latent(&mut self) {
         let now_ms = std::time::SystemTime::now()
@@ -664,7 +695,7 @@ impl Message<TriggerEvent> for BlueprintService {
             "Triggering event for blueprints"
         );
 
-        let handlers = self.find_event_handlers(&msg.event_type);
+        let handlers = self.find_event_handlers(&msg.event_type, &msg.data);
         let mut results = Vec::new();
 
         for (blueprint, event_node_id) in handlers {
@@ -978,21 +1009,51 @@ impl Message<HandleEvent> for BlueprintService {
                 }))
             }
             Event::PointValueChanged { point, value, .. } => {
+                let tags = self
+                    .tag_store
+                    .as_ref()
+                    .and_then(|store| store.point_tags(point))
+                    .unwrap_or_default();
                 ("PointValueChanged".to_string(), serde_json::json!({
                     "point": point,
                     "value": value,
+                    "tags": tags,
                 }))
             }
-            Event::AlarmRaised { source, message, severity, .. } => {
+            Event::AlarmRaised { alarm_id, config_id, source, message, severity, value, .. } => {
                 ("AlarmRaised".to_string(), serde_json::json!({
+                    "alarm_id": alarm_id,
+                    "config_id": config_id,
                     "source": source,
                     "message": message,
                     "severity": format!("{:?}", severity),
+                    "value": value,
+                }))
+            }
+            Event::AlarmAcknowledged { alarm_id, config_id, source, severity, acknowledged_by, .. } => {
+                ("AlarmAcknowledged".to_string(), serde_json::json!({
+                    "alarm_id": alarm_id,
+                    "config_id": config_id,
+                    "source": source,
+                    "severity": format!("{:?}", severity),
+                    "acknowledged_by": acknowledged_by,
                 }))
             }
-            Event::AlarmCleared { source, .. } => {
+            Event::AlarmReturnedToNormal { alarm_id, config_id, source, severity, value, .. } => {
+                ("AlarmReturnedToNormal".to_string(), serde_json::json!({
+                    "alarm_id": alarm_id,
+                    "config_id": config_id,
+                    "source": source,
+                    "severity": format!("{:?}", severity),
+                    "value": value,
+                }))
+            }
+            Event::AlarmCleared { alarm_id, config_id, source, severity, .. } => {
                 ("AlarmCleared".to_string(), serde_json::json!({
+                    "alarm_id": alarm_id,
+                    "config_id": config_id,
                     "source": source,
+                    "severity": format!("{:?}", severity),
                 }))
             }
             Event::DeviceStatusChanged { device, network, status, .. } => {
@@ -1002,6 +1063,18 @@ impl Message<HandleEvent> for BlueprintService {
                     "status": format!("{:?}", status),
                 }))
             }
+            Event::HistoryStale { point, last_logged, stale_after_secs, .. } => {
+                ("HistoryStale".to_string(), serde_json::json!({
+                    "point": point,
+                    "last_logged": last_logged,
+                    "stale_after_secs": stale_after_secs,
+                }))
+            }
+            Event::HistoryResumed { point, .. } => {
+                ("HistoryResumed".to_string(), serde_json::json!({
+                    "point": point,
+                }))
+            }
             Event::DeviceDiscovered { network, device, instance, address, .. } => {
                 ("DeviceDiscovered".to_string(), serde_json::json!({
                     "network": network,
@@ -1019,7 +1092,7 @@ impl Message<HandleEvent> for BlueprintService {
         };
 
         // Trigger blueprints that listen for this event
-        let handlers = self.find_event_handlers(&event_type);
+        let handlers = self.find_event_handlers(&event_type, &data);
 
         for (blueprint, event_node_id) in handlers {
             let trigger = ExecutionTrigger::Event {
@@ -1133,7 +1206,7 @@ mod tests {
         std::fs::write(&blueprint_path, blueprint_json).unwrap();
         service.load_blueprint_file(&blueprint_path).unwrap();
 
-        let handlers = service.find_event_handlers("point_changed");
+        let handlers = service.find_event_handlers("point_changed", &Value::Null);
         assert_eq!(handlers.len(), 1);
         assert_eq!(handlers[0].1, "event");
     }
@@ -210,16 +210,40 @@ impl Message<ServiceMsg> for BlueprintServiceAdapter {
                             "value": value,
                         }))
                     }
-                    Event::AlarmRaised { source, message, severity, .. } => {
+                    Event::AlarmRaised { alarm_id, config_id, source, message, severity, value, .. } => {
                         ("AlarmRaised".to_string(), serde_json::json!({
+                            "alarm_id": alarm_id,
+                            "config_id": config_id,
                             "source": source,
                             "message": message,
                             "severity": format!("{:?}", severity),
+                            "value": value,
+                        }))
+                    }
+                    Event::AlarmAcknowledged { alarm_id, config_id, source, severity, acknowledged_by, .. } => {
+                        ("AlarmAcknowledged".to_string(), serde_json::json!({
+                            "alarm_id": alarm_id,
+                            "config_id": config_id,
+                            "source": source,
+                            "severity": format!("{:?}", severity),
+                            "acknowledged_by": acknowledged_by,
                         }))
                     }
-                    Event::AlarmCleared { source, .. } => {
+                    Event::AlarmReturnedToNormal { alarm_id, config_id, source, severity, value, .. } => {
+                        ("AlarmReturnedToNormal".to_string(), serde_json::json!({
+                            "alarm_id": alarm_id,
+                            "config_id": config_id,
+                            "source": source,
+                            "severity": format!("{:?}", severity),
+                            "value": value,
+                        }))
+                    }
+                    Event::AlarmCleared { alarm_id, config_id, source, severity, .. } => {
                         ("AlarmCleared".to_string(), serde_json::json!({
+                            "alarm_id": alarm_id,
+                            "config_id": config_id,
                             "source": source,
+                            "severity": format!("{:?}", severity),
                         }))
                     }
                     Event::DeviceStatusChanged { device, network, status, .. } => {
@@ -229,6 +253,18 @@ impl Message<ServiceMsg> for BlueprintServiceAdapter {
                             "status": format!("{:?}", status),
                         }))
                     }
+                    Event::HistoryStale { point, last_logged, stale_after_secs, .. } => {
+                        ("HistoryStale".to_string(), serde_json::json!({
+                            "point": point,
+                            "last_logged": last_logged,
+                            "stale_after_secs": stale_after_secs,
+                        }))
+                    }
+                    Event::HistoryResumed { point, .. } => {
+                        ("HistoryResumed".to_string(), serde_json::json!({
+                            "point": point,
+                        }))
+                    }
                     Event::DeviceDiscovered { network, device, instance, address, .. } => {
                         ("DeviceDiscovered".to_string(), serde_json::json!({
                             "network": network,
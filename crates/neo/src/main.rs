@@ -2,20 +2,23 @@ use std::path::PathBuf;
 
 use kameo::actor::Spawn;
 use kameo_actors::DeliveryStrategy;
-use neo::actors::bacnet::{BACnetIOActor, BACnetNetworkActor};
+use neo::actors::bacnet::{BACnetIOActor, BACnetNetworkActor, CommandStore};
 use neo::actors::{EventRouter, PubSubBroker};
 use neo::blueprints::{
     start_background_tasks, BlueprintService, ListBlueprints, RegisterServiceBlueprints,
     SetServiceRefs,
 };
 use neo::messages::NetworkMsg;
+use neo::points::TagStore;
 use neo::services::{
     // Actor-based services
-    AlarmActor, HistoryActor, HistoryConfig,
+    AckPolicy, AlarmActor, CalculatedPointActor, CalculatedPointsConfig, DeviceAlarmConfig,
+    HistoryActor, HistoryConfig, NotificationConfig, TagActor, VirtualPointActor,
+    VirtualPointStoreConfig,
     // Pool and plugin loading
     JsRuntimePoolActor, load_plugins,
     // Service actor infrastructure
-    ServiceActorRef, ServiceMetadata, ActorServiceType,
+    ServiceActorRef, ServiceMetadata, ServiceMsg, ActorServiceType,
     // Registry
     RegistryMsg, ServiceRegistry,
 };
@@ -78,13 +81,55 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     info!("");
     info!("Registering built-in services...");
 
+    // Tag Actor (tags, rules and equipment defined in data/tags.json, if present)
+    let tags_path = PathBuf::from("./data/tags.json");
+    let tag_store = if tags_path.exists() {
+        TagStore::load(&tags_path).unwrap_or_else(|e| {
+            tracing::warn!("  Invalid {}: {}", tags_path.display(), e);
+            TagStore::default()
+        })
+    } else {
+        TagStore::default()
+    };
+    let tag_actor = TagActor::spawn(TagActor::new(tag_store.clone()));
+    let tag_ref = ServiceActorRef::new(
+        tag_actor,
+        ServiceMetadata {
+            id: "tags".to_string(),
+            name: "Tag Service".to_string(),
+            description: "Semantic tags for points, equipment and sites".to_string(),
+            service_type: ActorServiceType::Native,
+        },
+    );
+    registry
+        .ask(RegistryMsg::Register {
+            actor_ref: tag_ref,
+            subscriptions: vec!["PointValueChanged".to_string()],
+        })
+        .await?;
+    info!("  Tag Service registered (subscribed to PointValueChanged)");
+
     // History Actor
     let history_config = HistoryConfig {
         db_path: "./data/history.redb".to_string(),
         retention_days: 365,
         sample_interval_ms: 1000,
+        ..Default::default()
     };
-    let history_actor = HistoryActor::spawn(HistoryActor::new(history_config));
+    let purge_interval = Duration::from_secs(history_config.purge_interval_secs);
+    let flush_interval = Duration::from_millis(history_config.flush_interval_ms);
+    let forward_interval = Duration::from_millis(history_config.forward_interval_ms);
+    let history_actor = HistoryActor::spawn(
+        HistoryActor::new(history_config)
+            .with_tag_store(tag_store.clone())
+            .with_pubsub(pubsub.clone()),
+    );
+    let history_purge_handle = HistoryActor::start_purge_task(history_actor.clone(), purge_interval);
+    let history_flush_handle = HistoryActor::start_flush_task(history_actor.clone(), flush_interval);
+    let history_policy_handle =
+        HistoryActor::start_policy_task(history_actor.clone(), Duration::from_secs(1));
+    let history_forward_handle =
+        HistoryActor::start_forward_task(history_actor.clone(), forward_interval);
     let history_ref = ServiceActorRef::new(
         history_actor,
         ServiceMetadata {
@@ -100,10 +145,51 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             subscriptions: vec!["PointValueChanged".to_string()],
         })
         .await?;
-    info!("  History Service registered (subscribed to PointValueChanged)");
-
-    // Alarm Actor
-    let alarm_actor = AlarmActor::spawn(AlarmActor::new());
+    info!("  History Service registered (subscribed to PointValueChanged, hourly purge)");
+
+    // Alarm Actor (rules defined in data/alarms/*.json and *.toml, reloaded on change;
+    // notification classes in data/alarm_notifications.json, acknowledgement
+    // rules in data/alarm_ack_policy.json and the device communication alarm's
+    // delay and severity in data/alarm_devices.json, if present)
+    let notifications_path = PathBuf::from("./data/alarm_notifications.json");
+    let notification_config = match std::fs::read_to_string(&notifications_path) {
+        Ok(contents) => serde_json::from_str::<NotificationConfig>(&contents)
+            .map_err(|e| e.to_string())
+            .and_then(|config| config.validate().map(|()| config).map_err(|e| e.to_string()))
+            .unwrap_or_else(|e| {
+                tracing::warn!("  Invalid {}: {}", notifications_path.display(), e);
+                NotificationConfig::default()
+            }),
+        Err(_) => NotificationConfig::default(),
+    };
+    let ack_policy_path = PathBuf::from("./data/alarm_ack_policy.json");
+    let ack_policy = match std::fs::read_to_string(&ack_policy_path) {
+        Ok(contents) => serde_json::from_str::<AckPolicy>(&contents).unwrap_or_else(|e| {
+            tracing::warn!("  Invalid {}: {}", ack_policy_path.display(), e);
+            AckPolicy::default()
+        }),
+        Err(_) => AckPolicy::default(),
+    };
+    let device_alarm_path = PathBuf::from("./data/alarm_devices.json");
+    let device_alarm = match std::fs::read_to_string(&device_alarm_path) {
+        Ok(contents) => serde_json::from_str::<DeviceAlarmConfig>(&contents).unwrap_or_else(|e| {
+            tracing::warn!("  Invalid {}: {}", device_alarm_path.display(), e);
+            DeviceAlarmConfig::default()
+        }),
+        Err(_) => DeviceAlarmConfig::default(),
+    };
+    let alarm_actor = AlarmActor::spawn(
+        AlarmActor::new()
+            .with_db_path("./data/alarms.redb")
+            .with_rules_dir("./data/alarms")
+            .with_notifications(notification_config)
+            .with_ack_policy(ack_policy)
+            .with_device_alarm(device_alarm)
+            .with_registry(registry.clone())
+            .with_tag_store(tag_store.clone())
+            .with_pubsub(pubsub.clone()),
+    );
+    let alarm_timer_handle = AlarmActor::start_timer_task(alarm_actor.clone());
     let alarm_ref = ServiceActorRef::new(
         alarm_actor,
         ServiceMetadata {
@@ -116,10 +202,79 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     registry
         .ask(RegistryMsg::Register {
             actor_ref: alarm_ref,
+            subscriptions: vec![
+                "PointValueChanged".to_string(),
+                "HistoryStale".to_string(),
+                "HistoryResumed".to_string(),
+                "DeviceStatusChanged".to_string(),
+            ],
+        })
+        .await?;
+    info!(
+        "  Alarm Service registered (subscribed to PointValueChanged, HistoryStale/Resumed, \
+         DeviceStatusChanged)"
+    );
+
+    // Virtual Point Actor (points defined in data/virtual_points.json, if present)
+    let virtual_points_path = PathBuf::from("./data/virtual_points.json");
+    let virtual_points_config = match std::fs::read_to_string(&virtual_points_path) {
+        Ok(contents) => serde_json::from_str::<VirtualPointStoreConfig>(&contents)
+            .unwrap_or_else(|e| {
+                tracing::warn!("  Invalid {}: {}", virtual_points_path.display(), e);
+                VirtualPointStoreConfig::default()
+            }),
+        Err(_) => VirtualPointStoreConfig::default(),
+    };
+    let virtual_point_actor =
+        VirtualPointActor::spawn(VirtualPointActor::new(virtual_points_config, pubsub.clone()));
+    let virtual_expiry_handle = VirtualPointActor::start_expiry_task(virtual_point_actor.clone());
+    let virtual_point_ref = ServiceActorRef::new(
+        virtual_point_actor,
+        ServiceMetadata {
+            id: "virtual_points".to_string(),
+            name: "Virtual Point Service".to_string(),
+            description: "Software points owned by Neo".to_string(),
+            service_type: ActorServiceType::Native,
+        },
+    );
+    registry
+        .ask(RegistryMsg::Register {
+            actor_ref: virtual_point_ref,
+            subscriptions: vec![],
+        })
+        .await?;
+    info!("  Virtual Point Service registered (command expiry every 1s)");
+
+    // Calculated Point Actor (points defined in data/calculated_points.json, if present)
+    let calculated_points_path = PathBuf::from("./data/calculated_points.json");
+    let calculated_points_config = match std::fs::read_to_string(&calculated_points_path) {
+        Ok(contents) => serde_json::from_str::<CalculatedPointsConfig>(&contents)
+            .unwrap_or_else(|e| {
+                tracing::warn!("  Invalid {}: {}", calculated_points_path.display(), e);
+                CalculatedPointsConfig::default()
+            }),
+        Err(_) => CalculatedPointsConfig::default(),
+    };
+    let calculated_point_actor = CalculatedPointActor::spawn(CalculatedPointActor::new(
+        calculated_points_config,
+        pubsub.clone(),
+    ));
+    let calculated_point_ref = ServiceActorRef::new(
+        calculated_point_actor,
+        ServiceMetadata {
+            id: "calculated_points".to_string(),
+            name: "Calculated Point Service".to_string(),
+            description: "Points computed from expressions over other points".to_string(),
+            service_type: ActorServiceType::Native,
+        },
+    );
+    registry
+        .ask(RegistryMsg::Register {
+            actor_ref: calculated_point_ref,
             subscriptions: vec!["PointValueChanged".to_string()],
         })
         .await?;
-    info!("  Alarm Service registered (subscribed to PointValueChanged)");
+    info!("  Calculated Point Service registered (subscribed to PointValueChanged)");
 
     // ─────────────────────────────────────────────────────────────────────────
     // 4. Load Plugins
@@ -184,6 +339,9 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         }
     }
 
+    // Event nodes may filter points by tag (config "tag_filter")
+    blueprint_service.set_tag_store(tag_store.clone());
+
     // Start file watching for hot reload
     if let Err(e) = blueprint_service.start_watching() {
         tracing::warn!("  Hot reload disabled: {}", e);
@@ -244,14 +402,44 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     info!("");
     info!("Starting BACnet network...");
 
+    // Commands on device points are kept in data/bacnet_commands.redb, so
+    // overrides written before a restart still expire and release
+    let command_store = match CommandStore::open(&PathBuf::from("./data/bacnet_commands.redb")) {
+        Ok(store) => Some(store),
+        Err(e) => {
+            tracing::warn!("  Point commands will not be kept across restarts: {}", e);
+            None
+        }
+    };
     let bacnet_network = BACnetNetworkActor::spawn(BACnetNetworkActor::new(
         "MainNetwork".to_string(),
         10, // Poll devices every 10 seconds
         pubsub.clone(),
         io_actor.clone(),
-    ));
+    )
+    .with_tag_store(tag_store.clone())
+    .with_command_store(command_store));
 
     info!("  Network 'MainNetwork' created");
+
+    // Point writes and command queries for BACnet points go through the network
+    let bacnet_ref = ServiceActorRef::new(
+        bacnet_network.clone(),
+        ServiceMetadata {
+            id: "bacnet".to_string(),
+            name: "BACnet Network".to_string(),
+            description: "Commands BACnet points through their priority arrays".to_string(),
+            service_type: ActorServiceType::Native,
+        },
+    );
+    bacnet_ref.ask(ServiceMsg::Start).await?;
+    registry
+        .ask(RegistryMsg::Register {
+            actor_ref: bacnet_ref,
+            subscriptions: vec![],
+        })
+        .await?;
+    info!("  Registered as service 'bacnet' (WritePoint, GetPointCommands)");
     info!("    Auto-discovery: enabled");
     info!("    Discovery interval: 60 seconds");
     info!("    Polling interval: 10 seconds");
@@ -340,6 +528,12 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Stop background tasks
     polling_handle.abort();
     discovery_handle.abort();
+    virtual_expiry_handle.abort();
+    alarm_timer_handle.abort();
+    history_purge_handle.abort();
+    history_flush_handle.abort();
+    history_policy_handle.abort();
+    history_forward_handle.abort();
     blueprint_handle.abort();
 
     // Stop all services
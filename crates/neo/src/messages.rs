@@ -1,7 +1,9 @@
+use crate::points::{CommandPriority, EngineeringUnit};
 use crate::types::*;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::time::Instant;
+use uuid::Uuid;
 
 /// Events that can be published via pub-sub
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -11,22 +13,64 @@ pub enum Event {
         point: String,
         value: PropertyValue,
         quality: PointQuality,
+        /// Engineering units of the value, if known
+        #[serde(default)]
+        units: Option<EngineeringUnit>,
         #[serde(skip, default = "Instant::now")]
         timestamp: Instant,
         timestamp_utc: DateTime<Utc>,
     },
 
     AlarmRaised {
+        alarm_id: Uuid,
+        /// Alarm configuration that raised the alarm
+        config_id: Uuid,
         source: String,
         message: String,
         severity: AlarmSeverity,
+        /// Value that triggered the alarm, if any
+        #[serde(default)]
+        value: Option<PropertyValue>,
         #[serde(skip, default = "Instant::now")]
         timestamp: Instant,
         timestamp_utc: DateTime<Utc>,
     },
 
+    /// An operator acknowledged an alarm
+    AlarmAcknowledged {
+        alarm_id: Uuid,
+        config_id: Uuid,
+        source: String,
+        severity: AlarmSeverity,
+        #[serde(default)]
+        acknowledged_by: Option<String>,
+        #[serde(default)]
+        comment: Option<String>,
+        #[serde(skip, default = "Instant::now")]
+        timestamp: Instant,
+        timestamp_utc: DateTime<Utc>,
+    },
+
+    /// The condition of an alarm is no longer met
+    AlarmReturnedToNormal {
+        alarm_id: Uuid,
+        config_id: Uuid,
+        source: String,
+        severity: AlarmSeverity,
+        /// Value that returned to normal, if any
+        #[serde(default)]
+        value: Option<PropertyValue>,
+        #[serde(skip, default = "Instant::now")]
+        timestamp: Instant,
+        timestamp_utc: DateTime<Utc>,
+    },
+
+    /// An alarm has ended and left the active alarm list
     AlarmCleared {
+        alarm_id: Uuid,
+        config_id: Uuid,
         source: String,
+        severity: AlarmSeverity,
         #[serde(skip, default = "Instant::now")]
         timestamp: Instant,
         timestamp_utc: DateTime<Utc>,
@@ -59,6 +103,26 @@ pub enum Event {
         timestamp_utc: DateTime<Utc>,
     },
 
+    /// A point has not logged history for longer than its logging policy's
+    /// `stale_after_secs`
+    HistoryStale {
+        point: String,
+        /// Last sample logged since the history service started, if any
+        last_logged: Option<DateTime<Utc>>,
+        stale_after_secs: u64,
+        #[serde(skip, default = "Instant::now")]
+        timestamp: Instant,
+        timestamp_utc: DateTime<Utc>,
+    },
+
+    /// A stale point is logging history again
+    HistoryResumed {
+        point: String,
+        #[serde(skip, default = "Instant::now")]
+        timestamp: Instant,
+        timestamp_utc: DateTime<Utc>,
+    },
+
     /// Custom events from plugins with arbitrary JSON data
     Custom {
         /// The custom event type name (e.g., "WeatherUpdated")
@@ -94,6 +158,16 @@ pub enum PointMsg {
 pub enum DeviceMsg {
     ReadProperty { object_id: ObjectIdentifier, property_id: PropertyIdentifier },
     WriteProperty { object_id: ObjectIdentifier, property_id: PropertyIdentifier, value: PropertyValue },
+    /// Command a point's present value at a Neo priority (Null releases the priority)
+    CommandPoint {
+        object_id: ObjectIdentifier,
+        value: PropertyValue,
+        priority: CommandPriority,
+        source: String,
+        expires_at: Option<DateTime<Utc>>,
+    },
+    /// Get the active commands of a point, highest priority first
+    GetCommands { object_id: ObjectIdentifier },
     Poll,
     GetStatus,
     DiscoverPoints,
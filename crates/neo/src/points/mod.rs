@@ -0,0 +1,14 @@
+// Point Model
+//
+// Protocol-independent metadata about points:
+// - priority: command priority arbitration for writable points
+// - tags: Project Haystack / Brick style semantic tags and tag filters
+// - units: BACnet engineering unit catalog and unit conversions
+
+pub mod priority;
+pub mod tags;
+pub mod units;
+
+pub use priority::{CommandPriority, PointCommand, PriorityArray, PRIORITY_LEVELS};
+pub use tags::{PointIdentity, TagConfig, TagEntity, TagFilter, TagRule, TagSet, TagStore, TagValue};
+pub use units::{EngineeringUnit, Quantity, UnitError};
@@ -0,0 +1,304 @@
+// Command Priority - Protocol-neutral write arbitration for writable points
+//
+// Every writable point carries a 16-level priority array modelled on BACnet's
+// commandable objects. Plugins, blueprints, schedules and operators write at
+// a priority; the point takes the value of the highest priority (lowest
+// number) active command and falls back to its relinquish default when every
+// level is empty. Releasing a command (writing Null at its priority) reverts
+// to the next active command.
+//
+// Each command records its source and may expire, so an operator override
+// like "68°F for 2 hours" releases itself:
+//
+//   priority 1  (manual life safety)   -
+//   priority 8  (manual operator)      68.0  by "user:alice" until 14:30
+//   priority 12 (scheduled)            72.0  by "blueprint:occupancy"
+//   priority 16 (default)              -
+//   effective value                    68.0  (priority 8)
+
+use std::fmt;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::types::{Error, PropertyValue, Result};
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Priority Levels
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Number of priority levels in a priority array
+pub const PRIORITY_LEVELS: usize = 16;
+
+/// A command priority level (1 = highest, 16 = lowest)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(try_from = "u8", into = "u8")]
+pub struct CommandPriority(u8);
+
+impl CommandPriority {
+    /// Manual life safety (fire, smoke control overrides)
+    pub const MANUAL_LIFE_SAFETY: Self = Self(1);
+    /// Automatic life safety
+    pub const AUTOMATIC_LIFE_SAFETY: Self = Self(2);
+    /// Critical equipment control
+    pub const CRITICAL_EQUIPMENT: Self = Self(5);
+    /// Minimum on/off times
+    pub const MINIMUM_ON_OFF: Self = Self(6);
+    /// Manual operator overrides
+    pub const MANUAL_OPERATOR: Self = Self(8);
+    /// Schedules
+    pub const SCHEDULED: Self = Self(12);
+    /// Lowest priority, used when a writer does not specify one
+    pub const DEFAULT: Self = Self(16);
+
+    /// Create a priority, validating the level
+    pub fn new(level: u8) -> Result<Self> {
+        if (1..=PRIORITY_LEVELS as u8).contains(&level) {
+            Ok(Self(level))
+        } else {
+            Err(Error::Config(format!(
+                "Command priority must be between 1 and {}, got {}",
+                PRIORITY_LEVELS, level
+            )))
+        }
+    }
+
+    /// The priority level (1-16)
+    pub fn level(&self) -> u8 {
+        self.0
+    }
+
+    /// Slot index in the priority array
+    fn index(&self) -> usize {
+        self.0 as usize - 1
+    }
+}
+
+impl Default for CommandPriority {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+impl TryFrom<u8> for CommandPriority {
+    type Error = Error;
+
+    fn try_from(level: u8) -> Result<Self> {
+        Self::new(level)
+    }
+}
+
+impl From<CommandPriority> for u8 {
+    fn from(priority: CommandPriority) -> Self {
+        priority.0
+    }
+}
+
+impl fmt::Display for CommandPriority {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Commands
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// An active command in a priority array
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+pub struct PointCommand {
+    #[ts(type = "number")]
+    pub priority: CommandPriority,
+    #[ts(type = "unknown")]
+    pub value: PropertyValue,
+    /// Who issued the command (plugin, blueprint, user)
+    pub source: String,
+    pub written_at: DateTime<Utc>,
+    /// When the command releases itself (None = until relinquished)
+    #[serde(default)]
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+impl PointCommand {
+    /// Whether the command has expired at the given time
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at.is_some_and(|at| at <= now)
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Priority Array
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// 16-level command priority array for one writable point
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PriorityArray {
+    slots: [Option<PointCommand>; PRIORITY_LEVELS],
+}
+
+impl PriorityArray {
+    /// Create an empty priority array
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Rebuild a priority array from stored commands, dropping expired ones
+    pub fn from_commands(commands: impl IntoIterator<Item = PointCommand>, now: DateTime<Utc>) -> Self {
+        let mut array = Self::new();
+        for command in commands {
+            if !command.is_expired(now) {
+                array.command(command);
+            }
+        }
+        array
+    }
+
+    /// Write a command at its priority, replacing any command at that level
+    ///
+    /// Returns the command it replaced.
+    pub fn command(&mut self, command: PointCommand) -> Option<PointCommand> {
+        let index = command.priority.index();
+        self.slots[index].replace(command)
+    }
+
+    /// Release the command at a priority
+    pub fn relinquish(&mut self, priority: CommandPriority) -> Option<PointCommand> {
+        self.slots[priority.index()].take()
+    }
+
+    /// Apply a write: Null relinquishes the priority, anything else commands it
+    pub fn write(
+        &mut self,
+        priority: CommandPriority,
+        value: PropertyValue,
+        source: &str,
+        expires_at: Option<DateTime<Utc>>,
+    ) {
+        if value == PropertyValue::Null {
+            self.relinquish(priority);
+        } else {
+            self.command(PointCommand {
+                priority,
+                value,
+                source: source.to_string(),
+                written_at: Utc::now(),
+                expires_at,
+            });
+        }
+    }
+
+    /// The command currently in control (highest priority)
+    pub fn active(&self) -> Option<&PointCommand> {
+        self.slots.iter().flatten().next()
+    }
+
+    /// The command at a specific priority
+    pub fn get(&self, priority: CommandPriority) -> Option<&PointCommand> {
+        self.slots[priority.index()].as_ref()
+    }
+
+    /// Effective value: the active command's value or the relinquish default
+    pub fn effective_value(&self, relinquish_default: &PropertyValue) -> PropertyValue {
+        self.active()
+            .map(|c| c.value.clone())
+            .unwrap_or_else(|| relinquish_default.clone())
+    }
+
+    /// Release every command that has expired at the given time
+    ///
+    /// Returns the released commands.
+    pub fn expire(&mut self, now: DateTime<Utc>) -> Vec<PointCommand> {
+        self.slots
+            .iter_mut()
+            .filter(|slot| slot.as_ref().is_some_and(|c| c.is_expired(now)))
+            .filter_map(Option::take)
+            .collect()
+    }
+
+    /// Earliest expiry among active commands
+    pub fn next_expiry(&self) -> Option<DateTime<Utc>> {
+        self.slots.iter().flatten().filter_map(|c| c.expires_at).min()
+    }
+
+    /// All active commands, highest priority first
+    pub fn commands(&self) -> Vec<PointCommand> {
+        self.slots.iter().flatten().cloned().collect()
+    }
+
+    /// Whether no command is active
+    pub fn is_empty(&self) -> bool {
+        self.slots.iter().all(Option::is_none)
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Tests
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn test_priority_arbitration_and_release() {
+        let mut array = PriorityArray::new();
+        let default = PropertyValue::Real(70.0);
+        assert_eq!(array.effective_value(&default), PropertyValue::Real(70.0));
+
+        array.write(CommandPriority::SCHEDULED, PropertyValue::Real(72.0), "blueprint:occupancy", None);
+        array.write(CommandPriority::MANUAL_OPERATOR, PropertyValue::Real(68.0), "user:alice", None);
+        array.write(CommandPriority::DEFAULT, PropertyValue::Real(75.0), "plugin:optimizer", None);
+
+        let active = array.active().unwrap();
+        assert_eq!(active.priority, CommandPriority::MANUAL_OPERATOR);
+        assert_eq!(active.source, "user:alice");
+        assert_eq!(array.effective_value(&default), PropertyValue::Real(68.0));
+        assert_eq!(array.commands().len(), 3);
+
+        // Releasing the override reverts to the next highest command
+        array.write(CommandPriority::MANUAL_OPERATOR, PropertyValue::Null, "user:alice", None);
+        assert_eq!(array.effective_value(&default), PropertyValue::Real(72.0));
+
+        array.relinquish(CommandPriority::SCHEDULED);
+        array.relinquish(CommandPriority::DEFAULT);
+        assert!(array.is_empty());
+        assert_eq!(array.effective_value(&default), PropertyValue::Real(70.0));
+    }
+
+    #[test]
+    fn test_priority_expiry() {
+        let now = Utc::now();
+        let mut array = PriorityArray::new();
+        array.write(CommandPriority::SCHEDULED, PropertyValue::Real(72.0), "schedule", None);
+        array.write(
+            CommandPriority::MANUAL_OPERATOR,
+            PropertyValue::Real(68.0),
+            "user:alice",
+            Some(now + Duration::hours(2)),
+        );
+        assert_eq!(array.next_expiry(), Some(now + Duration::hours(2)));
+
+        assert!(array.expire(now + Duration::hours(1)).is_empty());
+        let released = array.expire(now + Duration::hours(2));
+        assert_eq!(released.len(), 1);
+        assert_eq!(released[0].source, "user:alice");
+        assert_eq!(array.active().unwrap().value, PropertyValue::Real(72.0));
+        assert_eq!(array.next_expiry(), None);
+
+        // Expired commands are dropped when restoring
+        let restored = PriorityArray::from_commands(released, now + Duration::hours(3));
+        assert!(restored.is_empty());
+    }
+
+    #[test]
+    fn test_priority_validation() {
+        assert!(CommandPriority::new(0).is_err());
+        assert!(CommandPriority::new(17).is_err());
+        assert_eq!(CommandPriority::new(8).unwrap(), CommandPriority::MANUAL_OPERATOR);
+        assert!(serde_json::from_str::<CommandPriority>("20").is_err());
+        assert_eq!(serde_json::to_string(&CommandPriority::SCHEDULED).unwrap(), "12");
+    }
+}
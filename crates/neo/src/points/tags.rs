@@ -0,0 +1,836 @@
+// Semantic Tags - Project Haystack style tagging for points and equipment
+//
+// Points, equipment and sites carry a set of tags (markers, strings, numbers
+// and refs). Points reference their equipment with `equipRef`, equipment its
+// site with `siteRef` (and optionally a parent `equipRef`). Brick classes can
+// be recorded as a string tag (`brick: "Zone_Air_Temperature_Sensor"`).
+//
+// Tags are assigned by explicit config and by rules matched against the point
+// path / object name, and queried with Haystack filter expressions:
+//
+//   zone and air and temp and sensor
+//   equipRef->ahu and not occupied
+//   equipRef == @vav-3 and (temp or humidity)
+//   equipRef->siteRef == @hq and area > 10000
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::sync::Arc;
+
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use wildmatch::WildMatch;
+
+use crate::types::{Error, Result};
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Tag Values
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// A single tag value
+///
+/// JSON encoding: `true` is a marker, numbers are numbers, strings starting
+/// with `@` are refs and any other string is a string tag.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "serde_json::Value", into = "serde_json::Value")]
+pub enum TagValue {
+    /// Presence-only tag (e.g., `sensor`)
+    Marker,
+    /// String tag (e.g., `dis: "VAV-3 Zone Temp"`)
+    Str(String),
+    /// Numeric tag (e.g., `area: 12000`)
+    Number(f64),
+    /// Reference to another entity by id (e.g., `equipRef: @vav-3`)
+    Ref(String),
+}
+
+impl TryFrom<serde_json::Value> for TagValue {
+    type Error = String;
+
+    fn try_from(value: serde_json::Value) -> std::result::Result<Self, Self::Error> {
+        match value {
+            serde_json::Value::Bool(true) => Ok(TagValue::Marker),
+            serde_json::Value::Number(n) => n
+                .as_f64()
+                .map(TagValue::Number)
+                .ok_or_else(|| format!("Invalid number tag {}", n)),
+            serde_json::Value::String(s) => match s.strip_prefix('@') {
+                Some(id) if !id.is_empty() => Ok(TagValue::Ref(id.to_string())),
+                _ => Ok(TagValue::Str(s)),
+            },
+            other => Err(format!("Unsupported tag value {}", other)),
+        }
+    }
+}
+
+impl From<TagValue> for serde_json::Value {
+    fn from(value: TagValue) -> Self {
+        match value {
+            TagValue::Marker => serde_json::Value::Bool(true),
+            TagValue::Str(s) => serde_json::Value::String(s),
+            TagValue::Number(n) => serde_json::json!(n),
+            TagValue::Ref(id) => serde_json::Value::String(format!("@{}", id)),
+        }
+    }
+}
+
+impl fmt::Display for TagValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TagValue::Marker => write!(f, "M"),
+            TagValue::Str(s) => write!(f, "{:?}", s),
+            TagValue::Number(n) => write!(f, "{}", n),
+            TagValue::Ref(id) => write!(f, "@{}", id),
+        }
+    }
+}
+
+/// A set of tags keyed by tag name
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct TagSet(BTreeMap<String, TagValue>);
+
+impl TagSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a tag set from marker names (e.g., `["zone", "air", "temp", "sensor"]`)
+    pub fn markers<'a>(names: impl IntoIterator<Item = &'a str>) -> Self {
+        Self(
+            names
+                .into_iter()
+                .map(|n| (n.to_string(), TagValue::Marker))
+                .collect(),
+        )
+    }
+
+    pub fn get(&self, name: &str) -> Option<&TagValue> {
+        self.0.get(name)
+    }
+
+    pub fn has(&self, name: &str) -> bool {
+        self.0.contains_key(name)
+    }
+
+    pub fn insert(&mut self, name: impl Into<String>, value: TagValue) {
+        self.0.insert(name.into(), value);
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<TagValue> {
+        self.0.remove(name)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &TagValue)> {
+        self.0.iter()
+    }
+
+    /// Overlay another tag set on top of this one
+    pub fn merge(&mut self, other: &TagSet) {
+        for (name, value) in &other.0 {
+            self.0.insert(name.clone(), value.clone());
+        }
+    }
+
+    /// The equipment this entity belongs to
+    pub fn equip_ref(&self) -> Option<&str> {
+        match self.get("equipRef") {
+            Some(TagValue::Ref(id)) => Some(id),
+            _ => None,
+        }
+    }
+
+    /// The site this entity belongs to
+    pub fn site_ref(&self) -> Option<&str> {
+        match self.get("siteRef") {
+            Some(TagValue::Ref(id)) => Some(id),
+            _ => None,
+        }
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Configuration
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// A site or piece of equipment that points reference
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TagEntity {
+    /// Entity id, referenced as `@id` (e.g., "ahu-1")
+    pub id: String,
+    /// Tags (e.g., `{"equip": true, "ahu": true, "siteRef": "@hq"}`)
+    #[serde(default)]
+    pub tags: TagSet,
+}
+
+/// Rule that tags every point matching its patterns
+///
+/// All given patterns must match. For example, `object_name: "*ZN-T*"` with
+/// tags `{zone, air, temp, sensor}` tags every zone air temp sensor.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TagRule {
+    /// Glob pattern against the point path
+    #[serde(default)]
+    pub path: Option<String>,
+    /// Glob pattern against the BACnet object name
+    #[serde(default)]
+    pub object_name: Option<String>,
+    /// Glob pattern against the point description
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Tags applied to matching points
+    pub tags: TagSet,
+}
+
+impl TagRule {
+    fn matches(&self, point: &PointIdentity<'_>) -> bool {
+        fn check(pattern: &Option<String>, value: Option<&str>) -> bool {
+            match pattern {
+                None => true,
+                Some(p) => value.map(|v| WildMatch::new(p).matches(v)).unwrap_or(false),
+            }
+        }
+
+        (self.path.is_some() || self.object_name.is_some() || self.description.is_some())
+            && check(&self.path, Some(point.path))
+            && check(&self.object_name, point.object_name)
+            && check(&self.description, point.description)
+    }
+}
+
+/// Tag configuration (loaded from `data/tags.json`)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TagConfig {
+    /// Sites and equipment
+    #[serde(default)]
+    pub entities: Vec<TagEntity>,
+    /// Rules applied in order (later rules override earlier ones)
+    #[serde(default)]
+    pub rules: Vec<TagRule>,
+    /// Explicit tags per point path (override rules)
+    #[serde(default)]
+    pub points: HashMap<String, TagSet>,
+}
+
+/// What is known about a point when its tags are resolved
+#[derive(Debug, Clone, Copy)]
+pub struct PointIdentity<'a> {
+    pub path: &'a str,
+    pub object_name: Option<&'a str>,
+    pub description: Option<&'a str>,
+}
+
+impl<'a> PointIdentity<'a> {
+    pub fn from_path(path: &'a str) -> Self {
+        Self {
+            path,
+            object_name: None,
+            description: None,
+        }
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Tag Filters
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CompareOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum FilterNode {
+    /// Tag path is present (`equipRef->ahu`)
+    Has(Vec<String>),
+    /// Tag path is missing
+    Missing(Vec<String>),
+    /// Tag path compared to a value
+    Compare(Vec<String>, CompareOp, TagValue),
+    And(Box<FilterNode>, Box<FilterNode>),
+    Or(Box<FilterNode>, Box<FilterNode>),
+}
+
+/// A parsed Haystack-style tag filter
+#[derive(Debug, Clone, PartialEq)]
+pub struct TagFilter {
+    source: String,
+    root: FilterNode,
+}
+
+impl fmt::Display for TagFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.source)
+    }
+}
+
+impl std::str::FromStr for TagFilter {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::parse(s)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum FilterToken {
+    Name(String),
+    Value(TagValue),
+    Arrow,
+    Op(CompareOp),
+    LParen,
+    RParen,
+}
+
+fn tokenize_filter(source: &str) -> Result<Vec<FilterToken>> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    let err = |msg: String| Error::Config(format!("Invalid tag filter '{}': {}", source, msg));
+
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+        match c {
+            c if c.is_whitespace() => i += 1,
+            '(' => {
+                tokens.push(FilterToken::LParen);
+                i += 1;
+            }
+            ')' => {
+                tokens.push(FilterToken::RParen);
+                i += 1;
+            }
+            '-' if next == Some('>') => {
+                tokens.push(FilterToken::Arrow);
+                i += 2;
+            }
+            '=' if next == Some('=') => {
+                tokens.push(FilterToken::Op(CompareOp::Eq));
+                i += 2;
+            }
+            '!' if next == Some('=') => {
+                tokens.push(FilterToken::Op(CompareOp::Ne));
+                i += 2;
+            }
+            '<' | '>' => {
+                let op = match (c, next) {
+                    ('<', Some('=')) => CompareOp::Le,
+                    ('>', Some('=')) => CompareOp::Ge,
+                    ('<', _) => CompareOp::Lt,
+                    _ => CompareOp::Gt,
+                };
+                i += if next == Some('=') { 2 } else { 1 };
+                tokens.push(FilterToken::Op(op));
+            }
+            '"' => {
+                let end = chars[i + 1..]
+                    .iter()
+                    .position(|&c| c == '"')
+                    .map(|p| i + 1 + p)
+                    .ok_or_else(|| err("unterminated string".to_string()))?;
+                tokens.push(FilterToken::Value(TagValue::Str(
+                    chars[i + 1..end].iter().collect(),
+                )));
+                i = end + 1;
+            }
+            '@' => {
+                let start = i + 1;
+                i = start;
+                while i < chars.len() && !chars[i].is_whitespace() && chars[i] != ')' {
+                    i += 1;
+                }
+                if i == start {
+                    return Err(err("empty ref".to_string()));
+                }
+                tokens.push(FilterToken::Value(TagValue::Ref(
+                    chars[start..i].iter().collect(),
+                )));
+            }
+            c if c.is_ascii_digit() || (c == '-' && next.is_some_and(|n| n.is_ascii_digit())) => {
+                let start = i;
+                i += 1;
+                while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                    i += 1;
+                }
+                let text: String = chars[start..i].iter().collect();
+                let n = text
+                    .parse::<f64>()
+                    .map_err(|_| err(format!("invalid number '{}'", text)))?;
+                tokens.push(FilterToken::Value(TagValue::Number(n)));
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let start = i;
+                while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                    i += 1;
+                }
+                tokens.push(FilterToken::Name(chars[start..i].iter().collect()));
+            }
+            other => return Err(err(format!("unexpected character '{}'", other))),
+        }
+    }
+
+    Ok(tokens)
+}
+
+struct FilterParser<'a> {
+    source: &'a str,
+    tokens: Vec<FilterToken>,
+    pos: usize,
+}
+
+impl FilterParser<'_> {
+    fn err(&self, msg: &str) -> Error {
+        Error::Config(format!("Invalid tag filter '{}': {}", self.source, msg))
+    }
+
+    fn peek(&self) -> Option<&FilterToken> {
+        self.tokens.get(self.pos)
+    }
+
+    fn peek_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some(FilterToken::Name(n)) if n == keyword)
+    }
+
+    fn parse_or(&mut self) -> Result<FilterNode> {
+        let mut lhs = self.parse_and()?;
+        while self.peek_keyword("or") {
+            self.pos += 1;
+            let rhs = self.parse_and()?;
+            lhs = FilterNode::Or(Box::new(lhs), Box::new(rhs));
+        }
+        Ok(lhs)
+    }
+
+    fn parse_and(&mut self) -> Result<FilterNode> {
+        let mut lhs = self.parse_term()?;
+        while self.peek_keyword("and") {
+            self.pos += 1;
+            let rhs = self.parse_term()?;
+            lhs = FilterNode::And(Box::new(lhs), Box::new(rhs));
+        }
+        Ok(lhs)
+    }
+
+    fn parse_term(&mut self) -> Result<FilterNode> {
+        if self.peek() == Some(&FilterToken::LParen) {
+            self.pos += 1;
+            let inner = self.parse_or()?;
+            if self.peek() != Some(&FilterToken::RParen) {
+                return Err(self.err("expected ')'"));
+            }
+            self.pos += 1;
+            return Ok(inner);
+        }
+
+        if self.peek_keyword("not") {
+            self.pos += 1;
+            return Ok(FilterNode::Missing(self.parse_path()?));
+        }
+
+        let path = self.parse_path()?;
+        if let Some(FilterToken::Op(op)) = self.peek().cloned() {
+            self.pos += 1;
+            let value = match self.tokens.get(self.pos).cloned() {
+                Some(FilterToken::Value(v)) => v,
+                Some(FilterToken::Name(n)) if n == "true" => TagValue::Marker,
+                _ => return Err(self.err("expected a value after comparison")),
+            };
+            self.pos += 1;
+            return Ok(FilterNode::Compare(path, op, value));
+        }
+
+        Ok(FilterNode::Has(path))
+    }
+
+    fn parse_path(&mut self) -> Result<Vec<String>> {
+        let mut path = Vec::new();
+        loop {
+            match self.tokens.get(self.pos) {
+                Some(FilterToken::Name(n)) if !matches!(n.as_str(), "and" | "or" | "not") => {
+                    path.push(n.clone());
+                    self.pos += 1;
+                }
+                _ => return Err(self.err("expected a tag name")),
+            }
+            if self.peek() == Some(&FilterToken::Arrow) {
+                self.pos += 1;
+            } else {
+                return Ok(path);
+            }
+        }
+    }
+}
+
+impl TagFilter {
+    /// Parse a filter expression
+    pub fn parse(source: &str) -> Result<Self> {
+        let mut parser = FilterParser {
+            source,
+            tokens: tokenize_filter(source)?,
+            pos: 0,
+        };
+        let root = parser.parse_or()?;
+        if parser.pos < parser.tokens.len() {
+            return Err(parser.err("unexpected trailing input"));
+        }
+        Ok(Self {
+            source: source.to_string(),
+            root,
+        })
+    }
+
+    /// Evaluate the filter against a tag set, resolving `->` through `resolve`
+    pub fn matches<'a>(&self, tags: &'a TagSet, resolve: &dyn Fn(&str) -> Option<&'a TagSet>) -> bool {
+        eval_filter(&self.root, tags, resolve)
+    }
+}
+
+fn resolve_path<'a>(
+    tags: &'a TagSet,
+    path: &[String],
+    resolve: &dyn Fn(&str) -> Option<&'a TagSet>,
+) -> Option<&'a TagValue> {
+    let (last, refs) = path.split_last()?;
+    let mut current = tags;
+    for name in refs {
+        match current.get(name)? {
+            TagValue::Ref(id) => current = resolve(id)?,
+            _ => return None,
+        }
+    }
+    current.get(last)
+}
+
+fn compare(actual: &TagValue, op: CompareOp, expected: &TagValue) -> bool {
+    use std::cmp::Ordering;
+
+    let ordering = match (actual, expected) {
+        (TagValue::Number(a), TagValue::Number(b)) => a.partial_cmp(b),
+        (TagValue::Str(a), TagValue::Str(b)) => Some(a.cmp(b)),
+        (TagValue::Ref(a), TagValue::Ref(b)) => Some(a.cmp(b)),
+        (TagValue::Marker, TagValue::Marker) => Some(Ordering::Equal),
+        _ => None,
+    };
+
+    match (ordering, op) {
+        (None, CompareOp::Ne) => true,
+        (None, _) => false,
+        (Some(o), CompareOp::Eq) => o == Ordering::Equal,
+        (Some(o), CompareOp::Ne) => o != Ordering::Equal,
+        (Some(o), CompareOp::Lt) => o == Ordering::Less,
+        (Some(o), CompareOp::Le) => o != Ordering::Greater,
+        (Some(o), CompareOp::Gt) => o == Ordering::Greater,
+        (Some(o), CompareOp::Ge) => o != Ordering::Less,
+    }
+}
+
+fn eval_filter<'a>(
+    node: &FilterNode,
+    tags: &'a TagSet,
+    resolve: &dyn Fn(&str) -> Option<&'a TagSet>,
+) -> bool {
+    match node {
+        FilterNode::Has(path) => resolve_path(tags, path, resolve).is_some(),
+        FilterNode::Missing(path) => resolve_path(tags, path, resolve).is_none(),
+        FilterNode::Compare(path, op, expected) => resolve_path(tags, path, resolve)
+            .map(|actual| compare(actual, *op, expected))
+            .unwrap_or(false),
+        FilterNode::And(a, b) => eval_filter(a, tags, resolve) && eval_filter(b, tags, resolve),
+        FilterNode::Or(a, b) => eval_filter(a, tags, resolve) || eval_filter(b, tags, resolve),
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Tag Registry
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Tags for all known points, sites and equipment
+#[derive(Debug, Default)]
+pub struct TagRegistry {
+    config: TagConfig,
+    /// Sites and equipment keyed by id
+    entities: HashMap<String, TagSet>,
+    /// Resolved tags keyed by point path
+    points: HashMap<String, TagSet>,
+    /// Object name and description of registered points, to re-resolve them
+    /// when the configuration changes
+    identities: HashMap<String, (Option<String>, Option<String>)>,
+}
+
+impl TagRegistry {
+    pub fn new(config: TagConfig) -> Self {
+        let mut registry = Self::default();
+        registry.set_config(config);
+        registry
+    }
+
+    /// Replace the configuration and re-resolve all known points
+    pub fn set_config(&mut self, config: TagConfig) {
+        self.entities = config
+            .entities
+            .iter()
+            .map(|e| (e.id.clone(), e.tags.clone()))
+            .collect();
+        self.config = config;
+
+        let paths: Vec<String> = self.points.keys().cloned().collect();
+        for path in paths {
+            let (object_name, description) = self
+                .identities
+                .get(&path)
+                .map(|(name, description)| (name.as_deref(), description.as_deref()))
+                .unwrap_or_default();
+            let tags = self.resolve(&PointIdentity {
+                path: &path,
+                object_name,
+                description,
+            });
+            self.points.insert(path, tags);
+        }
+        for path in self.config.points.keys().cloned().collect::<Vec<_>>() {
+            let tags = self.resolve(&PointIdentity::from_path(&path));
+            self.points.insert(path, tags);
+        }
+    }
+
+    pub fn config(&self) -> &TagConfig {
+        &self.config
+    }
+
+    /// Compute the tags of a point from the rules and explicit config
+    fn resolve(&self, point: &PointIdentity<'_>) -> TagSet {
+        let mut tags = TagSet::markers(["point"]);
+        for rule in &self.config.rules {
+            if rule.matches(point) {
+                tags.merge(&rule.tags);
+            }
+        }
+        if let Some(explicit) = self.config.points.get(point.path) {
+            tags.merge(explicit);
+        }
+        tags
+    }
+
+    /// Register a point (or refresh its metadata) and return its resolved tags
+    pub fn register_point(&mut self, point: PointIdentity<'_>) -> TagSet {
+        let mut tags = self.resolve(&point);
+        // Keep tags set at runtime that no rule or config provides
+        if let Some(existing) = self.points.get(point.path) {
+            for (name, value) in existing.iter() {
+                if !tags.has(name) {
+                    tags.insert(name.clone(), value.clone());
+                }
+            }
+        }
+        self.points.insert(point.path.to_string(), tags.clone());
+        self.identities.insert(
+            point.path.to_string(),
+            (
+                point.object_name.map(str::to_string),
+                point.description.map(str::to_string),
+            ),
+        );
+        tags
+    }
+
+    /// Whether a point has been registered
+    pub fn contains_point(&self, path: &str) -> bool {
+        self.points.contains_key(path)
+    }
+
+    /// Set tags on a point at runtime (merged over its current tags)
+    pub fn set_point_tags(&mut self, path: &str, tags: &TagSet) {
+        self.points
+            .entry(path.to_string())
+            .or_insert_with(|| TagSet::markers(["point"]))
+            .merge(tags);
+    }
+
+    pub fn point_tags(&self, path: &str) -> Option<&TagSet> {
+        self.points.get(path)
+    }
+
+    pub fn entity(&self, id: &str) -> Option<&TagSet> {
+        self.entities.get(id)
+    }
+
+    /// Whether a point matches a filter
+    pub fn matches(&self, path: &str, filter: &TagFilter) -> bool {
+        self.points
+            .get(path)
+            .map(|tags| filter.matches(tags, &|id| self.entities.get(id)))
+            .unwrap_or(false)
+    }
+
+    /// All point paths matching a filter, sorted
+    pub fn query(&self, filter: &TagFilter) -> Vec<String> {
+        let mut paths: Vec<String> = self
+            .points
+            .iter()
+            .filter(|(_, tags)| filter.matches(tags, &|id| self.entities.get(id)))
+            .map(|(path, _)| path.clone())
+            .collect();
+        paths.sort();
+        paths
+    }
+
+    pub fn point_count(&self) -> usize {
+        self.points.len()
+    }
+}
+
+/// Shared, thread-safe handle to the tag registry
+///
+/// Cloned into the services that resolve tag filters (alarms, history,
+/// blueprints) and into device actors that register discovered points.
+#[derive(Debug, Clone, Default)]
+pub struct TagStore {
+    inner: Arc<RwLock<TagRegistry>>,
+}
+
+impl TagStore {
+    pub fn new(config: TagConfig) -> Self {
+        Self {
+            inner: Arc::new(RwLock::new(TagRegistry::new(config))),
+        }
+    }
+
+    /// Load the tag configuration from a JSON file
+    pub fn load(path: impl AsRef<std::path::Path>) -> Result<Self> {
+        let contents = std::fs::read_to_string(path.as_ref())?;
+        let config: TagConfig = serde_json::from_str(&contents)
+            .map_err(|e| Error::Config(format!("Invalid tag config: {}", e)))?;
+        Ok(Self::new(config))
+    }
+
+    pub fn register_point(&self, point: PointIdentity<'_>) -> TagSet {
+        self.inner.write().register_point(point)
+    }
+
+    /// Register a point by path only, if it is not known yet
+    pub fn ensure_point(&self, path: &str) {
+        if !self.inner.read().contains_point(path) {
+            self.inner.write().register_point(PointIdentity::from_path(path));
+        }
+    }
+
+    pub fn set_point_tags(&self, path: &str, tags: &TagSet) {
+        self.inner.write().set_point_tags(path, tags);
+    }
+
+    pub fn point_tags(&self, path: &str) -> Option<TagSet> {
+        self.inner.read().point_tags(path).cloned()
+    }
+
+    pub fn matches(&self, path: &str, filter: &TagFilter) -> bool {
+        self.inner.read().matches(path, filter)
+    }
+
+    pub fn query(&self, filter: &TagFilter) -> Vec<String> {
+        self.inner.read().query(filter)
+    }
+
+    pub fn config(&self) -> TagConfig {
+        self.inner.read().config().clone()
+    }
+
+    pub fn set_config(&self, config: TagConfig) {
+        self.inner.write().set_config(config);
+    }
+
+    pub fn point_count(&self) -> usize {
+        self.inner.read().point_count()
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Tests
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn store() -> TagStore {
+        let config: TagConfig = serde_json::from_value(serde_json::json!({
+            "entities": [
+                {"id": "hq", "tags": {"site": true, "area": 12000}},
+                {"id": "ahu-1", "tags": {"equip": true, "ahu": true, "siteRef": "@hq"}},
+                {"id": "vav-3", "tags": {"equip": true, "vav": true, "equipRef": "@ahu-1", "siteRef": "@hq"}}
+            ],
+            "rules": [
+                {"object_name": "*ZN-T*", "tags": {"zone": true, "air": true, "temp": true, "sensor": true}},
+                {"path": "net/VAV-3/*", "tags": {"equipRef": "@vav-3"}}
+            ],
+            "points": {
+                "net/VAV-3/AV:1": {"zone": true, "air": true, "temp": true, "sp": true, "dis": "VAV-3 Setpoint"}
+            }
+        }))
+        .unwrap();
+        TagStore::new(config)
+    }
+
+    #[test]
+    fn test_rules_and_explicit_tags() {
+        let store = store();
+        let tags = store.register_point(PointIdentity {
+            path: "net/VAV-3/AI:1",
+            object_name: Some("VAV3-ZN-T"),
+            description: None,
+        });
+        assert!(tags.has("sensor"));
+        assert_eq!(tags.equip_ref(), Some("vav-3"));
+
+        // Reloading the config re-resolves from the object name, not just the path
+        store.set_config(store.config());
+        assert!(store.point_tags("net/VAV-3/AI:1").unwrap().has("sensor"));
+
+        let tags = store.point_tags("net/VAV-3/AV:1").unwrap();
+        assert!(tags.has("sp"));
+        assert_eq!(tags.get("dis"), Some(&TagValue::Str("VAV-3 Setpoint".to_string())));
+    }
+
+    #[test]
+    fn test_filter_queries() {
+        let store = store();
+        store.register_point(PointIdentity {
+            path: "net/VAV-3/AI:1",
+            object_name: Some("VAV3-ZN-T"),
+            description: None,
+        });
+        store.register_point(PointIdentity {
+            path: "net/AHU-1/AI:1",
+            object_name: Some("AHU1-DA-T"),
+            description: None,
+        });
+
+        let query = |f: &str| store.query(&TagFilter::parse(f).unwrap());
+
+        assert_eq!(query("zone and air and temp and sensor"), vec!["net/VAV-3/AI:1"]);
+        assert_eq!(query("temp and not sensor"), vec!["net/VAV-3/AV:1"]);
+        assert_eq!(query("equipRef == @vav-3 and (sp or sensor)").len(), 2);
+        assert_eq!(query("equipRef->equipRef == @ahu-1").len(), 2);
+        assert_eq!(query("equipRef->siteRef->area > 10000").len(), 2);
+        assert_eq!(query("dis == \"VAV-3 Setpoint\""), vec!["net/VAV-3/AV:1"]);
+        assert_eq!(query("point").len(), 3);
+    }
+
+    #[test]
+    fn test_filter_parse_errors() {
+        assert!(TagFilter::parse("zone and").is_err());
+        assert!(TagFilter::parse("(zone or air").is_err());
+        assert!(TagFilter::parse("equipRef ==").is_err());
+        assert!(TagFilter::parse("zone & air").is_err());
+    }
+}
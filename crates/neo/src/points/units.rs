@@ -0,0 +1,625 @@
+// Engineering Units - Typed unit catalog and conversions
+//
+// Covers the BACnet EngineeringUnits enumeration (ASHRAE 135, values 0-247).
+// Every unit belongs to a physical quantity and carries a linear conversion
+// to that quantity's reference unit (SI), so values convert between any two
+// units of the same quantity:
+//
+//   °F <-> °C <-> K, CFM <-> L/s <-> m³/h, kW <-> BTU/h <-> tons,
+//   Pa <-> inH2O <-> psi, ...
+//
+// Units serialize as their BACnet name ("degrees-fahrenheit") and parse from
+// the name, the symbol ("°F") or a common alias ("degF", "cfm").
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::types::PropertyValue;
+
+/// Physical quantity measured by a unit
+///
+/// Only units of the same quantity can be converted. Units with quantity
+/// `Other` have no conversions (beyond to themselves).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Quantity {
+    Temperature,
+    TemperatureDifference,
+    TemperatureRate,
+    DegreeDays,
+    Pressure,
+    Power,
+    Energy,
+    VolumetricFlow,
+    Volume,
+    Length,
+    Area,
+    Mass,
+    MassFlow,
+    Velocity,
+    Time,
+    Frequency,
+    Angle,
+    AngularVelocity,
+    Current,
+    Voltage,
+    Resistance,
+    Conductance,
+    ApparentPower,
+    ReactivePower,
+    ApparentEnergy,
+    ReactiveEnergy,
+    Enthalpy,
+    Entropy,
+    Illuminance,
+    PowerDensity,
+    EnergyDensity,
+    Ratio,
+    RelativeHumidity,
+    HumidityRatio,
+    MassConcentration,
+    Radioactivity,
+    AbsorbedDose,
+    DoseEquivalent,
+    Obscuration,
+    Dimensionless,
+    Other,
+}
+
+/// Errors raised when converting between units
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum UnitError {
+    /// The units measure different quantities (e.g., °F and kW)
+    #[error("Cannot convert {from} to {to}: incompatible units")]
+    Incompatible {
+        from: EngineeringUnit,
+        to: EngineeringUnit,
+    },
+    /// The value is not numeric
+    #[error("Cannot convert non-numeric value {0}")]
+    NotNumeric(String),
+    /// The text does not name a known unit
+    #[error("Unknown engineering unit '{0}'")]
+    Unknown(String),
+}
+
+const FT: f64 = 0.3048;
+const FT2: f64 = FT * FT;
+const FT3: f64 = FT * FT * FT;
+const US_GAL: f64 = 3.785411784e-3;
+const IMP_GAL: f64 = 4.54609e-3;
+const LB: f64 = 0.45359237;
+const SHORT_TON: f64 = 2000.0 * LB;
+const BTU: f64 = 1055.05585262;
+const REFRIGERATION_TON: f64 = 12000.0 * BTU / 3600.0;
+const F_SCALE: f64 = 5.0 / 9.0;
+const DEG: f64 = std::f64::consts::PI / 180.0;
+
+macro_rules! engineering_units {
+    ($( $variant:ident = $id:literal, $name:literal, $symbol:literal, $quantity:ident, $scale:expr, $offset:expr; )*) => {
+        /// BACnet engineering unit
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub enum EngineeringUnit {
+            $( $variant, )*
+        }
+
+        impl EngineeringUnit {
+            /// Every unit in the catalog
+            pub const ALL: &'static [EngineeringUnit] = &[ $( EngineeringUnit::$variant, )* ];
+
+            /// BACnet EngineeringUnits enumeration value
+            pub fn bacnet_id(self) -> u32 {
+                match self {
+                    $( EngineeringUnit::$variant => $id, )*
+                }
+            }
+
+            /// Look up a unit by its BACnet enumeration value
+            pub fn from_bacnet_id(id: u32) -> Option<Self> {
+                match id {
+                    $( $id => Some(EngineeringUnit::$variant), )*
+                    _ => None,
+                }
+            }
+
+            /// BACnet name (e.g., "degrees-fahrenheit")
+            pub fn name(self) -> &'static str {
+                match self {
+                    $( EngineeringUnit::$variant => $name, )*
+                }
+            }
+
+            /// Display symbol (e.g., "°F")
+            pub fn symbol(self) -> &'static str {
+                match self {
+                    $( EngineeringUnit::$variant => $symbol, )*
+                }
+            }
+
+            /// Quantity measured by this unit
+            pub fn quantity(self) -> Quantity {
+                match self {
+                    $( EngineeringUnit::$variant => Quantity::$quantity, )*
+                }
+            }
+
+            /// (scale, offset) such that `reference = value * scale + offset`
+            fn factors(self) -> (f64, f64) {
+                match self {
+                    $( EngineeringUnit::$variant => ($scale, $offset), )*
+                }
+            }
+        }
+    };
+}
+
+engineering_units! {
+    // Acceleration
+    MetersPerSecondPerSecond = 166, "meters-per-second-per-second", "m/s²", Other, 1.0, 0.0;
+
+    // Area
+    SquareMeters = 0, "square-meters", "m²", Area, 1.0, 0.0;
+    SquareCentimeters = 116, "square-centimeters", "cm²", Area, 1e-4, 0.0;
+    SquareFeet = 1, "square-feet", "ft²", Area, FT2, 0.0;
+    SquareInches = 115, "square-inches", "in²", Area, 6.4516e-4, 0.0;
+
+    // Currency
+    Currency1 = 105, "currency1", "¤1", Other, 1.0, 0.0;
+    Currency2 = 106, "currency2", "¤2", Other, 1.0, 0.0;
+    Currency3 = 107, "currency3", "¤3", Other, 1.0, 0.0;
+    Currency4 = 108, "currency4", "¤4", Other, 1.0, 0.0;
+    Currency5 = 109, "currency5", "¤5", Other, 1.0, 0.0;
+    Currency6 = 110, "currency6", "¤6", Other, 1.0, 0.0;
+    Currency7 = 111, "currency7", "¤7", Other, 1.0, 0.0;
+    Currency8 = 112, "currency8", "¤8", Other, 1.0, 0.0;
+    Currency9 = 113, "currency9", "¤9", Other, 1.0, 0.0;
+    Currency10 = 114, "currency10", "¤10", Other, 1.0, 0.0;
+
+    // Electrical
+    Milliamperes = 2, "milliamperes", "mA", Current, 1e-3, 0.0;
+    Amperes = 3, "amperes", "A", Current, 1.0, 0.0;
+    AmperesPerMeter = 167, "amperes-per-meter", "A/m", Other, 1.0, 0.0;
+    AmperesPerSquareMeter = 168, "amperes-per-square-meter", "A/m²", Other, 1.0, 0.0;
+    AmpereSquareMeters = 169, "ampere-square-meters", "A·m²", Other, 1.0, 0.0;
+    Decibels = 199, "decibels", "dB", Other, 1.0, 0.0;
+    DecibelsMillivolt = 200, "decibels-millivolt", "dBmV", Other, 1.0, 0.0;
+    DecibelsVolt = 201, "decibels-volt", "dBV", Other, 1.0, 0.0;
+    Farads = 170, "farads", "F", Other, 1.0, 0.0;
+    Henrys = 171, "henrys", "H", Other, 1.0, 0.0;
+    Ohms = 4, "ohms", "Ω", Resistance, 1.0, 0.0;
+    OhmMeterSquaredPerMeter = 237, "ohm-meter-squared-per-meter", "Ω·m²/m", Other, 1.0, 0.0;
+    OhmMeters = 172, "ohm-meters", "Ω·m", Other, 1.0, 0.0;
+    Milliohms = 145, "milliohms", "mΩ", Resistance, 1e-3, 0.0;
+    Kilohms = 122, "kilohms", "kΩ", Resistance, 1e3, 0.0;
+    Megohms = 123, "megohms", "MΩ", Resistance, 1e6, 0.0;
+    Microsiemens = 190, "microsiemens", "µS", Conductance, 1e-6, 0.0;
+    Millisiemens = 202, "millisiemens", "mS", Conductance, 1e-3, 0.0;
+    Siemens = 173, "siemens", "S", Conductance, 1.0, 0.0;
+    SiemensPerMeter = 174, "siemens-per-meter", "S/m", Other, 1.0, 0.0;
+    Teslas = 175, "teslas", "T", Other, 1.0, 0.0;
+    Volts = 5, "volts", "V", Voltage, 1.0, 0.0;
+    Millivolts = 124, "millivolts", "mV", Voltage, 1e-3, 0.0;
+    Kilovolts = 6, "kilovolts", "kV", Voltage, 1e3, 0.0;
+    Megavolts = 7, "megavolts", "MV", Voltage, 1e6, 0.0;
+    VoltAmperes = 8, "volt-amperes", "VA", ApparentPower, 1.0, 0.0;
+    KilovoltAmperes = 9, "kilovolt-amperes", "kVA", ApparentPower, 1e3, 0.0;
+    MegavoltAmperes = 10, "megavolt-amperes", "MVA", ApparentPower, 1e6, 0.0;
+    VoltAmperesReactive = 11, "volt-amperes-reactive", "var", ReactivePower, 1.0, 0.0;
+    KilovoltAmperesReactive = 12, "kilovolt-amperes-reactive", "kvar", ReactivePower, 1e3, 0.0;
+    MegavoltAmperesReactive = 13, "megavolt-amperes-reactive", "Mvar", ReactivePower, 1e6, 0.0;
+    VoltsPerDegreeKelvin = 176, "volts-per-degree-kelvin", "V/K", Other, 1.0, 0.0;
+    VoltsPerMeter = 177, "volts-per-meter", "V/m", Other, 1.0, 0.0;
+    DegreesPhase = 14, "degrees-phase", "°φ", Angle, DEG, 0.0;
+    PowerFactor = 15, "power-factor", "PF", Other, 1.0, 0.0;
+    Webers = 178, "webers", "Wb", Other, 1.0, 0.0;
+    AmpereSeconds = 238, "ampere-seconds", "A·s", Other, 1.0, 0.0;
+    VoltAmpereHours = 239, "volt-ampere-hours", "VAh", ApparentEnergy, 1.0, 0.0;
+    KilovoltAmpereHours = 240, "kilovolt-ampere-hours", "kVAh", ApparentEnergy, 1e3, 0.0;
+    MegavoltAmpereHours = 241, "megavolt-ampere-hours", "MVAh", ApparentEnergy, 1e6, 0.0;
+    VoltAmpereHoursReactive = 242, "volt-ampere-hours-reactive", "varh", ReactiveEnergy, 1.0, 0.0;
+    KilovoltAmpereHoursReactive = 243, "kilovolt-ampere-hours-reactive", "kvarh", ReactiveEnergy, 1e3, 0.0;
+    MegavoltAmpereHoursReactive = 244, "megavolt-ampere-hours-reactive", "Mvarh", ReactiveEnergy, 1e6, 0.0;
+    VoltSquareHours = 245, "volt-square-hours", "V²h", Other, 1.0, 0.0;
+    AmpereSquareHours = 246, "ampere-square-hours", "A²h", Other, 1.0, 0.0;
+
+    // Energy
+    Joules = 16, "joules", "J", Energy, 1.0, 0.0;
+    Kilojoules = 17, "kilojoules", "kJ", Energy, 1e3, 0.0;
+    Megajoules = 126, "megajoules", "MJ", Energy, 1e6, 0.0;
+    WattHours = 18, "watt-hours", "Wh", Energy, 3600.0, 0.0;
+    KilowattHours = 19, "kilowatt-hours", "kWh", Energy, 3.6e6, 0.0;
+    MegawattHours = 146, "megawatt-hours", "MWh", Energy, 3.6e9, 0.0;
+    WattHoursReactive = 203, "watt-hours-reactive", "Whr", ReactiveEnergy, 1.0, 0.0;
+    KilowattHoursReactive = 204, "kilowatt-hours-reactive", "kWhr", ReactiveEnergy, 1e3, 0.0;
+    MegawattHoursReactive = 205, "megawatt-hours-reactive", "MWhr", ReactiveEnergy, 1e6, 0.0;
+    Btus = 20, "btus", "BTU", Energy, BTU, 0.0;
+    KiloBtus = 147, "kilo-btus", "kBTU", Energy, BTU * 1e3, 0.0;
+    MegaBtus = 148, "mega-btus", "MBTU", Energy, BTU * 1e6, 0.0;
+    Therms = 21, "therms", "thm", Energy, BTU * 1e5, 0.0;
+    TonHours = 22, "ton-hours", "ton·h", Energy, REFRIGERATION_TON * 3600.0, 0.0;
+
+    // Enthalpy
+    JoulesPerKilogramDryAir = 23, "joules-per-kilogram-dry-air", "J/kg", Enthalpy, 1.0, 0.0;
+    KilojoulesPerKilogramDryAir = 149, "kilojoules-per-kilogram-dry-air", "kJ/kg", Enthalpy, 1e3, 0.0;
+    MegajoulesPerKilogramDryAir = 150, "megajoules-per-kilogram-dry-air", "MJ/kg", Enthalpy, 1e6, 0.0;
+    KilojoulesPerKilogram = 125, "kilojoules-per-kilogram", "kJ/kg", Enthalpy, 1e3, 0.0;
+    BtusPerPoundDryAir = 24, "btus-per-pound-dry-air", "BTU/lb", Enthalpy, BTU / LB, 0.0;
+    BtusPerPound = 117, "btus-per-pound", "BTU/lb", Enthalpy, BTU / LB, 0.0;
+
+    // Entropy
+    JoulesPerDegreeKelvin = 127, "joules-per-degree-kelvin", "J/K", Entropy, 1.0, 0.0;
+    KilojoulesPerDegreeKelvin = 151, "kilojoules-per-degree-kelvin", "kJ/K", Entropy, 1e3, 0.0;
+    MegajoulesPerDegreeKelvin = 152, "megajoules-per-degree-kelvin", "MJ/K", Entropy, 1e6, 0.0;
+    JoulesPerKilogramDegreeKelvin = 128, "joules-per-kilogram-degree-kelvin", "J/(kg·K)", Other, 1.0, 0.0;
+
+    // Force
+    Newton = 153, "newton", "N", Other, 1.0, 0.0;
+
+    // Frequency
+    CyclesPerHour = 25, "cycles-per-hour", "cph", Frequency, 1.0 / 3600.0, 0.0;
+    CyclesPerMinute = 26, "cycles-per-minute", "cpm", Frequency, 1.0 / 60.0, 0.0;
+    Hertz = 27, "hertz", "Hz", Frequency, 1.0, 0.0;
+    Kilohertz = 129, "kilohertz", "kHz", Frequency, 1e3, 0.0;
+    Megahertz = 130, "megahertz", "MHz", Frequency, 1e6, 0.0;
+    PerHour = 131, "per-hour", "/h", Frequency, 1.0 / 3600.0, 0.0;
+
+    // Humidity
+    GramsOfWaterPerKilogramDryAir = 28, "grams-of-water-per-kilogram-dry-air", "g/kg", HumidityRatio, 1e-3, 0.0;
+    PercentRelativeHumidity = 29, "percent-relative-humidity", "%RH", RelativeHumidity, 1.0, 0.0;
+
+    // Length
+    Micrometers = 194, "micrometers", "µm", Length, 1e-6, 0.0;
+    Millimeters = 30, "millimeters", "mm", Length, 1e-3, 0.0;
+    Centimeters = 118, "centimeters", "cm", Length, 1e-2, 0.0;
+    Kilometers = 193, "kilometers", "km", Length, 1e3, 0.0;
+    Meters = 31, "meters", "m", Length, 1.0, 0.0;
+    Inches = 32, "inches", "in", Length, 0.0254, 0.0;
+    Feet = 33, "feet", "ft", Length, FT, 0.0;
+
+    // Light
+    Candelas = 179, "candelas", "cd", Other, 1.0, 0.0;
+    CandelasPerSquareMeter = 180, "candelas-per-square-meter", "cd/m²", Other, 1.0, 0.0;
+    WattsPerSquareFoot = 34, "watts-per-square-foot", "W/ft²", PowerDensity, 1.0 / FT2, 0.0;
+    WattsPerSquareMeter = 35, "watts-per-square-meter", "W/m²", PowerDensity, 1.0, 0.0;
+    Lumens = 36, "lumens", "lm", Other, 1.0, 0.0;
+    Luxes = 37, "luxes", "lx", Illuminance, 1.0, 0.0;
+    FootCandles = 38, "foot-candles", "fc", Illuminance, 1.0 / FT2, 0.0;
+
+    // Mass
+    Milligrams = 196, "milligrams", "mg", Mass, 1e-6, 0.0;
+    Grams = 195, "grams", "g", Mass, 1e-3, 0.0;
+    Kilograms = 39, "kilograms", "kg", Mass, 1.0, 0.0;
+    PoundsMass = 40, "pounds-mass", "lb", Mass, LB, 0.0;
+    Tons = 41, "tons", "ton", Mass, SHORT_TON, 0.0;
+
+    // Mass flow
+    GramsPerSecond = 154, "grams-per-second", "g/s", MassFlow, 1e-3, 0.0;
+    GramsPerMinute = 155, "grams-per-minute", "g/min", MassFlow, 1e-3 / 60.0, 0.0;
+    KilogramsPerSecond = 42, "kilograms-per-second", "kg/s", MassFlow, 1.0, 0.0;
+    KilogramsPerMinute = 43, "kilograms-per-minute", "kg/min", MassFlow, 1.0 / 60.0, 0.0;
+    KilogramsPerHour = 44, "kilograms-per-hour", "kg/h", MassFlow, 1.0 / 3600.0, 0.0;
+    PoundsMassPerSecond = 119, "pounds-mass-per-second", "lb/s", MassFlow, LB, 0.0;
+    PoundsMassPerMinute = 45, "pounds-mass-per-minute", "lb/min", MassFlow, LB / 60.0, 0.0;
+    PoundsMassPerHour = 46, "pounds-mass-per-hour", "lb/h", MassFlow, LB / 3600.0, 0.0;
+    TonsPerHour = 156, "tons-per-hour", "ton/h", MassFlow, SHORT_TON / 3600.0, 0.0;
+
+    // Power
+    Milliwatts = 132, "milliwatts", "mW", Power, 1e-3, 0.0;
+    Watts = 47, "watts", "W", Power, 1.0, 0.0;
+    Kilowatts = 48, "kilowatts", "kW", Power, 1e3, 0.0;
+    Megawatts = 49, "megawatts", "MW", Power, 1e6, 0.0;
+    BtusPerHour = 50, "btus-per-hour", "BTU/h", Power, BTU / 3600.0, 0.0;
+    KiloBtusPerHour = 157, "kilo-btus-per-hour", "kBTU/h", Power, BTU / 3.6, 0.0;
+    JoulePerHours = 247, "joule-per-hours", "J/h", Power, 1.0 / 3600.0, 0.0;
+    Horsepower = 51, "horsepower", "hp", Power, 745.699872, 0.0;
+    TonsRefrigeration = 52, "tons-refrigeration", "tonR", Power, REFRIGERATION_TON, 0.0;
+
+    // Pressure
+    Pascals = 53, "pascals", "Pa", Pressure, 1.0, 0.0;
+    Hectopascals = 133, "hectopascals", "hPa", Pressure, 100.0, 0.0;
+    Kilopascals = 54, "kilopascals", "kPa", Pressure, 1e3, 0.0;
+    Millibars = 134, "millibars", "mbar", Pressure, 100.0, 0.0;
+    Bars = 55, "bars", "bar", Pressure, 1e5, 0.0;
+    PoundsForcePerSquareInch = 56, "pounds-force-per-square-inch", "psi", Pressure, 6894.757293168, 0.0;
+    MillimetersOfWater = 206, "millimeters-of-water", "mmH₂O", Pressure, 9.80665, 0.0;
+    CentimetersOfWater = 57, "centimeters-of-water", "cmH₂O", Pressure, 98.0665, 0.0;
+    InchesOfWater = 58, "inches-of-water", "inH₂O", Pressure, 249.08891, 0.0;
+    MillimetersOfMercury = 59, "millimeters-of-mercury", "mmHg", Pressure, 133.322387415, 0.0;
+    CentimetersOfMercury = 60, "centimeters-of-mercury", "cmHg", Pressure, 1333.22387415, 0.0;
+    InchesOfMercury = 61, "inches-of-mercury", "inHg", Pressure, 3386.389, 0.0;
+
+    // Temperature
+    DegreesCelsius = 62, "degrees-celsius", "°C", Temperature, 1.0, 273.15;
+    DegreesKelvin = 63, "degrees-kelvin", "K", Temperature, 1.0, 0.0;
+    DegreesFahrenheit = 64, "degrees-fahrenheit", "°F", Temperature, F_SCALE, 459.67 * F_SCALE;
+    DeltaDegreesFahrenheit = 120, "delta-degrees-fahrenheit", "Δ°F", TemperatureDifference, F_SCALE, 0.0;
+    DeltaDegreesKelvin = 121, "delta-degrees-kelvin", "ΔK", TemperatureDifference, 1.0, 0.0;
+    DegreesKelvinPerHour = 181, "degrees-kelvin-per-hour", "K/h", TemperatureRate, 1.0, 0.0;
+    DegreesKelvinPerMinute = 182, "degrees-kelvin-per-minute", "K/min", TemperatureRate, 60.0, 0.0;
+    DegreesCelsiusPerHour = 91, "degrees-celsius-per-hour", "°C/h", TemperatureRate, 1.0, 0.0;
+    DegreesCelsiusPerMinute = 92, "degrees-celsius-per-minute", "°C/min", TemperatureRate, 60.0, 0.0;
+    DegreesFahrenheitPerHour = 93, "degrees-fahrenheit-per-hour", "°F/h", TemperatureRate, F_SCALE, 0.0;
+    DegreesFahrenheitPerMinute = 94, "degrees-fahrenheit-per-minute", "°F/min", TemperatureRate, F_SCALE * 60.0, 0.0;
+    DegreeDaysCelsius = 65, "degree-days-celsius", "°C·d", DegreeDays, 1.0, 0.0;
+    DegreeDaysFahrenheit = 66, "degree-days-fahrenheit", "°F·d", DegreeDays, F_SCALE, 0.0;
+    MinutesPerDegreeKelvin = 236, "minutes-per-degree-kelvin", "min/K", Other, 1.0, 0.0;
+
+    // Time
+    Years = 67, "years", "yr", Time, 31_557_600.0, 0.0;
+    Months = 68, "months", "mo", Time, 2_629_800.0, 0.0;
+    Weeks = 69, "weeks", "wk", Time, 604_800.0, 0.0;
+    Days = 70, "days", "d", Time, 86_400.0, 0.0;
+    Hours = 71, "hours", "h", Time, 3600.0, 0.0;
+    Minutes = 72, "minutes", "min", Time, 60.0, 0.0;
+    Seconds = 73, "seconds", "s", Time, 1.0, 0.0;
+    HundredthsSeconds = 158, "hundredths-seconds", "cs", Time, 1e-2, 0.0;
+    Milliseconds = 159, "milliseconds", "ms", Time, 1e-3, 0.0;
+
+    // Torque
+    NewtonMeters = 160, "newton-meters", "N·m", Other, 1.0, 0.0;
+
+    // Velocity
+    MillimetersPerSecond = 161, "millimeters-per-second", "mm/s", Velocity, 1e-3, 0.0;
+    MillimetersPerMinute = 162, "millimeters-per-minute", "mm/min", Velocity, 1e-3 / 60.0, 0.0;
+    MetersPerSecond = 74, "meters-per-second", "m/s", Velocity, 1.0, 0.0;
+    MetersPerMinute = 163, "meters-per-minute", "m/min", Velocity, 1.0 / 60.0, 0.0;
+    MetersPerHour = 164, "meters-per-hour", "m/h", Velocity, 1.0 / 3600.0, 0.0;
+    KilometersPerHour = 75, "kilometers-per-hour", "km/h", Velocity, 1.0 / 3.6, 0.0;
+    FeetPerSecond = 76, "feet-per-second", "ft/s", Velocity, FT, 0.0;
+    FeetPerMinute = 77, "feet-per-minute", "fpm", Velocity, FT / 60.0, 0.0;
+    MilesPerHour = 78, "miles-per-hour", "mph", Velocity, 0.44704, 0.0;
+
+    // Volume
+    CubicFeet = 79, "cubic-feet", "ft³", Volume, FT3, 0.0;
+    CubicMeters = 80, "cubic-meters", "m³", Volume, 1.0, 0.0;
+    ImperialGallons = 81, "imperial-gallons", "gal (imp)", Volume, IMP_GAL, 0.0;
+    Milliliters = 197, "milliliters", "mL", Volume, 1e-6, 0.0;
+    Liters = 82, "liters", "L", Volume, 1e-3, 0.0;
+    UsGallons = 83, "us-gallons", "gal", Volume, US_GAL, 0.0;
+
+    // Volumetric flow
+    CubicFeetPerSecond = 142, "cubic-feet-per-second", "cfs", VolumetricFlow, FT3, 0.0;
+    CubicFeetPerMinute = 84, "cubic-feet-per-minute", "cfm", VolumetricFlow, FT3 / 60.0, 0.0;
+    CubicFeetPerHour = 191, "cubic-feet-per-hour", "cfh", VolumetricFlow, FT3 / 3600.0, 0.0;
+    CubicMetersPerSecond = 85, "cubic-meters-per-second", "m³/s", VolumetricFlow, 1.0, 0.0;
+    CubicMetersPerMinute = 165, "cubic-meters-per-minute", "m³/min", VolumetricFlow, 1.0 / 60.0, 0.0;
+    CubicMetersPerHour = 135, "cubic-meters-per-hour", "m³/h", VolumetricFlow, 1.0 / 3600.0, 0.0;
+    ImperialGallonsPerMinute = 86, "imperial-gallons-per-minute", "gpm (imp)", VolumetricFlow, IMP_GAL / 60.0, 0.0;
+    MillilitersPerSecond = 198, "milliliters-per-second", "mL/s", VolumetricFlow, 1e-6, 0.0;
+    LitersPerSecond = 87, "liters-per-second", "L/s", VolumetricFlow, 1e-3, 0.0;
+    LitersPerMinute = 88, "liters-per-minute", "L/min", VolumetricFlow, 1e-3 / 60.0, 0.0;
+    LitersPerHour = 136, "liters-per-hour", "L/h", VolumetricFlow, 1e-3 / 3600.0, 0.0;
+    UsGallonsPerMinute = 89, "us-gallons-per-minute", "gpm", VolumetricFlow, US_GAL / 60.0, 0.0;
+    UsGallonsPerHour = 192, "us-gallons-per-hour", "gph", VolumetricFlow, US_GAL / 3600.0, 0.0;
+
+    // Other
+    DegreesAngular = 90, "degrees-angular", "°", Angle, DEG, 0.0;
+    Radians = 103, "radians", "rad", Angle, 1.0, 0.0;
+    RadiansPerSecond = 184, "radians-per-second", "rad/s", AngularVelocity, 1.0, 0.0;
+    RevolutionsPerMinute = 104, "revolutions-per-minute", "rpm", AngularVelocity, 2.0 * std::f64::consts::PI / 60.0, 0.0;
+    JouleSeconds = 183, "joule-seconds", "J·s", Other, 1.0, 0.0;
+    KilogramsPerCubicMeter = 186, "kilograms-per-cubic-meter", "kg/m³", MassConcentration, 1.0, 0.0;
+    KilowattHoursPerSquareMeter = 137, "kilowatt-hours-per-square-meter", "kWh/m²", EnergyDensity, 3.6e6, 0.0;
+    KilowattHoursPerSquareFoot = 138, "kilowatt-hours-per-square-foot", "kWh/ft²", EnergyDensity, 3.6e6 / FT2, 0.0;
+    MegajoulesPerSquareMeter = 139, "megajoules-per-square-meter", "MJ/m²", EnergyDensity, 1e6, 0.0;
+    MegajoulesPerSquareFoot = 140, "megajoules-per-square-foot", "MJ/ft²", EnergyDensity, 1e6 / FT2, 0.0;
+    NoUnits = 95, "no-units", "", Dimensionless, 1.0, 0.0;
+    NewtonSeconds = 187, "newton-seconds", "N·s", Other, 1.0, 0.0;
+    NewtonsPerMeter = 188, "newtons-per-meter", "N/m", Other, 1.0, 0.0;
+    PartsPerMillion = 96, "parts-per-million", "ppm", Ratio, 1e-6, 0.0;
+    PartsPerBillion = 97, "parts-per-billion", "ppb", Ratio, 1e-9, 0.0;
+    Percent = 98, "percent", "%", Ratio, 1e-2, 0.0;
+    PerMille = 207, "per-mille", "‰", Ratio, 1e-3, 0.0;
+    GramsPerGram = 208, "grams-per-gram", "g/g", Ratio, 1.0, 0.0;
+    KilogramsPerKilogram = 209, "kilograms-per-kilogram", "kg/kg", Ratio, 1.0, 0.0;
+    GramsPerKilogram = 210, "grams-per-kilogram", "g/kg", Ratio, 1e-3, 0.0;
+    MilligramsPerGram = 211, "milligrams-per-gram", "mg/g", Ratio, 1e-3, 0.0;
+    MilligramsPerKilogram = 212, "milligrams-per-kilogram", "mg/kg", Ratio, 1e-6, 0.0;
+    PercentObscurationPerFoot = 143, "percent-obscuration-per-foot", "%/ft", Obscuration, 1.0 / FT, 0.0;
+    PercentObscurationPerMeter = 144, "percent-obscuration-per-meter", "%/m", Obscuration, 1.0, 0.0;
+    PercentPerSecond = 99, "percent-per-second", "%/s", Other, 1.0, 0.0;
+    PerMinute = 100, "per-minute", "/min", Frequency, 1.0 / 60.0, 0.0;
+    PerSecond = 101, "per-second", "/s", Frequency, 1.0, 0.0;
+    PsiPerDegreeFahrenheit = 102, "psi-per-degree-fahrenheit", "psi/°F", Other, 1.0, 0.0;
+    SquareMetersPerNewton = 185, "square-meters-per-newton", "m²/N", Other, 1.0, 0.0;
+    WattsPerMeterPerDegreeKelvin = 189, "watts-per-meter-per-degree-kelvin", "W/(m·K)", Other, 1.0, 0.0;
+    WattsPerSquareMeterDegreeKelvin = 141, "watts-per-square-meter-degree-kelvin", "W/(m²·K)", Other, 1.0, 0.0;
+    GramsPerMilliliter = 213, "grams-per-milliliter", "g/mL", MassConcentration, 1e3, 0.0;
+    GramsPerLiter = 214, "grams-per-liter", "g/L", MassConcentration, 1.0, 0.0;
+    MilligramsPerLiter = 215, "milligrams-per-liter", "mg/L", MassConcentration, 1e-3, 0.0;
+    MicrogramsPerLiter = 216, "micrograms-per-liter", "µg/L", MassConcentration, 1e-6, 0.0;
+    GramsPerCubicMeter = 217, "grams-per-cubic-meter", "g/m³", MassConcentration, 1e-3, 0.0;
+    MilligramsPerCubicMeter = 218, "milligrams-per-cubic-meter", "mg/m³", MassConcentration, 1e-6, 0.0;
+    MicrogramsPerCubicMeter = 219, "micrograms-per-cubic-meter", "µg/m³", MassConcentration, 1e-9, 0.0;
+    NanogramsPerCubicMeter = 220, "nanograms-per-cubic-meter", "ng/m³", MassConcentration, 1e-12, 0.0;
+    GramsPerCubicCentimeter = 221, "grams-per-cubic-centimeter", "g/cm³", MassConcentration, 1e3, 0.0;
+    Becquerels = 222, "becquerels", "Bq", Radioactivity, 1.0, 0.0;
+    Kilobecquerels = 223, "kilobecquerels", "kBq", Radioactivity, 1e3, 0.0;
+    Megabecquerels = 224, "megabecquerels", "MBq", Radioactivity, 1e6, 0.0;
+    Gray = 225, "gray", "Gy", AbsorbedDose, 1.0, 0.0;
+    Milligray = 226, "milligray", "mGy", AbsorbedDose, 1e-3, 0.0;
+    Microgray = 227, "microgray", "µGy", AbsorbedDose, 1e-6, 0.0;
+    Sieverts = 228, "sieverts", "Sv", DoseEquivalent, 1.0, 0.0;
+    Millisieverts = 229, "millisieverts", "mSv", DoseEquivalent, 1e-3, 0.0;
+    Microsieverts = 230, "microsieverts", "µSv", DoseEquivalent, 1e-6, 0.0;
+    MicrosievertsPerHour = 231, "microsieverts-per-hour", "µSv/h", Other, 1.0, 0.0;
+    DecibelsA = 232, "decibels-a", "dBA", Other, 1.0, 0.0;
+    NephelometricTurbidityUnit = 233, "nephelometric-turbidity-unit", "NTU", Other, 1.0, 0.0;
+    Ph = 234, "pH", "pH", Other, 1.0, 0.0;
+    GramsPerSquareMeter = 235, "grams-per-square-meter", "g/m²", Other, 1.0, 0.0;
+}
+
+/// Common spellings accepted when parsing units (compared case-insensitively)
+const ALIASES: &[(&str, EngineeringUnit)] = &[
+    ("degF", EngineeringUnit::DegreesFahrenheit),
+    ("deg F", EngineeringUnit::DegreesFahrenheit),
+    ("fahrenheit", EngineeringUnit::DegreesFahrenheit),
+    ("degC", EngineeringUnit::DegreesCelsius),
+    ("deg C", EngineeringUnit::DegreesCelsius),
+    ("celsius", EngineeringUnit::DegreesCelsius),
+    ("kelvin", EngineeringUnit::DegreesKelvin),
+    ("deltaF", EngineeringUnit::DeltaDegreesFahrenheit),
+    ("deltaK", EngineeringUnit::DeltaDegreesKelvin),
+    ("inH2O", EngineeringUnit::InchesOfWater),
+    ("in wc", EngineeringUnit::InchesOfWater),
+    ("mmH2O", EngineeringUnit::MillimetersOfWater),
+    ("cmH2O", EngineeringUnit::CentimetersOfWater),
+    ("cfm", EngineeringUnit::CubicFeetPerMinute),
+    ("ft3/min", EngineeringUnit::CubicFeetPerMinute),
+    ("l/s", EngineeringUnit::LitersPerSecond),
+    ("lps", EngineeringUnit::LitersPerSecond),
+    ("m3/h", EngineeringUnit::CubicMetersPerHour),
+    ("m3/s", EngineeringUnit::CubicMetersPerSecond),
+    ("btuh", EngineeringUnit::BtusPerHour),
+    ("btu/hr", EngineeringUnit::BtusPerHour),
+    ("mbh", EngineeringUnit::KiloBtusPerHour),
+    // "tons" is the mass unit's name, which stored units round-trip through
+    ("tr", EngineeringUnit::TonsRefrigeration),
+    ("tons-r", EngineeringUnit::TonsRefrigeration),
+    ("rh", EngineeringUnit::PercentRelativeHumidity),
+    ("% rh", EngineeringUnit::PercentRelativeHumidity),
+    ("sec", EngineeringUnit::Seconds),
+    ("hr", EngineeringUnit::Hours),
+];
+
+impl EngineeringUnit {
+    /// Whether values in this unit can be converted to `other`
+    pub fn is_convertible_to(self, other: EngineeringUnit) -> bool {
+        self == other || (self.quantity() == other.quantity() && self.quantity() != Quantity::Other)
+    }
+
+    /// Convert a value from this unit to another unit of the same quantity
+    pub fn convert(self, value: f64, to: EngineeringUnit) -> Result<f64, UnitError> {
+        if self == to {
+            return Ok(value);
+        }
+        if !self.is_convertible_to(to) {
+            return Err(UnitError::Incompatible { from: self, to });
+        }
+        let (from_scale, from_offset) = self.factors();
+        let (to_scale, to_offset) = to.factors();
+        Ok((value * from_scale + from_offset - to_offset) / to_scale)
+    }
+
+    /// Convert a numeric point value; the result is always Real
+    pub fn convert_property(
+        self,
+        value: &PropertyValue,
+        to: EngineeringUnit,
+    ) -> Result<PropertyValue, UnitError> {
+        let v = match value {
+            PropertyValue::Real(v) => *v as f64,
+            PropertyValue::Unsigned(v) => *v as f64,
+            other => return Err(UnitError::NotNumeric(format!("{:?}", other))),
+        };
+        Ok(PropertyValue::Real(self.convert(v, to)? as f32))
+    }
+}
+
+impl fmt::Display for EngineeringUnit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EngineeringUnit::NoUnits => write!(f, "{}", self.name()),
+            _ => write!(f, "{}", self.symbol()),
+        }
+    }
+}
+
+impl FromStr for EngineeringUnit {
+    type Err = UnitError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let by_name = || EngineeringUnit::ALL.iter().find(|u| u.name().eq_ignore_ascii_case(s));
+        // Symbols are case-sensitive (mS vs MS), aliases are not
+        let by_symbol = || EngineeringUnit::ALL.iter().find(|u| !u.symbol().is_empty() && u.symbol() == s);
+        let by_alias = || {
+            ALIASES
+                .iter()
+                .find(|(alias, _)| alias.eq_ignore_ascii_case(s))
+                .map(|(_, u)| u)
+        };
+
+        by_name()
+            .or_else(by_symbol)
+            .or_else(by_alias)
+            .copied()
+            .ok_or_else(|| UnitError::Unknown(s.to_string()))
+    }
+}
+
+impl Serialize for EngineeringUnit {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.name())
+    }
+}
+
+impl<'de> Deserialize<'de> for EngineeringUnit {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Tests
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < 1e-3 * expected.abs().max(1.0),
+            "expected {} but got {}",
+            expected,
+            actual
+        );
+    }
+
+    #[test]
+    fn test_catalog_is_consistent() {
+        for unit in EngineeringUnit::ALL {
+            assert_eq!(EngineeringUnit::from_bacnet_id(unit.bacnet_id()), Some(*unit));
+            assert_eq!(unit.name().parse::<EngineeringUnit>().unwrap(), *unit);
+        }
+        assert_eq!(EngineeringUnit::ALL.len(), 248);
+    }
+
+    #[test]
+    fn test_conversions() {
+        use EngineeringUnit::*;
+
+        assert_close(DegreesFahrenheit.convert(68.0, DegreesCelsius).unwrap(), 20.0);
+        assert_close(DegreesCelsius.convert(-40.0, DegreesFahrenheit).unwrap(), -40.0);
+        assert_close(DegreesCelsius.convert(0.0, DegreesKelvin).unwrap(), 273.15);
+        assert_close(DeltaDegreesKelvin.convert(10.0, DeltaDegreesFahrenheit).unwrap(), 18.0);
+        assert_close(CubicFeetPerMinute.convert(1000.0, LitersPerSecond).unwrap(), 471.947);
+        assert_close(Kilowatts.convert(1.0, BtusPerHour).unwrap(), 3412.14);
+        assert_close(TonsRefrigeration.convert(1.0, KiloBtusPerHour).unwrap(), 12.0);
+        assert_close(InchesOfWater.convert(1.0, Pascals).unwrap(), 249.089);
+
+        assert!(matches!(
+            DegreesFahrenheit.convert(70.0, Kilowatts),
+            Err(UnitError::Incompatible { .. })
+        ));
+        assert!(!Currency1.is_convertible_to(Currency2));
+    }
+
+    #[test]
+    fn test_parse_and_serde() {
+        assert_eq!("°F".parse::<EngineeringUnit>().unwrap(), EngineeringUnit::DegreesFahrenheit);
+        assert_eq!("degC".parse::<EngineeringUnit>().unwrap(), EngineeringUnit::DegreesCelsius);
+        assert_eq!("CFM".parse::<EngineeringUnit>().unwrap(), EngineeringUnit::CubicFeetPerMinute);
+        assert_eq!("TR".parse::<EngineeringUnit>().unwrap(), EngineeringUnit::TonsRefrigeration);
+        assert_eq!("tons".parse::<EngineeringUnit>().unwrap(), EngineeringUnit::Tons);
+        assert!("furlongs".parse::<EngineeringUnit>().is_err());
+
+        let json = serde_json::to_string(&EngineeringUnit::InchesOfWater).unwrap();
+        assert_eq!(json, "\"inches-of-water\"");
+        let unit: EngineeringUnit = serde_json::from_str("\"inH2O\"").unwrap();
+        assert_eq!(unit, EngineeringUnit::InchesOfWater);
+    }
+}
@@ -0,0 +1,72 @@
+// Alarm Acknowledgement - What operators must do to acknowledge and clear alarms
+//
+// Rules are set per severity: whether acknowledging requires a comment, and
+// whether alarms latch. A latching alarm whose condition returns to normal
+// stays active until it is acknowledged, then clears; if its condition is met
+// again first, the same alarm is re-raised. Non-latching alarms clear as
+// their condition returns to normal, acknowledged or not.
+//
+// Every step of an alarm (raised, acknowledged, commented, returned to
+// normal, cleared, re-raised) is recorded in its timeline, persisted with the
+// alarm store.
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use wildmatch::WildMatch;
+
+use crate::services::messages::{Alarm, AlarmFilter};
+use crate::types::AlarmSeverity;
+
+/// Acknowledgement rules per severity (severities without a rule need no
+/// comment and do not latch)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AckPolicy {
+    #[serde(default)]
+    pub rules: Vec<AckRule>,
+}
+
+/// Acknowledgement rule for one severity
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AckRule {
+    pub severity: AlarmSeverity,
+    /// Acknowledging requires a comment
+    #[serde(default)]
+    pub comment_required: bool,
+    /// Alarms stay active after returning to normal until acknowledged
+    #[serde(default)]
+    pub latching: bool,
+}
+
+impl AckPolicy {
+    fn rule(&self, severity: AlarmSeverity) -> Option<&AckRule> {
+        self.rules.iter().find(|r| r.severity == severity)
+    }
+
+    /// Whether acknowledging alarms of a severity requires a comment
+    pub fn comment_required(&self, severity: AlarmSeverity) -> bool {
+        self.rule(severity).is_some_and(|r| r.comment_required)
+    }
+
+    /// Whether alarms of a severity latch until acknowledged
+    pub fn latching(&self, severity: AlarmSeverity) -> bool {
+        self.rule(severity).is_some_and(|r| r.latching)
+    }
+}
+
+/// Alarms acknowledged by a bulk acknowledgement, and those skipped
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BulkAcknowledgement {
+    pub acknowledged: Vec<Uuid>,
+    /// Matching alarms left unacknowledged, as they require a comment
+    pub skipped: Vec<Uuid>,
+}
+
+/// Whether an alarm matches a filter
+pub(super) fn matches(filter: &AlarmFilter, alarm: &Alarm) -> bool {
+    filter
+        .source_pattern
+        .as_ref()
+        .is_none_or(|pattern| WildMatch::new(pattern).matches(&alarm.source))
+        && filter.min_severity.is_none_or(|min| alarm.severity >= min)
+        && filter.config_id.is_none_or(|id| alarm.config_id == Some(id))
+}
@@ -0,0 +1,123 @@
+// Alarm Condition Context - What conditions need beyond the current value
+//
+//   RateOfChange     the point's values over the window
+//   Flatline         since when the point has held its value (within the tolerance)
+//   Deviation        the current value of the reference point
+//   CommandMismatch  the current value of the feedback point
+//
+// The other conditions only need the current value. Reference and feedback
+// paths may use {parent}, the point's path up to its last '/', so one rule
+// covers every piece of equipment (e.g., "{parent}/zone-sp").
+//
+// RateOfChange and Flatline change without a new value arriving, so
+// the timer task re-evaluates them: the rate is measured over the window up
+// to now, so a step change stops counting once it is older than the window.
+// When the reference or feedback point changes, the points that refer to it
+// are re-evaluated.
+
+use std::collections::VecDeque;
+
+use chrono::{DateTime, Duration, Utc};
+
+use crate::points::EngineeringUnit;
+use crate::types::PropertyValue;
+
+/// Latest value of a point and when it was reported
+#[derive(Debug, Clone)]
+pub(super) struct PointContext {
+    pub value: PropertyValue,
+    pub units: Option<EngineeringUnit>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Recent values a condition keeps per point
+#[derive(Debug, Clone, Default)]
+pub(super) struct ValueHistory {
+    /// Values within the rate-of-change window
+    window: VecDeque<(DateTime<Utc>, f32)>,
+    /// Value the point has held (within the flatline tolerance), and since when
+    steady: Option<(PropertyValue, DateTime<Utc>)>,
+}
+
+impl ValueHistory {
+    /// Add a value to the rate window (a value already recorded at the same
+    /// time is not added again)
+    pub fn push(&mut self, at: DateTime<Utc>, value: f32) {
+        if self.window.back().is_some_and(|(last, _)| *last == at) {
+            return;
+        }
+        self.window.push_back((at, value));
+    }
+
+    /// Drop the values older than the window before `now`, and return the
+    /// change per minute from the oldest to the newest value left
+    pub fn per_minute(&mut self, now: DateTime<Utc>, window: Duration) -> Option<f32> {
+        while self
+            .window
+            .front()
+            .is_some_and(|(oldest, _)| now - *oldest > window)
+        {
+            self.window.pop_front();
+        }
+        let (first_at, first) = self.window.front()?;
+        let (last_at, last) = self.window.back()?;
+        let minutes = (*last_at - *first_at).num_milliseconds() as f32 / 60_000.0;
+        (minutes > 0.0).then(|| (last - first) / minutes)
+    }
+
+    /// Record a value for flatline detection and return since when the point
+    /// has held it; numeric changes within the tolerance do not count
+    pub fn hold(
+        &mut self,
+        value: &PropertyValue,
+        tolerance: f32,
+        now: DateTime<Utc>,
+    ) -> DateTime<Utc> {
+        if let Some((held, since)) = &self.steady {
+            let changed = match (numeric(held), numeric(value)) {
+                (Some(held), Some(new)) => (new - held).abs() > tolerance,
+                _ => held != value,
+            };
+            if !changed {
+                return *since;
+            }
+        }
+        self.steady = Some((value.clone(), now));
+        now
+    }
+}
+
+/// Path of a reference or feedback point for a point ({parent} is the
+/// point's path up to its last '/')
+pub(super) fn resolve_reference(reference: &str, point: &str) -> String {
+    let parent = point.rsplit_once('/').map(|(parent, _)| parent).unwrap_or("");
+    reference.replace("{parent}", parent)
+}
+
+/// Numeric value, if the value has one
+pub(super) fn numeric(value: &PropertyValue) -> Option<f32> {
+    match value {
+        PropertyValue::Real(v) => Some(*v),
+        PropertyValue::Unsigned(v) => Some(*v as f32),
+        PropertyValue::Enumerated(v) => Some(*v as f32),
+        _ => None,
+    }
+}
+
+/// On/off reading of a command or status (binary values are enumerated 0/1)
+pub(super) fn is_on(value: &PropertyValue) -> Option<bool> {
+    match value {
+        PropertyValue::Boolean(v) => Some(*v),
+        PropertyValue::Unsigned(v) | PropertyValue::Enumerated(v) => Some(*v != 0),
+        PropertyValue::Real(v) => Some(*v != 0.0),
+        _ => None,
+    }
+}
+
+/// Multi-state value
+pub(super) fn state_of(value: &PropertyValue) -> Option<u32> {
+    match value {
+        PropertyValue::Unsigned(v) | PropertyValue::Enumerated(v) => Some(*v),
+        _ => None,
+    }
+}
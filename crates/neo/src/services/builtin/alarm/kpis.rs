@@ -0,0 +1,277 @@
+// Alarm Floods and KPIs - How well the alarm system serves its operators
+//
+// A flood is more alarms than the threshold raised within the flood window
+// (ISA-18.2 suggests more than 10 in 10 minutes). When one starts, the alarms
+// raised within the window are grouped under a flood id, the earliest being
+// flagged first-out (the likely root cause); alarms raised while the rate
+// stays above the threshold join the flood.
+//
+// KPIs are computed over the alarm history of a time range, streamed from
+// the store one alarm at a time, following ISA-18.2: alarms per hour, the
+// most frequent alarms ("bad actors"), chattering alarms, standing alarms and
+// mean time to acknowledge.
+
+use std::collections::{BTreeMap, HashMap, VecDeque};
+
+use chrono::{DateTime, Duration, Utc};
+use uuid::Uuid;
+
+use crate::services::messages::{Alarm, AlarmCount, AlarmFlood, AlarmKpis};
+
+/// Default flood threshold: more alarms than this within the window
+pub(super) const DEFAULT_FLOOD_THRESHOLD: usize = 10;
+
+/// Default flood window in seconds
+pub(super) const DEFAULT_FLOOD_WINDOW_SECS: u32 = 600;
+
+/// Most frequent alarms listed in the KPIs
+const TOP_ALARMS: usize = 10;
+
+/// An alarm raised this many times within CHATTER_SECS is chattering
+const CHATTER_COUNT: usize = 3;
+
+/// Window in which repeated raises count as chattering
+const CHATTER_SECS: i64 = 60;
+
+/// Hours after which an active alarm is standing
+const STANDING_HOURS: i64 = 24;
+
+/// Tracks the rate of raised alarms and the current flood
+pub(super) struct FloodDetector {
+    threshold: usize,
+    window: Duration,
+    /// Alarms raised within the window, oldest first
+    recent: VecDeque<(DateTime<Utc>, Uuid)>,
+    /// Current flood and how many alarms it has grouped
+    current: Option<(Uuid, usize)>,
+}
+
+impl FloodDetector {
+    pub fn new(threshold: usize, window_secs: u32) -> Self {
+        Self {
+            threshold,
+            window: Duration::seconds(window_secs as i64),
+            recent: VecDeque::new(),
+            current: None,
+        }
+    }
+
+    /// Record a raised alarm; returns the flood it belongs to, if any, with
+    /// the alarms grouped when it starts the flood (those raised within the
+    /// window, earliest first, so the first is the first-out); the list is
+    /// empty when the alarm joins a flood already under way
+    pub fn record(&mut self, alarm_id: Uuid, at: DateTime<Utc>) -> Option<(Uuid, Vec<Uuid>)> {
+        self.expire(at);
+        self.recent.push_back((at, alarm_id));
+
+        if let Some((flood_id, count)) = &mut self.current {
+            *count += 1;
+            return Some((*flood_id, Vec::new()));
+        }
+        if self.recent.len() <= self.threshold {
+            return None;
+        }
+
+        let flood_id = Uuid::new_v4();
+        let members: Vec<Uuid> = self.recent.iter().map(|(_, id)| *id).collect();
+        self.current = Some((flood_id, members.len()));
+        tracing::warn!(
+            "Alarm flood started: {} alarms within {} seconds",
+            members.len(),
+            self.window.num_seconds()
+        );
+        Some((flood_id, members))
+    }
+
+    /// Drop alarms that left the window, ending the flood once the rate is
+    /// back at or below the threshold
+    pub fn expire(&mut self, now: DateTime<Utc>) {
+        while self
+            .recent
+            .front()
+            .is_some_and(|(at, _)| *at <= now - self.window)
+        {
+            self.recent.pop_front();
+        }
+        if self.recent.len() <= self.threshold {
+            if let Some((flood_id, count)) = self.current.take() {
+                tracing::info!("Alarm flood {} ended ({} alarms)", flood_id, count);
+            }
+        }
+    }
+
+    /// Whether a flood is under way
+    pub fn is_flooding(&self) -> bool {
+        self.current.is_some()
+    }
+
+    /// Forget recent alarms and the current flood
+    pub fn reset(&mut self) {
+        self.recent.clear();
+        self.current = None;
+    }
+}
+
+/// Raises of one alarm (config and source) within the KPI range
+struct RaiseCount {
+    /// Message of the latest raise
+    message: String,
+    count: usize,
+    /// Latest CHATTER_COUNT raise times, oldest first
+    recent: VecDeque<DateTime<Utc>>,
+    chattering: bool,
+}
+
+/// A flood seen within the KPI range
+struct FloodSummary {
+    started_at: DateTime<Utc>,
+    last_alarm_at: DateTime<Utc>,
+    alarm_count: u32,
+    /// First member, and the member flagged first-out
+    first: (Uuid, String),
+    first_out: Option<(Uuid, String)>,
+}
+
+/// KPIs over the alarms raised in a range, fed one alarm at a time (oldest
+/// first, as the store streams them) so a long range is never held in memory
+pub(super) struct KpiBuilder {
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    total: usize,
+    raises: HashMap<(Option<Uuid>, String), RaiseCount>,
+    ack_secs: f64,
+    acknowledged: usize,
+    floods: BTreeMap<Uuid, FloodSummary>,
+}
+
+impl KpiBuilder {
+    pub fn new(start: DateTime<Utc>, end: DateTime<Utc>) -> Self {
+        Self {
+            start,
+            end,
+            total: 0,
+            raises: HashMap::new(),
+            ack_secs: 0.0,
+            acknowledged: 0,
+            floods: BTreeMap::new(),
+        }
+    }
+
+    /// Count an alarm raised within the range
+    pub fn add(&mut self, alarm: &Alarm) {
+        self.total += 1;
+
+        let raise = self
+            .raises
+            .entry((alarm.config_id, alarm.source.clone()))
+            .or_insert_with(|| RaiseCount {
+                message: String::new(),
+                count: 0,
+                recent: VecDeque::with_capacity(CHATTER_COUNT),
+                chattering: false,
+            });
+        raise.message.clone_from(&alarm.message);
+        raise.count += 1;
+        if raise.recent.len() == CHATTER_COUNT {
+            raise.recent.pop_front();
+        }
+        raise.recent.push_back(alarm.triggered_at);
+        if raise.recent.len() == CHATTER_COUNT
+            && raise.recent[CHATTER_COUNT - 1] - raise.recent[0] <= Duration::seconds(CHATTER_SECS)
+        {
+            raise.chattering = true;
+        }
+
+        if let Some(acknowledged_at) = alarm.acknowledged_at {
+            self.ack_secs +=
+                (acknowledged_at - alarm.triggered_at).num_milliseconds() as f64 / 1000.0;
+            self.acknowledged += 1;
+        }
+
+        if let Some(flood_id) = alarm.flood_id {
+            let flood = self.floods.entry(flood_id).or_insert_with(|| FloodSummary {
+                started_at: alarm.triggered_at,
+                last_alarm_at: alarm.triggered_at,
+                alarm_count: 0,
+                first: (alarm.id, alarm.source.clone()),
+                first_out: None,
+            });
+            flood.last_alarm_at = alarm.triggered_at;
+            flood.alarm_count += 1;
+            if alarm.first_out && flood.first_out.is_none() {
+                flood.first_out = Some((alarm.id, alarm.source.clone()));
+            }
+        }
+    }
+
+    /// The KPIs, with standing alarms taken from the currently active ones
+    pub fn finish(self, active: &[Alarm], now: DateTime<Utc>) -> AlarmKpis {
+        let hours = (self.end - self.start).num_seconds() as f64 / 3600.0;
+        let alarms_per_hour = if hours > 0.0 { self.total as f64 / hours } else { 0.0 };
+
+        let count_of = |(config_id, source): &(Option<Uuid>, String), raise: &RaiseCount| {
+            AlarmCount {
+                config_id: *config_id,
+                source: source.clone(),
+                message: raise.message.clone(),
+                count: raise.count as u32,
+            }
+        };
+        let mut top_alarms: Vec<AlarmCount> = self
+            .raises
+            .iter()
+            .map(|(key, raise)| count_of(key, raise))
+            .collect();
+        top_alarms.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.source.cmp(&b.source)));
+        top_alarms.truncate(TOP_ALARMS);
+
+        let mut chattering_alarms: Vec<AlarmCount> = self
+            .raises
+            .iter()
+            .filter(|(_, raise)| raise.chattering)
+            .map(|(key, raise)| count_of(key, raise))
+            .collect();
+        chattering_alarms
+            .sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.source.cmp(&b.source)));
+
+        let standing_since = now - Duration::hours(STANDING_HOURS);
+        let mut standing_alarms: Vec<Alarm> = active
+            .iter()
+            .filter(|alarm| alarm.triggered_at < standing_since)
+            .cloned()
+            .collect();
+        standing_alarms.sort_by_key(|alarm| alarm.triggered_at);
+
+        let mean_time_to_acknowledge_secs =
+            (self.acknowledged > 0).then(|| self.ack_secs / self.acknowledged as f64);
+
+        let mut floods: Vec<AlarmFlood> = self
+            .floods
+            .into_iter()
+            .map(|(id, flood)| {
+                let (first_out, first_out_source) = flood.first_out.unwrap_or(flood.first);
+                AlarmFlood {
+                    id,
+                    started_at: flood.started_at,
+                    last_alarm_at: flood.last_alarm_at,
+                    alarm_count: flood.alarm_count,
+                    first_out,
+                    first_out_source,
+                }
+            })
+            .collect();
+        floods.sort_by_key(|flood| flood.started_at);
+
+        AlarmKpis {
+            start: self.start,
+            end: self.end,
+            total_alarms: self.total as u32,
+            alarms_per_hour,
+            top_alarms,
+            chattering_alarms,
+            standing_alarms,
+            mean_time_to_acknowledge_secs,
+            floods,
+        }
+    }
+}
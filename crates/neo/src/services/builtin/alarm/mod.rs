@@ -0,0 +1,3788 @@
+// Alarm Service - Monitors points and raises alarms based on conditions
+//
+// Actor-based alarm service that evaluates point values against configurable
+// conditions and manages alarm lifecycle (active, acknowledged, cleared).
+//
+// HistoryStale conditions are not evaluated against values: they are raised
+// when the history service reports that a point has stopped logging, and
+// cleared when it logs again.
+//
+// DeviceOffline conditions follow device status instead: their source is the
+// device ("{network}/{device}"), met when the device goes Offline or times
+// out and unmet once it is back Online. While a device is offline, alarms on
+// its points are not raised, and those already active are suppressed (see
+// suppression), so the operator sees the one root-cause alarm; conditions
+// still met when it comes back online raise their alarms then. A built-in
+// DeviceOffline rule for every device, with its own delay and severity, is
+// added with `with_device_alarm`.
+//
+// Each step of an alarm's lifecycle is published to the event bus:
+// AlarmRaised, AlarmAcknowledged, AlarmReturnedToNormal when its condition is
+// no longer met, and AlarmCleared when it leaves the active list (at the
+// same time, unless the alarm latches until acknowledged).
+//
+// Acknowledgement rules are set per severity (see acknowledgement): some
+// severities require an acknowledgement comment, and latching alarms only
+// clear once acknowledged. Each alarm keeps a persisted timeline of its
+// lifecycle and operator comments.
+//
+// Conditions are debounced (see timers): on- and off-delays, minimum on and
+// off times, and a deadband that limit conditions must move back past before
+// they stop being met, so a value hovering at a threshold does not flap.
+//
+// Configurations and alarm records are persisted in redb (see store), so
+// rules and unacknowledged alarms survive a restart, and alarm history is
+// paged from the store. Without a database path the store is kept in memory.
+//
+// Beyond the current value, conditions can use the point's context (see
+// context): its rate of change, when it last reported or changed, and the
+// values of related points such as a setpoint or a command's feedback.
+//
+// Operators can shelve alarms, and alarms can be suppressed by scheduled
+// windows or inhibited by another point's value (see suppression); suppressed
+// alarms leave the active list for a separate one.
+//
+// Raised alarms are routed to recipients by notification classes, over email,
+// webhooks or plugin callbacks, escalating while unacknowledged (see
+// notifications).
+//
+// Bursts of alarms are grouped into floods, flagging the first-out alarm, and
+// operator KPIs following ISA-18.2 are computed over the alarm history (see
+// kpis).
+//
+// Rules can also be defined in JSON or TOML files, reloaded as they change
+// (see rules). Whenever configurations change, active alarms follow: alarms
+// of removed or disabled rules clear, and the others take the new severity
+// and are re-evaluated against the new condition.
+
+mod acknowledgement;
+mod context;
+mod kpis;
+mod notifications;
+mod rules;
+mod store;
+mod suppression;
+mod timers;
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use kameo::actor::ActorRef;
+use kameo::message::{Context, Message};
+use kameo_actors::pubsub::Publish;
+use serde::{Deserialize, Serialize};
+use tokio::sync::oneshot;
+use uuid::Uuid;
+use wildmatch::WildMatch;
+
+pub use self::acknowledgement::{AckPolicy, AckRule, BulkAcknowledgement};
+use self::context::{PointContext, ValueHistory};
+use self::kpis::{DEFAULT_FLOOD_THRESHOLD, DEFAULT_FLOOD_WINDOW_SECS, FloodDetector, KpiBuilder};
+pub use self::notifications::{
+    DeliveryStatus, NotificationClass, NotificationConfig, NotificationHours, NotificationRecord,
+    Recipient, RecipientGroup, SmtpConfig,
+};
+use self::notifications::{Escalation, Notification, Notifier};
+use self::rules::RuleWatcher;
+pub use self::store::AlarmHistoryPage;
+use self::store::AlarmStore;
+pub use self::suppression::{
+    Inhibit, Shelf, ShelveTarget, SuppressedAlarm, Suppression, SuppressionWindow,
+};
+pub use self::timers::AlarmClock;
+use self::timers::ConditionState;
+use crate::actors::PubSubBroker;
+use crate::messages::Event;
+use crate::points::{EngineeringUnit, TagFilter, TagStore};
+use crate::services::actor::{ServiceMsg, ServiceReply, ServiceStateTracker};
+use crate::services::messages::{
+    Alarm, AlarmFilter, AlarmKpis, AlarmState, AlarmTimelineEntry, AlarmTimelineKind,
+    ServiceRequest, ServiceResponse,
+};
+use crate::services::registry::ServiceRegistry;
+use crate::types::{AlarmSeverity, DeviceStatus, Error, PropertyValue, Result, ServiceState};
+
+/// Alarms per GetAlarmHistory page when the request sets no limit
+const DEFAULT_HISTORY_LIMIT: u32 = 1000;
+
+/// Days of alarm history kept by default
+const DEFAULT_HISTORY_RETENTION_DAYS: u32 = 365;
+
+/// How often expired alarm history is purged, in seconds
+const HISTORY_PURGE_INTERVAL_SECS: i64 = 3600;
+
+/// Configuration for an alarm rule
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlarmConfig {
+    /// Unique identifier for this alarm configuration (derived from the file
+    /// and rule name when a rule file omits it)
+    pub id: Uuid,
+    /// Human-readable name
+    pub name: String,
+    /// Glob pattern to match point paths (e.g., "*/VAV-*/AI:1")
+    #[serde(default = "default_source_pattern")]
+    pub source_pattern: String,
+    /// Tag filter points must also match (e.g., "zone and air and temp and sensor")
+    #[serde(default)]
+    pub tag_filter: Option<String>,
+    /// Condition that triggers the alarm
+    pub condition: AlarmCondition,
+    /// Units of the condition thresholds; point values are converted to these
+    /// units before evaluation (e.g., "°F" on a point reporting °C)
+    #[serde(default)]
+    pub units: Option<EngineeringUnit>,
+    /// Severity of the alarm when triggered
+    pub severity: AlarmSeverity,
+    /// Seconds the condition must stay met before the alarm is raised (on-delay)
+    #[serde(default)]
+    pub delay_seconds: u32,
+    /// Seconds the condition must stay unmet before the alarm clears (off-delay)
+    #[serde(default)]
+    pub off_delay_seconds: u32,
+    /// Minimum seconds an alarm stays raised before it can clear
+    #[serde(default)]
+    pub min_on_seconds: u32,
+    /// Minimum seconds after an alarm clears before it can be raised again
+    #[serde(default)]
+    pub min_off_seconds: u32,
+    /// Hysteresis for limit conditions: once met, the value must move this far
+    /// back inside the limit (in the config's units) before the condition is
+    /// unmet; less than a deviation limit, and than half an out-of-range span
+    #[serde(default)]
+    pub deadband: f32,
+    /// Suppress this config's alarms while another point meets a condition
+    #[serde(default)]
+    pub inhibit: Option<Inhibit>,
+    /// Message template (can include {point}, {value}, {threshold}, {units})
+    pub message_template: String,
+    /// Whether this alarm config is enabled
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_source_pattern() -> String {
+    "*".to_string()
+}
+
+/// The built-in device communication alarm: a DeviceOffline rule for every
+/// device
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeviceAlarmConfig {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Seconds a device must stay offline before the alarm is raised
+    #[serde(default = "default_device_alarm_delay")]
+    pub delay_seconds: u32,
+    #[serde(default = "default_device_alarm_severity")]
+    pub severity: AlarmSeverity,
+}
+
+fn default_device_alarm_delay() -> u32 {
+    60
+}
+
+fn default_device_alarm_severity() -> AlarmSeverity {
+    AlarmSeverity::High
+}
+
+impl Default for DeviceAlarmConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            delay_seconds: default_device_alarm_delay(),
+            severity: default_device_alarm_severity(),
+        }
+    }
+}
+
+impl DeviceAlarmConfig {
+    /// The alarm rule, with a fixed id so it replaces its stored copy
+    pub fn rule(&self) -> AlarmConfig {
+        AlarmConfig {
+            id: Uuid::new_v5(&Uuid::NAMESPACE_URL, b"neo:alarm/device-communication"),
+            name: "Device Communication Lost".to_string(),
+            source_pattern: default_source_pattern(),
+            tag_filter: None,
+            condition: AlarmCondition::DeviceOffline,
+            units: None,
+            severity: self.severity,
+            delay_seconds: self.delay_seconds,
+            off_delay_seconds: 0,
+            min_on_seconds: 0,
+            min_off_seconds: 0,
+            deadband: 0.0,
+            inhibit: None,
+            message_template: "Device {point} {threshold}".to_string(),
+            enabled: self.enabled,
+        }
+    }
+}
+
+/// Alarm trigger conditions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum AlarmCondition {
+    /// Value exceeds threshold
+    HighLimit { value: f32 },
+    /// Value falls below threshold
+    LowLimit { value: f32 },
+    /// Value is outside range
+    OutOfRange { low: f32, high: f32 },
+    /// Value equals a specific value
+    Equals { value: PropertyValue },
+    /// Value does not equal a specific value
+    NotEquals { value: PropertyValue },
+    /// Boolean is true
+    IsTrue,
+    /// Boolean is false
+    IsFalse,
+    /// Point has stopped logging history (see the history logging policy's
+    /// `stale_after_secs`)
+    HistoryStale,
+    /// Value changes faster than `per_minute` (either direction), measured
+    /// across the last `window_secs`
+    RateOfChange { per_minute: f32, window_secs: u32 },
+    /// Value has not moved by more than `tolerance` for `minutes`
+    Flatline {
+        minutes: u32,
+        #[serde(default)]
+        tolerance: f32,
+    },
+    /// Value differs from a reference point by more than `limit`
+    /// (e.g., reference "{parent}/zone-sp")
+    Deviation { reference: String, limit: f32 },
+    /// Multi-state value is one of `states`
+    StateIn { states: Vec<u32> },
+    /// Command disagrees with its feedback point (e.g., feedback
+    /// "{parent}/fan-status"); `delay_seconds` gives the equipment time to respond
+    CommandMismatch { feedback: String },
+    /// Device has lost communication (status Offline or Timeout); matched
+    /// against "{network}/{device}" rather than point paths
+    DeviceOffline,
+}
+
+impl AlarmCondition {
+    /// Whether the condition is driven by events rather than point values
+    fn is_event_driven(&self) -> bool {
+        matches!(self, AlarmCondition::HistoryStale | AlarmCondition::DeviceOffline)
+    }
+
+    /// Evaluate the condition against a point value
+    fn evaluate(&self, value: &PropertyValue) -> bool {
+        match (self, value) {
+            // Numeric comparisons
+            (AlarmCondition::HighLimit { value: threshold }, PropertyValue::Real(v)) => {
+                v > threshold
+            }
+            (AlarmCondition::HighLimit { value: threshold }, PropertyValue::Unsigned(v)) => {
+                (*v as f32) > *threshold
+            }
+            (AlarmCondition::LowLimit { value: threshold }, PropertyValue::Real(v)) => {
+                v < threshold
+            }
+            (AlarmCondition::LowLimit { value: threshold }, PropertyValue::Unsigned(v)) => {
+                (*v as f32) < *threshold
+            }
+            (AlarmCondition::OutOfRange { low, high }, PropertyValue::Real(v)) => {
+                v < low || v > high
+            }
+            (AlarmCondition::OutOfRange { low, high }, PropertyValue::Unsigned(v)) => {
+                let v = *v as f32;
+                v < *low || v > *high
+            }
+
+            // Equality comparisons
+            (AlarmCondition::Equals { value: expected }, actual) => expected == actual,
+            (AlarmCondition::NotEquals { value: expected }, actual) => expected != actual,
+
+            // Boolean comparisons
+            (AlarmCondition::IsTrue, PropertyValue::Boolean(v)) => *v,
+            (AlarmCondition::IsFalse, PropertyValue::Boolean(v)) => !*v,
+
+            // Type mismatches don't trigger
+            _ => false,
+        }
+    }
+
+    /// The condition with its limits moved back by a deadband, for deciding
+    /// whether a met condition is still met (other conditions are unchanged)
+    fn relaxed(&self, deadband: f32) -> AlarmCondition {
+        match self {
+            AlarmCondition::HighLimit { value } => AlarmCondition::HighLimit {
+                value: value - deadband,
+            },
+            AlarmCondition::LowLimit { value } => AlarmCondition::LowLimit {
+                value: value + deadband,
+            },
+            AlarmCondition::OutOfRange { low, high } => AlarmCondition::OutOfRange {
+                low: low + deadband,
+                high: high - deadband,
+            },
+            AlarmCondition::Deviation { reference, limit } => AlarmCondition::Deviation {
+                reference: reference.clone(),
+                limit: limit - deadband,
+            },
+            other => other.clone(),
+        }
+    }
+
+    /// Get a description of the condition for messages
+    fn description(&self) -> String {
+        match self {
+            AlarmCondition::HighLimit { value } => format!("exceeds {}", value),
+            AlarmCondition::LowLimit { value } => format!("below {}", value),
+            AlarmCondition::OutOfRange { low, high } => format!("outside {} to {}", low, high),
+            AlarmCondition::Equals { value } => format!("equals {:?}", value),
+            AlarmCondition::NotEquals { value } => format!("not equal to {:?}", value),
+            AlarmCondition::IsTrue => "is true".to_string(),
+            AlarmCondition::IsFalse => "is false".to_string(),
+            AlarmCondition::HistoryStale => "has not logged history".to_string(),
+            AlarmCondition::RateOfChange { per_minute, .. } => {
+                format!("changes faster than {} per minute", per_minute)
+            }
+            AlarmCondition::Flatline { minutes, .. } => {
+                format!("has not changed for {} minutes", minutes)
+            }
+            AlarmCondition::Deviation { reference, limit } => {
+                format!("deviates from {} by more than {}", reference, limit)
+            }
+            AlarmCondition::StateIn { states } => format!("in state {:?}", states),
+            AlarmCondition::CommandMismatch { feedback } => {
+                format!("does not match {}", feedback)
+            }
+            AlarmCondition::DeviceOffline => "is not communicating".to_string(),
+        }
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Alarm Actor Messages
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Alarm-specific messages (beyond common ServiceMsg)
+#[derive(Debug)]
+pub enum AlarmMsg {
+    /// Get all active (non-cleared) alarms
+    GetActiveAlarms {
+        reply: oneshot::Sender<Vec<Alarm>>,
+    },
+    /// Acknowledge an alarm (fails if it is not active and unacknowledged, or
+    /// if its severity requires a comment and none is given)
+    AcknowledgeAlarm {
+        alarm_id: Uuid,
+        acknowledged_by: Option<String>,
+        comment: Option<String>,
+        reply: oneshot::Sender<Result<Alarm>>,
+    },
+    /// Acknowledge every unacknowledged active alarm matching a filter
+    AcknowledgeAlarms {
+        filter: AlarmFilter,
+        acknowledged_by: Option<String>,
+        comment: Option<String>,
+        reply: oneshot::Sender<BulkAcknowledgement>,
+    },
+    /// Add an operator comment to an alarm's timeline
+    CommentAlarm {
+        alarm_id: Uuid,
+        comment: String,
+        author: Option<String>,
+        reply: oneshot::Sender<Result<()>>,
+    },
+    /// Get the timeline of an alarm, oldest first
+    GetAlarmTimeline {
+        alarm_id: Uuid,
+        reply: oneshot::Sender<Vec<AlarmTimelineEntry>>,
+    },
+    /// Get a page of alarm history within a time range, newest first,
+    /// continuing after the cursor alarm of a previous page
+    GetAlarmHistory {
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        source_filter: Option<String>,
+        cursor: Option<Uuid>,
+        limit: Option<u32>,
+        reply: oneshot::Sender<Result<AlarmHistoryPage>>,
+    },
+    /// Add an alarm configuration (fails if the configuration is not valid)
+    AddConfig {
+        config: AlarmConfig,
+        reply: oneshot::Sender<Result<()>>,
+    },
+    /// Remove an alarm configuration
+    RemoveConfig {
+        id: Uuid,
+        reply: oneshot::Sender<bool>,
+    },
+    /// Get all alarm configurations
+    GetConfigs {
+        reply: oneshot::Sender<Vec<AlarmConfig>>,
+    },
+    /// Get the active alarms that are shelved, suppressed or inhibited
+    GetSuppressedAlarms {
+        reply: oneshot::Sender<Vec<SuppressedAlarm>>,
+    },
+    /// Shelve an alarm or every alarm of a config for a duration (None if
+    /// the alarm is not active, the config does not exist or the duration is
+    /// too long to represent)
+    Shelve {
+        target: ShelveTarget,
+        duration_secs: u64,
+        reason: String,
+        shelved_by: Option<String>,
+        reply: oneshot::Sender<Option<Shelf>>,
+    },
+    /// End a shelf before its duration
+    Unshelve {
+        target: ShelveTarget,
+        reply: oneshot::Sender<bool>,
+    },
+    /// Get the current shelves
+    GetShelves {
+        reply: oneshot::Sender<Vec<Shelf>>,
+    },
+    /// Add (or replace, by id) a suppression window; replies with its id
+    AddSuppressionWindow {
+        window: SuppressionWindow,
+        reply: oneshot::Sender<Uuid>,
+    },
+    /// Remove a suppression window
+    RemoveSuppressionWindow {
+        id: Uuid,
+        reply: oneshot::Sender<bool>,
+    },
+    /// Get the suppression windows (current and scheduled)
+    GetSuppressionWindows {
+        reply: oneshot::Sender<Vec<SuppressionWindow>>,
+    },
+    /// Get logged notification attempts, newest first, optionally for one alarm
+    GetNotificationLog {
+        alarm_id: Option<Uuid>,
+        limit: Option<u32>,
+        reply: oneshot::Sender<Vec<NotificationRecord>>,
+    },
+    /// Raise and clear alarms whose delays have elapsed
+    CheckTimers,
+}
+
+/// Reply type for AlarmMsg (for messages that don't use oneshot)
+#[derive(Debug, kameo::Reply)]
+pub enum AlarmReply {
+    /// Configuration added
+    ConfigAdded,
+    /// Timers checked
+    TimersChecked { transitions: usize },
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Alarm Actor
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Actor-based Alarm Service
+#[derive(kameo::Actor)]
+pub struct AlarmActor {
+    id: String,
+    name: String,
+    #[allow(dead_code)]
+    description: String,
+    state: ServiceStateTracker,
+    /// Alarm configurations
+    configs: Vec<AlarmConfig>,
+    /// Currently active alarms (keyed by alarm ID)
+    active_alarms: HashMap<Uuid, Alarm>,
+    /// Tracks which config triggered which alarm for each point
+    /// Key: (config_id, point_name) -> alarm_id
+    alarm_tracker: HashMap<(Uuid, String), Uuid>,
+    /// Latching alarms returned to normal and awaiting acknowledgement
+    /// Key: (config_id, point_name) -> alarm_id
+    latched: HashMap<(Uuid, String), Uuid>,
+    /// Condition state per (config_id, point_name), for delays and deadbands
+    conditions: HashMap<(Uuid, String), ConditionState>,
+    /// Latest value of each reported point (for conditions that need context)
+    points: HashMap<String, PointContext>,
+    /// Recent values per (config_id, point_name), for rate-of-change and flatline
+    histories: HashMap<(Uuid, String), ValueHistory>,
+    /// Conditions referring to a point (deviation references, command
+    /// feedback), re-evaluated when it changes: point -> (config_id, point_name)
+    dependents: HashMap<String, HashSet<(Uuid, String)>>,
+    /// Devices ("{network}/{device}") that have lost communication
+    offline_devices: HashSet<String>,
+    /// Shelved alarms and alarm configs
+    shelves: HashMap<ShelveTarget, Shelf>,
+    /// Scheduled suppression windows
+    windows: Vec<SuppressionWindow>,
+    /// Rate of raised alarms and the current alarm flood
+    floods: FloodDetector,
+    /// Acknowledgement rules per severity
+    ack_policy: AckPolicy,
+    /// Time source for delays
+    clock: AlarmClock,
+    /// Days of alarm history to keep (0 = keep forever)
+    history_retention_days: u32,
+    /// When expired history was last purged
+    last_history_purge: Option<DateTime<Utc>>,
+    /// Path of the alarm database (in memory when None)
+    db_path: Option<PathBuf>,
+    /// Persisted configurations and alarm records (open while started)
+    store: Option<Arc<AlarmStore>>,
+    /// Directory of alarm rule files
+    rules_dir: Option<PathBuf>,
+    /// Ids of the configurations loaded from each rule file
+    rule_files: HashMap<PathBuf, Vec<Uuid>>,
+    /// Errors of rule files that failed to load (their previous rules stay)
+    rule_errors: HashMap<PathBuf, String>,
+    /// Watcher reporting changed rule files
+    rule_watcher: Option<RuleWatcher>,
+    /// Semantic tags for resolving `tag_filter`
+    tag_store: Option<TagStore>,
+    /// Parsed tag filters keyed by filter text (None if invalid)
+    tag_filters: HashMap<String, Option<TagFilter>>,
+    /// Event bus for alarm lifecycle events
+    pubsub: Option<ActorRef<PubSubBroker>>,
+    /// Events waiting to be published
+    events: Vec<Event>,
+    /// Notification classes and the SMTP server
+    notification_config: NotificationConfig,
+    /// Escalation state of routed alarms
+    escalations: Vec<Escalation>,
+    /// Notifications waiting to be sent
+    notifications: Vec<Notification>,
+    /// Service registry for plugin callbacks
+    registry: Option<ActorRef<ServiceRegistry>>,
+}
+
+impl AlarmActor {
+    /// Create a new AlarmActor with default settings
+    pub fn new() -> Self {
+        Self {
+            id: "alarm".to_string(),
+            name: "Alarm Service".to_string(),
+            description: "Monitors points and raises alarms based on configurable conditions"
+                .to_string(),
+            state: ServiceStateTracker::new(),
+            configs: Vec::new(),
+            active_alarms: HashMap::new(),
+            alarm_tracker: HashMap::new(),
+            latched: HashMap::new(),
+            conditions: HashMap::new(),
+            points: HashMap::new(),
+            histories: HashMap::new(),
+            dependents: HashMap::new(),
+            offline_devices: HashSet::new(),
+            shelves: HashMap::new(),
+            windows: Vec::new(),
+            floods: FloodDetector::new(DEFAULT_FLOOD_THRESHOLD, DEFAULT_FLOOD_WINDOW_SECS),
+            ack_policy: AckPolicy::default(),
+            clock: AlarmClock::System,
+            history_retention_days: DEFAULT_HISTORY_RETENTION_DAYS,
+            last_history_purge: None,
+            db_path: None,
+            store: None,
+            rules_dir: None,
+            rule_files: HashMap::new(),
+            rule_errors: HashMap::new(),
+            rule_watcher: None,
+            tag_store: None,
+            tag_filters: HashMap::new(),
+            pubsub: None,
+            events: Vec::new(),
+            notification_config: NotificationConfig::default(),
+            escalations: Vec::new(),
+            notifications: Vec::new(),
+            registry: None,
+        }
+    }
+
+    /// Create a new AlarmActor with custom ID and history retention in days
+    /// (0 = keep forever)
+    pub fn with_config(id: impl Into<String>, history_retention_days: u32) -> Self {
+        let id = id.into();
+        Self {
+            name: format!("Alarm Service ({})", id),
+            id,
+            description: "Monitors points and raises alarms based on configurable conditions"
+                .to_string(),
+            state: ServiceStateTracker::new(),
+            configs: Vec::new(),
+            active_alarms: HashMap::new(),
+            alarm_tracker: HashMap::new(),
+            latched: HashMap::new(),
+            conditions: HashMap::new(),
+            points: HashMap::new(),
+            histories: HashMap::new(),
+            dependents: HashMap::new(),
+            offline_devices: HashSet::new(),
+            shelves: HashMap::new(),
+            windows: Vec::new(),
+            floods: FloodDetector::new(DEFAULT_FLOOD_THRESHOLD, DEFAULT_FLOOD_WINDOW_SECS),
+            ack_policy: AckPolicy::default(),
+            clock: AlarmClock::System,
+            history_retention_days,
+            last_history_purge: None,
+            db_path: None,
+            store: None,
+            rules_dir: None,
+            rule_files: HashMap::new(),
+            rule_errors: HashMap::new(),
+            rule_watcher: None,
+            tag_store: None,
+            tag_filters: HashMap::new(),
+            pubsub: None,
+            events: Vec::new(),
+            notification_config: NotificationConfig::default(),
+            escalations: Vec::new(),
+            notifications: Vec::new(),
+            registry: None,
+        }
+    }
+
+    /// Resolve alarm `tag_filter`s against the given tag store
+    pub fn with_tag_store(mut self, tag_store: TagStore) -> Self {
+        self.tag_store = Some(tag_store);
+        self
+    }
+
+    /// Publish alarm lifecycle events to the given broker
+    pub fn with_pubsub(mut self, pubsub: ActorRef<PubSubBroker>) -> Self {
+        self.pubsub = Some(pubsub);
+        self
+    }
+
+    /// Persist configurations and alarms in a redb database at this path
+    pub fn with_db_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.db_path = Some(path.into());
+        self
+    }
+
+    /// Load alarm rules from the JSON and TOML files in this directory, and
+    /// reload them as they change
+    pub fn with_rules_dir(mut self, path: impl Into<PathBuf>) -> Self {
+        self.rules_dir = Some(path.into());
+        self
+    }
+
+    /// Route raised alarms to recipients with these notification classes
+    pub fn with_notifications(mut self, config: NotificationConfig) -> Self {
+        self.notification_config = config;
+        self
+    }
+
+    /// Reach plugin recipients through the given service registry
+    pub fn with_registry(mut self, registry: ActorRef<ServiceRegistry>) -> Self {
+        self.registry = Some(registry);
+        self
+    }
+
+    /// Detect a flood when more than `threshold` alarms are raised within
+    /// `window_secs` seconds
+    pub fn with_flood_detection(mut self, threshold: usize, window_secs: u32) -> Self {
+        self.floods = FloodDetector::new(threshold, window_secs);
+        self
+    }
+
+    /// Apply these acknowledgement rules (required comments, latching)
+    pub fn with_ack_policy(mut self, policy: AckPolicy) -> Self {
+        self.ack_policy = policy;
+        self
+    }
+
+    /// Keep alarm history for this many days (0 = keep forever)
+    pub fn with_history_retention(mut self, days: u32) -> Self {
+        self.history_retention_days = days;
+        self
+    }
+
+    /// Raise the built-in alarm for devices that stop communicating
+    pub fn with_device_alarm(mut self, config: DeviceAlarmConfig) -> Self {
+        self.configs.push(config.rule());
+        self
+    }
+
+    /// Time delays with the given clock
+    pub fn with_clock(mut self, clock: AlarmClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Add an alarm configuration
+    pub fn add_config(&mut self, config: AlarmConfig) {
+        self.configs.push(config);
+        self.persist_configs();
+    }
+
+    /// Open the store, merge in its configurations and restore its active alarms
+    fn do_start(&mut self) -> Result<()> {
+        if self.store.is_none() {
+            self.store = Some(Arc::new(match &self.db_path {
+                Some(path) => AlarmStore::open(path)?,
+                None => AlarmStore::in_memory()?,
+            }));
+        }
+        // Rules from files are reloaded below
+        let file_rules = self.file_rule_ids();
+        self.configs.retain(|c| !file_rules.contains(&c.id));
+
+        let Some(store) = &self.store else {
+            return Ok(());
+        };
+
+        // Configurations added before start replace stored ones with the same id
+        let mut configs = store.configs()?;
+        for config in self.configs.drain(..) {
+            match configs.iter_mut().find(|c| c.id == config.id) {
+                Some(stored) => *stored = config,
+                None => configs.push(config),
+            }
+        }
+        store.save_configs(&configs)?;
+        self.configs = configs;
+
+        for alarm in store.active_alarms()? {
+            if let Some(config_id) = alarm.config_id {
+                let key = (config_id, alarm.source.clone());
+                if alarm.returned_to_normal_at.is_some() {
+                    self.latched.insert(key, alarm.id);
+                } else {
+                    let state =
+                        ConditionState::raised(alarm.triggered_at, alarm.value_at_trigger.clone());
+                    self.alarm_tracker.insert(key.clone(), alarm.id);
+                    self.conditions.insert(key, state);
+                }
+            }
+            self.active_alarms.insert(alarm.id, alarm);
+        }
+
+        self.shelves = store.shelves()?.into_iter().map(|s| (s.target, s)).collect();
+        self.windows = store.windows()?;
+        self.escalations = store.escalations()?;
+
+        self.load_rules();
+        Ok(())
+    }
+
+    /// Load every rule file and start watching the rules directory
+    fn load_rules(&mut self) {
+        let Some(dir) = self.rules_dir.clone() else {
+            return;
+        };
+        if let Err(e) = std::fs::create_dir_all(&dir) {
+            tracing::warn!("Cannot create alarm rules directory {}: {}", dir.display(), e);
+            return;
+        }
+
+        match rules::rule_files(&dir) {
+            Ok(paths) => {
+                for path in paths {
+                    self.reload_rule_file(&path);
+                }
+            }
+            Err(e) => tracing::warn!("Cannot read alarm rules directory {}: {}", dir.display(), e),
+        }
+
+        if self.rule_watcher.is_none() {
+            match RuleWatcher::start(&dir) {
+                Ok(watcher) => self.rule_watcher = Some(watcher),
+                Err(e) => tracing::warn!("Alarm rule hot reload disabled: {}", e),
+            }
+        }
+    }
+
+    /// Reload rule files changed since the last check
+    fn reload_changed_rules(&mut self) {
+        let Some(watcher) = &mut self.rule_watcher else {
+            return;
+        };
+        for path in watcher.changed() {
+            self.reload_rule_file(&path);
+        }
+    }
+
+    /// Load (or unload, if it is gone) one rule file; a file that fails to
+    /// load keeps its previous rules
+    fn reload_rule_file(&mut self, path: &Path) {
+        let rules = if path.exists() {
+            match rules::load_rule_file(path).and_then(|rules| self.check_rule_ids(path, rules)) {
+                Ok(rules) => rules,
+                Err(e) => {
+                    tracing::warn!("Failed to load alarm rules from {}: {}", path.display(), e);
+                    self.rule_errors.insert(path.to_path_buf(), e.to_string());
+                    return;
+                }
+            }
+        } else {
+            Vec::new()
+        };
+
+        self.rule_errors.remove(path);
+        let previous = self.rule_files.remove(path).unwrap_or_default();
+        if !rules.is_empty() {
+            let ids = rules.iter().map(|r| r.id).collect();
+            self.rule_files.insert(path.to_path_buf(), ids);
+        }
+        tracing::info!("Loaded {} alarm rules from {}", rules.len(), path.display());
+
+        self.configs.retain(|c| !previous.contains(&c.id));
+        self.configs.extend(rules);
+        self.apply_config_changes();
+    }
+
+    /// Reject rules whose ids belong to another rule file or a stored configuration
+    fn check_rule_ids(&self, path: &Path, rules: Vec<AlarmConfig>) -> Result<Vec<AlarmConfig>> {
+        let own = self.rule_files.get(path);
+        for rule in &rules {
+            let taken = self.configs.iter().any(|c| c.id == rule.id)
+                && !own.is_some_and(|ids| ids.contains(&rule.id));
+            if taken {
+                return Err(Error::Config(format!(
+                    "Rule '{}': id {} is already used by another configuration",
+                    rule.name, rule.id
+                )));
+            }
+        }
+        Ok(rules)
+    }
+
+    /// Ids of the configurations loaded from rule files
+    fn file_rule_ids(&self) -> HashSet<Uuid> {
+        self.rule_files.values().flatten().copied().collect()
+    }
+
+    /// Bring active alarms in line with the configurations: alarms of removed
+    /// or disabled configurations clear, the others take their configuration's
+    /// severity and are re-evaluated against its condition
+    fn apply_config_changes(&mut self) {
+        let now = self.clock.now();
+        let keys: Vec<(Uuid, String)> = self.conditions.keys().cloned().collect();
+        for key in keys {
+            let config = self.configs.iter().find(|c| c.id == key.0 && c.enabled).cloned();
+            let Some(config) = config else {
+                self.conditions.remove(&key);
+                self.histories.remove(&key);
+                self.clear_alarm(key.0, &key.1, None, now);
+                continue;
+            };
+
+            if let Some(alarm) = self
+                .alarm_tracker
+                .get(&key)
+                .and_then(|id| self.active_alarms.get_mut(id))
+            {
+                if alarm.severity != config.severity {
+                    alarm.severity = config.severity;
+                    let alarm = alarm.clone();
+                    self.persist_alarm(&alarm);
+                }
+            }
+
+            // Values are re-evaluated without the deadband, as the limits may have moved
+            let has_value =
+                !config.condition.is_event_driven() && self.points.contains_key(&key.1);
+            if has_value {
+                let undamped = AlarmConfig {
+                    deadband: 0.0,
+                    ..config.clone()
+                };
+                self.evaluate_config(&undamped, &key.1, now);
+            } else {
+                self.apply_condition(&config, &key.1, now, now);
+            }
+        }
+
+        // Latched alarms of removed or disabled configs clear too
+        let released: Vec<(Uuid, String)> = self
+            .latched
+            .keys()
+            .filter(|(config_id, _)| !self.configs.iter().any(|c| c.id == *config_id && c.enabled))
+            .cloned()
+            .collect();
+        for key in released {
+            if let Some(alarm_id) = self.latched.remove(&key) {
+                self.end_alarm(alarm_id, now);
+            }
+        }
+        self.persist_configs();
+    }
+
+    /// Write the configurations (except those from rule files) to the store
+    fn persist_configs(&self) {
+        if let Some(store) = &self.store {
+            let file_rules = self.file_rule_ids();
+            let configs: Vec<AlarmConfig> = self
+                .configs
+                .iter()
+                .filter(|c| !file_rules.contains(&c.id))
+                .cloned()
+                .collect();
+            if let Err(e) = store.save_configs(&configs) {
+                tracing::warn!("Failed to persist alarm configurations: {}", e);
+            }
+        }
+    }
+
+    /// Status details shared by the status message and request
+    fn status_extra(&self) -> serde_json::Value {
+        let rule_errors: HashMap<String, &String> = self
+            .rule_errors
+            .iter()
+            .map(|(path, error)| (path.display().to_string(), error))
+            .collect();
+        serde_json::json!({
+            "active_alarm_count": self.active_alarms.len(),
+            "config_count": self.configs.len(),
+            "offline_device_count": self.offline_devices.len(),
+            "shelf_count": self.shelves.len(),
+            "suppression_window_count": self.windows.len(),
+            "alarm_flood": self.floods.is_flooding(),
+            "notification_class_count": self.notification_config.classes.len(),
+            "latched_alarm_count": self.latched.len(),
+            "rule_file_count": self.rule_files.len(),
+            "rule_errors": rule_errors,
+        })
+    }
+
+    /// Write an alarm record to the store
+    fn persist_alarm(&self, alarm: &Alarm) {
+        if let Some(store) = &self.store {
+            if let Err(e) = store.save_alarm(alarm) {
+                tracing::warn!("Failed to persist alarm {}: {}", alarm.id, e);
+            }
+        }
+    }
+
+    /// A page of alarm history from the store
+    fn alarm_history(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        source_filter: Option<&str>,
+        cursor: Option<Uuid>,
+        limit: usize,
+    ) -> Result<AlarmHistoryPage> {
+        match &self.store {
+            Some(store) => store.history(start, end, source_filter, cursor, limit),
+            None => Ok(AlarmHistoryPage {
+                alarms: Vec::new(),
+                next_cursor: None,
+            }),
+        }
+    }
+
+    /// Alarm KPIs over a time range, from the store's alarm history
+    fn alarm_kpis(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> Result<AlarmKpis> {
+        let mut kpis = KpiBuilder::new(start, end);
+        if let Some(store) = &self.store {
+            store.for_each_alarm(start, end, |alarm| kpis.add(alarm))?;
+        }
+        let active: Vec<Alarm> = self.active_alarms.values().cloned().collect();
+        Ok(kpis.finish(&active, self.clock.now()))
+    }
+
+    /// Delete alarm history older than the retention period, at most once per
+    /// HISTORY_PURGE_INTERVAL_SECS; returns the number of alarm records deleted
+    fn purge_history(&mut self, now: DateTime<Utc>) -> usize {
+        if self.history_retention_days == 0
+            || self
+                .last_history_purge
+                .is_some_and(|last| (now - last).num_seconds() < HISTORY_PURGE_INTERVAL_SECS)
+        {
+            return 0;
+        }
+        let Some(store) = &self.store else {
+            return 0;
+        };
+        self.last_history_purge = Some(now);
+
+        let cutoff = now - chrono::Duration::days(self.history_retention_days as i64);
+        match store.purge(cutoff) {
+            Ok(purged) => {
+                if purged > 0 {
+                    tracing::info!("Purged {} alarm records older than {}", purged, cutoff);
+                }
+                purged
+            }
+            Err(e) => {
+                tracing::warn!("Failed to purge alarm history: {}", e);
+                0
+            }
+        }
+    }
+
+    /// Check a point against a config's tag filter (configs without one always match)
+    fn matches_tags(&mut self, config: &AlarmConfig, point: &str) -> bool {
+        match &config.tag_filter {
+            Some(filter_text) => self.matches_tag_filter(filter_text, point),
+            None => true,
+        }
+    }
+
+    /// Check a point against a tag filter
+    fn matches_tag_filter(&mut self, filter_text: &str, point: &str) -> bool {
+        let Some(store) = &self.tag_store else {
+            return false;
+        };
+
+        let filter = self
+            .tag_filters
+            .entry(filter_text.to_string())
+            .or_insert_with(|| match TagFilter::parse(filter_text) {
+                Ok(filter) => Some(filter),
+                Err(e) => {
+                    tracing::warn!("Invalid alarm tag filter '{}': {}", filter_text, e);
+                    None
+                }
+            });
+
+        filter.as_ref().map(|f| store.matches(point, f)).unwrap_or(false)
+    }
+
+    /// Convert a point value to the units of a config's thresholds
+    fn value_in_units(
+        config: &AlarmConfig,
+        point: &str,
+        value: &PropertyValue,
+        units: Option<EngineeringUnit>,
+    ) -> Option<PropertyValue> {
+        match (units, config.units) {
+            (Some(from), Some(to)) if from != to => match from.convert_property(value, to) {
+                Ok(converted) => Some(converted),
+                Err(e) => {
+                    tracing::warn!(
+                        "Alarm config '{}' cannot evaluate {}: {}",
+                        config.name,
+                        point,
+                        e
+                    );
+                    None
+                }
+            },
+            _ => Some(value.clone()),
+        }
+    }
+
+    /// Evaluate a point value against all alarm configs, and re-evaluate the
+    /// conditions that refer to the point
+    fn evaluate_point(
+        &mut self,
+        point: &str,
+        value: &PropertyValue,
+        units: Option<EngineeringUnit>,
+        timestamp: DateTime<Utc>,
+    ) {
+        let now = self.clock.now();
+        self.points.insert(
+            point.to_string(),
+            PointContext {
+                value: value.clone(),
+                units,
+                updated_at: now,
+            },
+        );
+
+        for config in &self.configs.clone() {
+            if !config.enabled || config.condition.is_event_driven() {
+                continue;
+            }
+
+            // Check if point matches the pattern and tag filter
+            if !WildMatch::new(&config.source_pattern).matches(point)
+                || !self.matches_tags(config, point)
+            {
+                continue;
+            }
+
+            self.evaluate_config(config, point, timestamp);
+        }
+
+        let dependents = self.dependents.get(point).cloned().unwrap_or_default();
+        for (config_id, dependent) in dependents {
+            let config = self.configs.iter().find(|c| c.id == config_id && c.enabled).cloned();
+            if let Some(config) = config {
+                self.evaluate_config(&config, &dependent, timestamp);
+            }
+        }
+    }
+
+    /// Evaluate a config's condition on a point from the point's latest value
+    /// and context (see context); true if an alarm was raised or cleared
+    fn evaluate_config(
+        &mut self,
+        config: &AlarmConfig,
+        point: &str,
+        timestamp: DateTime<Utc>,
+    ) -> bool {
+        let Some(reported) = self.points.get(point).cloned() else {
+            return false;
+        };
+
+        // Compare in the config's units; skip points in incompatible units
+        let Some(value) = Self::value_in_units(config, point, &reported.value, reported.units)
+        else {
+            return false;
+        };
+
+        // Apply the deadband while the condition is met
+        let key = (config.id, point.to_string());
+        let condition = match self.conditions.get(&key) {
+            Some(state) if state.met => config.condition.relaxed(config.deadband),
+            _ => config.condition.clone(),
+        };
+
+        let met = match &condition {
+            AlarmCondition::RateOfChange {
+                per_minute,
+                window_secs,
+            } => {
+                let now = self.clock.now();
+                let history = self.histories.entry(key).or_default();
+                if let Some(v) = context::numeric(&value) {
+                    history.push(reported.updated_at, v);
+                }
+                let window = chrono::Duration::seconds(*window_secs as i64);
+                history.per_minute(now, window).is_some_and(|rate| rate.abs() > *per_minute)
+            }
+            AlarmCondition::Flatline { minutes, tolerance } => {
+                let history = self.histories.entry(key).or_default();
+                let since = history.hold(&value, *tolerance, reported.updated_at);
+                self.clock.now() - since >= chrono::Duration::minutes(*minutes as i64)
+            }
+            AlarmCondition::Deviation { reference, limit } => {
+                let reference = self.reference_point(reference, config, point);
+                let setpoint = self.points.get(&reference).and_then(|r| {
+                    Self::value_in_units(config, &reference, &r.value, r.units)
+                        .as_ref()
+                        .and_then(context::numeric)
+                });
+                match (context::numeric(&value), setpoint) {
+                    (Some(v), Some(setpoint)) => (v - setpoint).abs() > *limit,
+                    _ => false,
+                }
+            }
+            AlarmCondition::StateIn { states } => {
+                context::state_of(&value).is_some_and(|state| states.contains(&state))
+            }
+            AlarmCondition::CommandMismatch { feedback } => {
+                let feedback = self.reference_point(feedback, config, point);
+                let status = self.points.get(&feedback).and_then(|f| context::is_on(&f.value));
+                match (context::is_on(&value), status) {
+                    (Some(command), Some(status)) => command != status,
+                    _ => false,
+                }
+            }
+            other => other.evaluate(&value),
+        };
+        self.update_condition(config, point, met, Some(&value), timestamp)
+    }
+
+    /// Resolve a reference or feedback path for a point, and register the
+    /// point's condition to be re-evaluated when the referenced point changes
+    fn reference_point(&mut self, reference: &str, config: &AlarmConfig, point: &str) -> String {
+        let reference = context::resolve_reference(reference, point);
+        self.dependents
+            .entry(reference.clone())
+            .or_default()
+            .insert((config.id, point.to_string()));
+        reference
+    }
+
+    /// Raise or clear HistoryStale alarms for a point
+    fn history_logging(&mut self, point: &str, stale: bool, timestamp: DateTime<Utc>) {
+        for config in &self.configs.clone() {
+            if !config.enabled
+                || !matches!(config.condition, AlarmCondition::HistoryStale)
+                || !WildMatch::new(&config.source_pattern).matches(point)
+                || !self.matches_tags(config, point)
+            {
+                continue;
+            }
+
+            self.update_condition(config, point, stale, None, timestamp);
+        }
+    }
+
+    /// Raise or clear DeviceOffline alarms for a device, and track whether
+    /// alarms on its points are suppressed
+    fn device_status(
+        &mut self,
+        network: &str,
+        device: &str,
+        status: DeviceStatus,
+        timestamp: DateTime<Utc>,
+    ) {
+        let offline = match status {
+            DeviceStatus::Offline | DeviceStatus::Timeout => true,
+            DeviceStatus::Online => false,
+            // The device is still communicating
+            DeviceStatus::Error => return,
+        };
+        let device = format!("{}/{}", network, device);
+        if offline {
+            self.offline_devices.insert(device.clone());
+        } else {
+            // Suppressed point alarms still met are raised by the timer task
+            self.offline_devices.remove(&device);
+        }
+
+        for config in &self.configs.clone() {
+            if config.enabled
+                && matches!(config.condition, AlarmCondition::DeviceOffline)
+                && WildMatch::new(&config.source_pattern).matches(&device)
+            {
+                self.update_condition(config, &device, offline, None, timestamp);
+            }
+        }
+    }
+
+    /// Whether a point alarm is suppressed because its device is offline
+    fn suppressed_by_device(&self, config: &AlarmConfig, point: &str) -> bool {
+        !matches!(config.condition, AlarmCondition::DeviceOffline)
+            && self.offline_device(point).is_some()
+    }
+
+    /// The offline device a point belongs to, if any
+    fn offline_device(&self, point: &str) -> Option<&String> {
+        self.offline_devices.iter().find(|device| {
+            point
+                .strip_prefix(device.as_str())
+                .is_some_and(|rest| rest.starts_with('/'))
+        })
+    }
+
+    /// Record an evaluation of a config's condition on a point, and raise or
+    /// clear its alarm if that is due; true if it did
+    fn update_condition(
+        &mut self,
+        config: &AlarmConfig,
+        point: &str,
+        met: bool,
+        value: Option<&PropertyValue>,
+        timestamp: DateTime<Utc>,
+    ) -> bool {
+        let now = self.clock.now();
+        self.conditions
+            .entry((config.id, point.to_string()))
+            .or_insert_with(|| ConditionState::new(now))
+            .update(met, value, now);
+        self.apply_condition(config, point, now, timestamp)
+    }
+
+    /// Raise or clear the alarm for a config and point if its condition has
+    /// waited out the delays; true if it did
+    fn apply_condition(
+        &mut self,
+        config: &AlarmConfig,
+        point: &str,
+        now: DateTime<Utc>,
+        timestamp: DateTime<Utc>,
+    ) -> bool {
+        let key = (config.id, point.to_string());
+        let Some(state) = self.conditions.get(&key) else {
+            return false;
+        };
+        let value = state.value.clone();
+        let raised = self.alarm_tracker.contains_key(&key);
+
+        if !raised && state.raise_due(config, now) && !self.suppressed_by_device(config, point) {
+            self.raise_alarm(config, point, value.as_ref(), timestamp);
+            if let Some(state) = self.conditions.get_mut(&key) {
+                state.raised_at = Some(now);
+            }
+            true
+        } else if raised && state.clear_due(config, now) {
+            self.clear_alarm(config.id, point, value.as_ref(), timestamp);
+            if let Some(state) = self.conditions.get_mut(&key) {
+                state.cleared_at = Some(now);
+            }
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Raise and clear alarms whose delays have elapsed; returns the number of
+    /// alarms raised or cleared
+    fn check_timers(&mut self) -> usize {
+        let now = self.clock.now();
+        let mut transitions = 0;
+        self.expire_suppressions(now);
+        self.escalate(now);
+        self.floods.expire(now);
+
+        // Rate of change and flatline conditions change without a new value
+        let timed: Vec<(AlarmConfig, String)> = self
+            .conditions
+            .keys()
+            .filter_map(|(config_id, point)| {
+                self.configs
+                    .iter()
+                    .find(|c| c.id == *config_id && c.enabled)
+                    .filter(|c| {
+                        matches!(
+                            c.condition,
+                            AlarmCondition::RateOfChange { .. } | AlarmCondition::Flatline { .. }
+                        )
+                    })
+                    .map(|c| (c.clone(), point.clone()))
+            })
+            .collect();
+        for (config, point) in timed {
+            if self.evaluate_config(&config, &point, now) {
+                transitions += 1;
+            }
+        }
+
+        let pending: Vec<(Uuid, String)> = self
+            .conditions
+            .iter()
+            .filter(|(key, state)| state.met != self.alarm_tracker.contains_key(*key))
+            .map(|(key, _)| key.clone())
+            .collect();
+
+        for (config_id, point) in pending {
+            let Some(config) = self.configs.iter().find(|c| c.id == config_id).cloned() else {
+                self.conditions.remove(&(config_id, point));
+                continue;
+            };
+            if config.enabled && self.apply_condition(&config, &point, now, now) {
+                transitions += 1;
+            }
+        }
+        transitions
+    }
+
+    /// Start a background task that checks alarm timers every second
+    pub fn start_timer_task(actor_ref: ActorRef<Self>) -> tokio::task::JoinHandle<()> {
+        let weak_ref = actor_ref.downgrade();
+
+        tokio::spawn(async move {
+            let mut tick = tokio::time::interval(Duration::from_secs(1));
+            tick.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+
+            loop {
+                tick.tick().await;
+                let Some(actor_ref) = weak_ref.upgrade() else {
+                    tracing::debug!("Alarm timer task exiting");
+                    break;
+                };
+                if let Err(e) = actor_ref.tell(AlarmMsg::CheckTimers).await {
+                    tracing::warn!("Failed to check alarm timers: {}", e);
+                }
+            }
+        })
+    }
+
+    /// Raise an alarm for a config and point, unless one is already raised
+    fn raise_alarm(
+        &mut self,
+        config: &AlarmConfig,
+        point: &str,
+        value: Option<&PropertyValue>,
+        timestamp: DateTime<Utc>,
+    ) {
+        let tracker_key = (config.id, point.to_string());
+        if self.alarm_tracker.contains_key(&tracker_key) {
+            return;
+        }
+
+        // A latched alarm is raised again rather than anew
+        if let Some(alarm) = self
+            .latched
+            .remove(&tracker_key)
+            .and_then(|id| self.active_alarms.get_mut(&id))
+        {
+            alarm.returned_to_normal_at = None;
+            let alarm = alarm.clone();
+            self.events.push(Event::AlarmRaised {
+                alarm_id: alarm.id,
+                config_id: config.id,
+                source: alarm.source.clone(),
+                message: alarm.message.clone(),
+                severity: alarm.severity,
+                value: value.cloned(),
+                timestamp: std::time::Instant::now(),
+                timestamp_utc: timestamp,
+            });
+            self.persist_alarm(&alarm);
+            self.record_timeline(alarm.id, AlarmTimelineKind::ReRaised, timestamp, None, None);
+            self.alarm_tracker.insert(tracker_key, alarm.id);
+            tracing::warn!("Alarm re-raised: {} - {}", config.name, point);
+            return;
+        }
+
+        let mut alarm = self.create_alarm(config, point, value, timestamp);
+        let alarm_id = alarm.id;
+        if let Some((flood_id, members)) = self.floods.record(alarm_id, timestamp) {
+            alarm.flood_id = Some(flood_id);
+            alarm.first_out = members.first() == Some(&alarm_id);
+            self.group_flood(flood_id, &members);
+        }
+        self.events.push(Event::AlarmRaised {
+            alarm_id,
+            config_id: config.id,
+            source: alarm.source.clone(),
+            message: alarm.message.clone(),
+            severity: alarm.severity,
+            value: alarm.value_at_trigger.clone(),
+            timestamp: std::time::Instant::now(),
+            timestamp_utc: timestamp,
+        });
+
+        self.persist_alarm(&alarm);
+        self.record_timeline(alarm_id, AlarmTimelineKind::Raised, timestamp, None, None);
+        self.route_alarm(&alarm);
+        self.active_alarms.insert(alarm_id, alarm);
+        self.alarm_tracker.insert(tracker_key, alarm_id);
+
+        tracing::warn!(
+            "Alarm raised: {} - {} ({})",
+            config.name,
+            point,
+            config.condition.description()
+        );
+    }
+
+    /// Mark the alarms grouped when a flood starts (active or already
+    /// cleared) with the flood, the earliest as its first-out
+    fn group_flood(&mut self, flood_id: Uuid, members: &[Uuid]) {
+        for (index, id) in members.iter().enumerate() {
+            let stored = match self.active_alarms.get(id) {
+                Some(alarm) => Some(alarm.clone()),
+                None => self.store.as_ref().and_then(|store| store.alarm(*id).ok().flatten()),
+            };
+            // The alarm being raised is marked (and persisted) by raise_alarm
+            let Some(mut alarm) = stored else {
+                continue;
+            };
+            alarm.flood_id = Some(flood_id);
+            alarm.first_out = index == 0;
+            self.persist_alarm(&alarm);
+            if let Some(active) = self.active_alarms.get_mut(id) {
+                *active = alarm;
+            }
+        }
+    }
+
+    /// Notify the first recipient group of each notification class that
+    /// routes a raised alarm; a suppressed alarm's group is notified once it
+    /// is no longer suppressed
+    fn route_alarm(&mut self, alarm: &Alarm) {
+        let now = self.clock.now();
+        let suppressed = self.suppression(alarm, now).is_some();
+        let routed = self.escalations.len();
+        for class in self.notification_config.classes.clone() {
+            if class.groups.is_empty() || !class.routes(alarm, now) {
+                continue;
+            }
+            if let Some(filter_text) = &class.tag_filter {
+                if !self.matches_tag_filter(filter_text, &alarm.source) {
+                    continue;
+                }
+            }
+            if !suppressed {
+                self.queue_notifications(alarm, &class, 0);
+            }
+            self.escalations.push(Escalation {
+                alarm_id: alarm.id,
+                class: class.name.clone(),
+                level: 0,
+                notified_at: (!suppressed).then_some(now),
+            });
+        }
+        if self.escalations.len() > routed {
+            self.persist_escalations();
+        }
+    }
+
+    /// Notify the first recipient group of alarms no longer suppressed, and
+    /// the next group of alarms left unacknowledged past their group's
+    /// escalation time; acknowledged and cleared alarms stop escalating
+    fn escalate(&mut self, now: DateTime<Utc>) {
+        let mut changed = false;
+        for mut escalation in std::mem::take(&mut self.escalations) {
+            let alarm = self
+                .active_alarms
+                .get(&escalation.alarm_id)
+                .filter(|alarm| alarm.state == AlarmState::Active)
+                .cloned();
+            let class = self
+                .notification_config
+                .classes
+                .iter()
+                .find(|c| c.name == escalation.class)
+                .cloned();
+            let (Some(alarm), Some(class)) = (alarm, class) else {
+                changed = true;
+                continue;
+            };
+            if escalation.level >= class.groups.len() {
+                changed = true;
+                continue;
+            }
+            let suppressed = self.suppression(&alarm, now).is_some();
+
+            // Alarms raised while suppressed are notified once they are not
+            let Some(notified_at) = escalation.notified_at else {
+                if !suppressed {
+                    tracing::info!(
+                        "Notifying {} of alarm {}, no longer suppressed",
+                        class.groups[escalation.level].name,
+                        alarm.id
+                    );
+                    self.queue_notifications(&alarm, &class, escalation.level);
+                    escalation.notified_at = Some(now);
+                    changed = true;
+                }
+                self.escalations.push(escalation);
+                continue;
+            };
+
+            let next = escalation.level + 1;
+            let Some(minutes) = class
+                .groups
+                .get(escalation.level)
+                .and_then(|group| group.escalate_after_minutes)
+                .filter(|_| next < class.groups.len())
+            else {
+                changed = true;
+                continue;
+            };
+
+            // Suppressed alarms wait to escalate until they are no longer suppressed
+            let due = now >= notified_at + chrono::Duration::minutes(minutes as i64);
+            if due && !suppressed {
+                tracing::info!("Escalating alarm {} to {}", alarm.id, class.groups[next].name);
+                self.queue_notifications(&alarm, &class, next);
+                escalation.level = next;
+                escalation.notified_at = Some(now);
+                changed = true;
+            }
+            self.escalations.push(escalation);
+        }
+        if changed {
+            self.persist_escalations();
+        }
+    }
+
+    /// Queue notifications of an alarm to a class's recipient group
+    fn queue_notifications(&mut self, alarm: &Alarm, class: &NotificationClass, level: usize) {
+        let group = &class.groups[level];
+        for recipient in &group.recipients {
+            self.notifications.push(Notification {
+                alarm: alarm.clone(),
+                class: class.name.clone(),
+                group: group.name.clone(),
+                level,
+                recipient: recipient.clone(),
+            });
+        }
+    }
+
+    /// Logged notification attempts from the store
+    fn notification_log(&self, alarm_id: Option<Uuid>, limit: usize) -> Vec<NotificationRecord> {
+        let Some(store) = &self.store else {
+            return Vec::new();
+        };
+        store.notifications(alarm_id, limit).unwrap_or_else(|e| {
+            tracing::warn!("Failed to read alarm notification log: {}", e);
+            Vec::new()
+        })
+    }
+
+    /// Return the alarm raised for a config and point, if any, to normal as
+    /// its condition is no longer met (or the config is removed); it clears,
+    /// unless it latches and is unacknowledged
+    fn clear_alarm(
+        &mut self,
+        config_id: Uuid,
+        point: &str,
+        value: Option<&PropertyValue>,
+        timestamp: DateTime<Utc>,
+    ) {
+        let key = (config_id, point.to_string());
+        let Some(alarm_id) = self.alarm_tracker.remove(&key) else {
+            return;
+        };
+        let Some(alarm) = self.active_alarms.get_mut(&alarm_id) else {
+            return;
+        };
+        let latches =
+            alarm.state == AlarmState::Active && self.ack_policy.latching(alarm.severity);
+        if latches {
+            alarm.returned_to_normal_at = Some(timestamp);
+        }
+        let alarm = alarm.clone();
+
+        self.events.push(Event::AlarmReturnedToNormal {
+            alarm_id,
+            config_id,
+            source: point.to_string(),
+            severity: alarm.severity,
+            value: value.cloned(),
+            timestamp: std::time::Instant::now(),
+            timestamp_utc: timestamp,
+        });
+        self.record_timeline(alarm_id, AlarmTimelineKind::ReturnedToNormal, timestamp, None, None);
+
+        if latches {
+            self.persist_alarm(&alarm);
+            self.latched.insert(key, alarm_id);
+            tracing::info!("Alarm latched until acknowledged: {}", alarm.message);
+        } else {
+            self.end_alarm(alarm_id, timestamp);
+        }
+    }
+
+    /// Clear an active alarm, removing it from the active list; returns the
+    /// cleared alarm
+    fn end_alarm(&mut self, alarm_id: Uuid, timestamp: DateTime<Utc>) -> Option<Alarm> {
+        let mut alarm = self.active_alarms.remove(&alarm_id)?;
+        alarm.state = AlarmState::Cleared;
+        alarm.cleared_at = Some(timestamp);
+        self.persist_alarm(&alarm);
+        self.record_timeline(alarm_id, AlarmTimelineKind::Cleared, timestamp, None, None);
+        tracing::info!("Alarm cleared: {}", alarm.message);
+        if self.shelves.remove(&ShelveTarget::Alarm(alarm_id)).is_some() {
+            self.persist_shelves();
+        }
+
+        if let Some(config_id) = alarm.config_id {
+            self.events.push(Event::AlarmCleared {
+                alarm_id,
+                config_id,
+                source: alarm.source.clone(),
+                severity: alarm.severity,
+                timestamp: std::time::Instant::now(),
+                timestamp_utc: timestamp,
+            });
+        }
+        Some(alarm)
+    }
+
+    /// Create a new alarm from a config
+    fn create_alarm(
+        &self,
+        config: &AlarmConfig,
+        point: &str,
+        value: Option<&PropertyValue>,
+        timestamp: DateTime<Utc>,
+    ) -> Alarm {
+        let value_text = value.map(|v| format!("{:?}", v)).unwrap_or_default();
+        let message = config
+            .message_template
+            .replace("{point}", point)
+            .replace("{value}", &value_text)
+            .replace("{threshold}", &config.condition.description())
+            .replace("{units}", config.units.map(|u| u.symbol()).unwrap_or(""));
+
+        Alarm {
+            id: Uuid::new_v4(),
+            config_id: Some(config.id),
+            source: point.to_string(),
+            message,
+            severity: config.severity,
+            state: AlarmState::Active,
+            triggered_at: timestamp,
+            acknowledged_at: None,
+            acknowledged_by: None,
+            ack_comment: None,
+            returned_to_normal_at: None,
+            cleared_at: None,
+            value_at_trigger: value.cloned(),
+            flood_id: None,
+            first_out: false,
+        }
+    }
+
+    /// Get all active (non-cleared) alarms
+    fn get_active_alarms(&self) -> Vec<Alarm> {
+        let now = self.clock.now();
+        self.active_alarms
+            .values()
+            .filter(|alarm| self.suppression(alarm, now).is_none())
+            .cloned()
+            .collect()
+    }
+
+    /// Get the active alarms that are shelved, suppressed or inhibited
+    fn get_suppressed_alarms(&self) -> Vec<SuppressedAlarm> {
+        let now = self.clock.now();
+        self.active_alarms
+            .values()
+            .filter_map(|alarm| {
+                self.suppression(alarm, now).map(|suppression| SuppressedAlarm {
+                    alarm: alarm.clone(),
+                    suppression,
+                })
+            })
+            .collect()
+    }
+
+    /// Why an alarm is suppressed, if it is
+    fn suppression(&self, alarm: &Alarm, now: DateTime<Utc>) -> Option<Suppression> {
+        let config_shelf = alarm
+            .config_id
+            .and_then(|id| self.shelves.get(&ShelveTarget::Config(id)));
+        let alarm_shelf = self.shelves.get(&ShelveTarget::Alarm(alarm.id));
+        let shelf = alarm_shelf.into_iter().chain(config_shelf).find(|s| now < s.until);
+        if let Some(shelf) = shelf {
+            return Some(Suppression::Shelved {
+                shelf: shelf.clone(),
+            });
+        }
+
+        if let Some(window) = self.windows.iter().find(|w| w.suppresses(&alarm.source, now)) {
+            return Some(Suppression::Window {
+                window_id: window.id,
+                name: window.name.clone(),
+                until: window.end,
+            });
+        }
+
+        // A device alarm's source is the device itself, so it never matches
+        if let Some(device) = self.offline_device(&alarm.source) {
+            return Some(Suppression::DeviceOffline {
+                device: device.clone(),
+            });
+        }
+
+        let inhibit = alarm
+            .config_id
+            .and_then(|id| self.configs.iter().find(|c| c.id == id))
+            .and_then(|config| config.inhibit.as_ref())?;
+        let point = context::resolve_reference(&inhibit.point, &alarm.source);
+        let inhibited = self
+            .points
+            .get(&point)
+            .is_some_and(|p| inhibit.condition.evaluate(&p.value));
+        inhibited.then_some(Suppression::Inhibited { point })
+    }
+
+    /// Shelve an alarm or alarm config for a duration; None if the alarm is
+    /// not active, the config does not exist or the shelf would end too far
+    /// in the future to represent
+    fn shelve(
+        &mut self,
+        target: ShelveTarget,
+        duration: chrono::Duration,
+        reason: String,
+        shelved_by: Option<String>,
+    ) -> Option<Shelf> {
+        let exists = match target {
+            ShelveTarget::Alarm(id) => self.active_alarms.contains_key(&id),
+            ShelveTarget::Config(id) => self.configs.iter().any(|c| c.id == id),
+        };
+        if !exists {
+            return None;
+        }
+
+        let now = self.clock.now();
+        let shelf = Shelf {
+            target,
+            reason,
+            shelved_by,
+            shelved_at: now,
+            until: now.checked_add_signed(duration)?,
+        };
+        tracing::info!("Shelved {:?} until {}: {}", target, shelf.until, shelf.reason);
+        self.shelves.insert(target, shelf.clone());
+        self.persist_shelves();
+        Some(shelf)
+    }
+
+    /// Unshelve an alarm or alarm config; false if it was not shelved
+    fn unshelve(&mut self, target: ShelveTarget) -> bool {
+        let removed = self.shelves.remove(&target).is_some();
+        if removed {
+            self.persist_shelves();
+        }
+        removed
+    }
+
+    /// Remove shelves and suppression windows that have ended
+    fn expire_suppressions(&mut self, now: DateTime<Utc>) {
+        let shelves = self.shelves.len();
+        self.shelves.retain(|target, shelf| {
+            let expired = now >= shelf.until;
+            if expired {
+                tracing::info!("Unshelved {:?}", target);
+            }
+            !expired
+        });
+        if self.shelves.len() != shelves {
+            self.persist_shelves();
+        }
+
+        let windows = self.windows.len();
+        self.windows.retain(|window| now < window.end);
+        if self.windows.len() != windows {
+            self.persist_windows();
+        }
+    }
+
+    /// Write the shelves to the store
+    fn persist_shelves(&self) {
+        if let Some(store) = &self.store {
+            let shelves: Vec<Shelf> = self.shelves.values().cloned().collect();
+            if let Err(e) = store.save_shelves(&shelves) {
+                tracing::warn!("Failed to persist alarm shelves: {}", e);
+            }
+        }
+    }
+
+    /// Write the notification escalations to the store
+    fn persist_escalations(&self) {
+        if let Some(store) = &self.store {
+            if let Err(e) = store.save_escalations(&self.escalations) {
+                tracing::warn!("Failed to persist alarm escalations: {}", e);
+            }
+        }
+    }
+
+    /// Write the suppression windows to the store
+    fn persist_windows(&self) {
+        if let Some(store) = &self.store {
+            if let Err(e) = store.save_windows(&self.windows) {
+                tracing::warn!("Failed to persist alarm suppression windows: {}", e);
+            }
+        }
+    }
+
+    /// Acknowledge an alarm; a latched alarm that has returned to normal
+    /// clears as it is acknowledged
+    fn acknowledge_alarm(
+        &mut self,
+        alarm_id: Uuid,
+        acknowledged_by: Option<String>,
+        comment: Option<String>,
+    ) -> Result<Alarm> {
+        let comment = comment.filter(|c| !c.trim().is_empty());
+        let alarm = self
+            .active_alarms
+            .get_mut(&alarm_id)
+            .filter(|alarm| alarm.state == AlarmState::Active)
+            .ok_or_else(|| {
+                Error::NotFound(format!("Alarm {} not found or already acknowledged", alarm_id))
+            })?;
+        if comment.is_none() && self.ack_policy.comment_required(alarm.severity) {
+            return Err(Error::Service(format!(
+                "A comment is required to acknowledge {:?} alarms",
+                alarm.severity
+            )));
+        }
+
+        let now = self.clock.now();
+        alarm.state = AlarmState::Acknowledged;
+        alarm.acknowledged_at = Some(now);
+        alarm.acknowledged_by = acknowledged_by;
+        alarm.ack_comment = comment;
+        let alarm = alarm.clone();
+
+        if let Some(config_id) = alarm.config_id {
+            self.events.push(Event::AlarmAcknowledged {
+                alarm_id,
+                config_id,
+                source: alarm.source.clone(),
+                severity: alarm.severity,
+                acknowledged_by: alarm.acknowledged_by.clone(),
+                comment: alarm.ack_comment.clone(),
+                timestamp: std::time::Instant::now(),
+                timestamp_utc: now,
+            });
+        }
+        self.persist_alarm(&alarm);
+        self.record_timeline(
+            alarm_id,
+            AlarmTimelineKind::Acknowledged,
+            now,
+            alarm.acknowledged_by.clone(),
+            alarm.ack_comment.clone(),
+        );
+
+        if alarm.returned_to_normal_at.is_some() {
+            if let Some(config_id) = alarm.config_id {
+                self.latched.remove(&(config_id, alarm.source.clone()));
+            }
+            return Ok(self.end_alarm(alarm_id, now).unwrap_or(alarm));
+        }
+        Ok(alarm)
+    }
+
+    /// Acknowledge the unacknowledged active alarms matching a filter, oldest
+    /// first (shelved and suppressed alarms are left alone); alarms requiring
+    /// a comment are skipped when none is given
+    fn acknowledge_matching(
+        &mut self,
+        filter: &AlarmFilter,
+        acknowledged_by: Option<String>,
+        comment: Option<String>,
+    ) -> BulkAcknowledgement {
+        let mut matching: Vec<Alarm> = self
+            .get_active_alarms()
+            .into_iter()
+            .filter(|alarm| {
+                alarm.state == AlarmState::Active && acknowledgement::matches(filter, alarm)
+            })
+            .collect();
+        matching.sort_by_key(|alarm| alarm.triggered_at);
+
+        let mut result = BulkAcknowledgement::default();
+        for alarm in matching {
+            match self.acknowledge_alarm(alarm.id, acknowledged_by.clone(), comment.clone()) {
+                Ok(_) => result.acknowledged.push(alarm.id),
+                Err(_) => result.skipped.push(alarm.id),
+            }
+        }
+        result
+    }
+
+    /// Add an operator comment to the timeline of an alarm (active or stored)
+    fn comment_alarm(&self, alarm_id: Uuid, comment: String, author: Option<String>) -> Result<()> {
+        if comment.trim().is_empty() {
+            return Err(Error::Service("Alarm comment is empty".to_string()));
+        }
+        let known = self.active_alarms.contains_key(&alarm_id)
+            || self
+                .store
+                .as_ref()
+                .is_some_and(|store| matches!(store.alarm(alarm_id), Ok(Some(_))));
+        if !known {
+            return Err(Error::NotFound(format!("Alarm {} not found", alarm_id)));
+        }
+        let now = self.clock.now();
+        self.record_timeline(alarm_id, AlarmTimelineKind::Commented, now, author, Some(comment));
+        Ok(())
+    }
+
+    /// Append an entry to an alarm's timeline in the store
+    fn record_timeline(
+        &self,
+        alarm_id: Uuid,
+        kind: AlarmTimelineKind,
+        at: DateTime<Utc>,
+        user: Option<String>,
+        comment: Option<String>,
+    ) {
+        let Some(store) = &self.store else {
+            return;
+        };
+        let entry = AlarmTimelineEntry {
+            at,
+            kind,
+            user,
+            comment,
+        };
+        if let Err(e) = store.append_timeline(alarm_id, &entry) {
+            tracing::warn!("Failed to record alarm {} timeline: {}", alarm_id, e);
+        }
+    }
+
+    /// Timeline of an alarm, oldest first
+    fn alarm_timeline(&self, alarm_id: Uuid) -> Result<Vec<AlarmTimelineEntry>> {
+        match &self.store {
+            Some(store) => store.timeline(alarm_id),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Send queued notifications (in the background) and publish queued events
+    async fn publish_events(&mut self) {
+        let notifications = std::mem::take(&mut self.notifications);
+        let notifier = Notifier {
+            smtp: self.notification_config.smtp.clone(),
+            registry: self.registry.clone(),
+            store: self.store.clone(),
+        };
+        notifier.dispatch(notifications);
+
+        let events = std::mem::take(&mut self.events);
+        let Some(pubsub) = &self.pubsub else {
+            return;
+        };
+        for event in events {
+            if let Err(e) = pubsub.tell(Publish(event)).await {
+                tracing::warn!("Failed to publish alarm event: {}", e);
+            }
+        }
+    }
+}
+
+impl Default for AlarmActor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Handle common ServiceMsg
+impl Message<ServiceMsg> for AlarmActor {
+    type Reply = ServiceReply;
+
+    async fn handle(
+        &mut self,
+        msg: ServiceMsg,
+        _ctx: &mut Context<Self, Self::Reply>,
+    ) -> Self::Reply {
+        match msg {
+            ServiceMsg::Start => {
+                self.state.set_starting();
+                if let Err(e) = self.do_start() {
+                    self.state.set_failed();
+                    return ServiceReply::Failed(e.to_string());
+                }
+                self.state.set_running();
+                tracing::info!(
+                    "Alarm actor started ({} alarm configurations, {} active alarms)",
+                    self.configs.len(),
+                    self.active_alarms.len()
+                );
+                ServiceReply::Started
+            }
+
+            ServiceMsg::Stop => {
+                self.state.set_stopping();
+                // Clear tracker; active alarms are restored from the store on start
+                self.alarm_tracker.clear();
+                self.latched.clear();
+                self.conditions.clear();
+                self.histories.clear();
+                self.dependents.clear();
+                self.floods.reset();
+                self.state.set_stopped();
+                tracing::info!("Alarm actor stopped");
+                ServiceReply::Stopped
+            }
+
+            ServiceMsg::GetStatus => ServiceReply::Status {
+                id: self.id.clone(),
+                name: self.name.clone(),
+                state: self.state.state(),
+                uptime_secs: self.state.uptime_secs(),
+                extra: Some(self.status_extra()),
+            },
+
+            ServiceMsg::GetConfig => ServiceReply::Config {
+                config: serde_json::json!({ "alarms": self.configs }),
+            },
+
+            ServiceMsg::SetConfig { config } => {
+                let Ok(alarms) = serde_json::from_value::<Vec<AlarmConfig>>(
+                    config.get("alarms").cloned().unwrap_or_default(),
+                ) else {
+                    return ServiceReply::Failed("Invalid alarm configuration format".to_string());
+                };
+                for alarm in &alarms {
+                    if let Err(e) = rules::validate(alarm) {
+                        return ServiceReply::Failed(format!("Alarm '{}': {}", alarm.name, e));
+                    }
+                }
+
+                // Rules from files stay; they change with their files
+                let file_rules = self.file_rule_ids();
+                self.configs.retain(|c| file_rules.contains(&c.id));
+                self.configs.extend(alarms);
+                self.apply_config_changes();
+                ServiceReply::ConfigSet
+            }
+
+            ServiceMsg::OnEvent { event } => {
+                if self.state.state() != ServiceState::Running {
+                    return ServiceReply::EventHandled;
+                }
+
+                match event {
+                    Event::PointValueChanged {
+                        point,
+                        value,
+                        units,
+                        timestamp_utc,
+                        ..
+                    } => self.evaluate_point(&point, &value, units, timestamp_utc),
+                    Event::HistoryStale {
+                        point,
+                        timestamp_utc,
+                        ..
+                    } => self.history_logging(&point, true, timestamp_utc),
+                    Event::HistoryResumed {
+                        point,
+                        timestamp_utc,
+                        ..
+                    } => self.history_logging(&point, false, timestamp_utc),
+                    Event::DeviceStatusChanged {
+                        device,
+                        network,
+                        status,
+                        timestamp_utc,
+                        ..
+                    } => self.device_status(&network, &device, status, timestamp_utc),
+                    _ => {}
+                }
+                self.publish_events().await;
+                ServiceReply::EventHandled
+            }
+
+            ServiceMsg::HandleRequest { request, reply } => {
+                let response = match request {
+                    ServiceRequest::GetStatus => ServiceResponse::Status {
+                        id: self.id.clone(),
+                        name: self.name.clone(),
+                        state: self.state.state(),
+                        uptime_seconds: self.state.uptime_secs(),
+                        extra: Some(self.status_extra()),
+                    },
+
+                    ServiceRequest::GetConfig => ServiceResponse::Config {
+                        config: serde_json::json!({ "alarms": self.configs }),
+                    },
+
+                    ServiceRequest::GetActiveAlarms => {
+                        let alarms = self.get_active_alarms();
+                        ServiceResponse::ActiveAlarms { alarms }
+                    }
+
+                    ServiceRequest::AcknowledgeAlarm {
+                        alarm_id,
+                        acknowledged_by,
+                        comment,
+                    } => match self.acknowledge_alarm(alarm_id, acknowledged_by, comment) {
+                        Ok(_) => ServiceResponse::AlarmAcknowledged { alarm_id },
+                        Err(Error::NotFound(message)) => ServiceResponse::Error {
+                            code: "NOT_FOUND".to_string(),
+                            message,
+                        },
+                        Err(e) => ServiceResponse::Error {
+                            code: "COMMENT_REQUIRED".to_string(),
+                            message: e.to_string(),
+                        },
+                    },
+
+                    ServiceRequest::AcknowledgeAlarms {
+                        filter,
+                        acknowledged_by,
+                        comment,
+                    } => {
+                        let result = self.acknowledge_matching(&filter, acknowledged_by, comment);
+                        ServiceResponse::AlarmsAcknowledged {
+                            acknowledged: result.acknowledged,
+                            skipped: result.skipped,
+                        }
+                    }
+
+                    ServiceRequest::CommentAlarm {
+                        alarm_id,
+                        comment,
+                        author,
+                    } => match self.comment_alarm(alarm_id, comment, author) {
+                        Ok(()) => ServiceResponse::AlarmCommented { alarm_id },
+                        Err(Error::NotFound(message)) => ServiceResponse::Error {
+                            code: "NOT_FOUND".to_string(),
+                            message,
+                        },
+                        Err(e) => ServiceResponse::Error {
+                            code: "INVALID_COMMENT".to_string(),
+                            message: e.to_string(),
+                        },
+                    },
+
+                    ServiceRequest::GetAlarmTimeline { alarm_id } => {
+                        match self.alarm_timeline(alarm_id) {
+                            Ok(entries) => ServiceResponse::AlarmTimeline { alarm_id, entries },
+                            Err(e) => ServiceResponse::Error {
+                                code: "QUERY_FAILED".to_string(),
+                                message: e.to_string(),
+                            },
+                        }
+                    }
+
+                    ServiceRequest::GetAlarmHistory {
+                        start,
+                        end,
+                        source_filter,
+                        limit,
+                        cursor,
+                    } => {
+                        let limit = limit.unwrap_or(DEFAULT_HISTORY_LIMIT) as usize;
+                        match self.alarm_history(
+                            start,
+                            end,
+                            source_filter.as_deref(),
+                            cursor,
+                            limit,
+                        ) {
+                            Ok(page) => ServiceResponse::AlarmHistory {
+                                alarms: page.alarms,
+                                next_cursor: page.next_cursor,
+                            },
+                            Err(e) => ServiceResponse::Error {
+                                code: "QUERY_FAILED".to_string(),
+                                message: e.to_string(),
+                            },
+                        }
+                    }
+
+                    ServiceRequest::GetAlarmKpis { start, end } => {
+                        match self.alarm_kpis(start, end) {
+                            Ok(kpis) => ServiceResponse::AlarmKpis { kpis },
+                            Err(e) => ServiceResponse::Error {
+                                code: "QUERY_FAILED".to_string(),
+                                message: e.to_string(),
+                            },
+                        }
+                    }
+
+                    _ => ServiceResponse::Error {
+                        code: "UNSUPPORTED".to_string(),
+                        message: "Request not supported by Alarm service".to_string(),
+                    },
+                };
+
+                let _ = reply.send(response);
+                self.publish_events().await;
+                ServiceReply::RequestHandled
+            }
+        }
+    }
+}
+
+// Handle alarm-specific messages
+impl Message<AlarmMsg> for AlarmActor {
+    type Reply = AlarmReply;
+
+    async fn handle(
+        &mut self,
+        msg: AlarmMsg,
+        _ctx: &mut Context<Self, Self::Reply>,
+    ) -> Self::Reply {
+        match msg {
+            AlarmMsg::GetActiveAlarms { reply } => {
+                let alarms = self.get_active_alarms();
+                let _ = reply.send(alarms);
+                AlarmReply::ConfigAdded // Placeholder reply
+            }
+
+            AlarmMsg::AcknowledgeAlarm {
+                alarm_id,
+                acknowledged_by,
+                comment,
+                reply,
+            } => {
+                let result = self.acknowledge_alarm(alarm_id, acknowledged_by, comment);
+                let _ = reply.send(result);
+                self.publish_events().await;
+                AlarmReply::ConfigAdded // Placeholder reply
+            }
+
+            AlarmMsg::AcknowledgeAlarms {
+                filter,
+                acknowledged_by,
+                comment,
+                reply,
+            } => {
+                let result = self.acknowledge_matching(&filter, acknowledged_by, comment);
+                let _ = reply.send(result);
+                self.publish_events().await;
+                AlarmReply::ConfigAdded // Placeholder reply
+            }
+
+            AlarmMsg::CommentAlarm {
+                alarm_id,
+                comment,
+                author,
+                reply,
+            } => {
+                let _ = reply.send(self.comment_alarm(alarm_id, comment, author));
+                AlarmReply::ConfigAdded // Placeholder reply
+            }
+
+            AlarmMsg::GetAlarmTimeline { alarm_id, reply } => {
+                let entries = self.alarm_timeline(alarm_id).unwrap_or_else(|e| {
+                    tracing::warn!("Failed to read alarm {} timeline: {}", alarm_id, e);
+                    Vec::new()
+                });
+                let _ = reply.send(entries);
+                AlarmReply::ConfigAdded // Placeholder reply
+            }
+
+            AlarmMsg::GetAlarmHistory {
+                start,
+                end,
+                source_filter,
+                cursor,
+                limit,
+                reply,
+            } => {
+                let limit = limit.unwrap_or(DEFAULT_HISTORY_LIMIT) as usize;
+                let page = self.alarm_history(start, end, source_filter.as_deref(), cursor, limit);
+                let _ = reply.send(page);
+                AlarmReply::ConfigAdded // Placeholder reply
+            }
+
+            AlarmMsg::AddConfig { config, reply } => {
+                let added = rules::validate(&config)
+                    .map_err(|e| Error::Config(format!("Alarm '{}': {}", config.name, e)));
+                if added.is_ok() {
+                    self.add_config(config);
+                }
+                let _ = reply.send(added);
+                AlarmReply::ConfigAdded
+            }
+
+            AlarmMsg::RemoveConfig { id, reply } => {
+                let file_rule = self.file_rule_ids().contains(&id);
+                let position = self.configs.iter().position(|c| c.id == id);
+                let removed = match position {
+                    Some(pos) if !file_rule => {
+                        self.configs.remove(pos);
+                        self.apply_config_changes();
+                        true
+                    }
+                    _ => false,
+                };
+                let _ = reply.send(removed);
+                AlarmReply::ConfigAdded // Placeholder reply
+            }
+
+            AlarmMsg::GetConfigs { reply } => {
+                let _ = reply.send(self.configs.clone());
+                AlarmReply::ConfigAdded // Placeholder reply
+            }
+
+            AlarmMsg::GetSuppressedAlarms { reply } => {
+                let _ = reply.send(self.get_suppressed_alarms());
+                AlarmReply::ConfigAdded // Placeholder reply
+            }
+
+            AlarmMsg::Shelve {
+                target,
+                duration_secs,
+                reason,
+                shelved_by,
+                reply,
+            } => {
+                let shelf = i64::try_from(duration_secs)
+                    .ok()
+                    .and_then(chrono::Duration::try_seconds)
+                    .and_then(|duration| self.shelve(target, duration, reason, shelved_by));
+                let _ = reply.send(shelf);
+                AlarmReply::ConfigAdded // Placeholder reply
+            }
+
+            AlarmMsg::Unshelve { target, reply } => {
+                let _ = reply.send(self.unshelve(target));
+                AlarmReply::ConfigAdded // Placeholder reply
+            }
+
+            AlarmMsg::GetShelves { reply } => {
+                let _ = reply.send(self.shelves.values().cloned().collect());
+                AlarmReply::ConfigAdded // Placeholder reply
+            }
+
+            AlarmMsg::AddSuppressionWindow { mut window, reply } => {
+                if window.id.is_nil() {
+                    window.id = Uuid::new_v4();
+                }
+                let id = window.id;
+                self.windows.retain(|w| w.id != id);
+                self.windows.push(window);
+                self.persist_windows();
+                let _ = reply.send(id);
+                AlarmReply::ConfigAdded // Placeholder reply
+            }
+
+            AlarmMsg::RemoveSuppressionWindow { id, reply } => {
+                let count = self.windows.len();
+                self.windows.retain(|w| w.id != id);
+                let removed = self.windows.len() != count;
+                if removed {
+                    self.persist_windows();
+                }
+                let _ = reply.send(removed);
+                AlarmReply::ConfigAdded // Placeholder reply
+            }
+
+            AlarmMsg::GetSuppressionWindows { reply } => {
+                let _ = reply.send(self.windows.clone());
+                AlarmReply::ConfigAdded // Placeholder reply
+            }
+
+            AlarmMsg::GetNotificationLog {
+                alarm_id,
+                limit,
+                reply,
+            } => {
+                let limit = limit.unwrap_or(DEFAULT_HISTORY_LIMIT) as usize;
+                let _ = reply.send(self.notification_log(alarm_id, limit));
+                AlarmReply::ConfigAdded // Placeholder reply
+            }
+
+            AlarmMsg::CheckTimers => {
+                if self.state.state() != ServiceState::Running {
+                    return AlarmReply::TimersChecked { transitions: 0 };
+                }
+                self.reload_changed_rules();
+                self.purge_history(self.clock.now());
+                let transitions = self.check_timers();
+                self.publish_events().await;
+                AlarmReply::TimersChecked { transitions }
+            }
+        }
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Tests
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::points::TagSet;
+    use crate::types::PointQuality;
+    use kameo::actor::Spawn;
+    use tempfile::tempdir;
+    use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::TcpListener;
+    use tokio::sync::mpsc;
+
+    #[tokio::test]
+    async fn test_alarm_actor_lifecycle() {
+        let mut actor_state = AlarmActor::new();
+
+        // Add a high limit alarm config
+        actor_state.add_config(AlarmConfig {
+            id: Uuid::new_v4(),
+            name: "High Temperature".to_string(),
+            source_pattern: "*/temperature".to_string(),
+            tag_filter: None,
+            condition: AlarmCondition::HighLimit { value: 80.0 },
+            units: None,
+            severity: AlarmSeverity::High,
+            delay_seconds: 0,
+            off_delay_seconds: 0,
+            min_on_seconds: 0,
+            min_off_seconds: 0,
+            deadband: 0.0,
+            inhibit: None,
+            message_template: "Temperature at {point} {threshold}: {value}".to_string(),
+            enabled: true,
+        });
+
+        // Spawn the actor
+        let actor = AlarmActor::spawn(actor_state);
+
+        // Start service
+        let reply = actor.ask(ServiceMsg::Start).await.unwrap();
+        assert!(matches!(reply, ServiceReply::Started));
+
+        // Check status
+        let reply = actor.ask(ServiceMsg::GetStatus).await.unwrap();
+        if let ServiceReply::Status { state, .. } = reply {
+            assert_eq!(state, ServiceState::Running);
+        } else {
+            panic!("Expected Status reply");
+        }
+
+        // Send a normal value - should not trigger alarm
+        let event = Event::PointValueChanged {
+            point: "zone1/temperature".to_string(),
+            value: PropertyValue::Real(72.0),
+            quality: PointQuality::Good,
+            units: None,
+            timestamp: std::time::Instant::now(),
+            timestamp_utc: Utc::now(),
+        };
+        let _ = actor.ask(ServiceMsg::OnEvent { event }).await;
+
+        // Query active alarms
+        let (reply_tx, reply_rx) = oneshot::channel();
+        let _ = actor.ask(AlarmMsg::GetActiveAlarms { reply: reply_tx }).await;
+        let active = reply_rx.await.unwrap();
+        assert!(active.is_empty());
+
+        // Send a high value - should trigger alarm
+        let event = Event::PointValueChanged {
+            point: "zone1/temperature".to_string(),
+            value: PropertyValue::Real(85.0),
+            quality: PointQuality::Good,
+            units: None,
+            timestamp: std::time::Instant::now(),
+            timestamp_utc: Utc::now(),
+        };
+        let _ = actor.ask(ServiceMsg::OnEvent { event }).await;
+
+        // Query active alarms again
+        let (reply_tx, reply_rx) = oneshot::channel();
+        let _ = actor.ask(AlarmMsg::GetActiveAlarms { reply: reply_tx }).await;
+        let active = reply_rx.await.unwrap();
+        assert_eq!(active.len(), 1);
+        assert_eq!(active[0].severity, AlarmSeverity::High);
+        assert_eq!(active[0].state, AlarmState::Active);
+
+        // Acknowledge the alarm
+        let alarm_id = active[0].id;
+        let (reply_tx, reply_rx) = oneshot::channel();
+        let _ = actor
+            .ask(AlarmMsg::AcknowledgeAlarm {
+                alarm_id,
+                acknowledged_by: Some("operator1".to_string()),
+                comment: None,
+                reply: reply_tx,
+            })
+            .await;
+        let acked = reply_rx.await.unwrap().unwrap();
+        assert_eq!(acked.state, AlarmState::Acknowledged);
+
+        // Configs are validated: a deadband this wide would never let the alarm clear
+        let mut invalid = serde_json::json!({
+            "id": Uuid::new_v4(),
+            "name": "Zone Deviation",
+            "condition": { "type": "Deviation", "reference": "{parent}/zone-sp", "limit": 2.0 },
+            "severity": "Medium",
+            "deadband": 2.0,
+            "message_template": "{point}",
+        });
+        let config = serde_json::json!({ "alarms": [invalid.clone()] });
+        let reply = actor.ask(ServiceMsg::SetConfig { config }).await.unwrap();
+        assert!(matches!(reply, ServiceReply::Failed(_)));
+
+        invalid["condition"] =
+            serde_json::json!({ "type": "OutOfRange", "low": 60.0, "high": 80.0 });
+        invalid["deadband"] = serde_json::json!(10.0);
+        let config: AlarmConfig = serde_json::from_value(invalid).unwrap();
+        let (reply_tx, reply_rx) = oneshot::channel();
+        let _ = actor.ask(AlarmMsg::AddConfig { config, reply: reply_tx }).await;
+        assert!(matches!(reply_rx.await.unwrap(), Err(Error::Config(_))));
+
+        // Stop service
+        let reply = actor.ask(ServiceMsg::Stop).await.unwrap();
+        assert!(matches!(reply, ServiceReply::Stopped));
+    }
+
+    #[test]
+    fn test_alarm_tag_filter() {
+        let tags = TagStore::default();
+        tags.set_point_tags("net/VAV-3/AI:1", &TagSet::markers(["zone", "air", "temp", "sensor"]));
+        tags.set_point_tags("net/AHU-1/AI:1", &TagSet::markers(["discharge", "air", "temp", "sensor"]));
+
+        let mut actor = AlarmActor::new().with_tag_store(tags);
+        actor.add_config(AlarmConfig {
+            id: Uuid::new_v4(),
+            name: "Zone Too Warm".to_string(),
+            source_pattern: "*".to_string(),
+            tag_filter: Some("zone and air and temp and sensor".to_string()),
+            condition: AlarmCondition::HighLimit { value: 80.0 },
+            units: None,
+            severity: AlarmSeverity::Medium,
+            delay_seconds: 0,
+            off_delay_seconds: 0,
+            min_on_seconds: 0,
+            min_off_seconds: 0,
+            deadband: 0.0,
+            inhibit: None,
+            message_template: "{point} too warm: {value}".to_string(),
+            enabled: true,
+        });
+
+        actor.evaluate_point("net/AHU-1/AI:1", &PropertyValue::Real(90.0), None, Utc::now());
+        assert!(actor.active_alarms.is_empty());
+
+        actor.evaluate_point("net/VAV-3/AI:1", &PropertyValue::Real(90.0), None, Utc::now());
+        assert_eq!(actor.active_alarms.len(), 1);
+    }
+
+    #[test]
+    fn test_alarm_converts_units() {
+        let mut actor = AlarmActor::new();
+        actor.add_config(AlarmConfig {
+            id: Uuid::new_v4(),
+            name: "Zone Too Warm".to_string(),
+            source_pattern: "*".to_string(),
+            tag_filter: None,
+            condition: AlarmCondition::HighLimit { value: 80.0 },
+            units: Some(EngineeringUnit::DegreesFahrenheit),
+            severity: AlarmSeverity::Medium,
+            delay_seconds: 0,
+            off_delay_seconds: 0,
+            min_on_seconds: 0,
+            min_off_seconds: 0,
+            deadband: 0.0,
+            inhibit: None,
+            message_template: "{point} above {threshold}{units}".to_string(),
+            enabled: true,
+        });
+        let celsius = Some(EngineeringUnit::DegreesCelsius);
+
+        // 25 °C = 77 °F
+        actor.evaluate_point("zone/1", &PropertyValue::Real(25.0), celsius, Utc::now());
+        assert!(actor.active_alarms.is_empty());
+
+        // Incompatible units are never compared
+        let kw = Some(EngineeringUnit::Kilowatts);
+        actor.evaluate_point("meter/1", &PropertyValue::Real(500.0), kw, Utc::now());
+        assert!(actor.active_alarms.is_empty());
+
+        // 30 °C = 86 °F
+        actor.evaluate_point("zone/1", &PropertyValue::Real(30.0), celsius, Utc::now());
+        assert_eq!(actor.active_alarms.len(), 1);
+    }
+
+    #[test]
+    fn test_alarm_history_stale() {
+        let mut actor = AlarmActor::new();
+        actor.add_config(AlarmConfig {
+            id: Uuid::new_v4(),
+            name: "Trend Stopped".to_string(),
+            source_pattern: "*/sat".to_string(),
+            tag_filter: None,
+            condition: AlarmCondition::HistoryStale,
+            units: None,
+            severity: AlarmSeverity::Low,
+            delay_seconds: 0,
+            off_delay_seconds: 0,
+            min_on_seconds: 0,
+            min_off_seconds: 0,
+            deadband: 0.0,
+            inhibit: None,
+            message_template: "{point} {threshold}".to_string(),
+            enabled: true,
+        });
+
+        // Values never trigger a stale alarm
+        actor.evaluate_point("ahu-1/sat", &PropertyValue::Real(55.0), None, Utc::now());
+        assert!(actor.active_alarms.is_empty());
+
+        actor.history_logging("ahu-1/rat", true, Utc::now());
+        assert!(actor.active_alarms.is_empty());
+
+        actor.history_logging("ahu-1/sat", true, Utc::now());
+        let active = actor.get_active_alarms();
+        assert_eq!(active.len(), 1);
+        assert_eq!(active[0].message, "ahu-1/sat has not logged history");
+        assert_eq!(active[0].value_at_trigger, None);
+
+        // A value change does not clear it; logging again does
+        actor.evaluate_point("ahu-1/sat", &PropertyValue::Real(56.0), None, Utc::now());
+        assert_eq!(actor.get_active_alarms().len(), 1);
+        actor.history_logging("ahu-1/sat", false, Utc::now());
+        assert!(actor.get_active_alarms().is_empty());
+    }
+
+    #[test]
+    fn test_alarm_delays_and_deadband() {
+        let clock = AlarmClock::manual(Utc::now());
+        let mut actor = AlarmActor::new().with_clock(clock.clone());
+        actor.add_config(AlarmConfig {
+            id: Uuid::new_v4(),
+            name: "High Temperature".to_string(),
+            source_pattern: "*/temperature".to_string(),
+            tag_filter: None,
+            condition: AlarmCondition::HighLimit { value: 80.0 },
+            units: None,
+            severity: AlarmSeverity::High,
+            delay_seconds: 60,
+            off_delay_seconds: 30,
+            min_on_seconds: 300,
+            min_off_seconds: 0,
+            deadband: 2.0,
+            inhibit: None,
+            message_template: "{point} {threshold}".to_string(),
+            enabled: true,
+        });
+        let point = "zone1/temperature";
+        let set = |actor: &mut AlarmActor, value: f32, secs: i64| {
+            actor.evaluate_point(point, &PropertyValue::Real(value), None, clock.now());
+            clock.advance(chrono::Duration::seconds(secs));
+            actor.check_timers()
+        };
+
+        // A high value that drops back within the on-delay never raises
+        assert_eq!(set(&mut actor, 85.0, 30), 0);
+        assert_eq!(set(&mut actor, 75.0, 40), 0);
+        assert!(actor.get_active_alarms().is_empty());
+
+        // One that stays high raises once the on-delay elapses
+        assert_eq!(set(&mut actor, 85.0, 59), 0);
+        clock.advance(chrono::Duration::seconds(1));
+        assert_eq!(actor.check_timers(), 1);
+        assert_eq!(actor.get_active_alarms().len(), 1);
+
+        // 79 is inside the deadband, so the condition is still met
+        assert_eq!(set(&mut actor, 79.0, 30), 0);
+        assert_eq!(actor.get_active_alarms().len(), 1);
+
+        // Off-delay elapsed, but the alarm has not been raised for min_on_seconds
+        assert_eq!(set(&mut actor, 77.0, 30), 0);
+        assert_eq!(actor.get_active_alarms().len(), 1);
+        clock.advance(chrono::Duration::seconds(240));
+        assert_eq!(actor.check_timers(), 1);
+        assert!(actor.get_active_alarms().is_empty());
+    }
+
+    #[test]
+    fn test_alarm_context_conditions() {
+        let clock = AlarmClock::manual(Utc::now());
+        let mut actor = AlarmActor::new().with_clock(clock.clone());
+        let rules = [
+            ("*/sat", AlarmCondition::RateOfChange { per_minute: 2.0, window_secs: 300 }),
+            ("*/rat", AlarmCondition::Flatline { minutes: 30, tolerance: 0.1 }),
+            (
+                "*/zone-temp",
+                AlarmCondition::Deviation {
+                    reference: "{parent}/zone-sp".to_string(),
+                    limit: 3.0,
+                },
+            ),
+            ("*/mode", AlarmCondition::StateIn { states: vec![3, 4] }),
+            (
+                "*/fan-cmd",
+                AlarmCondition::CommandMismatch {
+                    feedback: "{parent}/fan-status".to_string(),
+                },
+            ),
+        ];
+        for (pattern, condition) in rules {
+            let delay_seconds = match condition {
+                AlarmCondition::CommandMismatch { .. } => 60,
+                _ => 0,
+            };
+            actor.add_config(AlarmConfig {
+                id: Uuid::new_v4(),
+                name: pattern.to_string(),
+                source_pattern: pattern.to_string(),
+                tag_filter: None,
+                condition,
+                units: None,
+                severity: AlarmSeverity::Medium,
+                delay_seconds,
+                off_delay_seconds: 0,
+                min_on_seconds: 0,
+                min_off_seconds: 0,
+                deadband: 0.0,
+                inhibit: None,
+                message_template: "{point} {threshold}".to_string(),
+                enabled: true,
+            });
+        }
+        let set = |actor: &mut AlarmActor, point: &str, value: PropertyValue| {
+            actor.evaluate_point(point, &value, None, clock.now());
+        };
+        let active = |actor: &AlarmActor| {
+            let mut sources: Vec<String> =
+                actor.get_active_alarms().into_iter().map(|a| a.source).collect();
+            sources.sort();
+            sources
+        };
+        let minutes = |m: i64| chrono::Duration::minutes(m);
+
+        set(&mut actor, "ahu-1/rat", PropertyValue::Real(70.0));
+        set(&mut actor, "ahu-1/mode", PropertyValue::Unsigned(2));
+        set(&mut actor, "ahu-1/sat", PropertyValue::Real(55.0));
+        assert!(active(&actor).is_empty());
+
+        // 3 degrees in a minute exceeds 2 per minute
+        clock.advance(minutes(1));
+        set(&mut actor, "ahu-1/sat", PropertyValue::Real(58.0));
+        assert_eq!(active(&actor), vec!["ahu-1/sat"]);
+
+        // Multi-state value in the alarm set
+        set(&mut actor, "ahu-1/mode", PropertyValue::Unsigned(3));
+        assert_eq!(active(&actor), vec!["ahu-1/mode", "ahu-1/sat"]);
+        set(&mut actor, "ahu-1/mode", PropertyValue::Unsigned(1));
+
+        // Deviation from the setpoint; moving the setpoint re-evaluates the zone
+        set(&mut actor, "vav-1/zone-sp", PropertyValue::Real(72.0));
+        set(&mut actor, "vav-1/zone-temp", PropertyValue::Real(74.0));
+        assert_eq!(active(&actor), vec!["ahu-1/sat"]);
+        set(&mut actor, "vav-1/zone-temp", PropertyValue::Real(76.0));
+        assert_eq!(active(&actor), vec!["ahu-1/sat", "vav-1/zone-temp"]);
+        set(&mut actor, "vav-1/zone-sp", PropertyValue::Real(75.0));
+        assert_eq!(active(&actor), vec!["ahu-1/sat"]);
+
+        // Fan commanded on but not running raises after the delay
+        set(&mut actor, "ahu-1/fan-cmd", PropertyValue::Boolean(true));
+        set(&mut actor, "ahu-1/fan-status", PropertyValue::Boolean(false));
+        assert_eq!(actor.check_timers(), 0);
+        clock.advance(minutes(1));
+        assert_eq!(actor.check_timers(), 1);
+        assert_eq!(active(&actor), vec!["ahu-1/fan-cmd", "ahu-1/sat"]);
+        set(&mut actor, "ahu-1/fan-status", PropertyValue::Boolean(true));
+        assert_eq!(active(&actor), vec!["ahu-1/sat"]);
+
+        // A change within the tolerance does not end the flatline
+        set(&mut actor, "ahu-1/rat", PropertyValue::Real(70.05));
+
+        // Once the step is older than the window the supply air rate clears,
+        // though the point has not changed since
+        clock.advance(minutes(3));
+        assert_eq!(actor.check_timers(), 0);
+        clock.advance(minutes(2));
+        assert_eq!(actor.check_timers(), 1);
+        assert!(active(&actor).is_empty());
+
+        // Return air held within the tolerance for 30 minutes
+        clock.advance(minutes(22));
+        assert_eq!(actor.check_timers(), 0);
+        clock.advance(minutes(1));
+        assert_eq!(actor.check_timers(), 1);
+        assert_eq!(active(&actor), vec!["ahu-1/rat"]);
+
+        // A change beyond the tolerance ends the flatline
+        set(&mut actor, "ahu-1/rat", PropertyValue::Real(71.0));
+        assert!(active(&actor).is_empty());
+    }
+
+    #[test]
+    fn test_alarm_device_offline() {
+        let clock = AlarmClock::manual(Utc::now());
+        let mut actor = AlarmActor::new()
+            .with_clock(clock.clone())
+            .with_device_alarm(DeviceAlarmConfig {
+                enabled: true,
+                delay_seconds: 30,
+                severity: AlarmSeverity::Critical,
+            });
+        actor.add_config(AlarmConfig {
+            id: Uuid::new_v4(),
+            name: "High Temperature".to_string(),
+            source_pattern: "*/temperature".to_string(),
+            tag_filter: None,
+            condition: AlarmCondition::HighLimit { value: 80.0 },
+            units: None,
+            severity: AlarmSeverity::High,
+            delay_seconds: 0,
+            off_delay_seconds: 0,
+            min_on_seconds: 0,
+            min_off_seconds: 0,
+            deadband: 0.0,
+            inhibit: None,
+            message_template: "{point} {threshold}".to_string(),
+            enabled: true,
+        });
+        let active = |actor: &AlarmActor| {
+            let mut sources: Vec<String> =
+                actor.get_active_alarms().into_iter().map(|a| a.source).collect();
+            sources.sort();
+            sources
+        };
+
+        actor.device_status("net1", "dev1", DeviceStatus::Timeout, clock.now());
+        assert!(active(&actor).is_empty());
+
+        // Point alarms on the offline device are suppressed, others are not
+        let high = PropertyValue::Real(90.0);
+        actor.evaluate_point("net1/dev1/temperature", &high, None, clock.now());
+        actor.evaluate_point("net1/dev2/temperature", &high, None, clock.now());
+        assert_eq!(active(&actor), vec!["net1/dev2/temperature"]);
+
+        // The device alarm waits out its delay
+        clock.advance(chrono::Duration::seconds(30));
+        assert_eq!(actor.check_timers(), 1);
+        assert_eq!(active(&actor), vec!["net1/dev1", "net1/dev2/temperature"]);
+        let device_alarm = actor
+            .get_active_alarms()
+            .into_iter()
+            .find(|a| a.source == "net1/dev1")
+            .unwrap();
+        assert_eq!(device_alarm.severity, AlarmSeverity::Critical);
+        assert_eq!(device_alarm.message, "Device net1/dev1 is not communicating");
+
+        // Back online: the device alarm clears and the suppressed alarm is raised
+        actor.device_status("net1", "dev1", DeviceStatus::Online, clock.now());
+        assert_eq!(active(&actor), vec!["net1/dev2/temperature"]);
+        assert_eq!(actor.check_timers(), 1);
+        assert_eq!(active(&actor), vec!["net1/dev1/temperature", "net1/dev2/temperature"]);
+
+        // Alarms raised before their device went offline move to the suppressed list
+        actor.device_status("net1", "dev2", DeviceStatus::Offline, clock.now());
+        assert_eq!(active(&actor), vec!["net1/dev1/temperature"]);
+        let suppressed = actor.get_suppressed_alarms();
+        assert_eq!(suppressed.len(), 1);
+        assert_eq!(suppressed[0].alarm.source, "net1/dev2/temperature");
+        assert!(matches!(
+            &suppressed[0].suppression,
+            Suppression::DeviceOffline { device } if device == "net1/dev2"
+        ));
+        actor.device_status("net1", "dev2", DeviceStatus::Online, clock.now());
+        assert_eq!(active(&actor), vec!["net1/dev1/temperature", "net1/dev2/temperature"]);
+    }
+
+    #[test]
+    fn test_alarm_shelving_and_suppression() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("alarms.redb");
+        let clock = AlarmClock::manual(Utc::now());
+        let mut actor = AlarmActor::new()
+            .with_db_path(&db_path)
+            .with_clock(clock.clone());
+        let configs = [
+            (
+                "*/dat",
+                AlarmCondition::LowLimit { value: 50.0 },
+                Some(Inhibit {
+                    point: "{parent}/fan-status".to_string(),
+                    condition: AlarmCondition::IsFalse,
+                }),
+            ),
+            ("*/zone-temp", AlarmCondition::HighLimit { value: 80.0 }, None),
+        ];
+        for (pattern, condition, inhibit) in configs {
+            actor.add_config(AlarmConfig {
+                id: Uuid::new_v4(),
+                name: pattern.to_string(),
+                source_pattern: pattern.to_string(),
+                tag_filter: None,
+                condition,
+                units: None,
+                severity: AlarmSeverity::Medium,
+                delay_seconds: 0,
+                off_delay_seconds: 0,
+                min_on_seconds: 0,
+                min_off_seconds: 0,
+                deadband: 0.0,
+                inhibit,
+                message_template: "{point} {threshold}".to_string(),
+                enabled: true,
+            });
+        }
+        actor.do_start().unwrap();
+        let active = |actor: &AlarmActor| {
+            let mut sources: Vec<String> =
+                actor.get_active_alarms().into_iter().map(|a| a.source).collect();
+            sources.sort();
+            sources
+        };
+
+        // Low discharge temperature is inhibited while the fan is off
+        let set = |actor: &mut AlarmActor, point: &str, value: PropertyValue| {
+            actor.evaluate_point(point, &value, None, clock.now());
+        };
+        set(&mut actor, "ahu-1/fan-status", PropertyValue::Boolean(false));
+        set(&mut actor, "ahu-1/dat", PropertyValue::Real(45.0));
+        assert!(active(&actor).is_empty());
+        let suppressed = actor.get_suppressed_alarms();
+        assert_eq!(suppressed.len(), 1);
+        assert!(matches!(
+            &suppressed[0].suppression,
+            Suppression::Inhibited { point } if point == "ahu-1/fan-status"
+        ));
+        set(&mut actor, "ahu-1/fan-status", PropertyValue::Boolean(true));
+        assert_eq!(active(&actor), vec!["ahu-1/dat"]);
+
+        // Shelving an alarm for an hour
+        set(&mut actor, "vav-1/zone-temp", PropertyValue::Real(85.0));
+        let zone_alarm = actor
+            .get_active_alarms()
+            .into_iter()
+            .find(|a| a.source == "vav-1/zone-temp")
+            .unwrap();
+        let hour = chrono::Duration::hours(1);
+        let target = ShelveTarget::Alarm(zone_alarm.id);
+        let shelf = actor.shelve(target, hour, "sensor fault".to_string(), Some("op".to_string()));
+        assert_eq!(shelf.unwrap().until, clock.now() + hour);
+        assert_eq!(active(&actor), vec!["ahu-1/dat"]);
+        let unknown = ShelveTarget::Config(Uuid::new_v4());
+        assert!(actor.shelve(unknown, hour, String::new(), None).is_none());
+        let forever = chrono::Duration::MAX;
+        assert!(actor.shelve(target, forever, String::new(), None).is_none());
+        assert_eq!(actor.shelves[&target].until, clock.now() + hour);
+
+        // A maintenance window on the AHU
+        actor.windows.push(SuppressionWindow {
+            id: Uuid::new_v4(),
+            name: "AHU-1 maintenance".to_string(),
+            source_pattern: "ahu-1/*".to_string(),
+            start: clock.now(),
+            end: clock.now() + chrono::Duration::minutes(30),
+            reason: None,
+        });
+        actor.persist_windows();
+        assert!(active(&actor).is_empty());
+        assert_eq!(actor.get_suppressed_alarms().len(), 2);
+
+        // Shelves and windows survive a restart
+        drop(actor);
+        let mut actor = AlarmActor::new()
+            .with_db_path(&db_path)
+            .with_clock(clock.clone());
+        actor.do_start().unwrap();
+        assert!(active(&actor).is_empty());
+        assert_eq!(actor.get_suppressed_alarms().len(), 2);
+
+        // The window ends, then the shelf
+        clock.advance(chrono::Duration::minutes(31));
+        actor.check_timers();
+        assert!(actor.windows.is_empty());
+        assert_eq!(active(&actor), vec!["ahu-1/dat"]);
+        clock.advance(chrono::Duration::minutes(30));
+        actor.check_timers();
+        assert!(actor.shelves.is_empty());
+        assert_eq!(active(&actor), vec!["ahu-1/dat", "vav-1/zone-temp"]);
+    }
+
+    /// Minimal SMTP server forwarding each message it receives
+    async fn smtp_stand_in(listener: TcpListener, messages: mpsc::UnboundedSender<String>) {
+        while let Ok((stream, _)) = listener.accept().await {
+            let (reader, mut writer) = stream.into_split();
+            let mut lines = BufReader::new(reader).lines();
+            writer.write_all(b"220 stand-in\r\n").await.unwrap();
+            let mut data: Option<String> = None;
+            while let Ok(Some(line)) = lines.next_line().await {
+                if data.is_some() {
+                    if line == "." {
+                        let _ = messages.send(data.take().unwrap());
+                        writer.write_all(b"250 OK\r\n").await.unwrap();
+                    } else if let Some(message) = data.as_mut() {
+                        message.push_str(&line);
+                        message.push('\n');
+                    }
+                    continue;
+                }
+                let reply: &[u8] = if line.starts_with("EHLO") {
+                    b"250-stand-in\r\n250 OK\r\n"
+                } else if line == "DATA" {
+                    data = Some(String::new());
+                    b"354 go ahead\r\n"
+                } else if line == "QUIT" {
+                    b"221 bye\r\n"
+                } else {
+                    b"250 OK\r\n"
+                };
+                writer.write_all(reply).await.unwrap();
+            }
+        }
+    }
+
+    /// Minimal HTTP server forwarding each request body it receives
+    async fn webhook_stand_in(listener: TcpListener, bodies: mpsc::UnboundedSender<String>) {
+        while let Ok((stream, _)) = listener.accept().await {
+            let mut reader = BufReader::new(stream);
+            let mut content_length = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).await.unwrap();
+                let line = line.trim_end();
+                if line.is_empty() {
+                    break;
+                }
+                if let Some(length) = line.strip_prefix("Content-Length: ") {
+                    content_length = length.parse().unwrap();
+                }
+            }
+            let mut body = vec![0; content_length];
+            reader.read_exact(&mut body).await.unwrap();
+            let _ = bodies.send(String::from_utf8(body).unwrap());
+            let response = b"HTTP/1.1 204 No Content\r\nContent-Length: 0\r\n\r\n";
+            reader.get_mut().write_all(response).await.unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_alarm_notifications() {
+        let smtp = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let smtp_port = smtp.local_addr().unwrap().port();
+        let (mail_tx, mut mail) = mpsc::unbounded_channel();
+        tokio::spawn(smtp_stand_in(smtp, mail_tx));
+        let webhook = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let webhook_url = format!("http://{}/alarms", webhook.local_addr().unwrap());
+        let (hook_tx, mut hooks) = mpsc::unbounded_channel();
+        tokio::spawn(webhook_stand_in(webhook, hook_tx));
+
+        let config: NotificationConfig = serde_json::from_value(serde_json::json!({
+            "smtp": { "host": "127.0.0.1", "port": smtp_port, "from": "neo@example.com" },
+            "classes": [
+                {
+                    "name": "plant",
+                    "min_severity": "High",
+                    "groups": [
+                        {
+                            "name": "operators",
+                            "recipients": [
+                                { "type": "email", "address": "ops@example.com" },
+                                { "type": "webhook", "url": webhook_url },
+                            ],
+                            "escalate_after_minutes": 15,
+                        },
+                        {
+                            "name": "managers",
+                            "recipients": [
+                                { "type": "email", "address": "manager@example.com" },
+                                { "type": "plugin", "plugin": "pager" },
+                            ],
+                        },
+                    ],
+                },
+                {
+                    "name": "critical",
+                    "min_severity": "Critical",
+                    "groups": [{ "name": "on-call", "recipients": [] }],
+                },
+            ],
+        }))
+        .unwrap();
+        config.validate().unwrap();
+
+        let clock = AlarmClock::manual(Utc::now());
+        let mut actor = AlarmActor::new()
+            .with_clock(clock.clone())
+            .with_notifications(config.clone());
+        actor.add_config(AlarmConfig {
+            id: Uuid::new_v4(),
+            name: "High Temperature".to_string(),
+            source_pattern: "*/temp".to_string(),
+            tag_filter: None,
+            condition: AlarmCondition::HighLimit { value: 80.0 },
+            units: None,
+            severity: AlarmSeverity::High,
+            delay_seconds: 0,
+            off_delay_seconds: 0,
+            min_on_seconds: 0,
+            min_off_seconds: 0,
+            deadband: 0.0,
+            inhibit: None,
+            message_template: "{point} is {value}".to_string(),
+            enabled: true,
+        });
+        actor.do_start().unwrap();
+        let notifier = Notifier {
+            smtp: config.smtp.clone(),
+            registry: None,
+            store: actor.store.clone(),
+        };
+
+        // Both alarms go to the operators (the critical class does not route them)
+        let high = PropertyValue::Real(90.0);
+        actor.evaluate_point("ahu-1/temp", &high, None, clock.now());
+        actor.evaluate_point("ahu-2/temp", &high, None, clock.now());
+        let queued = std::mem::take(&mut actor.notifications);
+        assert_eq!(queued.len(), 4);
+        for notification in queued {
+            let record = notifier.deliver(notification).await;
+            assert_eq!(record.status, DeliveryStatus::Delivered);
+        }
+        let message = mail.recv().await.unwrap();
+        assert!(message.contains("To: <ops@example.com>"));
+        assert!(message.contains("Subject: [High] ahu-"));
+        let body: serde_json::Value = serde_json::from_str(&hooks.recv().await.unwrap()).unwrap();
+        assert_eq!(body["group"], "operators");
+
+        // Only the unacknowledged alarm escalates to the managers
+        let acknowledged = actor
+            .get_active_alarms()
+            .into_iter()
+            .find(|a| a.source == "ahu-2/temp")
+            .unwrap();
+        actor.acknowledge_alarm(acknowledged.id, Some("operator".to_string()), None).unwrap();
+        clock.advance(chrono::Duration::minutes(14));
+        actor.check_timers();
+        assert!(actor.notifications.is_empty());
+        clock.advance(chrono::Duration::minutes(1));
+        actor.check_timers();
+        let queued = std::mem::take(&mut actor.notifications);
+        assert_eq!(queued.len(), 2);
+        assert!(queued.iter().all(|n| n.alarm.source == "ahu-1/temp" && n.level == 1));
+
+        // Every attempt is logged, including the plugin without a registry
+        for notification in queued {
+            notifier.deliver(notification).await;
+        }
+        let log = actor.notification_log(None, 10);
+        assert_eq!(log.len(), 6);
+        let failed: Vec<&NotificationRecord> = log
+            .iter()
+            .filter(|r| matches!(r.status, DeliveryStatus::Failed { .. }))
+            .collect();
+        assert_eq!(failed.len(), 1);
+        assert_eq!(failed[0].recipient, Recipient::Plugin { plugin: "pager".to_string() });
+        assert_eq!(actor.notification_log(Some(acknowledged.id), 10).len(), 2);
+
+        // The last group does not escalate further
+        clock.advance(chrono::Duration::hours(1));
+        actor.check_timers();
+        assert!(actor.notifications.is_empty());
+        assert!(actor.escalations.is_empty());
+
+        // Addresses that would inject SMTP commands or headers, and https
+        // webhooks (no TLS), are rejected
+        let recipients = [
+            Recipient::Email { address: "ops@example.com>\r\nRCPT TO:<x@evil.com".to_string() },
+            Recipient::Email { address: "ops@example.com\nBcc: x@evil.com".to_string() },
+            Recipient::Webhook { url: "https://hooks.example.com/alarms".to_string() },
+            Recipient::Webhook { url: "ftp://hooks.example.com/alarms".to_string() },
+        ];
+        for recipient in recipients {
+            let mut bad = config.clone();
+            bad.classes[0].groups[0].recipients = vec![recipient.clone()];
+            assert!(matches!(bad.validate(), Err(Error::Config(_))), "{}", recipient);
+        }
+        let smtp_config = config.smtp.as_ref().unwrap();
+        let to = "ops@example.com>\r\nDATA";
+        let sent = notifications::send_email(smtp_config, to, "Test", "Test").await;
+        assert!(matches!(sent, Err(Error::Config(_))));
+    }
+
+    #[test]
+    fn test_alarm_notifies_after_suppression_and_restart() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("alarms.redb");
+        let config: NotificationConfig = serde_json::from_value(serde_json::json!({
+            "classes": [{
+                "name": "plant",
+                "groups": [
+                    {
+                        "name": "operators",
+                        "recipients": [{ "type": "email", "address": "ops@example.com" }],
+                        "escalate_after_minutes": 15,
+                    },
+                    {
+                        "name": "managers",
+                        "recipients": [{ "type": "email", "address": "manager@example.com" }],
+                    },
+                ],
+            }],
+        }))
+        .unwrap();
+        let config_id = Uuid::new_v4();
+        let clock = AlarmClock::manual(Utc::now());
+        let mut actor = AlarmActor::new()
+            .with_db_path(&db_path)
+            .with_clock(clock.clone())
+            .with_notifications(config.clone());
+        actor.add_config(AlarmConfig {
+            id: config_id,
+            name: "High Temperature".to_string(),
+            source_pattern: "*/temp".to_string(),
+            tag_filter: None,
+            condition: AlarmCondition::HighLimit { value: 80.0 },
+            units: None,
+            severity: AlarmSeverity::High,
+            delay_seconds: 0,
+            off_delay_seconds: 0,
+            min_on_seconds: 0,
+            min_off_seconds: 0,
+            deadband: 0.0,
+            inhibit: None,
+            message_template: "{point} is {value}".to_string(),
+            enabled: true,
+        });
+        actor.do_start().unwrap();
+
+        // Raised while shelved: nobody is notified until the shelf is removed
+        let target = ShelveTarget::Config(config_id);
+        actor.shelve(target, chrono::Duration::hours(1), String::new(), None).unwrap();
+        actor.evaluate_point("ahu-1/temp", &PropertyValue::Real(90.0), None, clock.now());
+        actor.check_timers();
+        assert!(actor.notifications.is_empty());
+        assert_eq!(actor.escalations.len(), 1);
+        assert!(actor.unshelve(target));
+        actor.check_timers();
+        let queued = std::mem::take(&mut actor.notifications);
+        assert_eq!(queued.len(), 1);
+        assert_eq!((queued[0].group.as_str(), queued[0].level), ("operators", 0));
+        drop(actor);
+
+        // After a restart the alarm still escalates to the managers
+        let mut actor = AlarmActor::new()
+            .with_db_path(&db_path)
+            .with_clock(clock.clone())
+            .with_notifications(config);
+        actor.do_start().unwrap();
+        clock.advance(chrono::Duration::minutes(15));
+        actor.check_timers();
+        let queued = std::mem::take(&mut actor.notifications);
+        assert_eq!(queued.len(), 1);
+        assert_eq!((queued[0].group.as_str(), queued[0].level), ("managers", 1));
+    }
+
+    #[test]
+    fn test_alarm_floods_and_kpis() {
+        let start = Utc::now();
+        let clock = AlarmClock::manual(start);
+        let mut actor = AlarmActor::new()
+            .with_clock(clock.clone())
+            .with_flood_detection(3, 60);
+        actor.add_config(AlarmConfig {
+            id: Uuid::new_v4(),
+            name: "High".to_string(),
+            source_pattern: "*".to_string(),
+            tag_filter: None,
+            condition: AlarmCondition::HighLimit { value: 80.0 },
+            units: None,
+            severity: AlarmSeverity::High,
+            delay_seconds: 0,
+            off_delay_seconds: 0,
+            min_on_seconds: 0,
+            min_off_seconds: 0,
+            deadband: 0.0,
+            inhibit: None,
+            message_template: "{point} high".to_string(),
+            enabled: true,
+        });
+        actor.do_start().unwrap();
+        let high = PropertyValue::Real(90.0);
+        let normal = PropertyValue::Real(70.0);
+        let raised = |actor: &AlarmActor, point: &str| {
+            actor.active_alarms.values().find(|a| a.source == point).cloned().unwrap()
+        };
+
+        // p1 chatters: raised three times within 20 seconds
+        actor.evaluate_point("p1", &high, None, clock.now());
+        let first_out = raised(&actor, "p1").id;
+        for value in [&normal, &high, &normal, &high] {
+            clock.advance(chrono::Duration::seconds(5));
+            actor.evaluate_point("p1", value, None, clock.now());
+        }
+        assert!(raised(&actor, "p1").flood_id.is_none());
+        assert!(!actor.floods.is_flooding());
+
+        // The fourth alarm within a minute starts a flood with the earlier three
+        clock.advance(chrono::Duration::seconds(5));
+        actor.evaluate_point("p2", &high, None, clock.now());
+        assert!(actor.floods.is_flooding());
+        let flood_id = raised(&actor, "p2").flood_id.unwrap();
+        assert!(!raised(&actor, "p2").first_out);
+        assert_eq!(raised(&actor, "p1").flood_id, Some(flood_id));
+        let stored = actor.store.as_ref().unwrap().alarm(first_out).unwrap().unwrap();
+        assert_eq!(stored.state, AlarmState::Cleared);
+        assert_eq!(stored.flood_id, Some(flood_id));
+        assert!(stored.first_out);
+
+        clock.advance(chrono::Duration::seconds(5));
+        actor.evaluate_point("p3", &high, None, clock.now());
+        assert_eq!(raised(&actor, "p3").flood_id, Some(flood_id));
+
+        // The flood ends once the rate drops
+        clock.advance(chrono::Duration::seconds(120));
+        actor.check_timers();
+        assert!(!actor.floods.is_flooding());
+        actor.evaluate_point("p4", &high, None, clock.now());
+        assert!(raised(&actor, "p4").flood_id.is_none());
+
+        clock.advance(chrono::Duration::seconds(60));
+        let p2 = raised(&actor, "p2").id;
+        actor.acknowledge_alarm(p2, None, None).unwrap();
+
+        let end = start + chrono::Duration::hours(2);
+        let kpis = actor.alarm_kpis(start, end).unwrap();
+        assert_eq!(kpis.total_alarms, 6);
+        assert_eq!(kpis.alarms_per_hour, 3.0);
+        assert_eq!(kpis.top_alarms[0].source, "p1");
+        assert_eq!(kpis.top_alarms[0].count, 3);
+        assert_eq!(kpis.top_alarms.len(), 4);
+        let chattering: Vec<&str> =
+            kpis.chattering_alarms.iter().map(|a| a.source.as_str()).collect();
+        assert_eq!(chattering, vec!["p1"]);
+        assert_eq!(kpis.mean_time_to_acknowledge_secs, Some(185.0));
+        assert_eq!(kpis.floods.len(), 1);
+        assert_eq!(kpis.floods[0].id, flood_id);
+        assert_eq!(kpis.floods[0].alarm_count, 5);
+        assert_eq!(kpis.floods[0].first_out, first_out);
+        assert_eq!(kpis.floods[0].first_out_source, "p1");
+        assert!(kpis.standing_alarms.is_empty());
+
+        // Alarms still active a day later are standing
+        clock.advance(chrono::Duration::hours(24));
+        let kpis = actor.alarm_kpis(start, end).unwrap();
+        let mut standing: Vec<&str> =
+            kpis.standing_alarms.iter().map(|a| a.source.as_str()).collect();
+        standing.sort();
+        assert_eq!(standing, vec!["p1", "p2", "p3", "p4"]);
+    }
+
+    #[test]
+    fn test_alarm_acknowledgement_workflow() {
+        let clock = AlarmClock::manual(Utc::now());
+        let mut actor = AlarmActor::new()
+            .with_clock(clock.clone())
+            .with_ack_policy(AckPolicy {
+                rules: vec![AckRule {
+                    severity: AlarmSeverity::Critical,
+                    comment_required: true,
+                    latching: true,
+                }],
+            });
+        let configs = [
+            ("*/pressure", AlarmSeverity::Critical),
+            ("*/temperature", AlarmSeverity::High),
+        ];
+        for (pattern, severity) in configs {
+            actor.add_config(AlarmConfig {
+                id: Uuid::new_v4(),
+                name: pattern.to_string(),
+                source_pattern: pattern.to_string(),
+                tag_filter: None,
+                condition: AlarmCondition::HighLimit { value: 80.0 },
+                units: None,
+                severity,
+                delay_seconds: 0,
+                off_delay_seconds: 0,
+                min_on_seconds: 0,
+                min_off_seconds: 0,
+                deadband: 0.0,
+                inhibit: None,
+                message_template: "{point} {threshold}".to_string(),
+                enabled: true,
+            });
+        }
+        actor.do_start().unwrap();
+        let high = PropertyValue::Real(90.0);
+        let normal = PropertyValue::Real(70.0);
+        for point in ["boiler/pressure", "zone1/temperature", "zone2/temperature"] {
+            actor.evaluate_point(point, &high, None, clock.now());
+        }
+        let critical = actor
+            .get_active_alarms()
+            .into_iter()
+            .find(|a| a.source == "boiler/pressure")
+            .unwrap()
+            .id;
+
+        // Critical alarms need a comment to be acknowledged
+        let result = actor.acknowledge_alarm(critical, None, Some("  ".to_string()));
+        assert!(matches!(result, Err(Error::Service(_))));
+
+        // The latching alarm stays active after returning to normal, and is
+        // raised again (the same alarm) if its condition is met again
+        clock.advance(chrono::Duration::seconds(10));
+        actor.evaluate_point("boiler/pressure", &normal, None, clock.now());
+        let latched = actor.active_alarms[&critical].clone();
+        assert_eq!(latched.returned_to_normal_at, Some(clock.now()));
+        assert!(!actor.events.iter().any(|e| matches!(e, Event::AlarmCleared { .. })));
+        clock.advance(chrono::Duration::seconds(10));
+        actor.evaluate_point("boiler/pressure", &high, None, clock.now());
+        assert!(actor.active_alarms[&critical].returned_to_normal_at.is_none());
+        assert_eq!(actor.active_alarms.len(), 3);
+        clock.advance(chrono::Duration::seconds(10));
+        actor.evaluate_point("boiler/pressure", &normal, None, clock.now());
+
+        actor
+            .comment_alarm(critical, "Checking relief valve".to_string(), Some("op1".to_string()))
+            .unwrap();
+        let unknown = actor.comment_alarm(Uuid::new_v4(), "Note".to_string(), None);
+        assert!(matches!(unknown, Err(Error::NotFound(_))));
+
+        // Bulk acknowledgement skips the alarm that needs a comment
+        let everything = AlarmFilter {
+            source_pattern: Some("*".to_string()),
+            ..Default::default()
+        };
+        let result = actor.acknowledge_matching(&everything, Some("op1".to_string()), None);
+        assert_eq!(result.acknowledged.len(), 2);
+        assert_eq!(result.skipped, vec![critical]);
+
+        // Acknowledging the latched alarm clears it
+        let critical_only = AlarmFilter {
+            min_severity: Some(AlarmSeverity::Critical),
+            ..Default::default()
+        };
+        let comment = Some("Relief valve reset".to_string());
+        let result = actor.acknowledge_matching(&critical_only, Some("op2".to_string()), comment);
+        assert_eq!(result.acknowledged, vec![critical]);
+        assert!(!actor.active_alarms.contains_key(&critical));
+        let stored = actor.store.as_ref().unwrap().alarm(critical).unwrap().unwrap();
+        assert_eq!(stored.state, AlarmState::Cleared);
+        assert_eq!(stored.ack_comment.as_deref(), Some("Relief valve reset"));
+
+        // Acknowledged non-latching alarms clear as they return to normal
+        actor.evaluate_point("zone1/temperature", &normal, None, clock.now());
+        assert_eq!(actor.active_alarms.len(), 1);
+
+        let timeline = actor.alarm_timeline(critical).unwrap();
+        let kinds: Vec<AlarmTimelineKind> = timeline.iter().map(|e| e.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                AlarmTimelineKind::Raised,
+                AlarmTimelineKind::ReturnedToNormal,
+                AlarmTimelineKind::ReRaised,
+                AlarmTimelineKind::ReturnedToNormal,
+                AlarmTimelineKind::Commented,
+                AlarmTimelineKind::Acknowledged,
+                AlarmTimelineKind::Cleared,
+            ]
+        );
+        assert_eq!(timeline[4].user.as_deref(), Some("op1"));
+        assert_eq!(timeline[5].user.as_deref(), Some("op2"));
+        assert_eq!(timeline[5].comment.as_deref(), Some("Relief valve reset"));
+
+        // Latched alarms are restored as latched
+        clock.advance(chrono::Duration::seconds(10));
+        actor.evaluate_point("boiler/pressure", &high, None, clock.now());
+        actor.evaluate_point("boiler/pressure", &normal, None, clock.now());
+        actor.alarm_tracker.clear();
+        actor.latched.clear();
+        actor.conditions.clear();
+        actor.do_start().unwrap();
+        assert_eq!(actor.latched.len(), 1);
+        assert!(actor.alarm_tracker.values().all(|id| actor.active_alarms.contains_key(id)));
+    }
+
+    #[test]
+    fn test_alarm_persistence_and_history_paging() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("alarms.redb");
+        let t0 = Utc::now();
+        let at = |secs: i64| t0 + chrono::Duration::seconds(secs);
+
+        let acked_id = {
+            let mut actor = AlarmActor::new().with_db_path(db_path.clone());
+            actor.do_start().unwrap();
+            actor.add_config(AlarmConfig {
+                id: Uuid::new_v4(),
+                name: "High Temperature".to_string(),
+                source_pattern: "*/temperature".to_string(),
+                tag_filter: None,
+                condition: AlarmCondition::HighLimit { value: 80.0 },
+                units: None,
+                severity: AlarmSeverity::High,
+                delay_seconds: 0,
+                off_delay_seconds: 0,
+                min_on_seconds: 0,
+                min_off_seconds: 0,
+                deadband: 0.0,
+                inhibit: None,
+                message_template: "{point} {threshold}".to_string(),
+                enabled: true,
+            });
+            for zone in 1..=3 {
+                let point = format!("zone{}/temperature", zone);
+                actor.evaluate_point(&point, &PropertyValue::Real(85.0), None, at(zone));
+            }
+            actor.evaluate_point("zone1/temperature", &PropertyValue::Real(70.0), None, at(4));
+
+            let active = actor.get_active_alarms();
+            let zone2 = active.iter().find(|a| a.source == "zone2/temperature").unwrap();
+            actor.acknowledge_alarm(zone2.id, Some("operator1".to_string()), None).unwrap();
+            zone2.id
+        };
+
+        // A restarted service has the configuration and the uncleared alarms
+        let mut actor = AlarmActor::new().with_db_path(db_path);
+        actor.do_start().unwrap();
+        assert_eq!(actor.configs.len(), 1);
+        let active = actor.get_active_alarms();
+        assert_eq!(active.len(), 2);
+        let acked = active.iter().find(|a| a.id == acked_id).unwrap();
+        assert_eq!(acked.state, AlarmState::Acknowledged);
+        assert_eq!(acked.acknowledged_by.as_deref(), Some("operator1"));
+
+        // Restored alarms clear when their condition returns to normal
+        actor.evaluate_point("zone2/temperature", &PropertyValue::Real(70.0), None, at(5));
+        assert_eq!(actor.get_active_alarms().len(), 1);
+
+        // History pages newest first, with the latest state of each alarm
+        let first = actor.alarm_history(at(0), at(10), None, None, 2).unwrap();
+        assert_eq!(first.alarms.len(), 2);
+        let cursor = first.next_cursor.unwrap();
+        let second = actor.alarm_history(at(0), at(10), None, Some(cursor), 2).unwrap();
+        assert!(second.next_cursor.is_none());
+
+        let history: Vec<_> = first
+            .alarms
+            .iter()
+            .chain(&second.alarms)
+            .map(|a| (a.source.as_str(), a.state))
+            .collect();
+        assert_eq!(
+            history,
+            vec![
+                ("zone3/temperature", AlarmState::Active),
+                ("zone2/temperature", AlarmState::Cleared),
+                ("zone1/temperature", AlarmState::Cleared),
+            ]
+        );
+
+        let filtered = actor.alarm_history(at(0), at(10), Some("zone1/*"), None, 10).unwrap();
+        assert_eq!(filtered.alarms.len(), 1);
+
+        // Cleared alarms older than the retention period are purged; active ones stay
+        actor.history_retention_days = 1;
+        let later = at(10) + chrono::Duration::days(2);
+        assert_eq!(actor.purge_history(later), 2);
+        let history = actor.alarm_history(at(0), later, None, None, 10).unwrap();
+        assert_eq!(history.alarms.len(), 1);
+        assert_eq!(history.alarms[0].source, "zone3/temperature");
+        assert_eq!(actor.purge_history(later), 0);
+    }
+
+    #[test]
+    fn test_alarm_rule_files() {
+        const ZONES: &str = r#"{"alarms": [{
+            "name": "Zone Too Warm",
+            "source_pattern": "*/temperature",
+            "condition": { "type": "HighLimit", "value": LIMIT },
+            "severity": "SEVERITY",
+            "message_template": "{point} {threshold}"
+        }]}"#;
+        const FANS: &str = r#"
+            [[alarms]]
+            name = "Fan Failed"
+            source_pattern = "*/fan-status"
+            condition = { type = "IsFalse" }
+            severity = "Medium"
+            message_template = "{point} {threshold}"
+        "#;
+
+        let dir = tempdir().unwrap();
+        let zones = dir.path().join("zones.json");
+        let fans = dir.path().join("fans.toml");
+        let write_zones = |limit: &str, severity: &str| {
+            let rules = ZONES.replace("LIMIT", limit).replace("SEVERITY", severity);
+            std::fs::write(&zones, rules).unwrap();
+        };
+        write_zones("80", "High");
+        std::fs::write(&fans, FANS).unwrap();
+
+        let mut actor = AlarmActor::new().with_rules_dir(dir.path());
+        actor.do_start().unwrap();
+        assert_eq!(actor.configs.len(), 2);
+        assert!(actor.store.as_ref().unwrap().configs().unwrap().is_empty());
+
+        actor.evaluate_point("zone1/temperature", &PropertyValue::Real(85.0), None, Utc::now());
+        let alarm_id = actor.get_active_alarms()[0].id;
+
+        // A changed rule keeps its id, and its active alarm takes the new severity
+        write_zones("80", "Critical");
+        actor.reload_rule_file(&zones);
+        let active = actor.get_active_alarms();
+        assert_eq!((active[0].id, active[0].severity), (alarm_id, AlarmSeverity::Critical));
+
+        // Raising the limit above the last value clears the alarm
+        write_zones("90", "Critical");
+        actor.reload_rule_file(&zones);
+        assert!(actor.get_active_alarms().is_empty());
+
+        // A bad file is reported and its rules stay loaded
+        std::fs::write(&zones, r#"{"alarms": [{"name": "Broken""#).unwrap();
+        actor.reload_rule_file(&zones);
+        assert!(actor.rule_errors.contains_key(&zones));
+        assert_eq!(actor.configs.len(), 2);
+
+        write_zones("90", "Critical");
+        actor.reload_rule_file(&zones);
+        assert!(actor.rule_errors.is_empty());
+
+        // Rules are validated against their condition
+        std::fs::write(&fans, FANS.replace("severity", "deadband = 2.0\nseverity")).unwrap();
+        actor.reload_rule_file(&fans);
+        assert!(actor.rule_errors.contains_key(&fans));
+
+        // Removing a file removes its rules
+        std::fs::remove_file(&fans).unwrap();
+        actor.reload_rule_file(&fans);
+        assert_eq!(actor.configs.len(), 1);
+        assert!(actor.rule_errors.is_empty());
+
+        // Only rule files may leave out the id
+        let rule: serde_json::Value =
+            serde_json::from_str(&ZONES.replace("LIMIT", "80").replace("SEVERITY", "High"))
+                .unwrap();
+        let config = serde_json::from_value::<AlarmConfig>(rule["alarms"][0].clone());
+        assert!(config.is_err());
+    }
+
+    #[test]
+    fn test_alarm_lifecycle_events() {
+        let mut actor = AlarmActor::new();
+        let config_id = Uuid::new_v4();
+        actor.add_config(AlarmConfig {
+            id: config_id,
+            name: "High Temperature".to_string(),
+            source_pattern: "*/temperature".to_string(),
+            tag_filter: None,
+            condition: AlarmCondition::HighLimit { value: 80.0 },
+            units: None,
+            severity: AlarmSeverity::High,
+            delay_seconds: 0,
+            off_delay_seconds: 0,
+            min_on_seconds: 0,
+            min_off_seconds: 0,
+            deadband: 0.0,
+            inhibit: None,
+            message_template: "{point} {threshold}".to_string(),
+            enabled: true,
+        });
+
+        actor.evaluate_point("zone1/temperature", &PropertyValue::Real(85.0), None, Utc::now());
+        let alarm_id = actor.get_active_alarms()[0].id;
+        actor.acknowledge_alarm(alarm_id, Some("operator1".to_string()), None).unwrap();
+        actor.evaluate_point("zone1/temperature", &PropertyValue::Real(75.0), None, Utc::now());
+
+        let events: Vec<_> = actor
+            .events
+            .iter()
+            .map(|event| match event {
+                Event::AlarmRaised {
+                    alarm_id,
+                    config_id,
+                    severity,
+                    value,
+                    ..
+                } => ("raised", *alarm_id, *config_id, *severity, value.clone()),
+                Event::AlarmAcknowledged {
+                    alarm_id,
+                    config_id,
+                    severity,
+                    ..
+                } => ("acknowledged", *alarm_id, *config_id, *severity, None),
+                Event::AlarmReturnedToNormal {
+                    alarm_id,
+                    config_id,
+                    severity,
+                    value,
+                    ..
+                } => ("returned", *alarm_id, *config_id, *severity, value.clone()),
+                Event::AlarmCleared {
+                    alarm_id,
+                    config_id,
+                    severity,
+                    ..
+                } => ("cleared", *alarm_id, *config_id, *severity, None),
+                other => panic!("Unexpected event {:?}", other),
+            })
+            .collect();
+        let high = AlarmSeverity::High;
+        assert_eq!(
+            events,
+            vec![
+                ("raised", alarm_id, config_id, high, Some(PropertyValue::Real(85.0))),
+                ("acknowledged", alarm_id, config_id, high, None),
+                ("returned", alarm_id, config_id, high, Some(PropertyValue::Real(75.0))),
+                ("cleared", alarm_id, config_id, high, None),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_alarm_conditions() {
+        // High limit
+        let cond = AlarmCondition::HighLimit { value: 80.0 };
+        assert!(cond.evaluate(&PropertyValue::Real(85.0)));
+        assert!(!cond.evaluate(&PropertyValue::Real(75.0)));
+
+        // Low limit
+        let cond = AlarmCondition::LowLimit { value: 50.0 };
+        assert!(cond.evaluate(&PropertyValue::Real(45.0)));
+        assert!(!cond.evaluate(&PropertyValue::Real(55.0)));
+
+        // Out of range
+        let cond = AlarmCondition::OutOfRange {
+            low: 60.0,
+            high: 80.0,
+        };
+        assert!(cond.evaluate(&PropertyValue::Real(55.0)));
+        assert!(cond.evaluate(&PropertyValue::Real(85.0)));
+        assert!(!cond.evaluate(&PropertyValue::Real(70.0)));
+
+        // Boolean conditions
+        let cond = AlarmCondition::IsTrue;
+        assert!(cond.evaluate(&PropertyValue::Boolean(true)));
+        assert!(!cond.evaluate(&PropertyValue::Boolean(false)));
+
+        let cond = AlarmCondition::IsFalse;
+        assert!(cond.evaluate(&PropertyValue::Boolean(false)));
+        assert!(!cond.evaluate(&PropertyValue::Boolean(true)));
+    }
+}
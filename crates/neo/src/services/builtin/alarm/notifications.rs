@@ -0,0 +1,531 @@
+// Alarm Notifications - Who hears about a raised alarm, and how
+//
+// Notification classes route raised alarms by severity, source pattern, tag
+// filter and time of day. Each class lists recipient groups in escalation
+// order: the first group is notified when the alarm is raised, and the next
+// one if the alarm has not been acknowledged within the group's
+// `escalate_after_minutes`. Recipients are reached by:
+//
+//   email     SMTP (plain, no TLS or authentication: a local relay, or a
+//             stand-in such as MailHog while testing)
+//   webhook   HTTP POST of the alarm as JSON (http:// URLs only: there is
+//             no TLS, so https:// URLs are rejected when the config is
+//             validated; put a TLS-terminating proxy in front if needed)
+//   plugin    a Custom "alarm_notification" request to a plugin service
+//
+// Email addresses are checked for characters that would break out of the SMTP
+// command or header they are written into, both on validation and on sending.
+//
+// Suppressed alarms (see suppression) are routed but not notified: their first
+// group hears about them once they are no longer suppressed, if they are still
+// active. Escalation state is kept in the alarm store, so alarms restored when
+// the service starts keep escalating. Deliveries run in a background task so a
+// slow recipient never holds up the alarm service, and every attempt is logged
+// in the alarm store with its delivery status.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Datelike, NaiveTime, Utc};
+use chrono_tz::Tz;
+use kameo::actor::ActorRef;
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+use uuid::Uuid;
+use wildmatch::WildMatch;
+
+use super::store::AlarmStore;
+use crate::services::messages::{Alarm, ServiceRequest, ServiceResponse, Weekday};
+use crate::services::registry::{RegistryMsg, RegistryReply, ServiceRegistry};
+use crate::types::{AlarmSeverity, Error, Result};
+
+/// How long a recipient has to accept a connection and each reply
+const DELIVERY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Notification routing settings
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NotificationConfig {
+    /// SMTP server for email recipients
+    #[serde(default)]
+    pub smtp: Option<SmtpConfig>,
+    #[serde(default)]
+    pub classes: Vec<NotificationClass>,
+}
+
+/// SMTP server settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SmtpConfig {
+    pub host: String,
+    #[serde(default = "default_smtp_port")]
+    pub port: u16,
+    /// Sender address
+    pub from: String,
+}
+
+impl NotificationConfig {
+    /// Check email addresses and webhook URLs, which cannot be sent to
+    /// otherwise
+    pub fn validate(&self) -> Result<()> {
+        if let Some(smtp) = &self.smtp {
+            check_address(&smtp.from)?;
+        }
+        let recipients = self
+            .classes
+            .iter()
+            .flat_map(|class| &class.groups)
+            .flat_map(|group| &group.recipients);
+        for recipient in recipients {
+            match recipient {
+                Recipient::Email { address } => check_address(address)?,
+                Recipient::Webhook { url } => {
+                    webhook_target(url)?;
+                }
+                Recipient::Plugin { .. } => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Reject addresses that would inject SMTP commands or headers
+fn check_address(address: &str) -> Result<()> {
+    if address.is_empty() || address.contains(['\r', '\n', '<', '>']) {
+        return Err(Error::Config(format!("Invalid email address {:?}", address)));
+    }
+    Ok(())
+}
+
+/// Split a webhook URL into its authority and path
+fn webhook_target(url: &str) -> Result<(&str, &str)> {
+    if url.starts_with("https://") {
+        return Err(Error::Config(format!(
+            "Webhook URL {} uses https, which is not supported; use an http:// URL \
+             (e.g., through a TLS-terminating proxy)",
+            url
+        )));
+    }
+    let rest = url
+        .strip_prefix("http://")
+        .ok_or_else(|| Error::Config(format!("Webhook URL {} is not an http:// URL", url)))?;
+    let target = match rest.find('/') {
+        Some(i) => (&rest[..i], &rest[i..]),
+        None => (rest, "/"),
+    };
+    if target.0.is_empty() || url.contains(char::is_whitespace) {
+        return Err(Error::Config(format!("Invalid webhook URL {}", url)));
+    }
+    Ok(target)
+}
+
+fn default_smtp_port() -> u16 {
+    25
+}
+
+/// Which alarms a set of recipient groups hears about
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotificationClass {
+    pub name: String,
+    /// Lowest severity routed
+    #[serde(default = "default_min_severity")]
+    pub min_severity: AlarmSeverity,
+    /// Glob pattern of the alarm sources routed
+    #[serde(default = "super::default_source_pattern")]
+    pub source_pattern: String,
+    /// Tag filter alarm sources must also match
+    #[serde(default)]
+    pub tag_filter: Option<String>,
+    /// When the class routes alarms (always when None)
+    #[serde(default)]
+    pub hours: Option<NotificationHours>,
+    /// Recipient groups in escalation order
+    pub groups: Vec<RecipientGroup>,
+}
+
+fn default_min_severity() -> AlarmSeverity {
+    AlarmSeverity::Low
+}
+
+impl NotificationClass {
+    /// Whether the class routes an alarm at the given time, by severity,
+    /// source and hours (the tag filter is checked by the alarm service)
+    pub fn routes(&self, alarm: &Alarm, now: DateTime<Utc>) -> bool {
+        alarm.severity >= self.min_severity
+            && WildMatch::new(&self.source_pattern).matches(&alarm.source)
+            && self.hours.as_ref().is_none_or(|hours| hours.contains(now))
+    }
+}
+
+/// Days and time of day a class routes alarms
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotificationHours {
+    /// Days routed (every day when empty)
+    #[serde(default)]
+    pub days: Vec<Weekday>,
+    /// Start of the period (e.g., "08:00")
+    pub start: NaiveTime,
+    /// End of the period; before `start` for periods over midnight
+    pub end: NaiveTime,
+    /// IANA timezone of the days and times (default UTC)
+    #[serde(default)]
+    pub timezone: Option<String>,
+}
+
+impl NotificationHours {
+    /// Whether the period includes the given time
+    pub fn contains(&self, at: DateTime<Utc>) -> bool {
+        let tz = match self.timezone.as_deref().map(str::parse::<Tz>) {
+            None => Tz::UTC,
+            Some(Ok(tz)) => tz,
+            Some(Err(e)) => {
+                tracing::warn!("Invalid notification timezone: {}", e);
+                Tz::UTC
+            }
+        };
+        let local = at.with_timezone(&tz);
+        if !self.days.is_empty() && !self.days.contains(&weekday(local.weekday())) {
+            return false;
+        }
+        let time = local.time();
+        if self.start <= self.end {
+            self.start <= time && time < self.end
+        } else {
+            time >= self.start || time < self.end
+        }
+    }
+}
+
+fn weekday(day: chrono::Weekday) -> Weekday {
+    match day {
+        chrono::Weekday::Mon => Weekday::Monday,
+        chrono::Weekday::Tue => Weekday::Tuesday,
+        chrono::Weekday::Wed => Weekday::Wednesday,
+        chrono::Weekday::Thu => Weekday::Thursday,
+        chrono::Weekday::Fri => Weekday::Friday,
+        chrono::Weekday::Sat => Weekday::Saturday,
+        chrono::Weekday::Sun => Weekday::Sunday,
+    }
+}
+
+/// Recipients notified together
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecipientGroup {
+    pub name: String,
+    pub recipients: Vec<Recipient>,
+    /// Minutes without acknowledgement before the next group is notified
+    /// (no escalation when None)
+    #[serde(default)]
+    pub escalate_after_minutes: Option<u32>,
+}
+
+/// Where a notification goes
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Recipient {
+    Email { address: String },
+    Webhook { url: String },
+    Plugin { plugin: String },
+}
+
+impl std::fmt::Display for Recipient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Recipient::Email { address } => write!(f, "email {}", address),
+            Recipient::Webhook { url } => write!(f, "webhook {}", url),
+            Recipient::Plugin { plugin } => write!(f, "plugin {}", plugin),
+        }
+    }
+}
+
+/// Outcome of a notification attempt
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum DeliveryStatus {
+    Delivered,
+    Failed { error: String },
+}
+
+/// Log entry of a notification attempt
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotificationRecord {
+    pub id: Uuid,
+    pub alarm_id: Uuid,
+    pub class: String,
+    pub group: String,
+    /// Position of the group in the class's escalation order
+    pub level: usize,
+    pub recipient: Recipient,
+    pub attempted_at: DateTime<Utc>,
+    pub status: DeliveryStatus,
+}
+
+/// A notification waiting to be sent
+#[derive(Debug, Clone)]
+pub(super) struct Notification {
+    pub alarm: Alarm,
+    pub class: String,
+    pub group: String,
+    pub level: usize,
+    pub recipient: Recipient,
+}
+
+/// Escalation state of an alarm routed by a class
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(super) struct Escalation {
+    pub alarm_id: Uuid,
+    pub class: String,
+    /// Group notified last
+    pub level: usize,
+    /// None while the alarm has been suppressed since it was raised, so no
+    /// group has been notified yet
+    pub notified_at: Option<DateTime<Utc>>,
+}
+
+/// Sends notifications and logs the attempts
+#[derive(Clone)]
+pub(super) struct Notifier {
+    pub smtp: Option<SmtpConfig>,
+    pub registry: Option<ActorRef<ServiceRegistry>>,
+    pub store: Option<Arc<AlarmStore>>,
+}
+
+impl Notifier {
+    /// Send notifications in a background task
+    pub fn dispatch(&self, notifications: Vec<Notification>) {
+        if notifications.is_empty() {
+            return;
+        }
+        let notifier = self.clone();
+        tokio::spawn(async move {
+            for notification in notifications {
+                notifier.deliver(notification).await;
+            }
+        });
+    }
+
+    /// Send a notification and log the attempt
+    pub async fn deliver(&self, notification: Notification) -> NotificationRecord {
+        let status = match self.send(&notification).await {
+            Ok(()) => DeliveryStatus::Delivered,
+            Err(e) => {
+                tracing::warn!(
+                    "Failed to notify {} of alarm {}: {}",
+                    notification.recipient,
+                    notification.alarm.id,
+                    e
+                );
+                DeliveryStatus::Failed {
+                    error: e.to_string(),
+                }
+            }
+        };
+        let record = NotificationRecord {
+            id: Uuid::new_v4(),
+            alarm_id: notification.alarm.id,
+            class: notification.class,
+            group: notification.group,
+            level: notification.level,
+            recipient: notification.recipient,
+            attempted_at: Utc::now(),
+            status,
+        };
+        if let Some(store) = &self.store {
+            if let Err(e) = store.save_notification(&record) {
+                tracing::warn!("Failed to log notification {}: {}", record.id, e);
+            }
+        }
+        record
+    }
+
+    async fn send(&self, notification: &Notification) -> Result<()> {
+        let alarm = &notification.alarm;
+        match &notification.recipient {
+            Recipient::Email { address } => {
+                let smtp = self
+                    .smtp
+                    .as_ref()
+                    .ok_or_else(|| Error::Config("No SMTP server configured".to_string()))?;
+                let subject = format!("[{:?}] {}", alarm.severity, alarm.message);
+                send_email(smtp, address, &subject, &email_body(notification)).await
+            }
+            Recipient::Webhook { url } => {
+                let body = serde_json::json!({
+                    "alarm": alarm,
+                    "class": notification.class,
+                    "group": notification.group,
+                    "level": notification.level,
+                });
+                post_json(url, &body).await
+            }
+            Recipient::Plugin { plugin } => {
+                let registry = self
+                    .registry
+                    .as_ref()
+                    .ok_or_else(|| Error::Service("No service registry".to_string()))?;
+                let request = ServiceRequest::Custom {
+                    action: "alarm_notification".to_string(),
+                    payload: serde_json::json!({
+                        "alarm": alarm,
+                        "class": notification.class,
+                        "group": notification.group,
+                        "level": notification.level,
+                    }),
+                };
+                let reply = registry
+                    .ask(RegistryMsg::Request {
+                        service_id: plugin.clone(),
+                        request,
+                    })
+                    .await
+                    .map_err(|e| Error::Actor(e.to_string()))?;
+                match reply {
+                    RegistryReply::Response(ServiceResponse::Error { code, message }) => {
+                        Err(Error::Service(format!("{}: {}", code, message)))
+                    }
+                    RegistryReply::Response(_) => Ok(()),
+                    RegistryReply::Failed(e) => Err(Error::Service(e)),
+                    _ => Err(Error::Service("Unexpected registry reply".to_string())),
+                }
+            }
+        }
+    }
+}
+
+fn email_body(notification: &Notification) -> String {
+    let alarm = &notification.alarm;
+    format!(
+        "{}\r\n\r\nSource: {}\r\nSeverity: {:?}\r\nRaised: {}\r\nAlarm: {}\r\n\
+         Notification class: {} ({})\r\n",
+        alarm.message,
+        alarm.source,
+        alarm.severity,
+        alarm.triggered_at.to_rfc3339(),
+        alarm.id,
+        notification.class,
+        notification.group,
+    )
+}
+
+/// Send an email over plain SMTP
+pub(super) async fn send_email(
+    smtp: &SmtpConfig,
+    to: &str,
+    subject: &str,
+    body: &str,
+) -> Result<()> {
+    check_address(&smtp.from)?;
+    check_address(to)?;
+    let address = format!("{}:{}", smtp.host, smtp.port);
+    let stream = connect(&address).await?;
+    let (reader, mut writer) = stream.into_split();
+    let mut reader = BufReader::new(reader);
+
+    smtp_reply(&mut reader, 220).await?;
+    smtp_command(&mut writer, &mut reader, "EHLO neo", 250).await?;
+    let mail_from = format!("MAIL FROM:<{}>", smtp.from);
+    smtp_command(&mut writer, &mut reader, &mail_from, 250).await?;
+    let rcpt_to = format!("RCPT TO:<{}>", to);
+    smtp_command(&mut writer, &mut reader, &rcpt_to, 250).await?;
+    smtp_command(&mut writer, &mut reader, "DATA", 354).await?;
+
+    let mut message = format!(
+        "From: <{}>\r\nTo: <{}>\r\nSubject: {}\r\nDate: {}\r\n\
+         Content-Type: text/plain; charset=utf-8\r\n\r\n",
+        smtp.from,
+        to,
+        subject.replace(['\r', '\n'], " "),
+        Utc::now().to_rfc2822(),
+    );
+    for line in body.lines() {
+        // Dot-stuffing: a line starting with '.' gets another
+        if line.starts_with('.') {
+            message.push('.');
+        }
+        message.push_str(line);
+        message.push_str("\r\n");
+    }
+    message.push('.');
+    smtp_command(&mut writer, &mut reader, &message, 250).await?;
+    smtp_command(&mut writer, &mut reader, "QUIT", 221).await?;
+    Ok(())
+}
+
+async fn smtp_command(
+    writer: &mut tokio::net::tcp::OwnedWriteHalf,
+    reader: &mut BufReader<tokio::net::tcp::OwnedReadHalf>,
+    command: &str,
+    expected: u16,
+) -> Result<()> {
+    writer.write_all(command.as_bytes()).await?;
+    writer.write_all(b"\r\n").await?;
+    smtp_reply(reader, expected).await
+}
+
+/// Read an SMTP reply (all its lines) and check its code
+async fn smtp_reply(
+    reader: &mut BufReader<tokio::net::tcp::OwnedReadHalf>,
+    expected: u16,
+) -> Result<()> {
+    loop {
+        let mut line = String::new();
+        let read = tokio::time::timeout(DELIVERY_TIMEOUT, reader.read_line(&mut line))
+            .await
+            .map_err(|_| Error::Timeout)??;
+        if read == 0 {
+            return Err(Error::Protocol("SMTP server closed the connection".to_string()));
+        }
+        // "250-..." continues a multi-line reply, "250 ..." ends it
+        if line.as_bytes().get(3) == Some(&b'-') {
+            continue;
+        }
+        let code: u16 = line.get(..3).and_then(|c| c.parse().ok()).unwrap_or(0);
+        if code != expected {
+            return Err(Error::Protocol(format!("SMTP: {}", line.trim_end())));
+        }
+        return Ok(());
+    }
+}
+
+/// POST a JSON body to an http:// URL and check for a 2xx status
+pub(super) async fn post_json(url: &str, body: &serde_json::Value) -> Result<()> {
+    let (authority, path) = webhook_target(url)?;
+    let address = if authority.contains(':') {
+        authority.to_string()
+    } else {
+        format!("{}:80", authority)
+    };
+
+    let body = serde_json::to_vec(body).map_err(|e| Error::Service(e.to_string()))?;
+    let mut stream = connect(&address).await?;
+    let head = format!(
+        "POST {} HTTP/1.1\r\nHost: {}\r\nContent-Type: application/json\r\n\
+         Content-Length: {}\r\nConnection: close\r\n\r\n",
+        path,
+        authority,
+        body.len()
+    );
+    stream.write_all(head.as_bytes()).await?;
+    stream.write_all(&body).await?;
+
+    let mut reader = BufReader::new(stream);
+    let mut status_line = String::new();
+    tokio::time::timeout(DELIVERY_TIMEOUT, reader.read_line(&mut status_line))
+        .await
+        .map_err(|_| Error::Timeout)??;
+    let status: u16 = status_line
+        .split_whitespace()
+        .nth(1)
+        .and_then(|s| s.parse().ok())
+        .ok_or_else(|| Error::Protocol(format!("Bad HTTP response: {}", status_line.trim())))?;
+    if !(200..300).contains(&status) {
+        return Err(Error::Protocol(format!("Webhook returned {}", status_line.trim())));
+    }
+    Ok(())
+}
+
+async fn connect(address: &str) -> Result<TcpStream> {
+    tokio::time::timeout(DELIVERY_TIMEOUT, TcpStream::connect(address))
+        .await
+        .map_err(|_| Error::Timeout)?
+        .map_err(Error::Io)
+}
@@ -0,0 +1,269 @@
+// Alarm Rule Files - Alarm configurations defined in JSON or TOML files
+//
+// Each .json or .toml file in the rules directory lists alarm rules under
+// "alarms" (the AlarmConfig fields). A rule without an `id` gets one derived
+// from its file name and rule name, so its alarms carry over across reloads
+// as long as neither changes.
+//
+// The directory is watched like the blueprints directory, and changed files
+// are reloaded by the timer task. A file that fails to parse or validate is
+// reported and the rules previously loaded from it stay in effect; removing
+// a file removes its rules. Rules from files are not written to the alarm
+// store: the files are their source of truth.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use notify::{Event as NotifyEvent, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::Deserialize;
+use tokio::sync::mpsc;
+use uuid::Uuid;
+
+use super::{AlarmCondition, AlarmConfig};
+use crate::points::TagFilter;
+use crate::types::{Error, Result};
+
+/// Placeholders a message template may use
+const PLACEHOLDERS: [&str; 4] = ["point", "value", "threshold", "units"];
+
+/// Contents of a rule file; rules are read as plain values, so those without
+/// an id can be given one before they are parsed
+#[derive(Debug, Deserialize)]
+struct RuleFile {
+    #[serde(default)]
+    alarms: Vec<serde_json::Map<String, serde_json::Value>>,
+}
+
+/// Whether a path is a rule file (by extension)
+pub(super) fn is_rule_file(path: &Path) -> bool {
+    matches!(
+        path.extension().and_then(|s| s.to_str()),
+        Some("json") | Some("toml")
+    )
+}
+
+/// Rule files in a directory, sorted by name
+pub(super) fn rule_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut paths = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if is_rule_file(&path) {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+    Ok(paths)
+}
+
+/// Read, parse and validate the rules of a file
+pub(super) fn load_rule_file(path: &Path) -> Result<Vec<AlarmConfig>> {
+    let content = std::fs::read_to_string(path)?;
+    let file: RuleFile = match path.extension().and_then(|s| s.to_str()) {
+        Some("toml") => toml::from_str(&content).map_err(|e| Error::Config(e.to_string()))?,
+        _ => serde_json::from_str(&content).map_err(|e| Error::Config(e.to_string()))?,
+    };
+
+    let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or_default();
+    let mut ids = HashSet::new();
+    let mut rules = Vec::new();
+    for mut rule in file.alarms {
+        let name = rule.get("name").and_then(|name| name.as_str()).unwrap_or_default().to_string();
+        let error = |e: &dyn std::fmt::Display| Error::Config(format!("Rule '{}': {}", name, e));
+        if rule.get("id").is_none_or(serde_json::Value::is_null) {
+            let key = format!("alarm-rule/{}/{}", stem, name);
+            let id = Uuid::new_v5(&Uuid::NAMESPACE_URL, key.as_bytes());
+            rule.insert("id".to_string(), serde_json::Value::String(id.to_string()));
+        }
+        let rule: AlarmConfig =
+            serde_json::from_value(serde_json::Value::Object(rule)).map_err(|e| error(&e))?;
+        validate(&rule).map_err(|e| error(&e))?;
+        if !ids.insert(rule.id) {
+            return Err(Error::Config(format!(
+                "Rule '{}': duplicate id {} (rules need unique names or ids)",
+                rule.name, rule.id
+            )));
+        }
+        rules.push(rule);
+    }
+    Ok(rules)
+}
+
+/// Check a rule against its condition and the point model
+pub(super) fn validate(rule: &AlarmConfig) -> Result<()> {
+    if rule.name.trim().is_empty() {
+        return Err(Error::Config("name is empty".to_string()));
+    }
+    if rule.source_pattern.is_empty() {
+        return Err(Error::Config("source_pattern is empty".to_string()));
+    }
+    if let Some(filter) = &rule.tag_filter {
+        if matches!(rule.condition, AlarmCondition::DeviceOffline) {
+            return Err(Error::Config("tag_filter does not apply to devices".to_string()));
+        }
+        TagFilter::parse(filter)?;
+    }
+
+    // Units apply to conditions comparing numbers, the deadband to those
+    // comparing against a limit
+    let (has_units, has_limit) = match &rule.condition {
+        AlarmCondition::HighLimit { value } | AlarmCondition::LowLimit { value } => {
+            if !value.is_finite() {
+                return Err(Error::Config(format!("limit {} is not a number", value)));
+            }
+            (true, true)
+        }
+        AlarmCondition::OutOfRange { low, high } => {
+            if !(low.is_finite() && high.is_finite() && low < high) {
+                return Err(Error::Config(format!("range {} to {} is not valid", low, high)));
+            }
+            (true, true)
+        }
+        AlarmCondition::Deviation { reference, limit } => {
+            if reference.trim().is_empty() {
+                return Err(Error::Config("reference is empty".to_string()));
+            }
+            if !(limit.is_finite() && *limit >= 0.0) {
+                return Err(Error::Config(format!("deviation limit {} is not valid", limit)));
+            }
+            (true, true)
+        }
+        AlarmCondition::RateOfChange {
+            per_minute,
+            window_secs,
+        } => {
+            if !(per_minute.is_finite() && *per_minute > 0.0) {
+                return Err(Error::Config(format!("rate {} is not valid", per_minute)));
+            }
+            if *window_secs == 0 {
+                return Err(Error::Config("window_secs must be positive".to_string()));
+            }
+            (true, false)
+        }
+        AlarmCondition::Flatline { minutes, .. } if *minutes == 0 => {
+            return Err(Error::Config("minutes must be positive".to_string()));
+        }
+        AlarmCondition::Flatline { tolerance, .. } => {
+            if !(tolerance.is_finite() && *tolerance >= 0.0) {
+                return Err(Error::Config(format!("tolerance {} is not valid", tolerance)));
+            }
+            (false, false)
+        }
+        AlarmCondition::StateIn { states } if states.is_empty() => {
+            return Err(Error::Config("states is empty".to_string()));
+        }
+        AlarmCondition::CommandMismatch { feedback } if feedback.trim().is_empty() => {
+            return Err(Error::Config("feedback is empty".to_string()));
+        }
+        _ => (false, false),
+    };
+    if !has_units && rule.units.is_some() {
+        return Err(Error::Config("units only apply to numeric conditions".to_string()));
+    }
+    if !(rule.deadband.is_finite() && rule.deadband >= 0.0) {
+        return Err(Error::Config(format!("deadband {} is not valid", rule.deadband)));
+    }
+    if !has_limit && rule.deadband > 0.0 {
+        return Err(Error::Config("deadband only applies to limit conditions".to_string()));
+    }
+    // A deadband this wide keeps the condition met once it is met
+    match &rule.condition {
+        AlarmCondition::Deviation { limit, .. } if rule.deadband > 0.0 => {
+            if rule.deadband >= *limit {
+                return Err(Error::Config(format!(
+                    "deadband {} must be less than the deviation limit {}",
+                    rule.deadband, limit
+                )));
+            }
+        }
+        AlarmCondition::OutOfRange { low, high } if rule.deadband > 0.0 => {
+            if rule.deadband * 2.0 >= high - low {
+                return Err(Error::Config(format!(
+                    "deadband {} must be less than half the range {} to {}",
+                    rule.deadband, low, high
+                )));
+            }
+        }
+        _ => {}
+    }
+
+    if let Some(inhibit) = &rule.inhibit {
+        if inhibit.point.trim().is_empty() {
+            return Err(Error::Config("inhibit point is empty".to_string()));
+        }
+        let value_condition = matches!(
+            inhibit.condition,
+            AlarmCondition::HighLimit { .. }
+                | AlarmCondition::LowLimit { .. }
+                | AlarmCondition::OutOfRange { .. }
+                | AlarmCondition::Equals { .. }
+                | AlarmCondition::NotEquals { .. }
+                | AlarmCondition::IsTrue
+                | AlarmCondition::IsFalse
+        );
+        if !value_condition {
+            return Err(Error::Config(
+                "inhibit condition must compare the point's value".to_string(),
+            ));
+        }
+    }
+
+    for placeholder in rule.message_template.split('{').skip(1) {
+        let Some((name, _)) = placeholder.split_once('}') else {
+            continue;
+        };
+        if !PLACEHOLDERS.contains(&name) {
+            return Err(Error::Config(format!(
+                "unknown placeholder {{{}}} in message_template",
+                name
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Watches the rules directory for changed rule files
+pub(super) struct RuleWatcher {
+    _watcher: RecommendedWatcher,
+    changes: mpsc::UnboundedReceiver<PathBuf>,
+}
+
+impl RuleWatcher {
+    /// Start watching a directory
+    pub fn start(dir: &Path) -> Result<Self> {
+        let (tx, changes) = mpsc::unbounded_channel();
+
+        let mut watcher = notify::recommended_watcher(move |res: notify::Result<NotifyEvent>| {
+            if let Ok(event) = res {
+                if matches!(
+                    event.kind,
+                    EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
+                ) {
+                    for path in event.paths.into_iter().filter(|p| is_rule_file(p)) {
+                        let _ = tx.send(path);
+                    }
+                }
+            }
+        })
+        .map_err(|e| Error::Other(format!("Failed to create file watcher: {}", e)))?;
+
+        watcher
+            .watch(dir, RecursiveMode::NonRecursive)
+            .map_err(|e| Error::Other(format!("Failed to watch directory: {}", e)))?;
+
+        Ok(Self {
+            _watcher: watcher,
+            changes,
+        })
+    }
+
+    /// Rule files changed since the last call, each once
+    pub fn changed(&mut self) -> Vec<PathBuf> {
+        let mut paths = Vec::new();
+        while let Ok(path) = self.changes.try_recv() {
+            if !paths.contains(&path) {
+                paths.push(path);
+            }
+        }
+        paths
+    }
+}
@@ -0,0 +1,564 @@
+// Alarm Store - Alarm configurations and alarm records in redb
+//
+// Configurations are rewritten whole whenever they change. Each alarm is
+// stored when it is raised and rewritten on every state change. Records are
+// keyed by trigger time, so history queries page through them newest first
+// without loading them all. Alarms that have not cleared are indexed
+// separately and restored when the service starts.
+//
+// History is kept for a retention period rather than a number of records:
+// purging deletes cleared alarms triggered before the cutoff, and
+// notification attempts made before it. Alarms that have not cleared are kept
+// however old they are.
+//
+// Shelves, suppression windows and notification escalations are rewritten
+// whole whenever they change.
+// Notification attempts are logged by time. Each alarm's timeline is stored
+// with it, and deleted with its record.
+
+use std::ops::Bound;
+use std::path::Path;
+
+use chrono::{DateTime, Utc};
+use redb::backends::InMemoryBackend;
+use redb::{Database, ReadableTable, TableDefinition};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use uuid::Uuid;
+use wildmatch::WildMatch;
+
+use super::AlarmConfig;
+use super::notifications::{Escalation, NotificationRecord};
+use super::suppression::{Shelf, SuppressionWindow};
+use crate::services::messages::{Alarm, AlarmState, AlarmTimelineEntry};
+use crate::types::{Error, Result};
+
+// Configurations: key is the position in the config list, value is the
+// JSON-serialized AlarmConfig
+const CONFIGS_TABLE: TableDefinition<u32, &[u8]> = TableDefinition::new("alarm_configs");
+
+// Alarm records: key is (triggered_at micros, alarm id), value is the
+// JSON-serialized Alarm
+const ALARMS_TABLE: TableDefinition<(i64, u128), &[u8]> = TableDefinition::new("alarms");
+
+// Trigger time (micros) of each stored alarm, keyed by alarm id
+const ALARM_TIMES_TABLE: TableDefinition<u128, i64> = TableDefinition::new("alarm_times");
+
+// Alarms that have not cleared: key is the alarm id, value is its trigger time (micros)
+const ACTIVE_TABLE: TableDefinition<u128, i64> = TableDefinition::new("alarms_active");
+
+// Suppressions and escalations: key is "shelves", "windows" or "escalations",
+// value is the JSON-serialized list
+const SUPPRESSIONS_TABLE: TableDefinition<&str, &[u8]> =
+    TableDefinition::new("alarm_suppressions");
+
+// Notification log: key is (attempted_at micros, record id), value is the
+// JSON-serialized NotificationRecord
+const NOTIFICATIONS_TABLE: TableDefinition<(i64, u128), &[u8]> =
+    TableDefinition::new("alarm_notifications");
+
+// Alarm timelines: key is the alarm id, value is the JSON-serialized list of
+// AlarmTimelineEntry, oldest first
+const TIMELINES_TABLE: TableDefinition<u128, &[u8]> = TableDefinition::new("alarm_timelines");
+
+/// A page of alarm history, newest first
+#[derive(Debug, Clone)]
+pub struct AlarmHistoryPage {
+    pub alarms: Vec<Alarm>,
+    /// Last alarm of the page, when more alarms match
+    pub next_cursor: Option<Uuid>,
+}
+
+/// Persistent alarm configurations and records
+pub(super) struct AlarmStore {
+    db: Database,
+}
+
+impl AlarmStore {
+    /// Open (or create) a store file
+    pub fn open(path: &Path) -> Result<Self> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(Error::Io)?;
+        }
+        let db = Database::create(path)
+            .map_err(|e| Error::Database(format!("Failed to open database: {}", e)))?;
+        Self::init(db)
+    }
+
+    /// A store kept in memory only
+    pub fn in_memory() -> Result<Self> {
+        let db = Database::builder()
+            .create_with_backend(InMemoryBackend::new())
+            .map_err(|e| Error::Database(e.to_string()))?;
+        Self::init(db)
+    }
+
+    fn init(db: Database) -> Result<Self> {
+        let write_txn = db.begin_write().map_err(|e| Error::Database(e.to_string()))?;
+        write_txn
+            .open_table(CONFIGS_TABLE)
+            .map_err(|e| Error::Database(e.to_string()))?;
+        write_txn
+            .open_table(ALARMS_TABLE)
+            .map_err(|e| Error::Database(e.to_string()))?;
+        write_txn
+            .open_table(ALARM_TIMES_TABLE)
+            .map_err(|e| Error::Database(e.to_string()))?;
+        write_txn
+            .open_table(ACTIVE_TABLE)
+            .map_err(|e| Error::Database(e.to_string()))?;
+        write_txn
+            .open_table(SUPPRESSIONS_TABLE)
+            .map_err(|e| Error::Database(e.to_string()))?;
+        write_txn
+            .open_table(NOTIFICATIONS_TABLE)
+            .map_err(|e| Error::Database(e.to_string()))?;
+        write_txn
+            .open_table(TIMELINES_TABLE)
+            .map_err(|e| Error::Database(e.to_string()))?;
+        write_txn
+            .commit()
+            .map_err(|e| Error::Database(e.to_string()))?;
+
+        Ok(Self { db })
+    }
+
+    /// Stored alarm configurations, in order
+    pub fn configs(&self) -> Result<Vec<AlarmConfig>> {
+        let read_txn = self.db.begin_read().map_err(|e| Error::Database(e.to_string()))?;
+        let table = read_txn
+            .open_table(CONFIGS_TABLE)
+            .map_err(|e| Error::Database(e.to_string()))?;
+
+        let mut configs = Vec::new();
+        for entry in table.iter().map_err(|e| Error::Database(e.to_string()))? {
+            let (_, value) = entry.map_err(|e| Error::Database(e.to_string()))?;
+            match serde_json::from_slice(value.value()) {
+                Ok(config) => configs.push(config),
+                Err(e) => tracing::warn!("Skipping unreadable alarm configuration: {}", e),
+            }
+        }
+        Ok(configs)
+    }
+
+    /// Replace the stored alarm configurations
+    pub fn save_configs(&self, configs: &[AlarmConfig]) -> Result<()> {
+        let write_txn = self.db.begin_write().map_err(|e| Error::Database(e.to_string()))?;
+        {
+            write_txn
+                .delete_table(CONFIGS_TABLE)
+                .map_err(|e| Error::Database(e.to_string()))?;
+            let mut table = write_txn
+                .open_table(CONFIGS_TABLE)
+                .map_err(|e| Error::Database(e.to_string()))?;
+            for (position, config) in configs.iter().enumerate() {
+                let bytes =
+                    serde_json::to_vec(config).map_err(|e| Error::Service(e.to_string()))?;
+                table
+                    .insert(position as u32, bytes.as_slice())
+                    .map_err(|e| Error::Database(e.to_string()))?;
+            }
+        }
+        write_txn
+            .commit()
+            .map_err(|e| Error::Database(e.to_string()))?;
+        Ok(())
+    }
+
+    /// Stored shelves
+    pub fn shelves(&self) -> Result<Vec<Shelf>> {
+        self.suppressions("shelves")
+    }
+
+    /// Replace the stored shelves
+    pub fn save_shelves(&self, shelves: &[Shelf]) -> Result<()> {
+        self.save_suppressions("shelves", shelves)
+    }
+
+    /// Stored suppression windows
+    pub fn windows(&self) -> Result<Vec<SuppressionWindow>> {
+        self.suppressions("windows")
+    }
+
+    /// Replace the stored suppression windows
+    pub fn save_windows(&self, windows: &[SuppressionWindow]) -> Result<()> {
+        self.save_suppressions("windows", windows)
+    }
+
+    /// Stored notification escalations
+    pub fn escalations(&self) -> Result<Vec<Escalation>> {
+        self.suppressions("escalations")
+    }
+
+    /// Replace the stored notification escalations
+    pub fn save_escalations(&self, escalations: &[Escalation]) -> Result<()> {
+        self.save_suppressions("escalations", escalations)
+    }
+
+    fn suppressions<T: DeserializeOwned>(&self, key: &str) -> Result<Vec<T>> {
+        let read_txn = self.db.begin_read().map_err(|e| Error::Database(e.to_string()))?;
+        let table = read_txn
+            .open_table(SUPPRESSIONS_TABLE)
+            .map_err(|e| Error::Database(e.to_string()))?;
+
+        let Some(value) = table.get(key).map_err(|e| Error::Database(e.to_string()))? else {
+            return Ok(Vec::new());
+        };
+        match serde_json::from_slice(value.value()) {
+            Ok(items) => Ok(items),
+            Err(e) => {
+                tracing::warn!("Skipping unreadable alarm {}: {}", key, e);
+                Ok(Vec::new())
+            }
+        }
+    }
+
+    fn save_suppressions<T: Serialize>(&self, key: &str, items: &[T]) -> Result<()> {
+        let bytes = serde_json::to_vec(items).map_err(|e| Error::Service(e.to_string()))?;
+        let write_txn = self.db.begin_write().map_err(|e| Error::Database(e.to_string()))?;
+        {
+            let mut table = write_txn
+                .open_table(SUPPRESSIONS_TABLE)
+                .map_err(|e| Error::Database(e.to_string()))?;
+            table
+                .insert(key, bytes.as_slice())
+                .map_err(|e| Error::Database(e.to_string()))?;
+        }
+        write_txn
+            .commit()
+            .map_err(|e| Error::Database(e.to_string()))?;
+        Ok(())
+    }
+
+    /// Stored alarms that have not cleared
+    pub fn active_alarms(&self) -> Result<Vec<Alarm>> {
+        let read_txn = self.db.begin_read().map_err(|e| Error::Database(e.to_string()))?;
+        let active = read_txn
+            .open_table(ACTIVE_TABLE)
+            .map_err(|e| Error::Database(e.to_string()))?;
+        let alarms = read_txn
+            .open_table(ALARMS_TABLE)
+            .map_err(|e| Error::Database(e.to_string()))?;
+
+        let mut restored = Vec::new();
+        for entry in active.iter().map_err(|e| Error::Database(e.to_string()))? {
+            let (id, ts) = entry.map_err(|e| Error::Database(e.to_string()))?;
+            let key = (ts.value(), id.value());
+            let Some(value) = alarms.get(key).map_err(|e| Error::Database(e.to_string()))? else {
+                continue;
+            };
+            match serde_json::from_slice(value.value()) {
+                Ok(alarm) => restored.push(alarm),
+                Err(e) => tracing::warn!("Skipping unreadable alarm record: {}", e),
+            }
+        }
+        Ok(restored)
+    }
+
+    /// A stored alarm by id
+    pub fn alarm(&self, id: Uuid) -> Result<Option<Alarm>> {
+        let read_txn = self.db.begin_read().map_err(|e| Error::Database(e.to_string()))?;
+        let times = read_txn
+            .open_table(ALARM_TIMES_TABLE)
+            .map_err(|e| Error::Database(e.to_string()))?;
+        let alarms = read_txn
+            .open_table(ALARMS_TABLE)
+            .map_err(|e| Error::Database(e.to_string()))?;
+
+        let Some(ts) = times.get(id.as_u128()).map_err(|e| Error::Database(e.to_string()))? else {
+            return Ok(None);
+        };
+        let key = (ts.value(), id.as_u128());
+        let Some(value) = alarms.get(key).map_err(|e| Error::Database(e.to_string()))? else {
+            return Ok(None);
+        };
+        serde_json::from_slice(value.value())
+            .map(Some)
+            .map_err(|e| Error::Database(e.to_string()))
+    }
+
+    /// Store an alarm (new or updated)
+    pub fn save_alarm(&self, alarm: &Alarm) -> Result<()> {
+        let ts = alarm.triggered_at.timestamp_micros();
+        let id = alarm.id.as_u128();
+        let bytes = serde_json::to_vec(alarm).map_err(|e| Error::Service(e.to_string()))?;
+
+        let write_txn = self.db.begin_write().map_err(|e| Error::Database(e.to_string()))?;
+        {
+            let mut alarms = write_txn
+                .open_table(ALARMS_TABLE)
+                .map_err(|e| Error::Database(e.to_string()))?;
+            let mut times = write_txn
+                .open_table(ALARM_TIMES_TABLE)
+                .map_err(|e| Error::Database(e.to_string()))?;
+            let mut active = write_txn
+                .open_table(ACTIVE_TABLE)
+                .map_err(|e| Error::Database(e.to_string()))?;
+
+            alarms
+                .insert((ts, id), bytes.as_slice())
+                .map_err(|e| Error::Database(e.to_string()))?;
+            times
+                .insert(id, ts)
+                .map_err(|e| Error::Database(e.to_string()))?;
+            if alarm.state == AlarmState::Cleared {
+                active.remove(id).map_err(|e| Error::Database(e.to_string()))?;
+            } else {
+                active
+                    .insert(id, ts)
+                    .map_err(|e| Error::Database(e.to_string()))?;
+            }
+        }
+        write_txn
+            .commit()
+            .map_err(|e| Error::Database(e.to_string()))?;
+        Ok(())
+    }
+
+    /// Timeline of an alarm, oldest first
+    pub fn timeline(&self, alarm_id: Uuid) -> Result<Vec<AlarmTimelineEntry>> {
+        let read_txn = self.db.begin_read().map_err(|e| Error::Database(e.to_string()))?;
+        let table = read_txn
+            .open_table(TIMELINES_TABLE)
+            .map_err(|e| Error::Database(e.to_string()))?;
+        match table
+            .get(alarm_id.as_u128())
+            .map_err(|e| Error::Database(e.to_string()))?
+        {
+            Some(value) => serde_json::from_slice(value.value())
+                .map_err(|e| Error::Database(e.to_string())),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Append an entry to an alarm's timeline
+    pub fn append_timeline(&self, alarm_id: Uuid, entry: &AlarmTimelineEntry) -> Result<()> {
+        let write_txn = self.db.begin_write().map_err(|e| Error::Database(e.to_string()))?;
+        {
+            let mut table = write_txn
+                .open_table(TIMELINES_TABLE)
+                .map_err(|e| Error::Database(e.to_string()))?;
+            let mut entries: Vec<AlarmTimelineEntry> = match table
+                .get(alarm_id.as_u128())
+                .map_err(|e| Error::Database(e.to_string()))?
+            {
+                Some(value) => serde_json::from_slice(value.value()).unwrap_or_default(),
+                None => Vec::new(),
+            };
+            entries.push(entry.clone());
+            let bytes = serde_json::to_vec(&entries).map_err(|e| Error::Service(e.to_string()))?;
+            table
+                .insert(alarm_id.as_u128(), bytes.as_slice())
+                .map_err(|e| Error::Database(e.to_string()))?;
+        }
+        write_txn
+            .commit()
+            .map_err(|e| Error::Database(e.to_string()))?;
+        Ok(())
+    }
+
+    /// Log a notification attempt
+    pub fn save_notification(&self, record: &NotificationRecord) -> Result<()> {
+        let key = (record.attempted_at.timestamp_micros(), record.id.as_u128());
+        let bytes = serde_json::to_vec(record).map_err(|e| Error::Service(e.to_string()))?;
+
+        let write_txn = self.db.begin_write().map_err(|e| Error::Database(e.to_string()))?;
+        {
+            let mut table = write_txn
+                .open_table(NOTIFICATIONS_TABLE)
+                .map_err(|e| Error::Database(e.to_string()))?;
+            table
+                .insert(key, bytes.as_slice())
+                .map_err(|e| Error::Database(e.to_string()))?;
+        }
+        write_txn
+            .commit()
+            .map_err(|e| Error::Database(e.to_string()))?;
+        Ok(())
+    }
+
+    /// Logged notification attempts, newest first, optionally for one alarm
+    pub fn notifications(
+        &self,
+        alarm_id: Option<Uuid>,
+        limit: usize,
+    ) -> Result<Vec<NotificationRecord>> {
+        let read_txn = self.db.begin_read().map_err(|e| Error::Database(e.to_string()))?;
+        let table = read_txn
+            .open_table(NOTIFICATIONS_TABLE)
+            .map_err(|e| Error::Database(e.to_string()))?;
+
+        let mut records = Vec::new();
+        for entry in table.iter().map_err(|e| Error::Database(e.to_string()))?.rev() {
+            if records.len() == limit {
+                break;
+            }
+            let (_, value) = entry.map_err(|e| Error::Database(e.to_string()))?;
+            let record: NotificationRecord = match serde_json::from_slice(value.value()) {
+                Ok(record) => record,
+                Err(e) => {
+                    tracing::warn!("Skipping unreadable notification record: {}", e);
+                    continue;
+                }
+            };
+            if alarm_id.is_none_or(|id| id == record.alarm_id) {
+                records.push(record);
+            }
+        }
+        Ok(records)
+    }
+
+    /// Alarms triggered within a time range, newest first, optionally filtered
+    /// by source pattern and continuing after the cursor alarm of a previous page
+    pub fn history(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        source_filter: Option<&str>,
+        cursor: Option<Uuid>,
+        limit: usize,
+    ) -> Result<AlarmHistoryPage> {
+        let read_txn = self.db.begin_read().map_err(|e| Error::Database(e.to_string()))?;
+        let alarms = read_txn
+            .open_table(ALARMS_TABLE)
+            .map_err(|e| Error::Database(e.to_string()))?;
+
+        let lower = (start.timestamp_micros(), 0u128);
+        let mut upper = Bound::Included((end.timestamp_micros(), u128::MAX));
+        if let Some(cursor) = cursor {
+            let times = read_txn
+                .open_table(ALARM_TIMES_TABLE)
+                .map_err(|e| Error::Database(e.to_string()))?;
+            let ts = times
+                .get(cursor.as_u128())
+                .map_err(|e| Error::Database(e.to_string()))?
+                .ok_or_else(|| Error::NotFound(format!("Alarm {} not found", cursor)))?
+                .value();
+            if ts <= end.timestamp_micros() {
+                upper = Bound::Excluded((ts, cursor.as_u128()));
+            }
+        }
+
+        let filter = source_filter.map(WildMatch::new);
+        let mut page = Vec::new();
+        let mut more = false;
+        let range = alarms
+            .range((Bound::Included(lower), upper))
+            .map_err(|e| Error::Database(e.to_string()))?;
+        for entry in range.rev() {
+            let (_, value) = entry.map_err(|e| Error::Database(e.to_string()))?;
+            let alarm: Alarm = match serde_json::from_slice(value.value()) {
+                Ok(alarm) => alarm,
+                Err(e) => {
+                    tracing::warn!("Skipping unreadable alarm record: {}", e);
+                    continue;
+                }
+            };
+            if filter.as_ref().is_some_and(|f| !f.matches(&alarm.source)) {
+                continue;
+            }
+            if page.len() == limit {
+                more = true;
+                break;
+            }
+            page.push(alarm);
+        }
+
+        let next_cursor = if more { page.last().map(|a| a.id) } else { None };
+        Ok(AlarmHistoryPage {
+            alarms: page,
+            next_cursor,
+        })
+    }
+
+    /// Visit the alarms triggered within a time range, oldest first
+    pub fn for_each_alarm(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        mut visit: impl FnMut(&Alarm),
+    ) -> Result<()> {
+        let read_txn = self.db.begin_read().map_err(|e| Error::Database(e.to_string()))?;
+        let alarms = read_txn
+            .open_table(ALARMS_TABLE)
+            .map_err(|e| Error::Database(e.to_string()))?;
+
+        let range = alarms
+            .range((start.timestamp_micros(), 0u128)..=(end.timestamp_micros(), u128::MAX))
+            .map_err(|e| Error::Database(e.to_string()))?;
+        for entry in range {
+            let (_, value) = entry.map_err(|e| Error::Database(e.to_string()))?;
+            match serde_json::from_slice::<Alarm>(value.value()) {
+                Ok(alarm) => visit(&alarm),
+                Err(e) => tracing::warn!("Skipping unreadable alarm record: {}", e),
+            }
+        }
+        Ok(())
+    }
+
+    /// Delete cleared alarms triggered before a cutoff, with their timelines,
+    /// and notification attempts made before it; returns the number of alarm
+    /// records deleted
+    pub fn purge(&self, before: DateTime<Utc>) -> Result<usize> {
+        let cutoff = before.timestamp_micros();
+        let write_txn = self.db.begin_write().map_err(|e| Error::Database(e.to_string()))?;
+        let purged = {
+            let mut alarms = write_txn
+                .open_table(ALARMS_TABLE)
+                .map_err(|e| Error::Database(e.to_string()))?;
+            let mut times = write_txn
+                .open_table(ALARM_TIMES_TABLE)
+                .map_err(|e| Error::Database(e.to_string()))?;
+            let active = write_txn
+                .open_table(ACTIVE_TABLE)
+                .map_err(|e| Error::Database(e.to_string()))?;
+            let mut timelines = write_txn
+                .open_table(TIMELINES_TABLE)
+                .map_err(|e| Error::Database(e.to_string()))?;
+            let mut notifications = write_txn
+                .open_table(NOTIFICATIONS_TABLE)
+                .map_err(|e| Error::Database(e.to_string()))?;
+
+            let mut expired = Vec::new();
+            for entry in alarms
+                .range(..(cutoff, 0u128))
+                .map_err(|e| Error::Database(e.to_string()))?
+            {
+                let (key, _) = entry.map_err(|e| Error::Database(e.to_string()))?;
+                let key = key.value();
+                let is_active = active
+                    .get(key.1)
+                    .map_err(|e| Error::Database(e.to_string()))?
+                    .is_some();
+                if !is_active {
+                    expired.push(key);
+                }
+            }
+            for key in &expired {
+                alarms.remove(*key).map_err(|e| Error::Database(e.to_string()))?;
+                times.remove(key.1).map_err(|e| Error::Database(e.to_string()))?;
+                timelines.remove(key.1).map_err(|e| Error::Database(e.to_string()))?;
+            }
+
+            let mut logged = Vec::new();
+            for entry in notifications
+                .range(..(cutoff, 0u128))
+                .map_err(|e| Error::Database(e.to_string()))?
+            {
+                let (key, _) = entry.map_err(|e| Error::Database(e.to_string()))?;
+                logged.push(key.value());
+            }
+            for key in logged {
+                notifications
+                    .remove(key)
+                    .map_err(|e| Error::Database(e.to_string()))?;
+            }
+
+            expired.len()
+        };
+        write_txn
+            .commit()
+            .map_err(|e| Error::Database(e.to_string()))?;
+        Ok(purged)
+    }
+}
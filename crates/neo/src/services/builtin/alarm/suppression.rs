@@ -0,0 +1,110 @@
+// Alarm Suppression - Alarms kept from operators while they are expected
+//
+//   Shelving   an operator silences an alarm, or every alarm of a config, for
+//              a duration with a reason; it unshelves when the duration ends
+//              (and a shelved alarm's shelf goes when the alarm clears)
+//   Windows    scheduled periods (e.g., maintenance) during which alarms from
+//              matching sources are suppressed
+//   Inhibits   a config's alarms are suppressed while another point's value
+//              meets a condition (e.g., the AHU fan is off)
+//   Devices    alarms on the points of a device that has stopped
+//              communicating are suppressed until it is back online
+//
+// Suppressed alarms are still raised, recorded and published on the event
+// bus. They are left out of the active alarm list and listed, with the
+// reason, by GetSuppressedAlarms instead. Shelves and windows are persisted
+// with the alarm store; expired ones are removed by the timer task.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use wildmatch::WildMatch;
+
+use super::AlarmCondition;
+use crate::services::messages::Alarm;
+
+/// What a shelf applies to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(tag = "type", content = "id")]
+pub enum ShelveTarget {
+    /// One alarm
+    Alarm(Uuid),
+    /// Every alarm of an alarm configuration
+    Config(Uuid),
+}
+
+/// An operator's shelving of an alarm or alarm configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Shelf {
+    pub target: ShelveTarget,
+    pub reason: String,
+    #[serde(default)]
+    pub shelved_by: Option<String>,
+    pub shelved_at: DateTime<Utc>,
+    /// When the shelf ends
+    pub until: DateTime<Utc>,
+}
+
+/// Scheduled period during which alarms from matching sources are suppressed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SuppressionWindow {
+    /// Unique identifier (assigned when added without one)
+    #[serde(default)]
+    pub id: Uuid,
+    pub name: String,
+    /// Glob pattern of the alarm sources suppressed (e.g., "net1/AHU-1/*")
+    #[serde(default = "super::default_source_pattern")]
+    pub source_pattern: String,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+impl SuppressionWindow {
+    /// Whether the window is in effect at the given time
+    pub fn is_active(&self, now: DateTime<Utc>) -> bool {
+        self.start <= now && now < self.end
+    }
+
+    /// Whether the window suppresses alarms from a source at the given time
+    pub fn suppresses(&self, source: &str, now: DateTime<Utc>) -> bool {
+        self.is_active(now) && WildMatch::new(&self.source_pattern).matches(source)
+    }
+}
+
+/// Suppresses a configuration's alarms while another point meets a condition
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Inhibit {
+    /// Inhibiting point; may use {parent}, the alarm point's path up to its
+    /// last '/' (e.g., "{parent}/fan-status")
+    pub point: String,
+    /// Condition on the inhibiting point's value (a limit, equality or
+    /// boolean condition, e.g., IsFalse)
+    pub condition: AlarmCondition,
+}
+
+/// Why an alarm is suppressed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum Suppression {
+    /// Shelved by an operator
+    Shelved { shelf: Shelf },
+    /// Within a suppression window
+    Window {
+        window_id: Uuid,
+        name: String,
+        until: DateTime<Utc>,
+    },
+    /// Inhibited by another point's value
+    Inhibited { point: String },
+    /// The point's device is not communicating
+    DeviceOffline { device: String },
+}
+
+/// A suppressed alarm and why it is suppressed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SuppressedAlarm {
+    pub alarm: Alarm,
+    pub suppression: Suppression,
+}
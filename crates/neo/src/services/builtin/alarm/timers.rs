@@ -0,0 +1,121 @@
+// Alarm Timers - When a met condition becomes an alarm, and when it clears
+//
+// Each alarm config tracks its condition per point. A condition that becomes
+// met raises the alarm once it has stayed met for `delay_seconds` (on-delay);
+// one that stops being met clears the alarm once it has stayed unmet for
+// `off_delay_seconds` (off-delay). On top of that, an alarm stays raised for
+// at least `min_on_seconds`, and is not raised again until `min_off_seconds`
+// after it cleared.
+//
+// Transitions that are not due when a value arrives are made by the timer
+// task, which checks pending conditions every second. Timers read the alarm
+// clock, which tests replace with a manual one.
+
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Duration, Utc};
+
+use super::AlarmConfig;
+use crate::types::PropertyValue;
+
+/// Time source for alarm timers
+#[derive(Debug, Clone, Default)]
+pub enum AlarmClock {
+    /// The system clock
+    #[default]
+    System,
+    /// A clock that only moves when advanced (for tests)
+    Manual(Arc<Mutex<DateTime<Utc>>>),
+}
+
+impl AlarmClock {
+    /// A manual clock starting at the given time
+    pub fn manual(start: DateTime<Utc>) -> Self {
+        Self::Manual(Arc::new(Mutex::new(start)))
+    }
+
+    /// Current time
+    pub fn now(&self) -> DateTime<Utc> {
+        match self {
+            Self::System => Utc::now(),
+            Self::Manual(now) => *now.lock().unwrap_or_else(|e| e.into_inner()),
+        }
+    }
+
+    /// Move a manual clock forward (no effect on the system clock)
+    pub fn advance(&self, by: Duration) {
+        if let Self::Manual(now) = self {
+            *now.lock().unwrap_or_else(|e| e.into_inner()) += by;
+        }
+    }
+}
+
+/// Condition of one alarm config on one point
+#[derive(Debug, Clone)]
+pub(super) struct ConditionState {
+    /// Whether the condition is met (with the deadband applied while met)
+    pub met: bool,
+    /// When `met` last changed
+    pub since: DateTime<Utc>,
+    /// Value of the last evaluation (None for conditions without a value)
+    pub value: Option<PropertyValue>,
+    /// When the alarm for this config and point was last raised
+    pub raised_at: Option<DateTime<Utc>>,
+    /// When the alarm for this config and point was last cleared
+    pub cleared_at: Option<DateTime<Utc>>,
+}
+
+impl ConditionState {
+    pub fn new(now: DateTime<Utc>) -> Self {
+        Self {
+            met: false,
+            since: now,
+            value: None,
+            raised_at: None,
+            cleared_at: None,
+        }
+    }
+
+    /// State of a condition whose alarm was raised at the given time (for
+    /// alarms restored from the store)
+    pub fn raised(at: DateTime<Utc>, value: Option<PropertyValue>) -> Self {
+        Self {
+            met: true,
+            since: at,
+            value,
+            raised_at: Some(at),
+            cleared_at: None,
+        }
+    }
+
+    /// Record an evaluation of the condition
+    pub fn update(&mut self, met: bool, value: Option<&PropertyValue>, now: DateTime<Utc>) {
+        if met != self.met {
+            self.met = met;
+            self.since = now;
+        }
+        self.value = value.cloned();
+    }
+
+    /// Whether a met condition has waited out the on-delay and minimum off time
+    pub fn raise_due(&self, config: &AlarmConfig, now: DateTime<Utc>) -> bool {
+        self.met
+            && now >= self.since + seconds(config.delay_seconds)
+            && self
+                .cleared_at
+                .is_none_or(|cleared| now >= cleared + seconds(config.min_off_seconds))
+    }
+
+    /// Whether an unmet condition has waited out the off-delay and minimum on time
+    pub fn clear_due(&self, config: &AlarmConfig, now: DateTime<Utc>) -> bool {
+        !self.met
+            && now >= self.since + seconds(config.off_delay_seconds)
+            && self
+                .raised_at
+                .is_none_or(|raised| now >= raised + seconds(config.min_on_seconds))
+    }
+}
+
+fn seconds(secs: u32) -> Duration {
+    Duration::seconds(secs as i64)
+}
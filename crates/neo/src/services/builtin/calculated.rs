@@ -0,0 +1,646 @@
+// Calculated Point Service - Points defined by expressions over other points
+//
+// Actor-based service that re-evaluates expressions whenever one of their
+// inputs publishes PointValueChanged, and publishes the result as an ordinary
+// point under "calc/". Calculated points may reference each other; circular
+// dependencies are rejected when a point is added.
+
+use std::collections::{HashMap, HashSet};
+use std::time::Instant;
+
+use chrono::{DateTime, Utc};
+use kameo::actor::ActorRef;
+use kameo::message::{Context, Message};
+use kameo_actors::pubsub::Publish;
+use serde::{Deserialize, Serialize};
+use tokio::sync::oneshot;
+use wildmatch::WildMatch;
+
+use crate::actors::PubSubBroker;
+use crate::messages::Event;
+use crate::points::EngineeringUnit;
+use crate::services::actor::{ServiceMsg, ServiceReply, ServiceStateTracker};
+use crate::services::messages::{PointSnapshot, ServiceRequest, ServiceResponse};
+use crate::types::{Error, PointQuality, PropertyValue, Result, ServiceState};
+
+use super::expression::{EvalContext, EvalError, ExprValue, Expression, InputValue};
+
+/// Prefix used for all calculated point paths
+pub const CALCULATED_POINT_PREFIX: &str = "calc/";
+
+/// Definition of a calculated point
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CalculatedPointConfig {
+    /// Output point path (must start with "calc/", e.g., "calc/AHU-1/delta_t")
+    pub path: String,
+    /// Expression over other point paths (e.g., "{net/AHU-1/AI:2} - {net/AHU-1/AI:1}")
+    pub expression: String,
+    /// Engineering units of the result (e.g., "degrees-fahrenheit" or "°F")
+    #[serde(default)]
+    pub units: Option<EngineeringUnit>,
+    /// Human-readable description
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+/// Configuration for the Calculated Point Service
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CalculatedPointsConfig {
+    /// Points created when the service starts
+    #[serde(default)]
+    pub points: Vec<CalculatedPointConfig>,
+}
+
+/// Runtime state of a calculated point
+#[derive(Debug, Clone)]
+struct CalculatedPoint {
+    config: CalculatedPointConfig,
+    expression: Expression,
+    value: PropertyValue,
+    quality: PointQuality,
+    last_update_utc: DateTime<Utc>,
+    /// Last evaluation error (cleared on success)
+    last_error: Option<String>,
+}
+
+impl CalculatedPoint {
+    fn snapshot(&self) -> PointSnapshot {
+        PointSnapshot {
+            path: self.config.path.clone(),
+            value: self.value.clone(),
+            quality: self.quality,
+            timestamp: self.last_update_utc,
+            units: self.config.units,
+        }
+    }
+}
+
+/// Latest known values of every point referenced by an expression
+#[derive(Debug, Default)]
+struct InputCache {
+    values: HashMap<String, InputValue>,
+}
+
+impl EvalContext for InputCache {
+    fn value(&self, path: &str) -> Option<InputValue> {
+        self.values.get(path).copied()
+    }
+
+    fn matching(&self, pattern: &str) -> Vec<InputValue> {
+        let pattern = WildMatch::new(pattern);
+        self.values
+            .iter()
+            .filter(|(path, _)| pattern.matches(path))
+            .map(|(_, v)| *v)
+            .collect()
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Calculated Point Actor Messages
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Calculated point messages (beyond common ServiceMsg)
+#[derive(Debug)]
+pub enum CalculatedPointMsg {
+    /// Add a calculated point (rejected if invalid or circular)
+    AddPoint {
+        config: CalculatedPointConfig,
+        reply: oneshot::Sender<Result<()>>,
+    },
+    /// Remove a calculated point
+    RemovePoint {
+        path: String,
+        reply: oneshot::Sender<bool>,
+    },
+    /// Read the current value of a calculated point
+    ReadPoint {
+        path: String,
+        reply: oneshot::Sender<Option<PointSnapshot>>,
+    },
+    /// List all calculated points
+    ListPoints {
+        reply: oneshot::Sender<Vec<PointSnapshot>>,
+    },
+    /// Get all calculated point definitions
+    GetConfigs {
+        reply: oneshot::Sender<Vec<CalculatedPointConfig>>,
+    },
+}
+
+/// Reply type for CalculatedPointMsg
+#[derive(Debug, kameo::Reply)]
+pub enum CalculatedPointReply {
+    /// Operation completed (result sent via oneshot)
+    Done,
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Calculated Point Actor
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Actor-based Calculated Point Service
+#[derive(kameo::Actor)]
+pub struct CalculatedPointActor {
+    id: String,
+    name: String,
+    #[allow(dead_code)]
+    description: String,
+    state: ServiceStateTracker,
+    config: CalculatedPointsConfig,
+    /// Calculated points keyed by output path
+    points: HashMap<String, CalculatedPoint>,
+    /// Input values seen on the event bus
+    inputs: InputCache,
+    pubsub: Option<ActorRef<PubSubBroker>>,
+}
+
+impl CalculatedPointActor {
+    /// Create a new CalculatedPointActor that publishes results to the given broker
+    pub fn new(config: CalculatedPointsConfig, pubsub: ActorRef<PubSubBroker>) -> Self {
+        Self {
+            pubsub: Some(pubsub),
+            ..Self::detached(config)
+        }
+    }
+
+    /// Create a CalculatedPointActor without an event bus (results are not published)
+    pub fn detached(config: CalculatedPointsConfig) -> Self {
+        Self {
+            id: "calculated_points".to_string(),
+            name: "Calculated Point Service".to_string(),
+            description: "Computes points from expressions over other points".to_string(),
+            state: ServiceStateTracker::new(),
+            config,
+            points: HashMap::new(),
+            inputs: InputCache::default(),
+            pubsub: None,
+        }
+    }
+
+    /// Start the service (internal implementation)
+    fn do_start(&mut self) {
+        self.state.set_starting();
+
+        for config in self.config.points.clone() {
+            if let Err(e) = self.add_point(config.clone()) {
+                tracing::warn!("Skipping calculated point '{}': {}", config.path, e);
+            }
+        }
+
+        self.state.set_running();
+        tracing::info!(
+            "Calculated point service started ({} points)",
+            self.points.len()
+        );
+    }
+
+    /// Stop the service (internal implementation)
+    fn do_stop(&mut self) {
+        self.state.set_stopping();
+        self.points.clear();
+        self.inputs.values.clear();
+        self.state.set_stopped();
+        tracing::info!("Calculated point service stopped");
+    }
+
+    /// Validate and add a calculated point
+    fn add_point(&mut self, config: CalculatedPointConfig) -> Result<()> {
+        if !config.path.starts_with(CALCULATED_POINT_PREFIX)
+            || config.path.len() <= CALCULATED_POINT_PREFIX.len()
+        {
+            return Err(Error::Config(format!(
+                "Calculated point path '{}' must start with '{}'",
+                config.path, CALCULATED_POINT_PREFIX
+            )));
+        }
+        if self.points.contains_key(&config.path) {
+            return Err(Error::Config(format!(
+                "Calculated point '{}' already exists",
+                config.path
+            )));
+        }
+
+        let expression =
+            Expression::parse(&config.expression).map_err(|e| Error::Config(e.to_string()))?;
+
+        if let Some(cycle) = self.find_cycle(&config.path, &expression) {
+            return Err(Error::Config(format!(
+                "Circular dependency: {}",
+                cycle.join(" -> ")
+            )));
+        }
+
+        let path = config.path.clone();
+        self.points.insert(
+            path.clone(),
+            CalculatedPoint {
+                config,
+                expression,
+                value: PropertyValue::Null,
+                quality: PointQuality::Uncertain,
+                last_update_utc: Utc::now(),
+                last_error: None,
+            },
+        );
+
+        tracing::debug!("Calculated point added: {}", path);
+        Ok(())
+    }
+
+    /// Calculated points whose outputs feed the given expression
+    fn upstream<'a>(&'a self, expression: &'a Expression) -> impl Iterator<Item = &'a str> + 'a {
+        let refs = expression.references();
+        self.points
+            .keys()
+            .filter(move |path| refs.iter().any(|r| r.matches(path)))
+            .map(|path| path.as_str())
+    }
+
+    /// Find a dependency cycle that adding `path = expression` would create
+    ///
+    /// Returns the cycle as a list of paths starting and ending at `path`.
+    fn find_cycle(&self, path: &str, expression: &Expression) -> Option<Vec<String>> {
+        if expression.depends_on(path) {
+            return Some(vec![path.to_string(), path.to_string()]);
+        }
+
+        // Depth-first search from each upstream calculated point back to `path`
+        let mut visited = HashSet::new();
+        let mut stack: Vec<Vec<String>> = self
+            .upstream(expression)
+            .map(|p| vec![path.to_string(), p.to_string()])
+            .collect();
+
+        while let Some(chain) = stack.pop() {
+            let current = chain.last().cloned().unwrap_or_default();
+            if !visited.insert(current.clone()) {
+                continue;
+            }
+            let Some(point) = self.points.get(&current) else {
+                continue;
+            };
+            if point.expression.depends_on(path) {
+                let mut cycle = chain.clone();
+                cycle.push(path.to_string());
+                return Some(cycle);
+            }
+            for next in self.upstream(&point.expression) {
+                let mut next_chain = chain.clone();
+                next_chain.push(next.to_string());
+                stack.push(next_chain);
+            }
+        }
+
+        None
+    }
+
+    /// Handle a point change: cache the input and re-evaluate dependents
+    async fn on_point_changed(
+        &mut self,
+        point: &str,
+        value: &PropertyValue,
+        quality: PointQuality,
+        units: Option<EngineeringUnit>,
+    ) {
+        let is_input = self
+            .points
+            .values()
+            .any(|p| p.expression.depends_on(point));
+        if !is_input {
+            return;
+        }
+
+        match ExprValue::from_property(value) {
+            Some(value) => {
+                let input = InputValue {
+                    value,
+                    quality,
+                    units,
+                };
+                self.inputs.values.insert(point.to_string(), input);
+            }
+            None => {
+                self.inputs.values.remove(point);
+            }
+        }
+
+        let dependents: Vec<String> = self
+            .points
+            .values()
+            .filter(|p| p.expression.depends_on(point))
+            .map(|p| p.config.path.clone())
+            .collect();
+
+        for path in dependents {
+            self.evaluate(&path).await;
+        }
+    }
+
+    /// Evaluate a calculated point and publish it if the result changed
+    async fn evaluate(&mut self, path: &str) {
+        let Some(point) = self.points.get_mut(path) else {
+            return;
+        };
+
+        let (value, quality) = match point.expression.evaluate(&self.inputs) {
+            Ok(result) => {
+                point.last_error = None;
+                (result.value.to_property(), result.quality)
+            }
+            // Not all inputs have reported yet - keep waiting
+            Err(EvalError::MissingInput(_)) if point.value == PropertyValue::Null => return,
+            Err(e) => {
+                if point.last_error.as_deref() != Some(e.to_string().as_str()) {
+                    tracing::warn!("Calculated point '{}' failed: {}", path, e);
+                }
+                point.last_error = Some(e.to_string());
+                (point.value.clone(), PointQuality::Bad)
+            }
+        };
+
+        if point.value == value && point.quality == quality {
+            return;
+        }
+
+        point.value = value;
+        point.quality = quality;
+        point.last_update_utc = Utc::now();
+
+        let snapshot = point.snapshot();
+        self.publish(snapshot).await;
+    }
+
+    /// Publish a calculated value to PubSub
+    async fn publish(&self, snapshot: PointSnapshot) {
+        if let Some(pubsub) = &self.pubsub {
+            let event = Event::PointValueChanged {
+                point: snapshot.path,
+                value: snapshot.value,
+                quality: snapshot.quality,
+                units: snapshot.units,
+                timestamp: Instant::now(),
+                timestamp_utc: snapshot.timestamp,
+            };
+            if let Err(e) = pubsub.tell(Publish(event)).await {
+                tracing::warn!("Failed to publish calculated point: {}", e);
+            }
+        }
+    }
+
+    fn list_points(&self) -> Vec<PointSnapshot> {
+        let mut points: Vec<PointSnapshot> = self.points.values().map(|p| p.snapshot()).collect();
+        points.sort_by(|a, b| a.path.cmp(&b.path));
+        points
+    }
+
+    fn status_extra(&self) -> serde_json::Value {
+        let errors: HashMap<&str, &str> = self
+            .points
+            .values()
+            .filter_map(|p| Some((p.config.path.as_str(), p.last_error.as_deref()?)))
+            .collect();
+        serde_json::json!({
+            "point_count": self.points.len(),
+            "input_count": self.inputs.values.len(),
+            "errors": errors,
+        })
+    }
+}
+
+// Handle common ServiceMsg
+impl Message<ServiceMsg> for CalculatedPointActor {
+    type Reply = ServiceReply;
+
+    async fn handle(
+        &mut self,
+        msg: ServiceMsg,
+        _ctx: &mut Context<Self, Self::Reply>,
+    ) -> Self::Reply {
+        match msg {
+            ServiceMsg::Start => {
+                self.do_start();
+                ServiceReply::Started
+            }
+
+            ServiceMsg::Stop => {
+                self.do_stop();
+                ServiceReply::Stopped
+            }
+
+            ServiceMsg::GetStatus => ServiceReply::Status {
+                id: self.id.clone(),
+                name: self.name.clone(),
+                state: self.state.state(),
+                uptime_secs: self.state.uptime_secs(),
+                extra: Some(self.status_extra()),
+            },
+
+            ServiceMsg::GetConfig => ServiceReply::Config {
+                config: serde_json::to_value(&self.config).unwrap_or_default(),
+            },
+
+            ServiceMsg::SetConfig { config } => {
+                if let Ok(new_config) = serde_json::from_value::<CalculatedPointsConfig>(config) {
+                    self.config = new_config;
+                    ServiceReply::ConfigSet
+                } else {
+                    ServiceReply::Failed("Invalid configuration format".to_string())
+                }
+            }
+
+            ServiceMsg::OnEvent { event } => {
+                if self.state.state() != ServiceState::Running {
+                    return ServiceReply::EventHandled;
+                }
+
+                if let Event::PointValueChanged {
+                    point,
+                    value,
+                    quality,
+                    units,
+                    ..
+                } = event
+                {
+                    self.on_point_changed(&point, &value, quality, units).await;
+                }
+                ServiceReply::EventHandled
+            }
+
+            ServiceMsg::HandleRequest { request, reply } => {
+                let response = match request {
+                    ServiceRequest::GetStatus => ServiceResponse::Status {
+                        id: self.id.clone(),
+                        name: self.name.clone(),
+                        state: self.state.state(),
+                        uptime_seconds: self.state.uptime_secs(),
+                        extra: Some(self.status_extra()),
+                    },
+
+                    ServiceRequest::GetConfig => ServiceResponse::Config {
+                        config: serde_json::to_value(&self.config).unwrap_or_default(),
+                    },
+
+                    ServiceRequest::ReadPoint { path } => match self.points.get(&path) {
+                        Some(point) => ServiceResponse::PointValue {
+                            point: point.snapshot(),
+                        },
+                        None => ServiceResponse::Error {
+                            code: "NOT_FOUND".to_string(),
+                            message: format!("Calculated point '{}' not found", path),
+                        },
+                    },
+
+                    ServiceRequest::ListPoints { pattern } => {
+                        let points = self
+                            .list_points()
+                            .into_iter()
+                            .filter(|p| {
+                                pattern
+                                    .as_ref()
+                                    .map(|f| WildMatch::new(f).matches(&p.path))
+                                    .unwrap_or(true)
+                            })
+                            .collect();
+                        ServiceResponse::Points { points }
+                    }
+
+                    _ => ServiceResponse::Error {
+                        code: "UNSUPPORTED".to_string(),
+                        message: "Request not supported by Calculated Point service".to_string(),
+                    },
+                };
+
+                let _ = reply.send(response);
+                ServiceReply::RequestHandled
+            }
+        }
+    }
+}
+
+// Handle calculated point messages
+impl Message<CalculatedPointMsg> for CalculatedPointActor {
+    type Reply = CalculatedPointReply;
+
+    async fn handle(
+        &mut self,
+        msg: CalculatedPointMsg,
+        _ctx: &mut Context<Self, Self::Reply>,
+    ) -> Self::Reply {
+        match msg {
+            CalculatedPointMsg::AddPoint { config, reply } => {
+                let path = config.path.clone();
+                let result = self.add_point(config);
+                if result.is_ok() {
+                    // Evaluate immediately in case all inputs are already known
+                    self.evaluate(&path).await;
+                }
+                let _ = reply.send(result);
+            }
+
+            CalculatedPointMsg::RemovePoint { path, reply } => {
+                let _ = reply.send(self.points.remove(&path).is_some());
+            }
+
+            CalculatedPointMsg::ReadPoint { path, reply } => {
+                let _ = reply.send(self.points.get(&path).map(|p| p.snapshot()));
+            }
+
+            CalculatedPointMsg::ListPoints { reply } => {
+                let _ = reply.send(self.list_points());
+            }
+
+            CalculatedPointMsg::GetConfigs { reply } => {
+                let configs = self.points.values().map(|p| p.config.clone()).collect();
+                let _ = reply.send(configs);
+            }
+        }
+        CalculatedPointReply::Done
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Tests
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kameo::actor::Spawn;
+
+    fn calc(path: &str, expression: &str) -> CalculatedPointConfig {
+        CalculatedPointConfig {
+            path: path.to_string(),
+            expression: expression.to_string(),
+            units: None,
+            description: None,
+        }
+    }
+
+    async fn send_value(
+        actor: &ActorRef<CalculatedPointActor>,
+        point: &str,
+        value: f32,
+        quality: PointQuality,
+    ) {
+        let event = Event::PointValueChanged {
+            point: point.to_string(),
+            value: PropertyValue::Real(value),
+            quality,
+            units: None,
+            timestamp: Instant::now(),
+            timestamp_utc: Utc::now(),
+        };
+        let _ = actor.ask(ServiceMsg::OnEvent { event }).await;
+    }
+
+    #[tokio::test]
+    async fn test_calculated_point_reevaluates_on_input_change() {
+        let config = CalculatedPointsConfig {
+            points: vec![calc("calc/AHU-1/delta_t", "{net/AHU-1/AI:2} - {net/AHU-1/AI:1}")],
+        };
+        let actor = CalculatedPointActor::spawn(CalculatedPointActor::detached(config));
+        let _ = actor.ask(ServiceMsg::Start).await.unwrap();
+
+        send_value(&actor, "net/AHU-1/AI:1", 55.0, PointQuality::Good).await;
+        send_value(&actor, "net/AHU-1/AI:2", 72.0, PointQuality::Good).await;
+
+        let (reply_tx, reply_rx) = oneshot::channel();
+        let _ = actor
+            .ask(CalculatedPointMsg::ReadPoint {
+                path: "calc/AHU-1/delta_t".to_string(),
+                reply: reply_tx,
+            })
+            .await;
+        let point = reply_rx.await.unwrap().unwrap();
+        assert_eq!(point.value, PropertyValue::Real(17.0));
+        assert_eq!(point.quality, PointQuality::Good);
+
+        // Input quality carries through to the result
+        send_value(&actor, "net/AHU-1/AI:1", 54.0, PointQuality::Stale).await;
+        let (reply_tx, reply_rx) = oneshot::channel();
+        let _ = actor
+            .ask(CalculatedPointMsg::ReadPoint {
+                path: "calc/AHU-1/delta_t".to_string(),
+                reply: reply_tx,
+            })
+            .await;
+        let point = reply_rx.await.unwrap().unwrap();
+        assert_eq!(point.value, PropertyValue::Real(18.0));
+        assert_eq!(point.quality, PointQuality::Stale);
+    }
+
+    #[test]
+    fn test_circular_dependencies_rejected() {
+        let mut actor = CalculatedPointActor::detached(CalculatedPointsConfig::default());
+
+        assert!(actor.add_point(calc("calc/a", "{calc/a} + 1")).is_err());
+
+        actor.add_point(calc("calc/a", "{calc/b} + 1")).unwrap();
+        actor.add_point(calc("calc/b", "{calc/c} * 2")).unwrap();
+        let err = actor.add_point(calc("calc/c", "avg({calc/*})")).unwrap_err();
+        assert!(err.to_string().contains("Circular dependency"));
+
+        actor.add_point(calc("calc/c", "{net/dev/AI:1}")).unwrap();
+    }
+}
@@ -0,0 +1,938 @@
+// Point Expressions - Small expression language over point paths
+//
+// Used by calculated points. Point references are written in braces and may
+// contain wildcards, which expand to a set of points for aggregate functions:
+//
+//   {MainNetwork/AHU-1/AI:1} - {MainNetwork/AHU-1/AI:2}
+//   avg({*/VAV-*/AI:1})
+//   {*/AHU-1/BI:1} && {*/AHU-1/AI:3} > 55 ? 1 : 0
+//   if({virtual/occupied}, 72, 65)
+//
+// A reference may ask for its value in specific units, converted from the
+// units the point publishes (e.g., an AHU reporting °F used in a °C formula):
+//
+//   {MainNetwork/AHU-1/AI:1|°C} - {MainNetwork/AHU-1/AI:2|°C}
+
+use std::fmt;
+
+use wildmatch::WildMatch;
+
+use crate::points::EngineeringUnit;
+use crate::types::{PointQuality, PropertyValue};
+
+/// Value produced while evaluating an expression
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExprValue {
+    Number(f64),
+    Bool(bool),
+}
+
+impl ExprValue {
+    /// Convert a point value, or None for values expressions can't use (Null, strings, ...)
+    pub fn from_property(value: &PropertyValue) -> Option<Self> {
+        match value {
+            PropertyValue::Real(v) => Some(ExprValue::Number(*v as f64)),
+            PropertyValue::Unsigned(v) => Some(ExprValue::Number(*v as f64)),
+            PropertyValue::Enumerated(v) => Some(ExprValue::Number(*v as f64)),
+            PropertyValue::Boolean(v) => Some(ExprValue::Bool(*v)),
+            _ => None,
+        }
+    }
+
+    /// Convert to a point value for publishing
+    pub fn to_property(self) -> PropertyValue {
+        match self {
+            ExprValue::Number(v) => PropertyValue::Real(v as f32),
+            ExprValue::Bool(v) => PropertyValue::Boolean(v),
+        }
+    }
+
+    fn as_number(self) -> Result<f64, EvalError> {
+        match self {
+            ExprValue::Number(v) => Ok(v),
+            ExprValue::Bool(v) => Ok(if v { 1.0 } else { 0.0 }),
+        }
+    }
+
+    fn as_bool(self) -> bool {
+        match self {
+            ExprValue::Number(v) => v != 0.0,
+            ExprValue::Bool(v) => v,
+        }
+    }
+}
+
+/// Errors raised while parsing an expression
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+#[error("Expression parse error at {position}: {message}")]
+pub struct ParseError {
+    pub position: usize,
+    pub message: String,
+}
+
+/// Errors raised while evaluating an expression
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum EvalError {
+    /// A referenced point has no usable value yet
+    #[error("Missing input: {0}")]
+    MissingInput(String),
+    /// An aggregate was applied to an empty set of points
+    #[error("No points match '{0}'")]
+    EmptySet(String),
+    /// Arithmetic error (division by zero, NaN)
+    #[error("Math error: {0}")]
+    Math(String),
+    /// An input can't be converted to the requested units
+    #[error("Unit error: {0}")]
+    Units(String),
+}
+
+/// A point reference inside an expression
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PointRef {
+    pub path: String,
+    /// Units the value should be converted to before use
+    pub units: Option<EngineeringUnit>,
+}
+
+impl PointRef {
+    /// Whether this reference contains wildcards and expands to a set
+    pub fn is_wildcard(&self) -> bool {
+        self.path.contains('*') || self.path.contains('?')
+    }
+
+    /// Whether the given point path is covered by this reference
+    pub fn matches(&self, path: &str) -> bool {
+        if self.is_wildcard() {
+            WildMatch::new(&self.path).matches(path)
+        } else {
+            self.path == path
+        }
+    }
+}
+
+/// Latest value of an input point
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct InputValue {
+    pub value: ExprValue,
+    pub quality: PointQuality,
+    /// Units the point publishes, if known
+    pub units: Option<EngineeringUnit>,
+}
+
+impl InputValue {
+    /// The value in the units requested by a reference
+    ///
+    /// Inputs without known units are used as-is.
+    fn in_units(self, point: &PointRef) -> Result<ExprValue, EvalError> {
+        match (self.value, self.units, point.units) {
+            (ExprValue::Number(v), Some(from), Some(to)) => from
+                .convert(v, to)
+                .map(ExprValue::Number)
+                .map_err(|e| EvalError::Units(format!("{{{}}}: {}", point.path, e))),
+            _ => Ok(self.value),
+        }
+    }
+}
+
+/// Source of input values during evaluation
+pub trait EvalContext {
+    /// Latest value of a single point
+    fn value(&self, path: &str) -> Option<InputValue>;
+
+    /// Latest values of all points matching a wildcard pattern
+    fn matching(&self, pattern: &str) -> Vec<InputValue>;
+}
+
+/// Result of a successful evaluation
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Evaluated {
+    pub value: ExprValue,
+    /// Worst quality among the inputs that were read
+    pub quality: PointQuality,
+}
+
+/// Rank qualities so the worst one can be carried through
+fn quality_rank(q: PointQuality) -> u8 {
+    match q {
+        PointQuality::Good => 0,
+        PointQuality::Uncertain => 1,
+        PointQuality::Stale => 2,
+        PointQuality::Bad => 3,
+    }
+}
+
+/// Return the worse of two qualities
+pub fn worst_quality(a: PointQuality, b: PointQuality) -> PointQuality {
+    if quality_rank(b) > quality_rank(a) { b } else { a }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// AST
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+    Pow,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    And,
+    Or,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum UnaryOp {
+    Neg,
+    Not,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    Number(f64),
+    Bool(bool),
+    Point(PointRef),
+    Unary(UnaryOp, Box<Expr>),
+    Binary(BinaryOp, Box<Expr>, Box<Expr>),
+    Conditional(Box<Expr>, Box<Expr>, Box<Expr>),
+    Call(String, Vec<Expr>),
+}
+
+/// Functions that accept wildcard references and any number of arguments
+const AGGREGATE_FUNCTIONS: &[&str] = &["min", "max", "avg", "sum", "count"];
+
+/// Functions with a fixed number of arguments
+const SCALAR_FUNCTIONS: &[(&str, usize)] = &[
+    ("abs", 1),
+    ("round", 1),
+    ("floor", 1),
+    ("ceil", 1),
+    ("sqrt", 1),
+    ("if", 3),
+    ("clamp", 3),
+];
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Tokenizer
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    Ident(String),
+    Point(PointRef),
+    Op(&'static str),
+    LParen,
+    RParen,
+    Comma,
+    Question,
+    Colon,
+}
+
+fn tokenize(source: &str) -> Result<Vec<(usize, Token)>, ParseError> {
+    const OPERATORS: &[&str] = &[
+        "==", "!=", "<=", ">=", "&&", "||", "+", "-", "*", "/", "%", "^", "<", ">", "!",
+    ];
+
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+            continue;
+        }
+
+        let start = i;
+        let token = if c == '{' {
+            let end = chars[i..]
+                .iter()
+                .position(|&c| c == '}')
+                .map(|p| i + p)
+                .ok_or_else(|| ParseError {
+                    position: start,
+                    message: "Unterminated point reference".to_string(),
+                })?;
+            let text: String = chars[i + 1..end].iter().collect();
+            let (path, units) = match text.rsplit_once('|') {
+                Some((path, units)) => {
+                    let units = units.trim().parse::<EngineeringUnit>().map_err(|e| ParseError {
+                        position: start,
+                        message: e.to_string(),
+                    })?;
+                    (path, Some(units))
+                }
+                None => (text.as_str(), None),
+            };
+            let path = path.trim().to_string();
+            if path.is_empty() {
+                return Err(ParseError {
+                    position: start,
+                    message: "Empty point reference".to_string(),
+                });
+            }
+            i = end + 1;
+            Token::Point(PointRef { path, units })
+        } else if c.is_ascii_digit() || c == '.' {
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                i += 1;
+            }
+            let text: String = chars[start..i].iter().collect();
+            let value = text.parse::<f64>().map_err(|_| ParseError {
+                position: start,
+                message: format!("Invalid number '{}'", text),
+            })?;
+            Token::Number(value)
+        } else if c.is_alphabetic() || c == '_' {
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            Token::Ident(chars[start..i].iter().collect())
+        } else {
+            i += 1;
+            match c {
+                '(' => Token::LParen,
+                ')' => Token::RParen,
+                ',' => Token::Comma,
+                '?' => Token::Question,
+                ':' => Token::Colon,
+                _ => {
+                    let two: String = chars[start..(start + 2).min(chars.len())].iter().collect();
+                    if let Some(op) = OPERATORS.iter().copied().find(|op| op.len() == 2 && *op == two) {
+                        i += 1;
+                        Token::Op(op)
+                    } else if let Some(op) = OPERATORS
+                        .iter()
+                        .copied()
+                        .find(|op| op.len() == 1 && op.starts_with(c))
+                    {
+                        Token::Op(op)
+                    } else {
+                        return Err(ParseError {
+                            position: start,
+                            message: format!("Unexpected character '{}'", c),
+                        });
+                    }
+                }
+            }
+        };
+
+        tokens.push((start, token));
+    }
+
+    Ok(tokens)
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Parser (recursive descent)
+// ─────────────────────────────────────────────────────────────────────────────
+
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    pos: usize,
+    end: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(_, t)| t)
+    }
+
+    fn position(&self) -> usize {
+        self.tokens.get(self.pos).map(|(p, _)| *p).unwrap_or(self.end)
+    }
+
+    fn error(&self, message: impl Into<String>) -> ParseError {
+        ParseError {
+            position: self.position(),
+            message: message.into(),
+        }
+    }
+
+    fn advance(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).map(|(_, t)| t.clone());
+        self.pos += 1;
+        token
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<(), ParseError> {
+        if self.peek() == Some(&expected) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(self.error(format!("Expected {:?}", expected)))
+        }
+    }
+
+    /// Consume an operator (or its keyword alias) if it is next
+    fn eat_op(&mut self, ops: &[&'static str]) -> Option<&'static str> {
+        let found = match self.peek() {
+            Some(Token::Op(op)) => ops.iter().copied().find(|o| o == op),
+            Some(Token::Ident(word)) => match word.as_str() {
+                "and" if ops.contains(&"&&") => Some("&&"),
+                "or" if ops.contains(&"||") => Some("||"),
+                "not" if ops.contains(&"!") => Some("!"),
+                _ => None,
+            },
+            _ => None,
+        };
+        if found.is_some() {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn parse_expr(&mut self) -> Result<Expr, ParseError> {
+        let condition = self.parse_or()?;
+        if self.peek() == Some(&Token::Question) {
+            self.pos += 1;
+            let then = self.parse_expr()?;
+            self.expect(Token::Colon)?;
+            let otherwise = self.parse_expr()?;
+            return Ok(Expr::Conditional(
+                Box::new(condition),
+                Box::new(then),
+                Box::new(otherwise),
+            ));
+        }
+        Ok(condition)
+    }
+
+    fn parse_or(&mut self) -> Result<Expr, ParseError> {
+        let mut lhs = self.parse_and()?;
+        while self.eat_op(&["||"]).is_some() {
+            let rhs = self.parse_and()?;
+            lhs = Expr::Binary(BinaryOp::Or, Box::new(lhs), Box::new(rhs));
+        }
+        Ok(lhs)
+    }
+
+    fn parse_and(&mut self) -> Result<Expr, ParseError> {
+        let mut lhs = self.parse_comparison()?;
+        while self.eat_op(&["&&"]).is_some() {
+            let rhs = self.parse_comparison()?;
+            lhs = Expr::Binary(BinaryOp::And, Box::new(lhs), Box::new(rhs));
+        }
+        Ok(lhs)
+    }
+
+    fn parse_comparison(&mut self) -> Result<Expr, ParseError> {
+        let lhs = self.parse_additive()?;
+        let op = match self.eat_op(&["==", "!=", "<=", ">=", "<", ">"]) {
+            Some("==") => BinaryOp::Eq,
+            Some("!=") => BinaryOp::Ne,
+            Some("<=") => BinaryOp::Le,
+            Some(">=") => BinaryOp::Ge,
+            Some("<") => BinaryOp::Lt,
+            Some(">") => BinaryOp::Gt,
+            _ => return Ok(lhs),
+        };
+        let rhs = self.parse_additive()?;
+        Ok(Expr::Binary(op, Box::new(lhs), Box::new(rhs)))
+    }
+
+    fn parse_additive(&mut self) -> Result<Expr, ParseError> {
+        let mut lhs = self.parse_multiplicative()?;
+        while let Some(op) = self.eat_op(&["+", "-"]) {
+            let op = if op == "+" { BinaryOp::Add } else { BinaryOp::Sub };
+            let rhs = self.parse_multiplicative()?;
+            lhs = Expr::Binary(op, Box::new(lhs), Box::new(rhs));
+        }
+        Ok(lhs)
+    }
+
+    fn parse_multiplicative(&mut self) -> Result<Expr, ParseError> {
+        let mut lhs = self.parse_unary()?;
+        while let Some(op) = self.eat_op(&["*", "/", "%"]) {
+            let op = match op {
+                "*" => BinaryOp::Mul,
+                "/" => BinaryOp::Div,
+                _ => BinaryOp::Rem,
+            };
+            let rhs = self.parse_unary()?;
+            lhs = Expr::Binary(op, Box::new(lhs), Box::new(rhs));
+        }
+        Ok(lhs)
+    }
+
+    fn parse_unary(&mut self) -> Result<Expr, ParseError> {
+        match self.eat_op(&["-", "!"]) {
+            Some("-") => Ok(Expr::Unary(UnaryOp::Neg, Box::new(self.parse_unary()?))),
+            Some(_) => Ok(Expr::Unary(UnaryOp::Not, Box::new(self.parse_unary()?))),
+            None => self.parse_power(),
+        }
+    }
+
+    fn parse_power(&mut self) -> Result<Expr, ParseError> {
+        let base = self.parse_primary()?;
+        if self.eat_op(&["^"]).is_some() {
+            // Right-associative
+            let exponent = self.parse_unary()?;
+            return Ok(Expr::Binary(BinaryOp::Pow, Box::new(base), Box::new(exponent)));
+        }
+        Ok(base)
+    }
+
+    fn parse_primary(&mut self) -> Result<Expr, ParseError> {
+        let position = self.position();
+        match self.advance() {
+            Some(Token::Number(v)) => Ok(Expr::Number(v)),
+            Some(Token::Point(point)) => Ok(Expr::Point(point)),
+            Some(Token::LParen) => {
+                let inner = self.parse_expr()?;
+                self.expect(Token::RParen)?;
+                Ok(inner)
+            }
+            Some(Token::Ident(word)) => match word.as_str() {
+                "true" => Ok(Expr::Bool(true)),
+                "false" => Ok(Expr::Bool(false)),
+                _ => self.parse_call(word.to_lowercase(), position),
+            },
+            Some(token) => Err(ParseError {
+                position,
+                message: format!("Unexpected token {:?}", token),
+            }),
+            None => Err(self.error("Unexpected end of expression")),
+        }
+    }
+
+    fn parse_call(&mut self, name: String, position: usize) -> Result<Expr, ParseError> {
+        let is_aggregate = AGGREGATE_FUNCTIONS.contains(&name.as_str());
+        let arity = SCALAR_FUNCTIONS
+            .iter()
+            .find(|(f, _)| *f == name)
+            .map(|(_, n)| *n);
+
+        if !is_aggregate && arity.is_none() {
+            return Err(ParseError {
+                position,
+                message: format!("Unknown function '{}'", name),
+            });
+        }
+
+        self.expect(Token::LParen)?;
+        let mut args = Vec::new();
+        if self.peek() != Some(&Token::RParen) {
+            loop {
+                args.push(self.parse_expr()?);
+                if self.peek() == Some(&Token::Comma) {
+                    self.pos += 1;
+                } else {
+                    break;
+                }
+            }
+        }
+        self.expect(Token::RParen)?;
+
+        let arity_ok = match arity {
+            Some(n) => args.len() == n,
+            None => !args.is_empty(),
+        };
+        if !arity_ok {
+            return Err(ParseError {
+                position,
+                message: format!("Wrong number of arguments to '{}'", name),
+            });
+        }
+
+        if !is_aggregate && args.iter().any(is_wildcard_ref) {
+            return Err(ParseError {
+                position,
+                message: format!("'{}' does not accept wildcard point sets", name),
+            });
+        }
+
+        Ok(Expr::Call(name, args))
+    }
+}
+
+fn is_wildcard_ref(expr: &Expr) -> bool {
+    matches!(expr, Expr::Point(r) if r.is_wildcard())
+}
+
+/// Reject wildcard references outside aggregate function arguments
+fn check_wildcards(expr: &Expr, in_aggregate: bool) -> Result<(), String> {
+    match expr {
+        Expr::Point(r) if r.is_wildcard() && !in_aggregate => Err(format!(
+            "Wildcard reference {{{}}} must be used inside min/max/avg/sum/count",
+            r.path
+        )),
+        Expr::Number(_) | Expr::Bool(_) | Expr::Point(_) => Ok(()),
+        Expr::Unary(_, e) => check_wildcards(e, false),
+        Expr::Binary(_, a, b) => {
+            check_wildcards(a, false)?;
+            check_wildcards(b, false)
+        }
+        Expr::Conditional(c, a, b) => {
+            check_wildcards(c, false)?;
+            check_wildcards(a, false)?;
+            check_wildcards(b, false)
+        }
+        Expr::Call(name, args) => {
+            let aggregate = AGGREGATE_FUNCTIONS.contains(&name.as_str());
+            args.iter().try_for_each(|a| check_wildcards(a, aggregate))
+        }
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Expression
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// A parsed expression
+#[derive(Debug, Clone, PartialEq)]
+pub struct Expression {
+    source: String,
+    ast: Expr,
+}
+
+impl fmt::Display for Expression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.source)
+    }
+}
+
+impl Expression {
+    /// Parse an expression
+    pub fn parse(source: &str) -> Result<Self, ParseError> {
+        let tokens = tokenize(source)?;
+        let mut parser = Parser {
+            tokens,
+            pos: 0,
+            end: source.len(),
+        };
+        let ast = parser.parse_expr()?;
+        if parser.pos < parser.tokens.len() {
+            return Err(parser.error("Unexpected trailing input"));
+        }
+        check_wildcards(&ast, false).map_err(|message| ParseError {
+            position: 0,
+            message,
+        })?;
+
+        Ok(Self {
+            source: source.to_string(),
+            ast,
+        })
+    }
+
+    /// The original expression text
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// All point references used by the expression
+    pub fn references(&self) -> Vec<PointRef> {
+        let mut refs = Vec::new();
+        collect_refs(&self.ast, &mut refs);
+        refs
+    }
+
+    /// Whether a changed point is an input of this expression
+    pub fn depends_on(&self, path: &str) -> bool {
+        self.references().iter().any(|r| r.matches(path))
+    }
+
+    /// Evaluate the expression against the given inputs
+    pub fn evaluate(&self, ctx: &dyn EvalContext) -> Result<Evaluated, EvalError> {
+        let mut quality = PointQuality::Good;
+        let value = eval(&self.ast, ctx, &mut quality)?;
+        if let ExprValue::Number(v) = value {
+            if !v.is_finite() {
+                return Err(EvalError::Math(format!("Result is not finite ({})", v)));
+            }
+        }
+        Ok(Evaluated { value, quality })
+    }
+}
+
+fn collect_refs(expr: &Expr, refs: &mut Vec<PointRef>) {
+    match expr {
+        Expr::Point(r) => {
+            if !refs.contains(r) {
+                refs.push(r.clone());
+            }
+        }
+        Expr::Number(_) | Expr::Bool(_) => {}
+        Expr::Unary(_, e) => collect_refs(e, refs),
+        Expr::Binary(_, a, b) => {
+            collect_refs(a, refs);
+            collect_refs(b, refs);
+        }
+        Expr::Conditional(c, a, b) => {
+            collect_refs(c, refs);
+            collect_refs(a, refs);
+            collect_refs(b, refs);
+        }
+        Expr::Call(_, args) => args.iter().for_each(|a| collect_refs(a, refs)),
+    }
+}
+
+fn eval(expr: &Expr, ctx: &dyn EvalContext, quality: &mut PointQuality) -> Result<ExprValue, EvalError> {
+    match expr {
+        Expr::Number(v) => Ok(ExprValue::Number(*v)),
+        Expr::Bool(v) => Ok(ExprValue::Bool(*v)),
+        Expr::Point(r) => {
+            let input = ctx
+                .value(&r.path)
+                .ok_or_else(|| EvalError::MissingInput(r.path.clone()))?;
+            *quality = worst_quality(*quality, input.quality);
+            input.in_units(r)
+        }
+        Expr::Unary(UnaryOp::Neg, e) => Ok(ExprValue::Number(-eval(e, ctx, quality)?.as_number()?)),
+        Expr::Unary(UnaryOp::Not, e) => Ok(ExprValue::Bool(!eval(e, ctx, quality)?.as_bool())),
+        Expr::Binary(op, a, b) => eval_binary(*op, a, b, ctx, quality),
+        Expr::Conditional(c, a, b) => {
+            if eval(c, ctx, quality)?.as_bool() {
+                eval(a, ctx, quality)
+            } else {
+                eval(b, ctx, quality)
+            }
+        }
+        Expr::Call(name, args) => eval_call(name, args, ctx, quality),
+    }
+}
+
+fn eval_binary(
+    op: BinaryOp,
+    a: &Expr,
+    b: &Expr,
+    ctx: &dyn EvalContext,
+    quality: &mut PointQuality,
+) -> Result<ExprValue, EvalError> {
+    // Short-circuit boolean operators
+    match op {
+        BinaryOp::And => {
+            return Ok(ExprValue::Bool(
+                eval(a, ctx, quality)?.as_bool() && eval(b, ctx, quality)?.as_bool(),
+            ));
+        }
+        BinaryOp::Or => {
+            return Ok(ExprValue::Bool(
+                eval(a, ctx, quality)?.as_bool() || eval(b, ctx, quality)?.as_bool(),
+            ));
+        }
+        _ => {}
+    }
+
+    let lhs = eval(a, ctx, quality)?;
+    let rhs = eval(b, ctx, quality)?;
+
+    if let (BinaryOp::Eq | BinaryOp::Ne, ExprValue::Bool(x), ExprValue::Bool(y)) = (op, lhs, rhs) {
+        return Ok(ExprValue::Bool((x == y) == (op == BinaryOp::Eq)));
+    }
+
+    let x = lhs.as_number()?;
+    let y = rhs.as_number()?;
+    let value = match op {
+        BinaryOp::Add => ExprValue::Number(x + y),
+        BinaryOp::Sub => ExprValue::Number(x - y),
+        BinaryOp::Mul => ExprValue::Number(x * y),
+        BinaryOp::Div | BinaryOp::Rem if y == 0.0 => {
+            return Err(EvalError::Math("Division by zero".to_string()));
+        }
+        BinaryOp::Div => ExprValue::Number(x / y),
+        BinaryOp::Rem => ExprValue::Number(x % y),
+        BinaryOp::Pow => ExprValue::Number(x.powf(y)),
+        BinaryOp::Eq => ExprValue::Bool(x == y),
+        BinaryOp::Ne => ExprValue::Bool(x != y),
+        BinaryOp::Lt => ExprValue::Bool(x < y),
+        BinaryOp::Le => ExprValue::Bool(x <= y),
+        BinaryOp::Gt => ExprValue::Bool(x > y),
+        BinaryOp::Ge => ExprValue::Bool(x >= y),
+        BinaryOp::And | BinaryOp::Or => unreachable!("handled above"),
+    };
+    Ok(value)
+}
+
+fn eval_call(
+    name: &str,
+    args: &[Expr],
+    ctx: &dyn EvalContext,
+    quality: &mut PointQuality,
+) -> Result<ExprValue, EvalError> {
+    if AGGREGATE_FUNCTIONS.contains(&name) {
+        // Expand wildcard references into their member values
+        let mut values = Vec::new();
+        for arg in args {
+            match arg {
+                Expr::Point(r) if r.is_wildcard() => {
+                    let members = ctx.matching(&r.path);
+                    if members.is_empty() && name != "count" {
+                        return Err(EvalError::EmptySet(r.path.clone()));
+                    }
+                    for input in members {
+                        *quality = worst_quality(*quality, input.quality);
+                        values.push(input.in_units(r)?.as_number()?);
+                    }
+                }
+                _ => values.push(eval(arg, ctx, quality)?.as_number()?),
+            }
+        }
+
+        let result = match name {
+            "min" => values.iter().copied().fold(f64::INFINITY, f64::min),
+            "max" => values.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+            "sum" => values.iter().sum(),
+            "count" => values.len() as f64,
+            _ => values.iter().sum::<f64>() / values.len() as f64,
+        };
+        return Ok(ExprValue::Number(result));
+    }
+
+    match name {
+        "if" => {
+            if eval(&args[0], ctx, quality)?.as_bool() {
+                eval(&args[1], ctx, quality)
+            } else {
+                eval(&args[2], ctx, quality)
+            }
+        }
+        "clamp" => {
+            let x = eval(&args[0], ctx, quality)?.as_number()?;
+            let lo = eval(&args[1], ctx, quality)?.as_number()?;
+            let hi = eval(&args[2], ctx, quality)?.as_number()?;
+            Ok(ExprValue::Number(x.max(lo).min(hi)))
+        }
+        _ => {
+            let x = eval(&args[0], ctx, quality)?.as_number()?;
+            let result = match name {
+                "abs" => x.abs(),
+                "round" => x.round(),
+                "floor" => x.floor(),
+                "ceil" => x.ceil(),
+                "sqrt" if x < 0.0 => {
+                    return Err(EvalError::Math("Square root of a negative number".to_string()));
+                }
+                _ => x.sqrt(),
+            };
+            Ok(ExprValue::Number(result))
+        }
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Tests
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    struct MapContext(HashMap<String, InputValue>);
+
+    impl EvalContext for MapContext {
+        fn value(&self, path: &str) -> Option<InputValue> {
+            self.0.get(path).copied()
+        }
+
+        fn matching(&self, pattern: &str) -> Vec<InputValue> {
+            let pattern = WildMatch::new(pattern);
+            self.0
+                .iter()
+                .filter(|(path, _)| pattern.matches(path))
+                .map(|(_, v)| *v)
+                .collect()
+        }
+    }
+
+    fn context() -> MapContext {
+        let degf = Some(EngineeringUnit::DegreesFahrenheit);
+        let input = |value, quality, units| InputValue { value, quality, units };
+
+        let mut values = HashMap::new();
+        values.insert("net/AHU-1/AI:1".to_string(), input(ExprValue::Number(55.0), PointQuality::Good, degf));
+        values.insert("net/AHU-1/AI:2".to_string(), input(ExprValue::Number(72.0), PointQuality::Good, degf));
+        values.insert("net/VAV-1/AI:1".to_string(), input(ExprValue::Number(70.0), PointQuality::Good, degf));
+        values.insert("net/VAV-2/AI:1".to_string(), input(ExprValue::Number(74.0), PointQuality::Stale, degf));
+        values.insert("net/AHU-1/BI:1".to_string(), input(ExprValue::Bool(true), PointQuality::Good, None));
+        values.insert(
+            "net/AHU-1/AI:3".to_string(),
+            input(ExprValue::Number(2000.0), PointQuality::Good, Some(EngineeringUnit::CubicFeetPerMinute)),
+        );
+        MapContext(values)
+    }
+
+    #[test]
+    fn test_arithmetic_and_precedence() {
+        let ctx = context();
+        let expr = Expression::parse("{net/AHU-1/AI:2} - {net/AHU-1/AI:1}").unwrap();
+        assert_eq!(expr.evaluate(&ctx).unwrap().value, ExprValue::Number(17.0));
+
+        let expr = Expression::parse("2 + 3 * 4 ^ 2 / 8").unwrap();
+        assert_eq!(expr.evaluate(&ctx).unwrap().value, ExprValue::Number(8.0));
+
+        let expr = Expression::parse("-(1 + 2) % 2").unwrap();
+        assert_eq!(expr.evaluate(&ctx).unwrap().value, ExprValue::Number(-1.0));
+    }
+
+    #[test]
+    fn test_aggregates_carry_quality() {
+        let ctx = context();
+        let expr = Expression::parse("avg({net/VAV-*/AI:1})").unwrap();
+        let result = expr.evaluate(&ctx).unwrap();
+        assert_eq!(result.value, ExprValue::Number(72.0));
+        assert_eq!(result.quality, PointQuality::Stale);
+
+        let expr = Expression::parse("max({net/VAV-*/AI:1}, 80) + count({net/none/*})").unwrap();
+        assert_eq!(expr.evaluate(&ctx).unwrap().value, ExprValue::Number(80.0));
+
+        let expr = Expression::parse("min({net/none/*})").unwrap();
+        assert!(matches!(expr.evaluate(&ctx), Err(EvalError::EmptySet(_))));
+    }
+
+    #[test]
+    fn test_unit_conversion() {
+        let ctx = context();
+        let expr = Expression::parse("{net/AHU-1/AI:2|°C} - {net/AHU-1/AI:1|degrees-celsius}").unwrap();
+        match expr.evaluate(&ctx).unwrap().value {
+            ExprValue::Number(v) => assert!((v - 17.0 * 5.0 / 9.0).abs() < 1e-9),
+            other => panic!("Expected a number, got {:?}", other),
+        }
+
+        let expr = Expression::parse("avg({net/VAV-*/AI:1|K})").unwrap();
+        match expr.evaluate(&ctx).unwrap().value {
+            ExprValue::Number(v) => assert!((v - 295.372).abs() < 1e-3),
+            other => panic!("Expected a number, got {:?}", other),
+        }
+
+        let expr = Expression::parse("{net/AHU-1/AI:3|°C}").unwrap();
+        assert!(matches!(expr.evaluate(&ctx), Err(EvalError::Units(_))));
+
+        assert!(Expression::parse("{net/AHU-1/AI:1|furlongs}").is_err());
+    }
+
+    #[test]
+    fn test_boolean_logic_and_conditionals() {
+        let ctx = context();
+        let expr = Expression::parse("{net/AHU-1/BI:1} && {net/AHU-1/AI:1} > 50 ? 1 : 0").unwrap();
+        assert_eq!(expr.evaluate(&ctx).unwrap().value, ExprValue::Number(1.0));
+
+        let expr = Expression::parse("if(not {net/AHU-1/BI:1} or false, 65, 72)").unwrap();
+        assert_eq!(expr.evaluate(&ctx).unwrap().value, ExprValue::Number(72.0));
+
+        let expr = Expression::parse("{net/AHU-1/AI:9} + 1").unwrap();
+        assert!(matches!(expr.evaluate(&ctx), Err(EvalError::MissingInput(_))));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(Expression::parse("1 +").is_err());
+        assert!(Expression::parse("{net/AHU-1/AI:1").is_err());
+        assert!(Expression::parse("foo(1)").is_err());
+        assert!(Expression::parse("abs(1, 2)").is_err());
+        assert!(Expression::parse("{net/*/AI:1} + 1").is_err());
+        assert!(Expression::parse("abs({net/*/AI:1})").is_err());
+    }
+}
@@ -0,0 +1,402 @@
+// History Aggregation - Bucketed aggregates and downsampling
+//
+// Buckets are aligned to multiples of the interval since the Unix epoch (so
+// "1d" buckets start at UTC midnight) and only buckets containing samples are
+// returned. Booleans aggregate as 0/1, so the average of a status point is its
+// duty cycle.
+//
+// The time-weighted average holds each value until the next sample (or the
+// end of the query); the last value of the previous bucket carries into the
+// start of the next one.
+//
+// Rollup rows can stand in for the samples of whole sub-buckets when only
+// count, min, max, sum, avg or last are requested.
+//
+// Downsampling uses Largest-Triangle-Three-Buckets, which keeps real samples
+// (peaks and dips included) rather than averaging them away.
+
+use chrono::{DateTime, Duration, Utc};
+
+use super::rollup::RollupRecord;
+use crate::points::EngineeringUnit;
+use crate::services::messages::{Aggregation, HistoryBucket, HistorySample};
+use crate::types::{Error, PointQuality, PropertyValue, Result};
+
+/// Parse an interval like "30s", "15m", "1h" or "1d"
+pub fn parse_interval(interval: &str) -> Result<Duration> {
+    let interval = interval.trim();
+    let split = interval
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(interval.len());
+    let (count, unit) = interval.split_at(split);
+
+    let count: i64 = count
+        .parse()
+        .map_err(|_| Error::Config(format!("Invalid interval '{}'", interval)))?;
+    let duration = match unit {
+        "s" => Duration::try_seconds(count),
+        "m" => Duration::try_minutes(count),
+        "h" => Duration::try_hours(count),
+        "d" => Duration::try_days(count),
+        "w" => Duration::try_weeks(count),
+        _ => {
+            return Err(Error::Config(format!(
+                "Invalid interval unit in '{}' (expected s, m, h, d or w)",
+                interval
+            )));
+        }
+    }
+    .ok_or_else(|| Error::Config(format!("Interval '{}' is too long", interval)))?;
+
+    if duration <= Duration::zero() {
+        return Err(Error::Config(format!("Interval '{}' must be positive", interval)));
+    }
+    Ok(duration)
+}
+
+/// Numeric value of a sample for aggregation, if it has one
+pub(super) fn numeric_value(value: &PropertyValue) -> Option<f64> {
+    match value {
+        PropertyValue::Real(v) => Some(*v as f64),
+        PropertyValue::Unsigned(v) => Some(*v as f64),
+        PropertyValue::Enumerated(v) => Some(*v as f64),
+        PropertyValue::Boolean(v) => Some(if *v { 1.0 } else { 0.0 }),
+        _ => None,
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Bucketed Aggregation
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Running aggregates for one bucket
+struct BucketAcc {
+    start: i64,
+    end: i64,
+    count: u64,
+    min: f64,
+    max: f64,
+    sum: f64,
+    first: f64,
+    last: f64,
+    /// Integral of the held value over time (value x microseconds)
+    area: f64,
+    /// Microseconds covered by `area`
+    covered: i64,
+}
+
+impl BucketAcc {
+    fn new(start: i64, width: i64) -> Self {
+        Self {
+            start,
+            end: start + width,
+            count: 0,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+            sum: 0.0,
+            first: 0.0,
+            last: 0.0,
+            area: 0.0,
+            covered: 0,
+        }
+    }
+
+    fn add(&mut self, value: f64) {
+        if self.count == 0 {
+            self.first = value;
+        }
+        self.count += 1;
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+        self.sum += value;
+        self.last = value;
+    }
+
+    /// Merge a pre-computed rollup of samples within this bucket
+    fn merge(&mut self, rollup: &RollupRecord) {
+        if self.count == 0 {
+            self.first = rollup.last;
+        }
+        self.count += rollup.count;
+        self.min = self.min.min(rollup.min);
+        self.max = self.max.max(rollup.max);
+        self.sum += rollup.sum;
+        self.last = rollup.last;
+    }
+
+    /// Account for `value` being held from `from` to `to` (clamped to the bucket)
+    fn hold(&mut self, from: i64, to: i64, value: f64) {
+        let from = from.max(self.start);
+        let to = to.min(self.end);
+        if to > from {
+            self.area += value * (to - from) as f64;
+            self.covered += to - from;
+        }
+    }
+}
+
+/// Streams time-ordered samples into fixed-width buckets
+pub struct Aggregator {
+    width: i64,
+    /// Samples are held no later than this (end of query, or now)
+    horizon: i64,
+    functions: Vec<Aggregation>,
+    current: Option<BucketAcc>,
+    previous: Option<(i64, f64)>,
+    /// Units of the most recent sample with units
+    units: Option<EngineeringUnit>,
+    buckets: Vec<HistoryBucket>,
+}
+
+impl Aggregator {
+    /// Create an aggregator (all functions when `functions` is empty)
+    pub fn new(interval: Duration, horizon: DateTime<Utc>, functions: &[Aggregation]) -> Self {
+        Self {
+            width: interval.num_microseconds().unwrap_or(i64::MAX).max(1),
+            horizon: horizon.timestamp_micros(),
+            functions: functions.to_vec(),
+            current: None,
+            previous: None,
+            units: None,
+            buckets: Vec::new(),
+        }
+    }
+
+    /// Add a sample (must be called in time order)
+    pub fn push(&mut self, timestamp: i64, value: f64) {
+        let start = timestamp.div_euclid(self.width) * self.width;
+
+        match &mut self.current {
+            Some(acc) if acc.start == start => {
+                if let Some((at, held)) = self.previous {
+                    acc.hold(at, timestamp, held);
+                }
+            }
+            _ => {
+                self.close_current();
+                let mut acc = BucketAcc::new(start, self.width);
+                // The previous value is held into this bucket until the sample
+                if let Some((at, held)) = self.previous {
+                    acc.hold(at, timestamp, held);
+                }
+                self.current = Some(acc);
+            }
+        }
+
+        if let Some(acc) = &mut self.current {
+            acc.add(value);
+        }
+        self.previous = Some((timestamp, value));
+    }
+
+    /// Add a rollup row covering [bucket_start, bucket_start + rollup width)
+    ///
+    /// Rollups must not be mixed into queries for first or time-weighted
+    /// averages, and the rollup width must divide the interval.
+    pub(super) fn push_rollup(&mut self, bucket_start: i64, rollup: &RollupRecord) {
+        let start = bucket_start.div_euclid(self.width) * self.width;
+        if self.current.as_ref().is_none_or(|acc| acc.start != start) {
+            self.close_current();
+            self.current = Some(BucketAcc::new(start, self.width));
+        }
+        if let Some(acc) = &mut self.current {
+            acc.merge(rollup);
+        }
+        self.previous = Some((rollup.last_ts, rollup.last));
+        self.units = rollup.units.or(self.units);
+    }
+
+    /// Add a history sample, skipping non-numeric and Bad quality samples
+    pub fn push_sample(&mut self, sample: &HistorySample) {
+        if sample.quality == PointQuality::Bad {
+            return;
+        }
+        if let Some(value) = numeric_value(&sample.value) {
+            self.units = sample.units.or(self.units);
+            self.push(sample.timestamp.timestamp_micros(), value);
+        }
+    }
+
+    /// Hold the last value to the end of its bucket and emit it
+    fn close_current(&mut self) {
+        if let Some(mut acc) = self.current.take() {
+            if let Some((at, held)) = self.previous {
+                acc.hold(at, acc.end.min(self.horizon), held);
+            }
+            let bucket = self.finish(acc);
+            self.buckets.push(bucket);
+        }
+    }
+
+    fn wants(&self, function: Aggregation) -> bool {
+        self.functions.is_empty() || self.functions.contains(&function)
+    }
+
+    fn finish(&self, acc: BucketAcc) -> HistoryBucket {
+        let pick = |function, value| self.wants(function).then_some(value);
+        let twa = if acc.covered > 0 {
+            acc.area / acc.covered as f64
+        } else {
+            acc.last
+        };
+
+        HistoryBucket {
+            start: DateTime::from_timestamp_micros(acc.start).unwrap_or_else(Utc::now),
+            end: DateTime::from_timestamp_micros(acc.end).unwrap_or_else(Utc::now),
+            count: acc.count,
+            min: pick(Aggregation::Min, acc.min),
+            max: pick(Aggregation::Max, acc.max),
+            avg: pick(Aggregation::Avg, acc.sum / acc.count as f64),
+            first: pick(Aggregation::First, acc.first),
+            last: pick(Aggregation::Last, acc.last),
+            sum: pick(Aggregation::Sum, acc.sum),
+            time_weighted_avg: pick(Aggregation::TimeWeightedAvg, twa),
+            units: None,
+        }
+    }
+
+    /// Finish aggregation and return the non-empty buckets in time order
+    pub fn finish_buckets(mut self) -> Vec<HistoryBucket> {
+        self.close_current();
+        for bucket in &mut self.buckets {
+            bucket.units = self.units;
+        }
+        self.buckets
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Downsampling
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Downsample to at most `threshold` samples with Largest-Triangle-Three-Buckets
+///
+/// Non-numeric samples are dropped when downsampling. Series at or below the
+/// threshold (or thresholds below 3) are returned unchanged.
+pub fn lttb(samples: Vec<HistorySample>, threshold: usize) -> Vec<HistorySample> {
+    if threshold < 3 || samples.len() <= threshold {
+        return samples;
+    }
+
+    let points: Vec<(f64, f64, HistorySample)> = samples
+        .into_iter()
+        .filter_map(|s| {
+            let y = numeric_value(&s.value)?;
+            Some((s.timestamp.timestamp_micros() as f64, y, s))
+        })
+        .collect();
+    if points.len() <= threshold {
+        return points.into_iter().map(|(_, _, s)| s).collect();
+    }
+
+    let n = points.len();
+    let every = (n - 2) as f64 / (threshold - 2) as f64;
+    let mut selected = Vec::with_capacity(threshold);
+    selected.push(0);
+    let mut a = 0;
+
+    for i in 0..threshold - 2 {
+        // Average of the next bucket is the third triangle vertex
+        let next_start = ((i + 1) as f64 * every) as usize + 1;
+        let next_end = (((i + 2) as f64 * every) as usize + 1).min(n);
+        let next = &points[next_start..next_end];
+        let avg_x = next.iter().map(|p| p.0).sum::<f64>() / next.len() as f64;
+        let avg_y = next.iter().map(|p| p.1).sum::<f64>() / next.len() as f64;
+
+        let start = (i as f64 * every) as usize + 1;
+        let end = next_start;
+        let (ax, ay) = (points[a].0, points[a].1);
+
+        let mut best = start;
+        let mut best_area = -1.0;
+        for (j, point) in points.iter().enumerate().take(end).skip(start) {
+            let area = ((ax - avg_x) * (point.1 - ay) - (ax - point.0) * (avg_y - ay)).abs();
+            if area > best_area {
+                best_area = area;
+                best = j;
+            }
+        }
+
+        selected.push(best);
+        a = best;
+    }
+    selected.push(n - 1);
+
+    let mut points: Vec<Option<HistorySample>> = points.into_iter().map(|(_, _, s)| Some(s)).collect();
+    selected
+        .into_iter()
+        .filter_map(|i| points[i].take())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(minute: i64, value: f32) -> HistorySample {
+        HistorySample {
+            timestamp: DateTime::from_timestamp(minute * 60, 0).unwrap(),
+            value: PropertyValue::Real(value),
+            quality: PointQuality::Good,
+            units: None,
+        }
+    }
+
+    #[test]
+    fn test_parse_interval() {
+        assert_eq!(parse_interval("15m").unwrap(), Duration::minutes(15));
+        assert_eq!(parse_interval("1d").unwrap(), Duration::days(1));
+        assert!(parse_interval("0h").is_err());
+        assert!(parse_interval("5y").is_err());
+        assert!(parse_interval("h").is_err());
+        assert!(matches!(parse_interval("99999999999999d"), Err(Error::Config(_))));
+        assert!(matches!(parse_interval("99999999999999999w"), Err(Error::Config(_))));
+    }
+
+    #[test]
+    fn test_bucket_aggregates() {
+        // 10 for 10 minutes, then 20 for 50 minutes, then 0 at 60
+        let horizon = DateTime::from_timestamp(3 * 3600, 0).unwrap();
+        let mut agg = Aggregator::new(Duration::hours(1), horizon, &[]);
+        for s in [sample(0, 10.0), sample(10, 20.0), sample(60, 0.0)] {
+            agg.push_sample(&s);
+        }
+        let buckets = agg.finish_buckets();
+
+        assert_eq!(buckets.len(), 2);
+        let first = &buckets[0];
+        assert_eq!(first.count, 2);
+        assert_eq!(first.min, Some(10.0));
+        assert_eq!(first.max, Some(20.0));
+        assert_eq!(first.avg, Some(15.0));
+        assert_eq!(first.first, Some(10.0));
+        assert_eq!(first.last, Some(20.0));
+        assert_eq!(first.sum, Some(30.0));
+        let twa = first.time_weighted_avg.unwrap();
+        assert!((twa - (10.0 * 10.0 + 20.0 * 50.0) / 60.0).abs() < 1e-9);
+
+        // The second bucket holds 0 until the horizon
+        assert_eq!(buckets[1].time_weighted_avg, Some(0.0));
+
+        // Only requested aggregates are set
+        let mut agg = Aggregator::new(Duration::hours(1), horizon, &[Aggregation::Max]);
+        agg.push_sample(&sample(0, 10.0));
+        let buckets = agg.finish_buckets();
+        assert_eq!(buckets[0].max, Some(10.0));
+        assert_eq!(buckets[0].avg, None);
+    }
+
+    #[test]
+    fn test_lttb_keeps_extremes() {
+        let samples: Vec<HistorySample> = (0..1000)
+            .map(|i| sample(i, if i == 500 { 100.0 } else { (i % 7) as f32 }))
+            .collect();
+
+        let reduced = lttb(samples, 50);
+        assert_eq!(reduced.len(), 50);
+        assert_eq!(reduced[0].timestamp, sample(0, 0.0).timestamp);
+        assert_eq!(reduced[49].timestamp, sample(999, 0.0).timestamp);
+        assert!(reduced.iter().any(|s| s.value == PropertyValue::Real(100.0)));
+        assert!(reduced.windows(2).all(|w| w[0].timestamp < w[1].timestamp));
+    }
+}
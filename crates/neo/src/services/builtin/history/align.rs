@@ -0,0 +1,203 @@
+// History Alignment - Several points on a common time grid
+//
+// Rows are produced one at a time from a cursor per point over the samples
+// table, so a table of any length is streamed to its sink without loading
+// the range into memory. With an interval, rows fall on multiples of the
+// interval since the Unix epoch; without one, there is a row at every
+// timestamp where any of the points has a sample.
+//
+// Each cursor keeps the samples either side of the row being produced:
+//
+//   previous   the last sample at or before the row (including one from
+//              before the range start)
+//   linear     interpolated between the samples either side of the row
+//   none       only a sample exactly at the row
+//
+// Bad quality and non-numeric samples are skipped.
+
+use redb::{ReadableTable, StorageError};
+
+use super::aggregate::numeric_value;
+use super::codec;
+use crate::points::EngineeringUnit;
+use crate::services::messages::Interpolation;
+use crate::types::{Error, PointQuality, PropertyValue, Result};
+
+/// What to align
+pub(super) struct AlignSpec {
+    pub points: Vec<String>,
+    /// Range in micros (inclusive)
+    pub start: i64,
+    pub end: i64,
+    /// Row spacing in micros (None = a row at every sample timestamp)
+    pub interval: Option<i64>,
+    pub interpolation: Interpolation,
+    /// Convert values to these units
+    pub units: Option<EngineeringUnit>,
+}
+
+/// Receives aligned rows in time order
+pub(super) trait RowSink {
+    /// Accept a row; returning false stops alignment
+    fn row(&mut self, timestamp: i64, values: &[Option<f64>]) -> Result<bool>;
+}
+
+type Entry<'a> = std::result::Result<
+    (
+        redb::AccessGuard<'a, (u32, i64)>,
+        redb::AccessGuard<'a, &'static [u8]>,
+    ),
+    StorageError,
+>;
+
+/// Decode a stored sample to (timestamp, value), skipping unusable samples
+fn decode_entry(entry: Entry<'_>, units: Option<EngineeringUnit>) -> Result<Option<(i64, f64)>> {
+    let (key, value) = entry.map_err(|e| Error::Database(e.to_string()))?;
+    let record = codec::decode(value.value())?;
+    if record.quality == PointQuality::Bad {
+        return Ok(None);
+    }
+    let Some(mut numeric) = numeric_value(&record.value) else {
+        return Ok(None);
+    };
+
+    // Same conversion rules as sample queries: unconvertible samples are skipped
+    if let (Some(from), Some(to), PropertyValue::Real(_) | PropertyValue::Unsigned(_)) =
+        (record.units, units, &record.value)
+    {
+        numeric = match from.convert(numeric, to) {
+            Ok(numeric) => numeric,
+            Err(e) => {
+                tracing::debug!("Skipping history sample at {}: {}", key.value().1, e);
+                return Ok(None);
+            }
+        };
+    }
+    Ok(Some((key.value().1, numeric)))
+}
+
+/// Samples of one point around the row being produced
+struct Cursor<'a> {
+    samples: Box<dyn Iterator<Item = Result<(i64, f64)>> + 'a>,
+    prev: Option<(i64, f64)>,
+    next: Option<(i64, f64)>,
+}
+
+impl<'a> Cursor<'a> {
+    fn open(
+        table: &'a impl ReadableTable<(u32, i64), &'static [u8]>,
+        id: Option<u32>,
+        spec: &AlignSpec,
+    ) -> Result<Self> {
+        let Some(id) = id else {
+            return Ok(Self {
+                samples: Box::new(std::iter::empty()),
+                prev: None,
+                next: None,
+            });
+        };
+        let units = spec.units;
+
+        let mut prev = None;
+        if spec.interpolation != Interpolation::None {
+            let before = table
+                .range((id, i64::MIN)..(id, spec.start))
+                .map_err(|e| Error::Database(e.to_string()))?;
+            for entry in before.rev() {
+                if let Some(sample) = decode_entry(entry, units)? {
+                    prev = Some(sample);
+                    break;
+                }
+            }
+        }
+
+        // Read past the end so linear interpolation has a sample to the right
+        let range = table
+            .range((id, spec.start)..=(id, i64::MAX))
+            .map_err(|e| Error::Database(e.to_string()))?;
+        let mut samples: Box<dyn Iterator<Item = Result<(i64, f64)>> + 'a> =
+            Box::new(range.filter_map(move |entry| decode_entry(entry, units).transpose()));
+        let next = samples.next().transpose()?;
+
+        Ok(Self {
+            samples,
+            prev,
+            next,
+        })
+    }
+
+    /// Move past every sample at or before `t`
+    fn advance_to(&mut self, t: i64) -> Result<()> {
+        while let Some(next) = self.next.filter(|(ts, _)| *ts <= t) {
+            self.prev = Some(next);
+            self.next = self.samples.next().transpose()?;
+        }
+        Ok(())
+    }
+
+    fn value_at(&self, t: i64, interpolation: Interpolation) -> Option<f64> {
+        match interpolation {
+            Interpolation::Previous => self.prev.map(|(_, v)| v),
+            Interpolation::None => self.prev.filter(|(ts, _)| *ts == t).map(|(_, v)| v),
+            Interpolation::Linear => match (self.prev, self.next) {
+                (Some((ts, v)), _) if ts == t => Some(v),
+                (Some((t0, v0)), Some((t1, v1))) => {
+                    Some(v0 + (v1 - v0) * (t - t0) as f64 / (t1 - t0) as f64)
+                }
+                _ => None,
+            },
+        }
+    }
+}
+
+/// Stream aligned rows to a sink, returning the number of rows produced
+///
+/// `ids` are the point ids of `spec.points`, in column order (None for points
+/// without history).
+pub(super) fn align(
+    table: &impl ReadableTable<(u32, i64), &'static [u8]>,
+    spec: &AlignSpec,
+    ids: &[Option<u32>],
+    sink: &mut impl RowSink,
+) -> Result<u64> {
+    let mut cursors = ids
+        .iter()
+        .map(|id| Cursor::open(table, *id, spec))
+        .collect::<Result<Vec<_>>>()?;
+    let mut values = vec![None; cursors.len()];
+    let mut rows = 0;
+
+    // First grid row at or after the start
+    let mut grid = spec.interval.map(|width| {
+        let slot = spec.start.div_euclid(width) * width;
+        if slot < spec.start { slot + width } else { slot }
+    });
+
+    loop {
+        let t = match (grid.as_mut(), spec.interval) {
+            (Some(t), Some(width)) => {
+                let row = *t;
+                *t += width;
+                row
+            }
+            _ => match cursors.iter().filter_map(|c| c.next.map(|(ts, _)| ts)).min() {
+                Some(t) => t,
+                None => break,
+            },
+        };
+        if t > spec.end {
+            break;
+        }
+
+        for (cursor, value) in cursors.iter_mut().zip(values.iter_mut()) {
+            cursor.advance_to(t)?;
+            *value = cursor.value_at(t, spec.interpolation);
+        }
+        rows += 1;
+        if !sink.row(t, &values)? {
+            break;
+        }
+    }
+
+    Ok(rows)
+}
@@ -0,0 +1,686 @@
+// History Backends - External time-series stores fed from the history
+//
+// The history store (see store) stays the service's own store: queries,
+// rollups, gap reports and subscriptions are served from it. Backends are
+// further targets every committed sample is forwarded to:
+//
+//   influx_file   InfluxDB line protocol appended to a file
+//   influx_tcp    InfluxDB line protocol written to a TCP socket (e.g. a
+//                 Telegraf socket_listener)
+//   postgres      batched INSERTs into a Postgres or TimescaleDB table
+//
+// Forwarding is store-and-forward: the write transaction that commits a
+// sample also queues its key in each backend's outbox. Each forwarding run
+// reads the next batch of every backend not already busy with one, and writes
+// it on a task of its own, so a slow or unreachable backend never holds up
+// the history actor; the task reports back, and the batch is removed from the
+// outbox once the backend has taken it. While a backend is unreachable its
+// outbox grows; it is drained in order once the backend is back, and survives
+// restarts. Samples purged before they were forwarded are dropped from the
+// outbox.
+//
+// Line protocol puts every sample in one measurement (default "history"),
+// tagged with point, quality and units. Numeric values (including booleans
+// and enumerations) are a float `value` field; other values are a JSON string
+// `text` field, so the two never clash. Postgres rows go to a table like:
+//
+//   CREATE TABLE history (
+//       time timestamptz NOT NULL, point text NOT NULL, value double precision,
+//       text text, quality text NOT NULL, units text
+//   );
+
+use std::fmt::Write as _;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use tokio::io::AsyncWriteExt;
+use tokio::net::TcpStream;
+use tokio::sync::Mutex;
+use tokio_postgres::types::ToSql;
+
+use super::aggregate::numeric_value;
+use crate::services::messages::HistorySample;
+use crate::types::{Error, PointQuality, PropertyValue, Result};
+
+/// How long to wait for a backend to accept a connection
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How long a backend may take to accept a forwarded batch
+const FORWARD_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Rows per INSERT statement (Postgres allows 65,535 parameters)
+const INSERT_ROWS: usize = 1000;
+
+/// An external store history is forwarded to
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackendConfig {
+    /// Unique name; keys the backend's outbox, so keep it stable
+    pub name: String,
+    #[serde(flatten)]
+    pub target: BackendTarget,
+}
+
+/// Where a backend writes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BackendTarget {
+    /// InfluxDB line protocol appended to a file
+    InfluxFile {
+        path: String,
+        #[serde(default = "default_measurement")]
+        measurement: String,
+    },
+    /// InfluxDB line protocol written to a TCP socket (e.g., "127.0.0.1:8094")
+    InfluxTcp {
+        address: String,
+        #[serde(default = "default_measurement")]
+        measurement: String,
+    },
+    /// Batched INSERTs into a Postgres/TimescaleDB table
+    Postgres {
+        /// Connection string (e.g., "host=localhost user=neo dbname=bms")
+        url: String,
+        #[serde(default = "default_table")]
+        table: String,
+    },
+}
+
+fn default_measurement() -> String {
+    "history".to_string()
+}
+
+fn default_table() -> String {
+    "history".to_string()
+}
+
+/// A store samples are forwarded to
+#[async_trait]
+pub(super) trait HistoryBackend: Send {
+    /// Write samples, oldest first; on error they stay queued and are sent
+    /// again, so a backend may see a sample more than once
+    async fn write(&mut self, samples: &[(String, HistorySample)]) -> Result<()>;
+}
+
+/// Create the backend for a config (connections are made on first write)
+pub(super) fn open(config: &BackendConfig) -> Result<Box<dyn HistoryBackend>> {
+    Ok(match &config.target {
+        BackendTarget::InfluxFile { path, measurement } => Box::new(InfluxFile {
+            path: PathBuf::from(path),
+            measurement: measurement.clone(),
+        }),
+        BackendTarget::InfluxTcp {
+            address,
+            measurement,
+        } => Box::new(InfluxTcp {
+            address: address.clone(),
+            measurement: measurement.clone(),
+            stream: None,
+        }),
+        BackendTarget::Postgres { url, table } => {
+            let valid = |part: &str| {
+                !part.is_empty() && part.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+            };
+            if !table.split('.').all(valid) {
+                return Err(Error::Config(format!(
+                    "History backend '{}' has an invalid table name '{}'",
+                    config.name, table
+                )));
+            }
+            Box::new(Postgres {
+                url: url.clone(),
+                table: table.clone(),
+                client: None,
+            })
+        }
+    })
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Forwarding
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// A backend samples are forwarded to, with its forwarding state
+pub(super) struct ForwardTarget {
+    pub name: String,
+    backend: Arc<Mutex<Box<dyn HistoryBackend>>>,
+    pub forwarded: u64,
+    /// Error of the last failed batch, until a batch succeeds
+    pub last_error: Option<String>,
+    /// A batch is being written; the next one is read once it is done
+    pub sending: bool,
+}
+
+impl ForwardTarget {
+    pub fn open(config: &BackendConfig) -> Result<Self> {
+        Ok(Self {
+            name: config.name.clone(),
+            backend: Arc::new(Mutex::new(open(config)?)),
+            forwarded: 0,
+            last_error: None,
+            sending: false,
+        })
+    }
+
+    /// Start writing a batch of queued samples (sequences are their outbox
+    /// keys)
+    pub fn start(
+        &mut self,
+        sequences: Vec<u64>,
+        samples: Vec<(String, HistorySample)>,
+    ) -> ForwardJob {
+        self.sending = true;
+        ForwardJob {
+            backend: self.name.clone(),
+            target: self.backend.clone(),
+            sequences,
+            samples,
+        }
+    }
+
+    /// Record how a batch went; true when its samples can leave the outbox
+    pub fn finish(&mut self, outcome: &ForwardOutcome) -> bool {
+        self.sending = false;
+        match &outcome.result {
+            Ok(()) => {
+                if self.last_error.take().is_some() {
+                    tracing::info!("History backend {} is available again", self.name);
+                }
+                self.forwarded += outcome.samples as u64;
+                true
+            }
+            Err(e) => {
+                if self.last_error.is_none() {
+                    tracing::warn!(
+                        "History backend {} is unavailable, buffering samples: {}",
+                        self.name,
+                        e
+                    );
+                }
+                self.last_error = Some(e.to_string());
+                false
+            }
+        }
+    }
+}
+
+/// A batch to write to a backend, outside the actor
+pub(super) struct ForwardJob {
+    backend: String,
+    target: Arc<Mutex<Box<dyn HistoryBackend>>>,
+    sequences: Vec<u64>,
+    samples: Vec<(String, HistorySample)>,
+}
+
+impl ForwardJob {
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    /// Write the batch
+    pub async fn run(self) -> ForwardOutcome {
+        let result = if self.samples.is_empty() {
+            Ok(())
+        } else {
+            let mut target = self.target.lock().await;
+            tokio::time::timeout(FORWARD_TIMEOUT, target.write(&self.samples))
+                .await
+                .unwrap_or(Err(Error::Timeout))
+        };
+        ForwardOutcome {
+            backend: self.backend,
+            sequences: self.sequences,
+            samples: self.samples.len(),
+            result,
+        }
+    }
+}
+
+/// How a forwarded batch went
+#[derive(Debug)]
+pub(super) struct ForwardOutcome {
+    pub backend: String,
+    /// Outbox keys of the batch
+    pub sequences: Vec<u64>,
+    pub samples: usize,
+    pub result: Result<()>,
+}
+
+fn quality_name(quality: PointQuality) -> &'static str {
+    match quality {
+        PointQuality::Good => "good",
+        PointQuality::Bad => "bad",
+        PointQuality::Uncertain => "uncertain",
+        PointQuality::Stale => "stale",
+    }
+}
+
+/// Value of a sample as (number, text); exactly one is set
+fn split_value(sample: &HistorySample) -> (Option<f64>, Option<String>) {
+    match (&sample.value, numeric_value(&sample.value)) {
+        // Widen through the shortest decimal, so 55.2 stays 55.2
+        (PropertyValue::Real(value), _) => (value.to_string().parse().ok(), None),
+        (_, Some(value)) => (Some(value), None),
+        (value, None) => (None, Some(serde_json::to_string(value).unwrap_or_default())),
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// InfluxDB line protocol
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Escape a measurement name (`tag` = false) or a tag key or value
+fn escape(text: &str, tag: bool, out: &mut String) {
+    for c in text.chars() {
+        if c == ',' || c == ' ' || (tag && c == '=') {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+}
+
+/// Append a sample as a line of line protocol (non-finite numbers are skipped)
+fn write_line(measurement: &str, point: &str, sample: &HistorySample, out: &mut String) {
+    let (number, text) = split_value(sample);
+    if number.is_some_and(|n| !n.is_finite()) {
+        return;
+    }
+
+    escape(measurement, false, out);
+    out.push_str(",point=");
+    escape(point, true, out);
+    out.push_str(",quality=");
+    out.push_str(quality_name(sample.quality));
+    if let Some(units) = sample.units {
+        out.push_str(",units=");
+        escape(units.symbol(), true, out);
+    }
+    match (number, text) {
+        (Some(number), _) => {
+            let _ = write!(out, " value={:?}", number);
+        }
+        (None, text) => {
+            let text = text.unwrap_or_default();
+            let _ = write!(out, " text=\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""));
+        }
+    }
+    let nanos = sample.timestamp.timestamp_micros().saturating_mul(1000);
+    let _ = writeln!(out, " {}", nanos);
+}
+
+fn line_protocol(measurement: &str, samples: &[(String, HistorySample)]) -> String {
+    let mut out = String::new();
+    for (point, sample) in samples {
+        write_line(measurement, point, sample, &mut out);
+    }
+    out
+}
+
+/// Line protocol appended to a file
+struct InfluxFile {
+    path: PathBuf,
+    measurement: String,
+}
+
+#[async_trait]
+impl HistoryBackend for InfluxFile {
+    async fn write(&mut self, samples: &[(String, HistorySample)]) -> Result<()> {
+        let lines = line_protocol(&self.measurement, samples);
+        if let Some(parent) = self.path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .await?;
+        file.write_all(lines.as_bytes()).await?;
+        file.flush().await?;
+        Ok(())
+    }
+}
+
+/// Line protocol written to a TCP socket, reconnecting after errors
+///
+/// TCP has no acknowledgement, so lines written just before the listener
+/// goes away can be lost.
+struct InfluxTcp {
+    address: String,
+    measurement: String,
+    stream: Option<TcpStream>,
+}
+
+#[async_trait]
+impl HistoryBackend for InfluxTcp {
+    async fn write(&mut self, samples: &[(String, HistorySample)]) -> Result<()> {
+        let lines = line_protocol(&self.measurement, samples);
+        let stream = match &mut self.stream {
+            Some(stream) => stream,
+            None => {
+                let connect = TcpStream::connect(&self.address);
+                let stream = tokio::time::timeout(CONNECT_TIMEOUT, connect)
+                    .await
+                    .map_err(|_| Error::Timeout)??;
+                self.stream.insert(stream)
+            }
+        };
+
+        if let Err(e) = stream.write_all(lines.as_bytes()).await {
+            self.stream = None;
+            return Err(e.into());
+        }
+        Ok(())
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Postgres
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// INSERT statement for `rows` rows
+fn insert_statement(table: &str, rows: usize) -> String {
+    let mut sql = format!(
+        "INSERT INTO {} (time, point, value, text, quality, units) VALUES ",
+        table
+    );
+    for row in 0..rows {
+        if row > 0 {
+            sql.push_str(", ");
+        }
+        let n = row * 6;
+        let _ = write!(
+            sql,
+            "(${}, ${}, ${}, ${}, ${}, ${})",
+            n + 1,
+            n + 2,
+            n + 3,
+            n + 4,
+            n + 5,
+            n + 6
+        );
+    }
+    sql
+}
+
+fn postgres_error(e: tokio_postgres::Error) -> Error {
+    Error::Database(format!("Postgres: {}", e))
+}
+
+/// Batched INSERTs into a Postgres table, one transaction per write
+struct Postgres {
+    url: String,
+    table: String,
+    client: Option<tokio_postgres::Client>,
+}
+
+impl Postgres {
+    async fn connect(&self) -> Result<tokio_postgres::Client> {
+        let connect = tokio_postgres::connect(&self.url, tokio_postgres::NoTls);
+        let (client, connection) = tokio::time::timeout(CONNECT_TIMEOUT, connect)
+            .await
+            .map_err(|_| Error::Timeout)?
+            .map_err(postgres_error)?;
+        tokio::spawn(async move {
+            if let Err(e) = connection.await {
+                tracing::warn!("History Postgres connection closed: {}", e);
+            }
+        });
+        Ok(client)
+    }
+}
+
+#[async_trait]
+impl HistoryBackend for Postgres {
+    async fn write(&mut self, samples: &[(String, HistorySample)]) -> Result<()> {
+        if self.client.as_ref().is_none_or(|client| client.is_closed()) {
+            self.client = Some(self.connect().await?);
+        }
+        let Some(client) = &mut self.client else {
+            return Ok(());
+        };
+
+        let transaction = client.transaction().await.map_err(postgres_error)?;
+        for chunk in samples.chunks(INSERT_ROWS) {
+            let rows: Vec<_> = chunk
+                .iter()
+                .map(|(point, sample)| {
+                    let (number, text) = split_value(sample);
+                    (
+                        sample.timestamp,
+                        point.as_str(),
+                        number,
+                        text,
+                        quality_name(sample.quality),
+                        sample.units.map(|u| u.symbol()),
+                    )
+                })
+                .collect();
+            let mut params: Vec<&(dyn ToSql + Sync)> = Vec::with_capacity(rows.len() * 6);
+            for (time, point, number, text, quality, units) in &rows {
+                params.extend_from_slice(&[time, point, number, text, quality, units]);
+            }
+            transaction
+                .execute(&insert_statement(&self.table, rows.len()), &params)
+                .await
+                .map_err(postgres_error)?;
+        }
+        transaction.commit().await.map_err(postgres_error)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::points::EngineeringUnit;
+    use chrono::DateTime;
+    use tokio::io::AsyncReadExt;
+    use tokio::net::TcpListener;
+
+    fn sample(value: PropertyValue, units: Option<EngineeringUnit>) -> HistorySample {
+        HistorySample {
+            timestamp: DateTime::from_timestamp(1_700_000_000, 0).unwrap(),
+            value,
+            quality: PointQuality::Good,
+            units,
+        }
+    }
+
+    #[test]
+    fn test_line_protocol() {
+        let samples = vec![
+            (
+                "bldg 1/ahu,1/sat".to_string(),
+                sample(PropertyValue::Real(55.2), Some(EngineeringUnit::DegreesFahrenheit)),
+            ),
+            ("bldg/fan/cmd".to_string(), sample(PropertyValue::Boolean(true), None)),
+            ("bldg/nan".to_string(), sample(PropertyValue::Real(f32::NAN), None)),
+        ];
+
+        assert_eq!(
+            line_protocol("bms history", &samples),
+            "bms\\ history,point=bldg\\ 1/ahu\\,1/sat,quality=good,units=°F value=55.2 \
+             1700000000000000000\n\
+             bms\\ history,point=bldg/fan/cmd,quality=good value=1.0 1700000000000000000\n"
+        );
+
+        // Other values are a text field
+        let samples = [("p".to_string(), sample(PropertyValue::Null, None))];
+        let text = line_protocol("history", &samples);
+        assert!(text.starts_with("history,point=p,quality=good text=\""));
+    }
+
+    #[test]
+    fn test_postgres_config() {
+        let config = |table: &str| BackendConfig {
+            name: "tsdb".to_string(),
+            target: BackendTarget::Postgres {
+                url: "host=localhost".to_string(),
+                table: table.to_string(),
+            },
+        };
+        assert!(open(&config("metrics.history")).is_ok());
+        assert!(open(&config("history; DROP TABLE x")).is_err());
+
+        assert_eq!(
+            insert_statement("history", 2),
+            "INSERT INTO history (time, point, value, text, quality, units) VALUES \
+             ($1, $2, $3, $4, $5, $6), ($7, $8, $9, $10, $11, $12)"
+        );
+    }
+
+    /// Send a backend message
+    async fn send(socket: &mut TcpStream, tag: u8, body: &[u8]) {
+        let mut message = vec![tag];
+        message.extend_from_slice(&(body.len() as i32 + 4).to_be_bytes());
+        message.extend_from_slice(body);
+        socket.write_all(&message).await.unwrap();
+    }
+
+    /// Read a NUL-terminated string from a message body
+    fn read_str(body: &[u8], pos: &mut usize) -> String {
+        let end = *pos + body[*pos..].iter().position(|b| *b == 0).unwrap();
+        let text = String::from_utf8_lossy(&body[*pos..end]).to_string();
+        *pos = end + 1;
+        text
+    }
+
+    fn read_i16(body: &[u8], pos: &mut usize) -> i16 {
+        *pos += 2;
+        i16::from_be_bytes([body[*pos - 2], body[*pos - 1]])
+    }
+
+    /// A stand-in Postgres server for one connection: it trusts the client,
+    /// answers the simple and extended query protocols, and returns the
+    /// statements it was sent and the parameters of each bind
+    async fn fake_postgres(listener: TcpListener) -> (Vec<String>, Vec<Vec<Option<Vec<u8>>>>) {
+        let (mut socket, _) = listener.accept().await.unwrap();
+        let len = socket.read_i32().await.unwrap();
+        let mut startup = vec![0; len as usize - 4];
+        socket.read_exact(&mut startup).await.unwrap();
+        send(&mut socket, b'R', &0i32.to_be_bytes()).await;
+        send(&mut socket, b'Z', b"I").await;
+
+        let mut statements: Vec<String> = Vec::new();
+        let mut binds = Vec::new();
+        let mut status = b'I';
+        while let Ok(tag) = socket.read_u8().await {
+            let len = socket.read_i32().await.unwrap();
+            let mut body = vec![0; len as usize - 4];
+            socket.read_exact(&mut body).await.unwrap();
+            let mut pos = 0;
+            match tag {
+                // Simple query (BEGIN, COMMIT, ROLLBACK)
+                b'Q' => {
+                    let query = read_str(&body, &mut pos);
+                    status = if query == "BEGIN" { b'T' } else { b'I' };
+                    send(&mut socket, b'C', format!("{}\0", query).as_bytes()).await;
+                    send(&mut socket, b'Z', &[status]).await;
+                    statements.push(query);
+                }
+                // Parse: statement name, then the query
+                b'P' => {
+                    read_str(&body, &mut pos);
+                    statements.push(read_str(&body, &mut pos));
+                    send(&mut socket, b'1', &[]).await;
+                }
+                // Describe: the parameters are the columns of the INSERT, in order
+                b'D' => {
+                    let count = statements.last().unwrap().matches('$').count();
+                    let mut description = (count as i16).to_be_bytes().to_vec();
+                    for index in 0..count {
+                        let oid: u32 = [1184, 25, 701, 25, 25, 25][index % 6];
+                        description.extend_from_slice(&oid.to_be_bytes());
+                    }
+                    send(&mut socket, b't', &description).await;
+                    send(&mut socket, b'n', &[]).await;
+                }
+                // Bind: portal, statement, formats, then length-prefixed values
+                b'B' => {
+                    read_str(&body, &mut pos);
+                    read_str(&body, &mut pos);
+                    let formats = read_i16(&body, &mut pos);
+                    pos += formats as usize * 2;
+                    let mut params = Vec::new();
+                    for _ in 0..read_i16(&body, &mut pos) {
+                        let len = i32::from_be_bytes(body[pos..pos + 4].try_into().unwrap());
+                        pos += 4;
+                        if len < 0 {
+                            params.push(None);
+                        } else {
+                            params.push(Some(body[pos..pos + len as usize].to_vec()));
+                            pos += len as usize;
+                        }
+                    }
+                    binds.push(params);
+                    send(&mut socket, b'2', &[]).await;
+                }
+                b'E' => {
+                    let rows = binds.last().map_or(0, |params| params.len() / 6);
+                    send(&mut socket, b'C', format!("INSERT 0 {}\0", rows).as_bytes()).await;
+                }
+                b'C' => send(&mut socket, b'3', &[]).await,
+                b'S' => send(&mut socket, b'Z', &[status]).await,
+                b'X' => break,
+                _ => {}
+            }
+        }
+        (statements, binds)
+    }
+
+    #[tokio::test]
+    async fn test_postgres_inserts() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = tokio::spawn(fake_postgres(listener));
+
+        let mut backend = open(&BackendConfig {
+            name: "tsdb".to_string(),
+            target: BackendTarget::Postgres {
+                url: format!("host=127.0.0.1 port={} user=neo dbname=bms sslmode=disable", port),
+                table: "history".to_string(),
+            },
+        })
+        .unwrap();
+        let samples = [
+            (
+                "ahu-1/sat".to_string(),
+                sample(PropertyValue::Real(55.2), Some(EngineeringUnit::DegreesFahrenheit)),
+            ),
+            ("ahu-1/mode".to_string(), sample(PropertyValue::Null, None)),
+        ];
+        backend.write(&samples).await.unwrap();
+        drop(backend);
+
+        let (statements, binds) = tokio::time::timeout(Duration::from_secs(5), server)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            statements,
+            vec![
+                "BEGIN".to_string(),
+                insert_statement("history", 2),
+                "COMMIT".to_string(),
+            ]
+        );
+
+        // One bind of both rows: time, point, value, text, quality, units
+        assert_eq!(binds.len(), 1);
+        let params = &binds[0];
+        assert_eq!(params.len(), 12);
+        let micros_since_2000 = (1_700_000_000i64 - 946_684_800) * 1_000_000;
+        assert_eq!(params[0], Some(micros_since_2000.to_be_bytes().to_vec()));
+        assert_eq!(params[1], Some(b"ahu-1/sat".to_vec()));
+        assert_eq!(params[2], Some(55.2f64.to_be_bytes().to_vec()));
+        assert_eq!(params[3], None);
+        assert_eq!(params[4], Some(b"good".to_vec()));
+        assert_eq!(params[5], Some("°F".as_bytes().to_vec()));
+        assert_eq!(params[7], Some(b"ahu-1/mode".to_vec()));
+        assert_eq!(params[8], None);
+        assert!(params[9].is_some());
+        assert_eq!(params[11], None);
+    }
+}
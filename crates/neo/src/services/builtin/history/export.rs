@@ -0,0 +1,288 @@
+// History Export - Aligned history written to CSV or Parquet files
+//
+// Exports stream rows from the aligner straight into the file: CSV rows are
+// written through a buffered writer, Parquet rows are buffered one row group
+// at a time. The file is written with ".partial" appended to its name and
+// renamed once complete, so a failed export never leaves a truncated file behind.
+//
+// CSV has a "timestamp" column (RFC 3339) followed by one column per point,
+// empty where a point has no value. Parquet has a UTC microsecond timestamp
+// column and an optional double column per point.
+
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use chrono::{DateTime, SecondsFormat};
+use parquet::basic::{Compression, LogicalType, Repetition, TimeUnit, Type as PhysicalType};
+use parquet::data_type::{DoubleType, Int64Type};
+use parquet::file::properties::WriterProperties;
+use parquet::file::writer::SerializedFileWriter;
+use parquet::format::MicroSeconds;
+use parquet::schema::types::Type;
+use redb::ReadableTable;
+
+use super::align::{AlignSpec, RowSink, align};
+use crate::services::messages::{ExportFormat, HistoryRow};
+use crate::types::{Error, Result};
+
+/// Rows per Parquet row group (bounds export memory use)
+const ROW_GROUP_ROWS: usize = 65_536;
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Sinks
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Collects rows in memory, up to a limit
+pub(super) struct TableSink {
+    pub rows: Vec<HistoryRow>,
+    limit: usize,
+}
+
+impl TableSink {
+    pub fn new(limit: usize) -> Self {
+        Self {
+            rows: Vec::new(),
+            limit,
+        }
+    }
+}
+
+impl RowSink for TableSink {
+    fn row(&mut self, timestamp: i64, values: &[Option<f64>]) -> Result<bool> {
+        if self.rows.len() >= self.limit {
+            return Ok(false);
+        }
+        self.rows.push(HistoryRow {
+            timestamp: DateTime::from_timestamp_micros(timestamp).unwrap_or_default(),
+            values: values.to_vec(),
+        });
+        Ok(self.rows.len() < self.limit)
+    }
+}
+
+/// Quote a CSV field if needed
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Writes rows as CSV
+struct CsvSink<W: Write> {
+    writer: W,
+    line: String,
+}
+
+impl<W: Write> CsvSink<W> {
+    fn new(mut writer: W, points: &[String]) -> Result<Self> {
+        let mut header = String::from("timestamp");
+        for point in points {
+            header.push(',');
+            header.push_str(&csv_field(point));
+        }
+        writeln!(writer, "{}", header)?;
+        Ok(Self {
+            writer,
+            line: String::new(),
+        })
+    }
+
+    fn finish(mut self) -> Result<()> {
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+impl<W: Write> RowSink for CsvSink<W> {
+    fn row(&mut self, timestamp: i64, values: &[Option<f64>]) -> Result<bool> {
+        use std::fmt::Write as _;
+
+        self.line.clear();
+        let timestamp = DateTime::from_timestamp_micros(timestamp).unwrap_or_default();
+        self.line
+            .push_str(&timestamp.to_rfc3339_opts(SecondsFormat::AutoSi, true));
+        for value in values {
+            self.line.push(',');
+            if let Some(value) = value {
+                let _ = write!(self.line, "{}", value);
+            }
+        }
+        writeln!(self.writer, "{}", self.line)?;
+        Ok(true)
+    }
+}
+
+/// One optional double column of a Parquet row group
+#[derive(Default)]
+struct ParquetColumn {
+    values: Vec<f64>,
+    /// 1 where the row has a value, 0 where it is null
+    def_levels: Vec<i16>,
+}
+
+/// Writes rows as Parquet, one row group per `ROW_GROUP_ROWS` rows
+struct ParquetSink {
+    writer: SerializedFileWriter<BufWriter<File>>,
+    timestamps: Vec<i64>,
+    columns: Vec<ParquetColumn>,
+}
+
+fn parquet_error(e: parquet::errors::ParquetError) -> Error {
+    Error::Service(format!("Parquet error: {}", e))
+}
+
+impl ParquetSink {
+    fn new(file: BufWriter<File>, points: &[String]) -> Result<Self> {
+        let timestamp = Type::primitive_type_builder("timestamp", PhysicalType::INT64)
+            .with_repetition(Repetition::REQUIRED)
+            .with_logical_type(Some(LogicalType::Timestamp {
+                is_adjusted_to_u_t_c: true,
+                unit: TimeUnit::MICROS(MicroSeconds {}),
+            }))
+            .build()
+            .map_err(parquet_error)?;
+        let mut fields = vec![Arc::new(timestamp)];
+        for point in points {
+            let column = Type::primitive_type_builder(point, PhysicalType::DOUBLE)
+                .with_repetition(Repetition::OPTIONAL)
+                .build()
+                .map_err(parquet_error)?;
+            fields.push(Arc::new(column));
+        }
+        let schema = Type::group_type_builder("history")
+            .with_fields(fields)
+            .build()
+            .map_err(parquet_error)?;
+
+        let properties = WriterProperties::builder()
+            .set_compression(Compression::SNAPPY)
+            .build();
+        let writer = SerializedFileWriter::new(file, Arc::new(schema), Arc::new(properties))
+            .map_err(parquet_error)?;
+
+        Ok(Self {
+            writer,
+            timestamps: Vec::with_capacity(ROW_GROUP_ROWS),
+            columns: points.iter().map(|_| ParquetColumn::default()).collect(),
+        })
+    }
+
+    /// Write buffered rows as a row group
+    fn write_row_group(&mut self) -> Result<()> {
+        if self.timestamps.is_empty() {
+            return Ok(());
+        }
+
+        let mut row_group = self.writer.next_row_group().map_err(parquet_error)?;
+        let mut index = 0;
+        while let Some(mut column) = row_group.next_column().map_err(parquet_error)? {
+            if index == 0 {
+                column
+                    .typed::<Int64Type>()
+                    .write_batch(&self.timestamps, None, None)
+                    .map_err(parquet_error)?;
+            } else {
+                let data = &self.columns[index - 1];
+                column
+                    .typed::<DoubleType>()
+                    .write_batch(&data.values, Some(&data.def_levels), None)
+                    .map_err(parquet_error)?;
+            }
+            column.close().map_err(parquet_error)?;
+            index += 1;
+        }
+        row_group.close().map_err(parquet_error)?;
+
+        self.timestamps.clear();
+        for column in &mut self.columns {
+            column.values.clear();
+            column.def_levels.clear();
+        }
+        Ok(())
+    }
+
+    fn finish(mut self) -> Result<()> {
+        self.write_row_group()?;
+        let mut file = self.writer.into_inner().map_err(parquet_error)?;
+        file.flush()?;
+        Ok(())
+    }
+}
+
+impl RowSink for ParquetSink {
+    fn row(&mut self, timestamp: i64, values: &[Option<f64>]) -> Result<bool> {
+        self.timestamps.push(timestamp);
+        for (column, value) in self.columns.iter_mut().zip(values) {
+            match value {
+                Some(value) => {
+                    column.values.push(*value);
+                    column.def_levels.push(1);
+                }
+                None => column.def_levels.push(0),
+            }
+        }
+        if self.timestamps.len() >= ROW_GROUP_ROWS {
+            self.write_row_group()?;
+        }
+        Ok(true)
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Export
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Write aligned history to a file, returning the number of rows written
+pub(super) fn export(
+    table: &impl ReadableTable<(u32, i64), &'static [u8]>,
+    spec: &AlignSpec,
+    ids: &[Option<u32>],
+    format: ExportFormat,
+    path: &Path,
+) -> Result<u64> {
+    // "trend.csv.partial", so exports of one name in two formats never share it
+    let mut partial = path.as_os_str().to_owned();
+    partial.push(".partial");
+    let partial = PathBuf::from(partial);
+    let result = write_file(table, spec, ids, format, &partial);
+
+    match result {
+        Ok(rows) => {
+            std::fs::rename(&partial, path)?;
+            Ok(rows)
+        }
+        Err(e) => {
+            let _ = std::fs::remove_file(&partial);
+            Err(e)
+        }
+    }
+}
+
+fn write_file(
+    table: &impl ReadableTable<(u32, i64), &'static [u8]>,
+    spec: &AlignSpec,
+    ids: &[Option<u32>],
+    format: ExportFormat,
+    path: &Path,
+) -> Result<u64> {
+    let file = BufWriter::new(File::create(path)?);
+
+    match format {
+        ExportFormat::Csv => {
+            let mut sink = CsvSink::new(file, &spec.points)?;
+            let rows = align(table, spec, ids, &mut sink)?;
+            sink.finish()?;
+            Ok(rows)
+        }
+        ExportFormat::Parquet => {
+            let mut sink = ParquetSink::new(file, &spec.points)?;
+            let rows = align(table, spec, ids, &mut sink)?;
+            sink.finish()?;
+            Ok(rows)
+        }
+    }
+}
@@ -0,0 +1,184 @@
+// History Gaps - Holes and bad data in a point's history
+//
+// A gap is a period longer than the point's expected interval between two
+// consecutive samples (or between the range start and the first sample, or
+// the last sample and the range end). Gaps are reported from sample to
+// sample, clipped to the range, so a gap that started before the range
+// starts at the range start.
+//
+// Runs of Bad or Stale samples are reported as quality periods, from the
+// first sample of the run to the next sample with a different quality.
+//
+// Coverage is the share of the range backed by good data: each sample that
+// is not Bad or Stale covers the time until the next sample, up to the
+// expected interval.
+
+use chrono::{DateTime, Utc};
+
+use crate::services::messages::{GapReport, HistoryGap, QualityPeriod};
+use crate::types::PointQuality;
+
+fn timestamp(micros: i64) -> DateTime<Utc> {
+    DateTime::from_timestamp_micros(micros).unwrap_or_default()
+}
+
+fn is_bad(quality: PointQuality) -> bool {
+    matches!(quality, PointQuality::Bad | PointQuality::Stale)
+}
+
+/// Builds a gap report from samples in time order
+pub(super) struct GapScanner {
+    start: i64,
+    end: i64,
+    expected: i64,
+    /// Last sample seen, possibly from before the range
+    prev: Option<(i64, PointQuality)>,
+    /// Bad quality run in progress (start, quality, samples)
+    run: Option<(i64, PointQuality, u64)>,
+    samples: u64,
+    covered: i64,
+    gaps: Vec<HistoryGap>,
+    bad_quality: Vec<QualityPeriod>,
+}
+
+impl GapScanner {
+    /// Scan [start, end] (micros) with the last sample before the range, if any
+    pub fn new(start: i64, end: i64, expected: i64, before: Option<(i64, PointQuality)>) -> Self {
+        let run = before
+            .filter(|(_, quality)| is_bad(*quality))
+            .map(|(_, quality)| (start, quality, 0));
+
+        Self {
+            start,
+            end,
+            expected: expected.max(1),
+            prev: before,
+            run,
+            samples: 0,
+            covered: 0,
+            gaps: Vec::new(),
+            bad_quality: Vec::new(),
+        }
+    }
+
+    /// Account for the time from the previous sample up to `t`
+    fn advance(&mut self, t: i64) {
+        let since = self.prev.map(|(ts, _)| ts).unwrap_or(self.start);
+        if t - since > self.expected {
+            self.gaps.push(HistoryGap {
+                start: timestamp(since.max(self.start)),
+                end: timestamp(t),
+            });
+        }
+        if let Some((ts, _)) = self.prev.filter(|(_, q)| !is_bad(*q)) {
+            let covered = t.min(ts + self.expected) - ts.max(self.start);
+            self.covered += covered.max(0);
+        }
+    }
+
+    fn close_run(&mut self, t: i64) {
+        if let Some((start, quality, samples)) = self.run.take() {
+            self.bad_quality.push(QualityPeriod {
+                start: timestamp(start),
+                end: timestamp(t),
+                quality,
+                samples,
+            });
+        }
+    }
+
+    /// Add the next sample in the range
+    pub fn sample(&mut self, ts: i64, quality: PointQuality) {
+        self.advance(ts);
+        self.samples += 1;
+
+        match self.run {
+            Some((_, run_quality, ref mut samples)) if run_quality == quality => *samples += 1,
+            _ => {
+                self.close_run(ts);
+                if is_bad(quality) {
+                    self.run = Some((ts, quality, 1));
+                }
+            }
+        }
+        self.prev = Some((ts, quality));
+    }
+
+    pub fn finish(mut self, point: String) -> GapReport {
+        let length = self.end - self.start;
+        if length > 0 {
+            self.advance(self.end);
+        }
+        self.close_run(self.end);
+
+        let coverage_percent = if length > 0 {
+            self.covered as f64 / length as f64 * 100.0
+        } else {
+            100.0
+        };
+
+        GapReport {
+            point,
+            start: timestamp(self.start),
+            end: timestamp(self.end),
+            expected_interval_secs: (self.expected / 1_000_000) as u64,
+            samples: self.samples,
+            gaps: self.gaps,
+            bad_quality: self.bad_quality,
+            coverage_percent,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MIN: i64 = 60_000_000;
+
+    #[test]
+    fn test_gaps_quality_and_coverage() {
+        // Expected every 15 minutes over two hours
+        let before = Some((-5 * MIN, PointQuality::Good));
+        let mut scanner = GapScanner::new(0, 120 * MIN, 15 * MIN, before);
+        for (m, quality) in [
+            (10, PointQuality::Good),
+            // 40 minutes without a sample
+            (50, PointQuality::Bad),
+            (60, PointQuality::Bad),
+            (70, PointQuality::Stale),
+            (80, PointQuality::Good),
+            (90, PointQuality::Good),
+        ] {
+            scanner.sample(m * MIN, quality);
+        }
+        let report = scanner.finish("ahu-1/sat".to_string());
+
+        assert_eq!(report.samples, 6);
+        let gaps: Vec<_> = report
+            .gaps
+            .iter()
+            .map(|g| (g.start.timestamp() / 60, g.end.timestamp() / 60))
+            .collect();
+        // Nothing logged for the last 30 minutes either
+        assert_eq!(gaps, vec![(10, 50), (90, 120)]);
+
+        let bad: Vec<_> = report
+            .bad_quality
+            .iter()
+            .map(|p| (p.start.timestamp() / 60, p.end.timestamp() / 60, p.quality, p.samples))
+            .collect();
+        assert_eq!(bad, vec![(50, 70, PointQuality::Bad, 2), (70, 80, PointQuality::Stale, 1)]);
+
+        // Good data: 0-10 (from before the range), 10-25, 80-90, 90-105
+        let expected = (10 + 15 + 10 + 15) as f64 / 120.0 * 100.0;
+        assert!((report.coverage_percent - expected).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_no_samples() {
+        let report = GapScanner::new(0, 60 * MIN, 15 * MIN, None).finish("p".to_string());
+        assert_eq!(report.gaps.len(), 1);
+        assert_eq!(report.coverage_percent, 0.0);
+    }
+}
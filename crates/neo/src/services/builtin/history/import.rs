@@ -0,0 +1,574 @@
+// History Import - Bulk-loading samples from CSV and JSON-lines files
+//
+// An import reads a file from the import directory on a task of its own and
+// sends parsed samples to the actor in batches. Each batch is committed in
+// one write transaction that skips samples already stored for the same point
+// and timestamp, so re-running an import, or importing a file that overlaps
+// recorded history, only adds what is missing. Imported samples bypass
+// logging policies and the `sample_interval_ms` rate limit, and are rolled up
+// like live samples.
+//
+// CSV files have a header row naming the columns, in any order:
+//
+//   timestamp,point,value,quality,units
+//   2024-03-10 01:45:00,AHU-1/SAT,55.2,good,degrees-fahrenheit
+//
+// quality (default good) and units are optional; fields may be quoted, one
+// row per line. JSON-lines files have one object per line with the same
+// fields, where the value may be a number, boolean, numeric string or null.
+//
+// Timestamps may be RFC 3339, Unix epoch seconds or milliseconds, a common
+// layout ("2024-03-10 01:45:00", "03/10/2024 1:45 AM"), or match the
+// import's strftime format. Times without an offset are local to the import's
+// time zone (UTC by default). A time repeated when clocks go back is taken as
+// the first occurrence, unless the point's previous row is already at or past
+// it, so a file stepping through the repeated hour twice keeps both hours; a
+// time skipped when clocks go forward is a row error.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, LocalResult, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use kameo::actor::ActorRef;
+use serde::Deserialize;
+use tokio::io::{AsyncBufReadExt, BufReader};
+use uuid::Uuid;
+
+use super::{HistoryActor, HistoryMsg, HistoryReply};
+use crate::points::EngineeringUnit;
+use crate::services::messages::{HistorySample, ImportFormat};
+use crate::types::{Error, PointQuality, PropertyValue, Result};
+
+/// Samples sent to the actor per batch
+const IMPORT_BATCH: usize = 5_000;
+
+/// Row errors kept in an import's progress
+const MAX_ERRORS: usize = 20;
+
+/// Layouts tried for timestamps without an offset
+const NAIVE_FORMATS: [&str; 8] = [
+    "%Y-%m-%d %H:%M:%S%.f",
+    "%Y-%m-%dT%H:%M:%S%.f",
+    "%Y-%m-%d %H:%M",
+    "%Y-%m-%dT%H:%M",
+    "%m/%d/%Y %H:%M:%S",
+    "%m/%d/%Y %H:%M",
+    "%m/%d/%Y %I:%M:%S %p",
+    "%m/%d/%Y %I:%M %p",
+];
+
+/// How an import file is read
+#[derive(Debug, Clone)]
+pub(super) struct ImportOptions {
+    pub format: ImportFormat,
+    /// Source point names to point paths
+    pub point_map: HashMap<String, String>,
+    /// Skip rows for points not in the map (otherwise names are kept)
+    pub mapped_only: bool,
+    /// Zone of timestamps without an offset
+    pub timezone: Tz,
+    /// strftime format of the timestamp column
+    pub timestamp_format: Option<String>,
+}
+
+/// Parse a time zone name (e.g., "America/Chicago"), UTC when None
+pub(super) fn parse_timezone(name: Option<&str>) -> Result<Tz> {
+    match name {
+        None => Ok(Tz::UTC),
+        Some(name) => name
+            .parse::<Tz>()
+            .map_err(|_| Error::Config(format!("Unknown time zone '{}'", name))),
+    }
+}
+
+/// A row as read from the file, before mapping and timestamp parsing
+struct RawRow {
+    timestamp: String,
+    point: String,
+    value: PropertyValue,
+    quality: PointQuality,
+    units: Option<EngineeringUnit>,
+}
+
+/// Result of parsing a row (errors are reported with the line number)
+type RowResult<T> = std::result::Result<T, String>;
+
+fn parse_value(text: &str) -> RowResult<PropertyValue> {
+    let text = text.trim();
+    if text.is_empty() || text.eq_ignore_ascii_case("null") {
+        return Ok(PropertyValue::Null);
+    }
+    if let Ok(value) = text.parse::<f64>() {
+        if !value.is_finite() {
+            return Err(format!("Invalid value '{}'", text));
+        }
+        return Ok(PropertyValue::Real(value as f32));
+    }
+    match text.to_ascii_lowercase().as_str() {
+        "true" | "on" | "active" => Ok(PropertyValue::Boolean(true)),
+        "false" | "off" | "inactive" => Ok(PropertyValue::Boolean(false)),
+        _ => Err(format!("Invalid value '{}'", text)),
+    }
+}
+
+fn parse_quality(text: Option<&str>) -> RowResult<PointQuality> {
+    let Some(text) = text.map(str::trim).filter(|t| !t.is_empty()) else {
+        return Ok(PointQuality::Good);
+    };
+    match text.to_ascii_lowercase().as_str() {
+        "good" | "ok" => Ok(PointQuality::Good),
+        "bad" | "fault" => Ok(PointQuality::Bad),
+        "uncertain" => Ok(PointQuality::Uncertain),
+        "stale" => Ok(PointQuality::Stale),
+        _ => Err(format!("Invalid quality '{}'", text)),
+    }
+}
+
+fn parse_units(text: Option<&str>) -> RowResult<Option<EngineeringUnit>> {
+    text.map(str::trim)
+        .filter(|t| !t.is_empty())
+        .map(|t| t.parse::<EngineeringUnit>().map_err(|e| e.to_string()))
+        .transpose()
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// CSV
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Split a CSV line into fields, unquoting quoted fields
+fn split_csv(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match (c, quoted) {
+            ('"', true) if chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            ('"', true) => quoted = false,
+            ('"', false) if field.is_empty() => quoted = true,
+            (',', false) => fields.push(std::mem::take(&mut field)),
+            _ => field.push(c),
+        }
+    }
+    fields.push(field);
+    fields
+}
+
+/// Column positions from a CSV header row
+struct CsvColumns {
+    timestamp: usize,
+    point: usize,
+    value: usize,
+    quality: Option<usize>,
+    units: Option<usize>,
+}
+
+impl CsvColumns {
+    fn from_header(line: &str) -> Result<Self> {
+        let names: Vec<String> = split_csv(line)
+            .iter()
+            .map(|name| name.trim().to_ascii_lowercase())
+            .collect();
+        let find = |name: &str| names.iter().position(|n| n == name);
+        let require = |name: &str| {
+            find(name).ok_or_else(|| Error::Config(format!("CSV header has no '{}' column", name)))
+        };
+
+        Ok(Self {
+            timestamp: require("timestamp")?,
+            point: require("point")?,
+            value: require("value")?,
+            quality: find("quality"),
+            units: find("units"),
+        })
+    }
+
+    fn parse(&self, line: &str) -> RowResult<RawRow> {
+        let fields = split_csv(line);
+        let field = |index: usize| {
+            fields
+                .get(index)
+                .map(String::as_str)
+                .ok_or_else(|| format!("Expected at least {} fields", index + 1))
+        };
+        let optional = |index: Option<usize>| index.and_then(|i| fields.get(i)).map(String::as_str);
+
+        Ok(RawRow {
+            timestamp: field(self.timestamp)?.to_string(),
+            point: field(self.point)?.trim().to_string(),
+            value: parse_value(field(self.value)?)?,
+            quality: parse_quality(optional(self.quality))?,
+            units: parse_units(optional(self.units))?,
+        })
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// JSON lines
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[derive(Deserialize)]
+struct JsonRow {
+    timestamp: serde_json::Value,
+    point: String,
+    #[serde(default)]
+    value: serde_json::Value,
+    #[serde(default)]
+    quality: Option<String>,
+    #[serde(default)]
+    units: Option<String>,
+}
+
+fn parse_json(line: &str) -> RowResult<RawRow> {
+    let row: JsonRow =
+        serde_json::from_str(line).map_err(|e| format!("Invalid JSON: {}", e))?;
+
+    let timestamp = match row.timestamp {
+        serde_json::Value::String(text) => text,
+        serde_json::Value::Number(number) => number.to_string(),
+        other => return Err(format!("Invalid timestamp {}", other)),
+    };
+    let value = match row.value {
+        serde_json::Value::Null => PropertyValue::Null,
+        serde_json::Value::Bool(value) => PropertyValue::Boolean(value),
+        serde_json::Value::Number(number) => parse_value(&number.to_string())?,
+        serde_json::Value::String(text) => parse_value(&text)?,
+        other => return Err(format!("Invalid value {}", other)),
+    };
+
+    Ok(RawRow {
+        timestamp,
+        point: row.point,
+        value,
+        quality: parse_quality(row.quality.as_deref())?,
+        units: parse_units(row.units.as_deref())?,
+    })
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Timestamps and point mapping
+// ─────────────────────────────────────────────────────────────────────────────
+
+impl ImportOptions {
+    /// Interpret a time without an offset in the import's time zone, given
+    /// the timestamp of the point's previous row
+    fn localize(
+        &self,
+        naive: NaiveDateTime,
+        previous: Option<DateTime<Utc>>,
+    ) -> RowResult<DateTime<Utc>> {
+        match self.timezone.from_local_datetime(&naive) {
+            LocalResult::Single(local) => Ok(local.with_timezone(&Utc)),
+            // Repeated when clocks go back: the second occurrence once the
+            // point's rows have reached the first
+            LocalResult::Ambiguous(first, second) => {
+                let first = first.with_timezone(&Utc);
+                if previous.is_some_and(|previous| previous >= first) {
+                    Ok(second.with_timezone(&Utc))
+                } else {
+                    Ok(first)
+                }
+            }
+            LocalResult::None => Err(format!(
+                "{} does not exist in {} (clocks went forward)",
+                naive, self.timezone
+            )),
+        }
+    }
+
+    fn parse_timestamp(
+        &self,
+        text: &str,
+        previous: Option<DateTime<Utc>>,
+    ) -> RowResult<DateTime<Utc>> {
+        let text = text.trim();
+
+        if let Some(format) = &self.timestamp_format {
+            if let Ok(timestamp) = DateTime::parse_from_str(text, format) {
+                return Ok(timestamp.with_timezone(&Utc));
+            }
+            let naive = NaiveDateTime::parse_from_str(text, format)
+                .map_err(|e| format!("Invalid timestamp '{}': {}", text, e))?;
+            return self.localize(naive, previous);
+        }
+
+        if let Ok(timestamp) = DateTime::parse_from_rfc3339(text) {
+            return Ok(timestamp.with_timezone(&Utc));
+        }
+        if let Ok(epoch) = text.parse::<f64>() {
+            // Epoch seconds reach 1e11 in the year 5138, so larger values are millis
+            let micros = if epoch.abs() >= 1e11 { epoch * 1e3 } else { epoch * 1e6 };
+            return DateTime::from_timestamp_micros(micros as i64)
+                .ok_or_else(|| format!("Timestamp '{}' out of range", text));
+        }
+        NAIVE_FORMATS
+            .iter()
+            .find_map(|format| NaiveDateTime::parse_from_str(text, format).ok())
+            .ok_or_else(|| format!("Unrecognized timestamp '{}'", text))
+            .and_then(|naive| self.localize(naive, previous))
+    }
+
+    /// Map a row to its point path and sample (None = unmapped point)
+    ///
+    /// `latest` holds the timestamp of each point's previous row.
+    fn sample(
+        &self,
+        row: RawRow,
+        latest: &mut HashMap<String, DateTime<Utc>>,
+    ) -> RowResult<Option<(String, HistorySample)>> {
+        let point = match self.point_map.get(&row.point) {
+            Some(path) => path.clone(),
+            None if self.mapped_only => return Ok(None),
+            None if row.point.is_empty() => return Err("Missing point".to_string()),
+            None => row.point,
+        };
+
+        let timestamp = self.parse_timestamp(&row.timestamp, latest.get(&point).copied())?;
+        latest.insert(point.clone(), timestamp);
+        Ok(Some((
+            point,
+            HistorySample {
+                timestamp,
+                value: row.value,
+                quality: row.quality,
+                units: row.units,
+            },
+        )))
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Import task
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Reader-side progress, sent with each batch
+#[derive(Debug, Default)]
+struct ReadProgress {
+    bytes_read: u64,
+    rows_read: u64,
+    skipped: u64,
+    /// Row errors not yet sent
+    errors: Vec<String>,
+    error_count: usize,
+}
+
+impl ReadProgress {
+    fn row_failed(&mut self, line: u64, e: String) {
+        self.skipped += 1;
+        if self.error_count < MAX_ERRORS {
+            self.errors.push(format!("line {}: {}", line, e));
+        }
+        self.error_count += 1;
+    }
+}
+
+/// Send a batch to the actor to be stored
+async fn send_batch(
+    actor_ref: &ActorRef<HistoryActor>,
+    import_id: Uuid,
+    samples: Vec<(String, HistorySample)>,
+    progress: &mut ReadProgress,
+) -> Result<()> {
+    let reply = actor_ref
+        .ask(HistoryMsg::ImportBatch {
+            import_id,
+            samples,
+            bytes_read: progress.bytes_read,
+            rows_read: progress.rows_read,
+            skipped: progress.skipped,
+            errors: std::mem::take(&mut progress.errors),
+        })
+        .await
+        .map_err(|e| Error::Actor(e.to_string()))?;
+
+    match reply {
+        HistoryReply::ImportUpdated { stored: true } => Ok(()),
+        _ => Err(Error::Service("Import batch was not stored".to_string())),
+    }
+}
+
+async fn read_file(
+    actor_ref: &ActorRef<HistoryActor>,
+    import_id: Uuid,
+    path: &Path,
+    options: &ImportOptions,
+) -> Result<()> {
+    let file = tokio::fs::File::open(path).await?;
+    let mut reader = BufReader::new(file);
+    let mut progress = ReadProgress::default();
+    let mut columns = None;
+    let mut batch = Vec::with_capacity(IMPORT_BATCH);
+    let mut line = String::new();
+    let mut line_number = 0;
+    let mut latest = HashMap::new();
+
+    loop {
+        line.clear();
+        let read = reader.read_line(&mut line).await?;
+        if read == 0 {
+            break;
+        }
+        line_number += 1;
+        progress.bytes_read += read as u64;
+
+        let text = line.trim_end_matches(['\r', '\n']).trim_start_matches('\u{feff}');
+        if text.trim().is_empty() {
+            continue;
+        }
+
+        let row = match (options.format, &columns) {
+            (ImportFormat::Csv, None) => {
+                columns = Some(CsvColumns::from_header(text)?);
+                continue;
+            }
+            (ImportFormat::Csv, Some(columns)) => columns.parse(text),
+            (ImportFormat::JsonLines, _) => parse_json(text),
+        };
+
+        progress.rows_read += 1;
+        match row.and_then(|row| options.sample(row, &mut latest)) {
+            Ok(Some(sample)) => batch.push(sample),
+            Ok(None) => progress.skipped += 1,
+            Err(e) => progress.row_failed(line_number, e),
+        }
+
+        if batch.len() >= IMPORT_BATCH {
+            send_batch(actor_ref, import_id, std::mem::take(&mut batch), &mut progress).await?;
+        }
+    }
+
+    send_batch(actor_ref, import_id, batch, &mut progress).await
+}
+
+/// Read an import file, storing its samples through the actor
+///
+/// The actor is told when the import finishes, with the error that stopped
+/// it, if any.
+pub(super) async fn run(
+    actor_ref: ActorRef<HistoryActor>,
+    import_id: Uuid,
+    path: PathBuf,
+    options: ImportOptions,
+) {
+    let error = read_file(&actor_ref, import_id, &path, &options)
+        .await
+        .err()
+        .map(|e| e.to_string());
+
+    if let Err(e) = actor_ref
+        .tell(HistoryMsg::ImportFinished { import_id, error })
+        .await
+    {
+        tracing::warn!("Failed to report history import {} finished: {}", import_id, e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(timezone: &str) -> ImportOptions {
+        ImportOptions {
+            format: ImportFormat::Csv,
+            point_map: HashMap::new(),
+            mapped_only: false,
+            timezone: parse_timezone(Some(timezone)).unwrap(),
+            timestamp_format: None,
+        }
+    }
+
+    #[test]
+    fn test_split_csv() {
+        assert_eq!(split_csv("a,b,,c"), vec!["a", "b", "", "c"]);
+        assert_eq!(
+            split_csv("\"AHU-1, SAT\",\"say \"\"hi\"\"\",1"),
+            vec!["AHU-1, SAT", "say \"hi\"", "1"]
+        );
+    }
+
+    #[test]
+    fn test_timestamps_and_time_zones() {
+        let utc = options("UTC");
+        let chicago = options("America/Chicago");
+        let at = |text: &str| DateTime::parse_from_rfc3339(text).unwrap().with_timezone(&Utc);
+
+        assert_eq!(
+            utc.parse_timestamp("2024-03-10T01:45:00-06:00", None).unwrap(),
+            at("2024-03-10T07:45:00Z")
+        );
+        assert_eq!(utc.parse_timestamp("1710055500", None).unwrap(), at("2024-03-10T07:25:00Z"));
+        assert_eq!(utc.parse_timestamp("1710055500000", None).unwrap(), at("2024-03-10T07:25:00Z"));
+        assert_eq!(
+            chicago.parse_timestamp("03/10/2024 1:45 AM", None).unwrap(),
+            at("2024-03-10T07:45:00Z")
+        );
+
+        // 2:30 AM does not exist on the spring-forward day
+        assert!(chicago.parse_timestamp("2024-03-10 02:30:00", None).is_err());
+        // 1:30 AM happens twice on the fall-back day: the first is CDT
+        assert_eq!(
+            chicago.parse_timestamp("2024-11-03 01:30:00", None).unwrap(),
+            at("2024-11-03T06:30:00Z")
+        );
+
+        let custom = ImportOptions {
+            timestamp_format: Some("%d.%m.%Y %H:%M".to_string()),
+            ..chicago
+        };
+        assert_eq!(
+            custom.parse_timestamp("10.03.2024 03:00", None).unwrap(),
+            at("2024-03-10T08:00:00Z")
+        );
+        assert!(parse_timezone(Some("Mars/Olympus")).is_err());
+    }
+
+    #[test]
+    fn test_repeated_hour_when_clocks_go_back() {
+        let chicago = options("America/Chicago");
+        let at = |text: &str| DateTime::parse_from_rfc3339(text).unwrap().with_timezone(&Utc);
+        let row = |point: &str, timestamp: &str| RawRow {
+            timestamp: timestamp.to_string(),
+            point: point.to_string(),
+            value: PropertyValue::Real(1.0),
+            quality: PointQuality::Good,
+            units: None,
+        };
+
+        // 01:00-01:59 happens twice: once in CDT, then again in CST
+        let rows = [
+            ("ahu/sat", "2024-11-03 00:30:00"),
+            ("ahu/sat", "2024-11-03 01:00:00"),
+            ("ahu/sat", "2024-11-03 01:30:00"),
+            ("ahu/sat", "2024-11-03 01:00:00"),
+            ("ahu/sat", "2024-11-03 01:30:00"),
+            ("ahu/sat", "2024-11-03 02:00:00"),
+            ("ahu/fan", "2024-11-03 01:00:00"),
+        ];
+        let mut latest = HashMap::new();
+        let timestamps: Vec<DateTime<Utc>> = rows
+            .iter()
+            .map(|(point, timestamp)| {
+                let sample = chicago.sample(row(point, timestamp), &mut latest).unwrap();
+                sample.unwrap().1.timestamp
+            })
+            .collect();
+        assert_eq!(
+            timestamps,
+            vec![
+                at("2024-11-03T05:30:00Z"),
+                at("2024-11-03T06:00:00Z"),
+                at("2024-11-03T06:30:00Z"),
+                at("2024-11-03T07:00:00Z"),
+                at("2024-11-03T07:30:00Z"),
+                at("2024-11-03T08:00:00Z"),
+                // Each point's rows are followed on their own
+                at("2024-11-03T06:00:00Z"),
+            ]
+        );
+    }
+}
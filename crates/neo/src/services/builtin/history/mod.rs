@@ -0,0 +1,3118 @@
+// History Service - Time-series storage for point data
+//
+// Actor-based history service that stores point values in a history store
+// and provides querying capabilities. Stores implement HistoryStore (see
+// store); the default keeps history in an embedded redb database.
+//
+// Which values are recorded is set by per-point logging policies (see
+// policy): disabled, change of value with a deadband, or clock-aligned
+// interval sampling, each with an optional heartbeat.
+//
+// Incoming samples are buffered and committed in one write transaction when
+// the buffer reaches `batch_size` or on the periodic flush. Queries flush
+// first, so they always see every accepted sample.
+//
+// Queries can downsample raw samples (LTTB) or aggregate them into fixed
+// time buckets (see aggregate). Several points can be queried or exported
+// (CSV, Parquet) as one table aligned on a common time grid (see align and
+// export); exports stream on a blocking thread from their own snapshot of
+// the store, so the actor keeps accepting samples meanwhile. Hourly and
+// daily rollups are maintained as samples are committed (see rollup); an
+// aggregate query whose interval is a whole number of hours or days reads the
+// coarsest rollup that fits, and only scans raw samples for the partial
+// buckets at the edges of the range.
+//
+// Trends from other systems are bulk-loaded from CSV and JSON-lines files
+// (see import). Imported samples bypass logging policies and rate limiting,
+// and samples already stored for a point and timestamp are kept.
+//
+// Gap reports list where a point's history has holes or bad-quality data
+// (see gaps). Points whose policy sets `stale_after_secs` are watched as they
+// log: HistoryStale is published when one stops, and HistoryResumed when it
+// logs again, so alarms can be raised on trends going quiet.
+//
+// Subscriptions stream the stored samples of a set of points from a start
+// time, then the samples recorded for them from then on, over a channel (see
+// stream; the backfill is capped and the channel bounded), so a trend can
+// show "the last hour, then live" without a gap or duplicate where the two
+// meet.
+//
+// Committed samples can also be forwarded to external time-series stores
+// (InfluxDB line protocol, Postgres/TimescaleDB; see backend), buffered in an
+// outbox per backend in the store while the target is unreachable.
+//
+// Samples older than the retention window (per point, see retention
+// overrides) are deleted by a background purge task in bounded batches, one
+// actor message per batch, so sample writes interleave with a long purge.
+// Rollups have their own retention, so raw samples can be purged sooner while
+// long-term trends stay available.
+
+mod aggregate;
+mod align;
+mod backend;
+mod codec;
+mod export;
+mod gaps;
+mod import;
+mod policy;
+mod rollup;
+mod store;
+mod stream;
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use kameo::actor::ActorRef;
+use kameo::message::{Context, Message};
+use kameo_actors::pubsub::Publish;
+use serde::{Deserialize, Serialize};
+use tokio::sync::{mpsc, oneshot};
+use uuid::Uuid;
+use wildmatch::WildMatch;
+
+use self::aggregate::{Aggregator, lttb, parse_interval};
+use self::align::AlignSpec;
+pub use self::backend::{BackendConfig, BackendTarget};
+use self::backend::{ForwardJob, ForwardOutcome, ForwardTarget};
+use self::export::TableSink;
+use self::gaps::GapScanner;
+use self::import::{ImportOptions, parse_timezone};
+pub use self::policy::{Deadband, LoggingMode, LoggingPolicy};
+use self::policy::{Observed, PointLog};
+use self::rollup::{ROLLUP_FUNCTIONS, RollupLevel, RollupRecord};
+use self::store::{HistoryStore, PendingSample, PurgeCursor, PurgeTable, RedbStore};
+use self::stream::{BACKFILL_CHUNK, MAX_BACKFILL_SAMPLES, Subscription};
+use crate::actors::PubSubBroker;
+use crate::messages::Event;
+use crate::points::{EngineeringUnit, TagFilter, TagStore};
+use crate::services::actor::{ServiceMsg, ServiceReply, ServiceStateTracker};
+use crate::services::messages::{
+    Aggregation, ExportFormat, GapReport, HistoryBucket, HistorySample, HistoryStreamItem,
+    HistoryTable, ImportProgress, ImportState, Interpolation, PointHistory, ServiceRequest,
+    ServiceResponse,
+};
+use crate::types::{Error, PointQuality, PropertyValue, Result, ServiceState};
+
+/// Configuration for the History Service
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryConfig {
+    /// Path to the database file
+    pub db_path: String,
+    /// Number of days to retain history (0 = keep forever)
+    pub retention_days: u32,
+    /// Minimum interval between samples in milliseconds (change-driven logging)
+    pub sample_interval_ms: u64,
+    /// Per-point logging policies, first match wins (unmatched points record
+    /// every change)
+    #[serde(default)]
+    pub policies: Vec<LoggingPolicy>,
+    /// Per-point retention, first matching pattern wins (e.g., 7 years for "*/meter/*")
+    #[serde(default)]
+    pub retention_overrides: Vec<RetentionOverride>,
+    /// Seconds between purge runs
+    #[serde(default = "default_purge_interval_secs")]
+    pub purge_interval_secs: u64,
+    /// Maximum samples deleted per purge batch
+    #[serde(default = "default_purge_batch_size")]
+    pub purge_batch_size: usize,
+    /// Compact the database file after a purge run that deleted samples
+    #[serde(default)]
+    pub compact_after_purge: bool,
+    /// Buffered samples that trigger an immediate commit
+    #[serde(default = "default_batch_size")]
+    pub batch_size: usize,
+    /// Milliseconds between commits of buffered samples
+    #[serde(default = "default_flush_interval_ms")]
+    pub flush_interval_ms: u64,
+    /// Pre-computed hourly and daily rollups
+    #[serde(default)]
+    pub rollups: RollupConfig,
+    /// Directory export files are written to
+    #[serde(default = "default_export_dir")]
+    pub export_dir: String,
+    /// Directory import files are read from
+    #[serde(default = "default_import_dir")]
+    pub import_dir: String,
+    /// Longest expected time between samples for gap reports, for points whose
+    /// logging policy does not set one
+    #[serde(default = "default_expected_interval_secs")]
+    pub expected_interval_secs: u64,
+    /// External stores committed samples are forwarded to
+    #[serde(default)]
+    pub backends: Vec<BackendConfig>,
+    /// Milliseconds between forwarding runs
+    #[serde(default = "default_forward_interval_ms")]
+    pub forward_interval_ms: u64,
+    /// Maximum samples sent to a backend per forwarding run
+    #[serde(default = "default_forward_batch_size")]
+    pub forward_batch_size: usize,
+}
+
+fn default_purge_interval_secs() -> u64 {
+    3600
+}
+
+fn default_purge_batch_size() -> usize {
+    1000
+}
+
+fn default_batch_size() -> usize {
+    500
+}
+
+fn default_flush_interval_ms() -> u64 {
+    1000
+}
+
+fn default_export_dir() -> String {
+    "./data/exports".to_string()
+}
+
+fn default_import_dir() -> String {
+    "./data/imports".to_string()
+}
+
+fn default_expected_interval_secs() -> u64 {
+    900
+}
+
+fn default_forward_interval_ms() -> u64 {
+    5000
+}
+
+fn default_forward_batch_size() -> usize {
+    5000
+}
+
+impl Default for HistoryConfig {
+    fn default() -> Self {
+        Self {
+            db_path: "./data/history.redb".to_string(),
+            retention_days: 365,
+            sample_interval_ms: 1000,
+            policies: Vec::new(),
+            retention_overrides: Vec::new(),
+            purge_interval_secs: default_purge_interval_secs(),
+            purge_batch_size: default_purge_batch_size(),
+            compact_after_purge: false,
+            batch_size: default_batch_size(),
+            flush_interval_ms: default_flush_interval_ms(),
+            rollups: RollupConfig::default(),
+            export_dir: default_export_dir(),
+            import_dir: default_import_dir(),
+            expected_interval_secs: default_expected_interval_secs(),
+            backends: Vec::new(),
+            forward_interval_ms: default_forward_interval_ms(),
+            forward_batch_size: default_forward_batch_size(),
+        }
+    }
+}
+
+impl HistoryConfig {
+    /// Check the logging policies
+    pub fn validate(&self) -> Result<()> {
+        self.policies.iter().try_for_each(LoggingPolicy::validate)
+    }
+
+    /// Retention in days for a point (0 = keep forever)
+    fn retention_days_for(&self, point: &str) -> u32 {
+        self.retention_overrides
+            .iter()
+            .find(|o| WildMatch::new(&o.pattern).matches(point))
+            .map(|o| o.days)
+            .unwrap_or(self.retention_days)
+    }
+
+    /// Retention cutoff (micros) of a point's rows in a table, or None to
+    /// keep them
+    fn purge_cutoff(&self, table: PurgeTable, point: &str, now: DateTime<Utc>) -> Option<i64> {
+        let days = match table {
+            PurgeTable::Samples => self.retention_days_for(point),
+            PurgeTable::Rollup(_) if !self.rollups.enabled => 0,
+            PurgeTable::Rollup(level) => self.rollups.retention_days(level),
+        };
+        match days {
+            0 => None,
+            days => Some((now - chrono::Duration::days(days as i64)).timestamp_micros()),
+        }
+    }
+}
+
+/// Retention override for points matching a glob pattern
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetentionOverride {
+    /// Point path pattern (e.g., "*/zone-*/temp")
+    pub pattern: String,
+    /// Number of days to retain (0 = keep forever)
+    pub days: u32,
+}
+
+/// Rollup settings
+///
+/// Rollups are rebuilt from raw samples when the service starts without rollup
+/// tables. Samples committed while rollups are disabled are not rolled up.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RollupConfig {
+    /// Maintain hourly and daily rollups and use them for aggregate queries
+    pub enabled: bool,
+    /// Days to retain hourly rollups (0 = keep forever)
+    pub hourly_retention_days: u32,
+    /// Days to retain daily rollups (0 = keep forever)
+    pub daily_retention_days: u32,
+}
+
+impl Default for RollupConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            hourly_retention_days: 730,
+            daily_retention_days: 0,
+        }
+    }
+}
+
+impl RollupConfig {
+    /// Retention in days for a rollup level (0 = keep forever)
+    fn retention_days(&self, level: RollupLevel) -> u32 {
+        match level {
+            RollupLevel::Hourly => self.hourly_retention_days,
+            RollupLevel::Daily => self.daily_retention_days,
+        }
+    }
+}
+
+/// Purge statistics reported in the service status
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PurgeStats {
+    /// Purge runs completed
+    pub runs: u64,
+    /// Samples and rollup rows deleted since the service started
+    pub total_deleted: u64,
+    /// Samples and rollup rows deleted by the last completed run
+    pub last_run_deleted: u64,
+    pub last_run_started: Option<DateTime<Utc>>,
+    pub last_run_completed: Option<DateTime<Utc>>,
+    /// Duration of the last completed run in milliseconds
+    pub last_run_ms: u64,
+    pub last_compaction: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+}
+
+/// Write statistics reported in the service status
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct WriteStats {
+    /// Samples committed since the service started
+    pub samples_written: u64,
+    /// Write transactions committed
+    pub commits: u64,
+    /// Samples in the last commit
+    pub last_commit_samples: usize,
+    /// Duration of the last commit in milliseconds
+    pub last_commit_ms: u64,
+    /// Samples lost to failed commits
+    pub failed_samples: u64,
+}
+
+/// State of a purge run in progress
+struct PurgeRun {
+    started: DateTime<Utc>,
+    started_at: Instant,
+    deleted: u64,
+    cursor: PurgeCursor,
+}
+
+/// Convert a sample to the requested units
+///
+/// Samples recorded without units, and non-numeric samples, are returned as-is.
+/// Samples whose units do not convert to the requested ones (the point's units
+/// changed) are skipped rather than failing the whole query.
+fn convert_sample(
+    mut sample: HistorySample,
+    units: Option<EngineeringUnit>,
+) -> Option<HistorySample> {
+    let (Some(from), Some(to)) = (sample.units, units) else {
+        return Some(sample);
+    };
+    if matches!(sample.value, PropertyValue::Real(_) | PropertyValue::Unsigned(_)) {
+        match from.convert_property(&sample.value, to) {
+            Ok(value) => sample.value = value,
+            Err(e) => {
+                tracing::debug!("Skipping history sample at {}: {}", sample.timestamp, e);
+                return None;
+            }
+        }
+        sample.units = Some(to);
+    }
+    Some(sample)
+}
+
+/// Resolve a bare file name (no directories, no "..") within a directory,
+/// creating the directory if needed
+fn file_in_dir(dir: &str, file_name: &str) -> Result<PathBuf> {
+    if Path::new(file_name).file_name() != Some(std::ffi::OsStr::new(file_name)) {
+        return Err(Error::Config(format!("Invalid file name '{}'", file_name)));
+    }
+    let dir = PathBuf::from(dir);
+    std::fs::create_dir_all(&dir)?;
+    Ok(dir.join(file_name))
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// History Actor Messages
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// History-specific messages (beyond common ServiceMsg)
+#[derive(Debug)]
+pub enum HistoryMsg {
+    /// Query history samples for a point
+    QueryHistory {
+        point: String,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        limit: Option<u32>,
+        /// Convert samples to these units
+        units: Option<EngineeringUnit>,
+        /// Downsample to at most this many samples (LTTB)
+        downsample: Option<u32>,
+        reply: oneshot::Sender<Result<Vec<HistorySample>>>,
+    },
+    /// Aggregate history samples for a point into fixed time buckets
+    AggregateHistory {
+        point: String,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        /// Bucket width (e.g., "15m", "1h", "1d")
+        interval: String,
+        /// Aggregates to compute (all when empty)
+        functions: Vec<Aggregation>,
+        units: Option<EngineeringUnit>,
+        reply: oneshot::Sender<Result<Vec<HistoryBucket>>>,
+    },
+    /// Query several points aligned on a common time grid
+    QueryTable {
+        points: Vec<String>,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        /// Row spacing (None = a row at every sample timestamp)
+        interval: Option<String>,
+        interpolation: Interpolation,
+        units: Option<EngineeringUnit>,
+        limit: Option<u32>,
+        reply: oneshot::Sender<Result<HistoryTable>>,
+    },
+    /// Query historical data for all points matching a tag filter
+    QueryHistoryByTags {
+        filter: String,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        limit: Option<u32>,
+        units: Option<EngineeringUnit>,
+        reply: oneshot::Sender<Result<Vec<PointHistory>>>,
+    },
+    /// Get retention configuration
+    GetRetention,
+    /// Set retention days
+    SetRetention { days: u32 },
+    /// Delete one batch of samples older than their retention window
+    PurgeBatch,
+    /// Compact the database file to reclaim space freed by purging
+    Compact {
+        reply: oneshot::Sender<Result<bool>>,
+    },
+    /// Commit buffered samples (sent periodically by the flush task)
+    Flush,
+    /// Record interval samples and heartbeats that are due (sent periodically
+    /// by the policy task)
+    SamplePolicies,
+    /// Send queued samples to backends (sent periodically by the forward task)
+    Forward,
+    /// A batch sent to a backend was written or failed (sent by the task
+    /// that wrote it)
+    ForwardDone {
+        backend: String,
+        /// Outbox keys of the batch
+        sequences: Vec<u64>,
+        samples: usize,
+        result: Result<()>,
+    },
+    /// Store a batch of imported samples (sent by an import task)
+    ImportBatch {
+        import_id: Uuid,
+        /// (point path, sample) pairs
+        samples: Vec<(String, HistorySample)>,
+        /// Reader progress so far
+        bytes_read: u64,
+        rows_read: u64,
+        skipped: u64,
+        /// Row errors since the previous batch
+        errors: Vec<String>,
+    },
+    /// An import task finished (error = why it stopped early)
+    ImportFinished {
+        import_id: Uuid,
+        error: Option<String>,
+    },
+    /// Stream the stored samples of points from `start`, then their samples
+    /// as they are recorded; replies with the subscription id once the
+    /// backfill has been sent (see stream for the channel's capacity)
+    Subscribe {
+        points: Vec<String>,
+        start: DateTime<Utc>,
+        units: Option<EngineeringUnit>,
+        sender: mpsc::Sender<HistoryStreamItem>,
+        reply: oneshot::Sender<Result<Uuid>>,
+    },
+    /// End a subscription
+    Unsubscribe { subscription_id: Uuid },
+}
+
+/// Reply type for HistoryMsg
+#[derive(Debug, kameo::Reply)]
+pub enum HistoryReply {
+    /// Query sent response via oneshot
+    QuerySent,
+    /// Retention days
+    Retention { days: u32 },
+    /// Retention updated
+    RetentionSet,
+    /// Purge batch finished (done = the run reached the last point)
+    Purged { deleted: usize, done: bool },
+    /// Compaction result sent via oneshot
+    CompactSent,
+    /// Buffered samples committed
+    Flushed { samples: usize },
+    /// Interval samples and heartbeats recorded
+    PolicySampled { samples: usize },
+    /// Import progress updated (stored = false when a batch was not stored
+    /// and the import has failed)
+    ImportUpdated { stored: bool },
+    /// Subscription ended (found = false if there was no such subscription)
+    Unsubscribed { found: bool },
+    /// Queued samples handed to backends, or written by one
+    Forwarded { samples: usize },
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// History Actor
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Actor-based History Service
+#[derive(kameo::Actor)]
+pub struct HistoryActor {
+    id: String,
+    name: String,
+    #[allow(dead_code)]
+    description: String,
+    state: ServiceStateTracker,
+    config: HistoryConfig,
+    /// Committed history (opened on start)
+    store: Option<Box<dyn HistoryStore>>,
+    /// Logging policy state per point
+    logs: HashMap<String, PointLog>,
+    /// Parsed policy tag filters, keyed by filter text
+    tag_filters: HashMap<String, Option<TagFilter>>,
+    /// Semantic tags for tag filter queries
+    tag_store: Option<TagStore>,
+    /// Samples waiting for the next commit
+    pending: Vec<PendingSample>,
+    write_stats: WriteStats,
+    /// Purge run in progress
+    purge: Option<PurgeRun>,
+    purge_stats: PurgeStats,
+    /// Imports since the service started
+    imports: HashMap<Uuid, ImportProgress>,
+    /// Event bus for HistoryStale and HistoryResumed
+    pubsub: Option<ActorRef<PubSubBroker>>,
+    /// Events waiting to be published
+    events: Vec<Event>,
+    /// When the service last started (stale checks for points that have not
+    /// logged since count from here)
+    started_at: Option<DateTime<Utc>>,
+    /// Live history subscriptions
+    subscriptions: HashMap<Uuid, Subscription>,
+    /// External stores samples are forwarded to (opened on start)
+    backends: Vec<ForwardTarget>,
+}
+
+impl HistoryActor {
+    /// Create a new HistoryActor with the given configuration
+    pub fn new(config: HistoryConfig) -> Self {
+        Self {
+            id: "history".to_string(),
+            name: "History Service".to_string(),
+            description: "Stores time-series point data for trending and analysis".to_string(),
+            state: ServiceStateTracker::new(),
+            config,
+            store: None,
+            logs: HashMap::new(),
+            tag_filters: HashMap::new(),
+            tag_store: None,
+            pending: Vec::new(),
+            write_stats: WriteStats::default(),
+            purge: None,
+            purge_stats: PurgeStats::default(),
+            imports: HashMap::new(),
+            pubsub: None,
+            events: Vec::new(),
+            started_at: None,
+            subscriptions: HashMap::new(),
+            backends: Vec::new(),
+        }
+    }
+
+    /// Resolve tag filter queries against the given tag store
+    pub fn with_tag_store(mut self, tag_store: TagStore) -> Self {
+        self.tag_store = Some(tag_store);
+        self
+    }
+
+    /// Publish HistoryStale and HistoryResumed to the given broker
+    pub fn with_pubsub(mut self, pubsub: ActorRef<PubSubBroker>) -> Self {
+        self.pubsub = Some(pubsub);
+        self
+    }
+
+    /// Create with default configuration
+    pub fn with_defaults() -> Self {
+        Self::new(HistoryConfig::default())
+    }
+
+    /// Start the service (internal implementation)
+    fn do_start(&mut self) -> Result<()> {
+        self.state.set_starting();
+
+        self.config.validate().inspect_err(|_| self.state.set_failed())?;
+        let backends = self.open_backends().inspect_err(|_| self.state.set_failed())?;
+
+        let store = RedbStore::open(Path::new(&self.config.db_path), self.config.rollups.enabled)
+            .inspect_err(|_| self.state.set_failed())?;
+        let point_count = store.points().len();
+        self.store = Some(Box::new(store));
+        self.backends = backends;
+        self.started_at = Some(Utc::now());
+        self.state.set_running();
+
+        tracing::info!(
+            "History service started (db: {}, retention: {} days, {} points)",
+            self.config.db_path,
+            self.config.retention_days,
+            point_count
+        );
+
+        Ok(())
+    }
+
+    /// Stop the service (internal implementation)
+    fn do_stop(&mut self) {
+        self.state.set_stopping();
+        self.flush_or_warn();
+        self.store = None;
+        self.purge = None;
+        self.logs.clear();
+        self.subscriptions.clear();
+        self.backends.clear();
+        self.state.set_stopped();
+        tracing::info!("History service stopped");
+    }
+
+    /// Create the configured backends
+    fn open_backends(&self) -> Result<Vec<ForwardTarget>> {
+        let mut backends: Vec<ForwardTarget> = Vec::new();
+        for config in &self.config.backends {
+            if backends.iter().any(|b| b.name == config.name) {
+                return Err(Error::Config(format!(
+                    "Duplicate history backend name '{}'",
+                    config.name
+                )));
+            }
+            backends.push(ForwardTarget::open(config)?);
+        }
+        Ok(backends)
+    }
+
+    /// Names of the backends, which key their outboxes
+    fn backend_names(&self) -> Vec<String> {
+        self.backends.iter().map(|b| b.name.clone()).collect()
+    }
+
+    /// The open store
+    fn store(&self) -> Result<&dyn HistoryStore> {
+        self.store
+            .as_deref()
+            .ok_or_else(|| Error::Service("Database not open".to_string()))
+    }
+
+    /// The open store, for writing
+    fn store_mut(&mut self) -> Result<&mut (dyn HistoryStore + 'static)> {
+        self.store
+            .as_deref_mut()
+            .ok_or_else(|| Error::Service("Database not open".to_string()))
+    }
+
+    /// Read the next batch of queued samples for each backend not already
+    /// writing one, returning the writes to run outside the actor
+    ///
+    /// A batch stays queued until its backend accepts it, so a failing backend
+    /// is retried with the same samples on the next run.
+    fn forward_jobs(&mut self) -> Vec<ForwardJob> {
+        let limit = self.config.forward_batch_size.max(1);
+        let mut jobs = Vec::new();
+        for index in 0..self.backends.len() {
+            if self.backends[index].sending {
+                continue;
+            }
+            let name = self.backends[index].name.clone();
+            let batch = match self.store().and_then(|store| store.outbox_batch(&name, limit)) {
+                Ok(batch) if batch.is_empty() => continue,
+                Ok(batch) => batch,
+                Err(e) => {
+                    tracing::warn!("Failed to read history outbox for {}: {}", name, e);
+                    continue;
+                }
+            };
+            let sequences: Vec<u64> = batch.iter().map(|(sequence, _)| *sequence).collect();
+            let samples: Vec<_> = batch.into_iter().filter_map(|(_, sample)| sample).collect();
+            jobs.push(self.backends[index].start(sequences, samples));
+        }
+        jobs
+    }
+
+    /// Record a finished batch, removing it from the outbox once the backend
+    /// has taken it; returns the number of samples forwarded
+    fn forward_done(&mut self, outcome: ForwardOutcome) -> usize {
+        let Some(target) = self.backends.iter_mut().find(|b| b.name == outcome.backend) else {
+            return 0;
+        };
+        if !target.finish(&outcome) {
+            return 0;
+        }
+        let removed = self
+            .store_mut()
+            .and_then(|store| store.remove_from_outbox(&outcome.backend, &outcome.sequences));
+        if let Err(e) = removed {
+            tracing::warn!("Failed to update history outbox for {}: {}", outcome.backend, e);
+        }
+        outcome.samples
+    }
+
+    /// Index of the logging policy for a point (None = record every change)
+    fn policy_index(&mut self, point: &str) -> Option<usize> {
+        let tag_store = self.tag_store.as_ref();
+        let tag_filters = &mut self.tag_filters;
+        self.config
+            .policies
+            .iter()
+            .position(|policy| policy.matches(point, tag_store, tag_filters))
+    }
+
+    /// Apply the point's logging policy to a new value
+    fn on_point_value(
+        &mut self,
+        point: &str,
+        value: PropertyValue,
+        quality: PointQuality,
+        units: Option<EngineeringUnit>,
+        timestamp: DateTime<Utc>,
+    ) -> Result<()> {
+        let policy = self.policy_index(point);
+        let observed = Observed {
+            value,
+            quality,
+            units,
+            at: timestamp,
+        };
+        let log = self.logs.entry(point.to_string()).or_default();
+        log.current = Some(observed.clone());
+
+        let record = match policy {
+            Some(index) => {
+                self.config.policies[index].records_change(log.recorded.as_ref(), &observed)
+            }
+            None => true,
+        };
+        if !record {
+            return Ok(());
+        }
+
+        // Rate limiting check
+        let now = Instant::now();
+        if let Some(last) = log.last_accepted {
+            if now.duration_since(last).as_millis() < self.config.sample_interval_ms as u128 {
+                return Ok(()); // Skip - too soon
+            }
+        }
+        log.last_accepted = Some(now);
+
+        self.record(point, observed)
+    }
+
+    /// Record interval samples and heartbeats that are due at `now`
+    fn sample_policies(&mut self, now: DateTime<Utc>) -> Result<usize> {
+        if self.config.policies.is_empty() {
+            return Ok(0);
+        }
+
+        let points: Vec<String> = self.logs.keys().cloned().collect();
+        let mut recorded = 0;
+        for point in points {
+            let Some(index) = self.policy_index(&point) else {
+                continue;
+            };
+            let Some(log) = self.logs.get(&point) else {
+                continue;
+            };
+            let due = self.config.policies[index].due(log, now);
+            let (Some(at), Some(current)) = (due, &log.current) else {
+                continue;
+            };
+
+            let observed = Observed {
+                at,
+                ..current.clone()
+            };
+            self.record(&point, observed)?;
+            recorded += 1;
+        }
+
+        Ok(recorded)
+    }
+
+    /// Record a value and remember it as the point's last recorded value
+    fn record(&mut self, point: &str, observed: Observed) -> Result<()> {
+        self.store_sample(point, &observed.value, observed.quality, observed.units, observed.at)?;
+        if let Some(log) = self.logs.get_mut(point) {
+            if log.stale {
+                log.stale = false;
+                tracing::info!("History logging resumed for {}", point);
+                self.events.push(Event::HistoryResumed {
+                    point: point.to_string(),
+                    timestamp: Instant::now(),
+                    timestamp_utc: observed.at,
+                });
+            }
+            log.recorded = Some(observed);
+        }
+        Ok(())
+    }
+
+    /// Queue HistoryStale for points that have stopped logging at `now`
+    ///
+    /// Points that have not logged since the service started are measured
+    /// from the start. Returns the number of points that went stale.
+    fn check_stale(&mut self, now: DateTime<Utc>) -> usize {
+        if !self.config.policies.iter().any(|p| p.stale_after_secs.is_some()) {
+            return 0;
+        }
+        let Some(started_at) = self.started_at else {
+            return 0;
+        };
+
+        let mut points = self.store().map(|store| store.points()).unwrap_or_default();
+        let stored: HashSet<String> = points.iter().cloned().collect();
+        points.extend(self.logs.keys().filter(|p| !stored.contains(*p)).cloned());
+
+        let mut stale = 0;
+        for point in points {
+            let Some(index) = self.policy_index(&point) else {
+                continue;
+            };
+            let policy = &self.config.policies[index];
+            let log = self.logs.entry(point.clone()).or_default();
+            let last_logged = log.recorded.as_ref().map(|r| r.at);
+            if log.stale || !policy.is_stale(last_logged.unwrap_or(started_at), now) {
+                continue;
+            }
+
+            log.stale = true;
+            stale += 1;
+            tracing::warn!("History for {} has not logged since {:?}", point, last_logged);
+            self.events.push(Event::HistoryStale {
+                point,
+                last_logged,
+                stale_after_secs: policy.stale_after_secs.unwrap_or_default(),
+                timestamp: Instant::now(),
+                timestamp_utc: now,
+            });
+        }
+        stale
+    }
+
+    /// Publish queued events
+    async fn publish_events(&mut self) {
+        let events = std::mem::take(&mut self.events);
+        let Some(pubsub) = &self.pubsub else {
+            return;
+        };
+        for event in events {
+            if let Err(e) = pubsub.tell(Publish(event)).await {
+                tracing::warn!("Failed to publish history event: {}", e);
+            }
+        }
+    }
+
+    /// Accept a point value into the write buffer
+    fn store_sample(
+        &mut self,
+        point: &str,
+        value: &PropertyValue,
+        quality: PointQuality,
+        units: Option<EngineeringUnit>,
+        timestamp: DateTime<Utc>,
+    ) -> Result<()> {
+        self.store()?;
+
+        self.pending.push(PendingSample::new(point, value, quality, units, timestamp)?);
+        self.stream_sample(point, value, quality, units, timestamp);
+
+        if self.pending.len() >= self.config.batch_size.max(1) {
+            self.flush()?;
+        }
+
+        Ok(())
+    }
+
+    /// Send the stored samples of points from `start` to a new subscription
+    /// and register it for live samples
+    ///
+    /// Fails once more than MAX_BACKFILL_SAMPLES would be sent.
+    fn subscribe(
+        &mut self,
+        points: Vec<String>,
+        start: DateTime<Utc>,
+        units: Option<EngineeringUnit>,
+        sender: mpsc::Sender<HistoryStreamItem>,
+    ) -> Result<Uuid> {
+        self.flush()?;
+        let mut subscription = Subscription::new(points, units, sender);
+        let closed = || {
+            Error::Service("Subscriber went away or fell behind during backfill".to_string())
+        };
+        let mut remaining = MAX_BACKFILL_SAMPLES;
+
+        for point in subscription.points().to_vec() {
+            let mut chunk = Vec::with_capacity(BACKFILL_CHUNK);
+            let mut open = true;
+            self.scan_samples(&point, start, DateTime::<Utc>::MAX_UTC, |sample| {
+                if remaining == 0 {
+                    return Err(Error::Config(format!(
+                        "Backfill exceeds {} samples; subscribe from a later start",
+                        MAX_BACKFILL_SAMPLES
+                    )));
+                }
+                remaining -= 1;
+                chunk.extend(convert_sample(sample, subscription.units()));
+                if chunk.len() >= BACKFILL_CHUNK {
+                    open = subscription.backfill(&point, std::mem::take(&mut chunk));
+                }
+                Ok(open)
+            })?;
+            if !open || !subscription.backfill(&point, chunk) {
+                return Err(closed());
+            }
+        }
+        if !subscription.backfill_complete(Utc::now()) {
+            return Err(closed());
+        }
+
+        let id = Uuid::new_v4();
+        self.subscriptions.insert(id, subscription);
+        tracing::debug!("History subscription {} started", id);
+        Ok(id)
+    }
+
+    /// Send a recorded sample to subscriptions, dropping those whose receiver
+    /// has gone away
+    fn stream_sample(
+        &mut self,
+        point: &str,
+        value: &PropertyValue,
+        quality: PointQuality,
+        units: Option<EngineeringUnit>,
+        timestamp: DateTime<Utc>,
+    ) {
+        if self.subscriptions.is_empty() {
+            return;
+        }
+
+        let sample = HistorySample {
+            timestamp,
+            value: value.clone(),
+            quality,
+            units,
+        };
+        self.subscriptions.retain(|id, subscription| {
+            let open = subscription.live(point, &sample);
+            if !open {
+                tracing::debug!("History subscription {} closed", id);
+            }
+            open
+        });
+    }
+
+    /// Commit buffered samples in a single write transaction
+    fn flush(&mut self) -> Result<usize> {
+        if self.pending.is_empty() {
+            return Ok(0);
+        }
+
+        let forward_to = self.backend_names();
+        self.store()?;
+        let started = Instant::now();
+        let pending = std::mem::take(&mut self.pending);
+
+        if let Err(e) = self.store_mut()?.write(&pending, false, &forward_to) {
+            self.write_stats.failed_samples += pending.len() as u64;
+            return Err(e);
+        }
+
+        let stats = &mut self.write_stats;
+        stats.samples_written += pending.len() as u64;
+        stats.commits += 1;
+        stats.last_commit_samples = pending.len();
+        stats.last_commit_ms = started.elapsed().as_millis() as u64;
+
+        Ok(pending.len())
+    }
+
+    /// Commit buffered samples, logging failures
+    fn flush_or_warn(&mut self) {
+        if let Err(e) = self.flush() {
+            tracing::warn!("Failed to commit history samples: {}", e);
+        }
+    }
+
+    /// Visit the samples of a point in time order until the visitor returns false
+    fn scan_samples(
+        &self,
+        point: &str,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        visit: impl FnMut(HistorySample) -> Result<bool>,
+    ) -> Result<()> {
+        self.scan_range(point, start.timestamp_micros(), end.timestamp_micros(), visit)
+    }
+
+    /// Visit the samples of a point between two timestamps (micros, inclusive)
+    fn scan_range(
+        &self,
+        point: &str,
+        start: i64,
+        end: i64,
+        mut visit: impl FnMut(HistorySample) -> Result<bool>,
+    ) -> Result<()> {
+        self.store()?.scan(point, start, end, &mut visit)
+    }
+
+    /// Longest expected time between samples of a point, in micros
+    fn expected_interval(&mut self, point: &str) -> i64 {
+        let secs = self
+            .policy_index(point)
+            .and_then(|index| self.config.policies[index].expected_interval_secs())
+            .unwrap_or(self.config.expected_interval_secs);
+        secs.max(1) as i64 * 1_000_000
+    }
+
+    /// Last sample of a point before a timestamp (micros), as (timestamp, quality)
+    fn sample_before(&self, point: &str, before: i64) -> Result<Option<(i64, PointQuality)>> {
+        self.store()?.sample_before(point, before)
+    }
+
+    /// Report gaps, bad-quality periods and coverage of points over a range
+    ///
+    /// The range ends at `now` at the latest, so the future is not a gap.
+    fn gap_reports(
+        &mut self,
+        points: Vec<String>,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        expected_interval: Option<&str>,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<GapReport>> {
+        let expected_interval = expected_interval
+            .map(parse_interval)
+            .transpose()?
+            .map(|i| i.num_microseconds().unwrap_or(i64::MAX));
+        let start = start.timestamp_micros();
+        let end = end.min(now).timestamp_micros().max(start);
+
+        points
+            .into_iter()
+            .map(|point| {
+                let expected = match expected_interval {
+                    Some(expected) => expected,
+                    None => self.expected_interval(&point),
+                };
+                let before = self.sample_before(&point, start)?;
+                let mut scanner = GapScanner::new(start, end, expected, before);
+                self.scan_range(&point, start, end, |sample| {
+                    scanner.sample(sample.timestamp.timestamp_micros(), sample.quality);
+                    Ok(true)
+                })?;
+                Ok(scanner.finish(point))
+            })
+            .collect()
+    }
+
+    /// Query samples for a point within a time range
+    fn query_samples(
+        &self,
+        point: &str,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        limit: Option<u32>,
+        units: Option<EngineeringUnit>,
+    ) -> Result<Vec<HistorySample>> {
+        let limit = limit.unwrap_or(10000) as usize;
+        let mut samples = Vec::new();
+        if limit == 0 {
+            return Ok(samples);
+        }
+
+        self.scan_samples(point, start, end, |sample| {
+            samples.extend(convert_sample(sample, units));
+            Ok(samples.len() < limit)
+        })?;
+
+        Ok(samples)
+    }
+
+    /// Query samples for a point, downsampled to at most `threshold` samples
+    ///
+    /// The whole range is read before downsampling; `limit` caps the result.
+    fn query_downsampled(
+        &self,
+        point: &str,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        threshold: u32,
+        limit: Option<u32>,
+        units: Option<EngineeringUnit>,
+    ) -> Result<Vec<HistorySample>> {
+        let mut samples = Vec::new();
+        self.scan_samples(point, start, end, |sample| {
+            samples.extend(convert_sample(sample, units));
+            Ok(true)
+        })?;
+
+        let mut samples = lttb(samples, threshold as usize);
+        if let Some(limit) = limit {
+            samples.truncate(limit as usize);
+        }
+        Ok(samples)
+    }
+
+    /// Query samples, downsampling if requested
+    fn query_history(
+        &self,
+        point: &str,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        limit: Option<u32>,
+        units: Option<EngineeringUnit>,
+        downsample: Option<u32>,
+    ) -> Result<Vec<HistorySample>> {
+        match downsample {
+            Some(threshold) => self.query_downsampled(point, start, end, threshold, limit, units),
+            None => self.query_samples(point, start, end, limit, units),
+        }
+    }
+
+    /// Visit the rollup rows of a point with bucket starts in [start, end)
+    fn scan_rollups(
+        &self,
+        point: &str,
+        level: RollupLevel,
+        start: i64,
+        end: i64,
+        mut visit: impl FnMut(i64, RollupRecord) -> Result<()>,
+    ) -> Result<()> {
+        self.store()?.scan_rollups(point, level, start, end, &mut visit)
+    }
+
+    /// Pick the coarsest rollup that can answer an aggregate query
+    ///
+    /// A rollup fits when its width divides the interval, every requested
+    /// function can be computed from it, and its retention covers the start.
+    fn plan_rollup(
+        &self,
+        start: DateTime<Utc>,
+        interval: chrono::Duration,
+        functions: &[Aggregation],
+    ) -> Option<RollupLevel> {
+        if !self.config.rollups.enabled
+            || functions.is_empty()
+            || !functions.iter().all(|f| ROLLUP_FUNCTIONS.contains(f))
+        {
+            return None;
+        }
+        let interval = interval.num_microseconds()?;
+
+        RollupLevel::ALL.into_iter().rev().find(|level| {
+            let retained = match self.config.rollups.retention_days(*level) {
+                0 => true,
+                days => start >= Utc::now() - chrono::Duration::days(days as i64),
+            };
+            interval % level.width() == 0 && retained
+        })
+    }
+
+    /// Aggregate samples for a point into fixed time buckets
+    ///
+    /// Whole rollup buckets inside the range are read from the planned rollup;
+    /// raw samples cover the partial buckets at either end.
+    fn aggregate_history(
+        &self,
+        point: &str,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        interval: &str,
+        functions: &[Aggregation],
+        units: Option<EngineeringUnit>,
+    ) -> Result<Vec<HistoryBucket>> {
+        let interval = parse_interval(interval)?;
+        let mut aggregator = Aggregator::new(interval, end.min(Utc::now()), functions);
+
+        let (start_us, end_us) = (start.timestamp_micros(), end.timestamp_micros());
+        let planned = self.plan_rollup(start, interval, functions).and_then(|level| {
+            // Whole rollup buckets within [start, end]
+            let first = level.bucket_start(start_us.saturating_add(level.width() - 1));
+            let last = level.bucket_start(end_us.saturating_add(1));
+            (first < last).then_some((level, first, last))
+        });
+        let (head_end, tail_start) =
+            planned.map_or((end_us, end_us + 1), |(_, first, last)| (first - 1, last));
+
+        self.scan_range(point, start_us, head_end, |sample| {
+            if let Some(sample) = convert_sample(sample, units) {
+                aggregator.push_sample(&sample);
+            }
+            Ok(true)
+        })?;
+        if let Some((level, first, last)) = planned {
+            tracing::debug!("Aggregating '{}' from {} rollups", point, level.name());
+            self.scan_rollups(point, level, first, last, |bucket_start, rollup| {
+                aggregator.push_rollup(bucket_start, &rollup.convert(units)?);
+                Ok(())
+            })?;
+        }
+        self.scan_range(point, tail_start, end_us, |sample| {
+            if let Some(sample) = convert_sample(sample, units) {
+                aggregator.push_sample(&sample);
+            }
+            Ok(true)
+        })?;
+
+        Ok(aggregator.finish_buckets())
+    }
+
+    /// Delete up to `purge_batch_size` samples and rollup rows older than their
+    /// retention window
+    ///
+    /// Continues the run in progress (or starts one) and returns the number of
+    /// rows deleted and whether the run is complete. Rollup rows are removed
+    /// once their bucket starts before the cutoff.
+    fn purge_batch(&mut self, now: DateTime<Utc>) -> Result<(usize, bool)> {
+        self.flush()?;
+
+        let mut run = self.purge.take().unwrap_or_else(|| PurgeRun {
+            started: now,
+            started_at: Instant::now(),
+            deleted: 0,
+            cursor: PurgeCursor::default(),
+        });
+        let batch_size = self.config.purge_batch_size;
+
+        let config = &self.config;
+        let store = self
+            .store
+            .as_deref_mut()
+            .ok_or_else(|| Error::Service("Database not open".to_string()))?;
+        let (deleted, done) = store.purge(&mut run.cursor, batch_size, &|table, point: &str| {
+            config.purge_cutoff(table, point, now)
+        })?;
+
+        run.deleted += deleted as u64;
+        self.purge_stats.total_deleted += deleted as u64;
+
+        if done {
+            let stats = &mut self.purge_stats;
+            stats.runs += 1;
+            stats.last_run_deleted = run.deleted;
+            stats.last_run_started = Some(run.started);
+            stats.last_run_completed = Some(Utc::now());
+            stats.last_run_ms = run.started_at.elapsed().as_millis() as u64;
+            stats.last_error = None;
+            tracing::info!(
+                "History purge complete: {} rows deleted in {}ms",
+                run.deleted,
+                stats.last_run_ms
+            );
+
+            if self.config.compact_after_purge && run.deleted > 0 {
+                if let Err(e) = self.compact() {
+                    tracing::warn!("History compaction failed: {}", e);
+                }
+            }
+        } else {
+            self.purge = Some(run);
+        }
+
+        Ok((deleted, done))
+    }
+
+    /// Compact the database file, returning whether any space was reclaimed
+    fn compact(&mut self) -> Result<bool> {
+        let compacted = self.store_mut()?.compact()?;
+        self.purge_stats.last_compaction = Some(Utc::now());
+        tracing::info!("History database compacted (space reclaimed: {})", compacted);
+        Ok(compacted)
+    }
+
+    /// Start a background task that purges expired samples every `interval`
+    ///
+    /// Each run is a sequence of PurgeBatch messages, so events queued behind a
+    /// batch are stored before the next one starts.
+    pub fn start_purge_task(
+        actor_ref: ActorRef<Self>,
+        interval: Duration,
+    ) -> tokio::task::JoinHandle<()> {
+        let weak_ref = actor_ref.downgrade();
+
+        tokio::spawn(async move {
+            let mut tick = tokio::time::interval(interval);
+            tick.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+
+            loop {
+                tick.tick().await;
+
+                loop {
+                    let Some(actor_ref) = weak_ref.upgrade() else {
+                        tracing::debug!("History purge task exiting");
+                        return;
+                    };
+                    match actor_ref.ask(HistoryMsg::PurgeBatch).await {
+                        Ok(HistoryReply::Purged { done: false, .. }) => {
+                            tokio::task::yield_now().await
+                        }
+                        Ok(_) => break,
+                        Err(e) => {
+                            tracing::warn!("History purge failed: {}", e);
+                            break;
+                        }
+                    }
+                }
+            }
+        })
+    }
+
+    /// Start a background task that commits buffered samples every `interval`
+    pub fn start_flush_task(
+        actor_ref: ActorRef<Self>,
+        interval: Duration,
+    ) -> tokio::task::JoinHandle<()> {
+        let weak_ref = actor_ref.downgrade();
+
+        tokio::spawn(async move {
+            let mut tick = tokio::time::interval(interval);
+            tick.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+
+            loop {
+                tick.tick().await;
+                let Some(actor_ref) = weak_ref.upgrade() else {
+                    tracing::debug!("History flush task exiting");
+                    return;
+                };
+                if let Err(e) = actor_ref.tell(HistoryMsg::Flush).await {
+                    tracing::warn!("History flush failed: {}", e);
+                }
+            }
+        })
+    }
+
+    /// Start a background task that forwards queued samples to backends
+    pub fn start_forward_task(
+        actor_ref: ActorRef<Self>,
+        interval: Duration,
+    ) -> tokio::task::JoinHandle<()> {
+        let weak_ref = actor_ref.downgrade();
+
+        tokio::spawn(async move {
+            let mut tick = tokio::time::interval(interval);
+            tick.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+
+            loop {
+                tick.tick().await;
+                let Some(actor_ref) = weak_ref.upgrade() else {
+                    tracing::debug!("History forward task exiting");
+                    return;
+                };
+                if let Err(e) = actor_ref.tell(HistoryMsg::Forward).await {
+                    tracing::warn!("History forwarding failed: {}", e);
+                }
+            }
+        })
+    }
+
+    /// Start a background task that records interval samples and heartbeats
+    ///
+    /// `interval` is the resolution of policy sampling; one second keeps
+    /// interval samples within a second of their slot.
+    pub fn start_policy_task(
+        actor_ref: ActorRef<Self>,
+        interval: Duration,
+    ) -> tokio::task::JoinHandle<()> {
+        let weak_ref = actor_ref.downgrade();
+
+        tokio::spawn(async move {
+            let mut tick = tokio::time::interval(interval);
+            tick.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+
+            loop {
+                tick.tick().await;
+                let Some(actor_ref) = weak_ref.upgrade() else {
+                    tracing::debug!("History policy task exiting");
+                    return;
+                };
+                if let Err(e) = actor_ref.tell(HistoryMsg::SamplePolicies).await {
+                    tracing::warn!("History policy sampling failed: {}", e);
+                }
+            }
+        })
+    }
+
+    fn status_extra(&self) -> serde_json::Value {
+        serde_json::json!({
+            "db_path": self.config.db_path,
+            "retention_days": self.config.retention_days,
+            "retention_overrides": self.config.retention_overrides.len(),
+            "point_count": self.store.as_ref().map_or(0, |store| store.points().len()),
+            "policies": self.config.policies.len(),
+            "rollups": self.config.rollups.enabled,
+            "pending_samples": self.pending.len(),
+            "writes": self.write_stats,
+            "purge": self.purge_stats,
+            "purge_in_progress": self.purge.is_some(),
+            "imports_running": self
+                .imports
+                .values()
+                .filter(|i| i.state == ImportState::Running)
+                .count(),
+            "stale_points": self.logs.values().filter(|log| log.stale).count(),
+            "subscriptions": self.subscriptions.len(),
+            "backends": self
+                .backends
+                .iter()
+                .map(|b| {
+                    serde_json::json!({
+                        "name": b.name,
+                        "forwarded": b.forwarded,
+                        "error": b.last_error,
+                    })
+                })
+                .collect::<Vec<_>>(),
+        })
+    }
+
+    /// Describe an aligned multi-point query
+    fn align_spec(
+        &self,
+        points: Vec<String>,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        interval: Option<&str>,
+        interpolation: Interpolation,
+        units: Option<EngineeringUnit>,
+    ) -> Result<AlignSpec> {
+        let interval = interval
+            .map(parse_interval)
+            .transpose()?
+            .map(|i| i.num_microseconds().unwrap_or(i64::MAX));
+
+        Ok(AlignSpec {
+            points,
+            start: start.timestamp_micros(),
+            end: end.timestamp_micros(),
+            interval,
+            interpolation,
+            units,
+        })
+    }
+
+    /// Query several points aligned on a common time grid
+    fn query_table(&self, spec: AlignSpec, limit: Option<u32>) -> Result<HistoryTable> {
+        let mut sink = TableSink::new(limit.unwrap_or(10000) as usize);
+        self.store()?.query_table(&spec, &mut sink)?;
+        Ok(HistoryTable {
+            points: spec.points,
+            rows: sink.rows,
+        })
+    }
+
+    /// Resolve an export file name within the export directory
+    fn export_path(&self, file_name: &str) -> Result<PathBuf> {
+        file_in_dir(&self.config.export_dir, file_name)
+    }
+
+    /// Export aligned history to a file on a blocking thread
+    ///
+    /// The export reads its own snapshot of the store, so samples committed
+    /// while it runs are not included. Returns the path and the number of rows written.
+    fn spawn_export(
+        &self,
+        spec: AlignSpec,
+        format: ExportFormat,
+        file_name: &str,
+    ) -> Result<tokio::task::JoinHandle<Result<(PathBuf, u64)>>> {
+        let path = self.export_path(file_name)?;
+        let point_count = spec.points.len();
+        let job = self.store()?.export(spec, format, path.clone())?;
+
+        Ok(tokio::task::spawn_blocking(move || {
+            let started = Instant::now();
+            let rows = job()?;
+            tracing::info!(
+                "Exported {} rows of history for {} points to {} in {}ms",
+                rows,
+                point_count,
+                path.display(),
+                started.elapsed().as_millis()
+            );
+            Ok((path, rows))
+        }))
+    }
+
+    /// Start importing a file from the import directory in the background
+    fn start_import(
+        &mut self,
+        actor_ref: ActorRef<Self>,
+        file_name: String,
+        options: ImportOptions,
+    ) -> Result<ImportProgress> {
+        self.store()?;
+        let path = file_in_dir(&self.config.import_dir, &file_name)?;
+        let total_bytes = std::fs::metadata(&path)
+            .map_err(|e| Error::NotFound(format!("{}: {}", path.display(), e)))?
+            .len();
+
+        let progress = ImportProgress {
+            import_id: Uuid::new_v4(),
+            file_name,
+            state: ImportState::Running,
+            started_at: Utc::now(),
+            finished_at: None,
+            total_bytes,
+            bytes_read: 0,
+            rows_read: 0,
+            imported: 0,
+            duplicates: 0,
+            skipped: 0,
+            errors: Vec::new(),
+            error: None,
+        };
+        self.imports.insert(progress.import_id, progress.clone());
+
+        tracing::info!("Importing history from {} ({} bytes)", path.display(), total_bytes);
+        tokio::spawn(import::run(actor_ref, progress.import_id, path, options));
+        Ok(progress)
+    }
+
+    /// Store imported samples, skipping samples already stored
+    ///
+    /// Returns the number of samples imported and the number of duplicates.
+    fn store_import_batch(&mut self, samples: &[(String, HistorySample)]) -> Result<(u64, u64)> {
+        // Buffered samples count as stored
+        self.flush()?;
+
+        let forward_to = self.backend_names();
+        let pending = samples
+            .iter()
+            .map(|(point, s)| PendingSample::new(point, &s.value, s.quality, s.units, s.timestamp))
+            .collect::<Result<Vec<_>>>()?;
+
+        let written = self.store_mut()?.write(&pending, true, &forward_to)?;
+        Ok((written as u64, (pending.len() - written) as u64))
+    }
+
+    /// Query samples for every point matching a tag filter
+    fn query_by_tags(
+        &self,
+        filter: &str,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        limit: Option<u32>,
+        units: Option<EngineeringUnit>,
+    ) -> Result<Vec<PointHistory>> {
+        let store = self
+            .tag_store
+            .as_ref()
+            .ok_or_else(|| Error::Service("Tag store not configured".to_string()))?;
+        let filter = TagFilter::parse(filter)?;
+
+        store
+            .query(&filter)
+            .into_iter()
+            .map(|point| {
+                let samples = self.query_samples(&point, start, end, limit, units)?;
+                Ok(PointHistory { point, samples })
+            })
+            .collect()
+    }
+}
+
+
+// Handle common ServiceMsg
+impl Message<ServiceMsg> for HistoryActor {
+    type Reply = ServiceReply;
+
+    async fn handle(
+        &mut self,
+        msg: ServiceMsg,
+        ctx: &mut Context<Self, Self::Reply>,
+    ) -> Self::Reply {
+        match msg {
+            ServiceMsg::Start => match self.do_start() {
+                Ok(_) => ServiceReply::Started,
+                Err(e) => ServiceReply::Failed(e.to_string()),
+            },
+
+            ServiceMsg::Stop => {
+                self.do_stop();
+                ServiceReply::Stopped
+            }
+
+            ServiceMsg::GetStatus => ServiceReply::Status {
+                id: self.id.clone(),
+                name: self.name.clone(),
+                state: self.state.state(),
+                uptime_secs: self.state.uptime_secs(),
+                extra: Some(self.status_extra()),
+            },
+
+            ServiceMsg::GetConfig => ServiceReply::Config {
+                config: serde_json::to_value(&self.config).unwrap_or_default(),
+            },
+
+            ServiceMsg::SetConfig { config } => {
+                if let Ok(new_config) = serde_json::from_value::<HistoryConfig>(config) {
+                    if let Err(e) = new_config.validate() {
+                        return ServiceReply::Failed(e.to_string());
+                    }
+                    self.config = new_config;
+                    self.tag_filters.clear();
+                    ServiceReply::ConfigSet
+                } else {
+                    ServiceReply::Failed("Invalid configuration format".to_string())
+                }
+            }
+
+            ServiceMsg::OnEvent { event } => {
+                if self.state.state() != ServiceState::Running {
+                    return ServiceReply::EventHandled;
+                }
+
+                if let Event::PointValueChanged {
+                    point,
+                    value,
+                    quality,
+                    units,
+                    timestamp_utc,
+                    ..
+                } = event
+                {
+                    if let Err(e) = self.on_point_value(&point, value, quality, units, timestamp_utc) {
+                        tracing::warn!("Failed to store history sample: {}", e);
+                    }
+                    self.publish_events().await;
+                }
+                ServiceReply::EventHandled
+            }
+
+            ServiceMsg::HandleRequest { request, reply } => {
+                // Queries must see buffered samples
+                self.flush_or_warn();
+
+                let response = match request {
+                    ServiceRequest::GetStatus => ServiceResponse::Status {
+                        id: self.id.clone(),
+                        name: self.name.clone(),
+                        state: self.state.state(),
+                        uptime_seconds: self.state.uptime_secs(),
+                        extra: Some(self.status_extra()),
+                    },
+
+                    ServiceRequest::GetConfig => ServiceResponse::Config {
+                        config: serde_json::to_value(&self.config).unwrap_or_default(),
+                    },
+
+                    ServiceRequest::QueryHistory {
+                        point,
+                        start,
+                        end,
+                        limit,
+                        units,
+                        downsample,
+                    } => match self.query_history(&point, start, end, limit, units, downsample) {
+                        Ok(samples) => ServiceResponse::HistoryData { samples },
+                        Err(e) => ServiceResponse::Error {
+                            code: "QUERY_FAILED".to_string(),
+                            message: e.to_string(),
+                        },
+                    },
+
+                    ServiceRequest::AggregateHistory {
+                        point,
+                        start,
+                        end,
+                        interval,
+                        functions,
+                        units,
+                    } => match self.aggregate_history(&point, start, end, &interval, &functions, units) {
+                        Ok(buckets) => ServiceResponse::HistoryBuckets { buckets },
+                        Err(e) => ServiceResponse::Error {
+                            code: "QUERY_FAILED".to_string(),
+                            message: e.to_string(),
+                        },
+                    },
+
+                    ServiceRequest::QueryHistoryByTags {
+                        filter,
+                        start,
+                        end,
+                        limit,
+                        units,
+                    } => match self.query_by_tags(&filter, start, end, limit, units) {
+                        Ok(series) => ServiceResponse::HistorySeries { series },
+                        Err(e) => ServiceResponse::Error {
+                            code: "QUERY_FAILED".to_string(),
+                            message: e.to_string(),
+                        },
+                    },
+
+                    ServiceRequest::QueryHistoryTable {
+                        points,
+                        start,
+                        end,
+                        interval,
+                        interpolation,
+                        units,
+                        limit,
+                    } => match self
+                        .align_spec(points, start, end, interval.as_deref(), interpolation, units)
+                        .and_then(|spec| self.query_table(spec, limit))
+                    {
+                        Ok(table) => ServiceResponse::HistoryTable { table },
+                        Err(e) => ServiceResponse::Error {
+                            code: "QUERY_FAILED".to_string(),
+                            message: e.to_string(),
+                        },
+                    },
+
+                    ServiceRequest::ExportHistory {
+                        points,
+                        start,
+                        end,
+                        interval,
+                        interpolation,
+                        units,
+                        format,
+                        file_name,
+                    } => {
+                        let export = self
+                            .align_spec(points, start, end, interval.as_deref(), interpolation, units)
+                            .and_then(|spec| self.spawn_export(spec, format, &file_name));
+                        match export {
+                            Ok(handle) => {
+                                // Reply once the file is written
+                                tokio::spawn(async move {
+                                    let response = match handle.await {
+                                        Ok(Ok((path, rows))) => ServiceResponse::HistoryExported {
+                                            path: path.to_string_lossy().to_string(),
+                                            rows,
+                                        },
+                                        Ok(Err(e)) => ServiceResponse::Error {
+                                            code: "EXPORT_FAILED".to_string(),
+                                            message: e.to_string(),
+                                        },
+                                        Err(e) => ServiceResponse::Error {
+                                            code: "EXPORT_FAILED".to_string(),
+                                            message: format!("Export task failed: {}", e),
+                                        },
+                                    };
+                                    let _ = reply.send(response);
+                                });
+                                return ServiceReply::RequestHandled;
+                            }
+                            Err(e) => ServiceResponse::Error {
+                                code: "EXPORT_FAILED".to_string(),
+                                message: e.to_string(),
+                            },
+                        }
+                    }
+
+                    ServiceRequest::ImportHistory {
+                        file_name,
+                        format,
+                        point_map,
+                        mapped_only,
+                        timezone,
+                        timestamp_format,
+                    } => {
+                        let import = parse_timezone(timezone.as_deref()).and_then(|timezone| {
+                            let options = ImportOptions {
+                                format,
+                                point_map,
+                                mapped_only,
+                                timezone,
+                                timestamp_format,
+                            };
+                            self.start_import(ctx.actor_ref().clone(), file_name, options)
+                        });
+                        match import {
+                            Ok(progress) => ServiceResponse::ImportProgress { progress },
+                            Err(e) => ServiceResponse::Error {
+                                code: "IMPORT_FAILED".to_string(),
+                                message: e.to_string(),
+                            },
+                        }
+                    }
+
+                    ServiceRequest::GetHistoryGaps {
+                        points,
+                        start,
+                        end,
+                        expected_interval,
+                    } => match self.gap_reports(
+                        points,
+                        start,
+                        end,
+                        expected_interval.as_deref(),
+                        Utc::now(),
+                    ) {
+                        Ok(reports) => ServiceResponse::HistoryGaps { reports },
+                        Err(e) => ServiceResponse::Error {
+                            code: "QUERY_FAILED".to_string(),
+                            message: e.to_string(),
+                        },
+                    },
+
+                    ServiceRequest::GetImportProgress { import_id } => {
+                        match self.imports.get(&import_id) {
+                            Some(progress) => ServiceResponse::ImportProgress {
+                                progress: progress.clone(),
+                            },
+                            None => ServiceResponse::Error {
+                                code: "NOT_FOUND".to_string(),
+                                message: format!("Import {} not found", import_id),
+                            },
+                        }
+                    }
+
+                    _ => ServiceResponse::Error {
+                        code: "UNSUPPORTED".to_string(),
+                        message: "Request not supported by History service".to_string(),
+                    },
+                };
+
+                let _ = reply.send(response);
+                ServiceReply::RequestHandled
+            }
+        }
+    }
+}
+
+// Handle history-specific messages
+impl Message<HistoryMsg> for HistoryActor {
+    type Reply = HistoryReply;
+
+    async fn handle(
+        &mut self,
+        msg: HistoryMsg,
+        ctx: &mut Context<Self, Self::Reply>,
+    ) -> Self::Reply {
+        match msg {
+            HistoryMsg::QueryHistory {
+                point,
+                start,
+                end,
+                limit,
+                units,
+                downsample,
+                reply,
+            } => {
+                self.flush_or_warn();
+                let result = self.query_history(&point, start, end, limit, units, downsample);
+                let _ = reply.send(result);
+                HistoryReply::QuerySent
+            }
+
+            HistoryMsg::AggregateHistory {
+                point,
+                start,
+                end,
+                interval,
+                functions,
+                units,
+                reply,
+            } => {
+                self.flush_or_warn();
+                let result = self.aggregate_history(&point, start, end, &interval, &functions, units);
+                let _ = reply.send(result);
+                HistoryReply::QuerySent
+            }
+
+            HistoryMsg::QueryHistoryByTags {
+                filter,
+                start,
+                end,
+                limit,
+                units,
+                reply,
+            } => {
+                self.flush_or_warn();
+                let result = self.query_by_tags(&filter, start, end, limit, units);
+                let _ = reply.send(result);
+                HistoryReply::QuerySent
+            }
+
+            HistoryMsg::QueryTable {
+                points,
+                start,
+                end,
+                interval,
+                interpolation,
+                units,
+                limit,
+                reply,
+            } => {
+                self.flush_or_warn();
+                let result = self
+                    .align_spec(points, start, end, interval.as_deref(), interpolation, units)
+                    .and_then(|spec| self.query_table(spec, limit));
+                let _ = reply.send(result);
+                HistoryReply::QuerySent
+            }
+
+            HistoryMsg::GetRetention => HistoryReply::Retention {
+                days: self.config.retention_days,
+            },
+
+            HistoryMsg::SetRetention { days } => {
+                self.config.retention_days = days;
+                HistoryReply::RetentionSet
+            }
+
+            HistoryMsg::PurgeBatch => {
+                // Nothing to purge while the service is stopped
+                if self.store.is_none() {
+                    return HistoryReply::Purged { deleted: 0, done: true };
+                }
+                match self.purge_batch(Utc::now()) {
+                    Ok((deleted, done)) => HistoryReply::Purged { deleted, done },
+                    Err(e) => {
+                        tracing::warn!("History purge batch failed: {}", e);
+                        self.purge = None;
+                        self.purge_stats.last_error = Some(e.to_string());
+                        HistoryReply::Purged { deleted: 0, done: true }
+                    }
+                }
+            }
+
+            HistoryMsg::Compact { reply } => {
+                let _ = reply.send(self.compact());
+                HistoryReply::CompactSent
+            }
+
+            HistoryMsg::Flush => match self.flush() {
+                Ok(samples) => HistoryReply::Flushed { samples },
+                Err(e) => {
+                    tracing::warn!("Failed to commit history samples: {}", e);
+                    HistoryReply::Flushed { samples: 0 }
+                }
+            },
+
+            HistoryMsg::SamplePolicies => {
+                if self.state.state() != ServiceState::Running {
+                    return HistoryReply::PolicySampled { samples: 0 };
+                }
+                let now = Utc::now();
+                let samples = match self.sample_policies(now) {
+                    Ok(samples) => samples,
+                    Err(e) => {
+                        tracing::warn!("Failed to record policy samples: {}", e);
+                        0
+                    }
+                };
+                self.check_stale(now);
+                self.publish_events().await;
+                HistoryReply::PolicySampled { samples }
+            }
+
+            HistoryMsg::Forward => {
+                if self.state.state() != ServiceState::Running {
+                    return HistoryReply::Forwarded { samples: 0 };
+                }
+                let jobs = self.forward_jobs();
+                let samples = jobs.iter().map(ForwardJob::len).sum();
+                for job in jobs {
+                    let actor_ref = ctx.actor_ref().clone();
+                    tokio::spawn(async move {
+                        let outcome = job.run().await;
+                        let done = HistoryMsg::ForwardDone {
+                            backend: outcome.backend,
+                            sequences: outcome.sequences,
+                            samples: outcome.samples,
+                            result: outcome.result,
+                        };
+                        if let Err(e) = actor_ref.tell(done).await {
+                            tracing::debug!("History forward result not delivered: {}", e);
+                        }
+                    });
+                }
+                HistoryReply::Forwarded { samples }
+            }
+
+            HistoryMsg::ForwardDone {
+                backend,
+                sequences,
+                samples,
+                result,
+            } => {
+                let outcome = ForwardOutcome {
+                    backend,
+                    sequences,
+                    samples,
+                    result,
+                };
+                let samples = self.forward_done(outcome);
+                HistoryReply::Forwarded { samples }
+            }
+
+            HistoryMsg::ImportBatch {
+                import_id,
+                samples,
+                bytes_read,
+                rows_read,
+                skipped,
+                errors,
+            } => {
+                if !self.imports.contains_key(&import_id) {
+                    return HistoryReply::ImportUpdated { stored: false };
+                }
+                let result = self.store_import_batch(&samples);
+
+                let Some(progress) = self.imports.get_mut(&import_id) else {
+                    return HistoryReply::ImportUpdated { stored: false };
+                };
+                progress.bytes_read = bytes_read;
+                progress.rows_read = rows_read;
+                progress.skipped = skipped;
+                progress.errors.extend(errors);
+                match result {
+                    Ok((imported, duplicates)) => {
+                        progress.imported += imported;
+                        progress.duplicates += duplicates;
+                        HistoryReply::ImportUpdated { stored: true }
+                    }
+                    Err(e) => {
+                        tracing::warn!("Failed to store imported history: {}", e);
+                        progress.state = ImportState::Failed;
+                        progress.error = Some(e.to_string());
+                        HistoryReply::ImportUpdated { stored: false }
+                    }
+                }
+            }
+
+            HistoryMsg::ImportFinished { import_id, error } => {
+                if let Some(progress) = self.imports.get_mut(&import_id) {
+                    // A failed batch has already recorded its error
+                    if progress.state == ImportState::Running {
+                        progress.state = match error {
+                            Some(_) => ImportState::Failed,
+                            None => ImportState::Completed,
+                        };
+                        progress.error = error;
+                    }
+                    progress.finished_at = Some(Utc::now());
+                    tracing::info!(
+                        "History import of {} {:?}: {} imported, {} duplicates, {} skipped",
+                        progress.file_name,
+                        progress.state,
+                        progress.imported,
+                        progress.duplicates,
+                        progress.skipped
+                    );
+                }
+                HistoryReply::ImportUpdated { stored: true }
+            }
+
+            HistoryMsg::Subscribe {
+                points,
+                start,
+                units,
+                sender,
+                reply,
+            } => {
+                let result = self.subscribe(points, start, units, sender);
+                let _ = reply.send(result);
+                HistoryReply::QuerySent
+            }
+
+            HistoryMsg::Unsubscribe { subscription_id } => {
+                let found = self.subscriptions.remove(&subscription_id).is_some();
+                HistoryReply::Unsubscribed { found }
+            }
+        }
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Tests
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::messages::ImportFormat;
+    use kameo::actor::Spawn;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn test_history_service_lifecycle() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test_history.redb");
+
+        let config = HistoryConfig {
+            db_path: db_path.to_string_lossy().to_string(),
+            retention_days: 30,
+            sample_interval_ms: 100,
+            ..Default::default()
+        };
+
+        let actor = HistoryActor::spawn(HistoryActor::new(config));
+
+        // Start
+        let reply = actor.ask(ServiceMsg::Start).await.unwrap();
+        assert!(matches!(reply, ServiceReply::Started));
+
+        // Check status
+        let reply = actor.ask(ServiceMsg::GetStatus).await.unwrap();
+        if let ServiceReply::Status { state, .. } = reply {
+            assert_eq!(state, ServiceState::Running);
+        } else {
+            panic!("Expected Status reply");
+        }
+
+        // Store a sample via event
+        let event = Event::PointValueChanged {
+            point: "test/point/1".to_string(),
+            value: PropertyValue::Real(72.5),
+            quality: PointQuality::Good,
+            units: None,
+            timestamp: std::time::Instant::now(),
+            timestamp_utc: Utc::now(),
+        };
+        let _ = actor.ask(ServiceMsg::OnEvent { event }).await;
+
+        // Query it back
+        let (reply_tx, reply_rx) = oneshot::channel();
+        let _ = actor
+            .ask(HistoryMsg::QueryHistory {
+                point: "test/point/1".to_string(),
+                start: Utc::now() - chrono::Duration::hours(1),
+                end: Utc::now() + chrono::Duration::hours(1),
+                limit: None,
+                units: None,
+                downsample: None,
+                reply: reply_tx,
+            })
+            .await;
+
+        let samples = reply_rx.await.unwrap().unwrap();
+        assert_eq!(samples.len(), 1);
+        assert!(matches!(samples[0].value, PropertyValue::Real(v) if (v - 72.5).abs() < 0.01));
+
+        // Stop
+        let reply = actor.ask(ServiceMsg::Stop).await.unwrap();
+        assert!(matches!(reply, ServiceReply::Stopped));
+    }
+
+    #[tokio::test]
+    async fn test_history_unit_conversion() {
+        let dir = tempdir().unwrap();
+        let config = HistoryConfig {
+            db_path: dir.path().join("test_history.redb").to_string_lossy().to_string(),
+            sample_interval_ms: 0,
+            ..Default::default()
+        };
+        let actor = HistoryActor::spawn(HistoryActor::new(config));
+        let _ = actor.ask(ServiceMsg::Start).await.unwrap();
+
+        // The point was recorded in °F, then (reconfigured) in percent
+        let now = Utc::now();
+        let recorded = [
+            (72.5, EngineeringUnit::DegreesFahrenheit, 2),
+            (40.0, EngineeringUnit::Percent, 1),
+        ];
+        for (value, units, minutes_ago) in recorded {
+            let event = Event::PointValueChanged {
+                point: "test/point/1".to_string(),
+                value: PropertyValue::Real(value),
+                quality: PointQuality::Good,
+                units: Some(units),
+                timestamp: std::time::Instant::now(),
+                timestamp_utc: now - chrono::Duration::minutes(minutes_ago),
+            };
+            let _ = actor.ask(ServiceMsg::OnEvent { event }).await;
+        }
+
+        // Samples that cannot convert to °C are skipped, not an error
+        let (reply_tx, reply_rx) = oneshot::channel();
+        let _ = actor
+            .ask(HistoryMsg::QueryHistory {
+                point: "test/point/1".to_string(),
+                start: now - chrono::Duration::hours(1),
+                end: now + chrono::Duration::hours(1),
+                limit: None,
+                units: Some(EngineeringUnit::DegreesCelsius),
+                downsample: None,
+                reply: reply_tx,
+            })
+            .await;
+        let samples = reply_rx.await.unwrap().unwrap();
+        assert_eq!(samples.len(), 1);
+        assert!(matches!(samples[0].value, PropertyValue::Real(v) if (v - 22.5).abs() < 0.01));
+        assert_eq!(samples[0].units, Some(EngineeringUnit::DegreesCelsius));
+    }
+
+    #[tokio::test]
+    async fn test_history_retention_purge() {
+        let dir = tempdir().unwrap();
+        let config = HistoryConfig {
+            db_path: dir.path().join("test_history.redb").to_string_lossy().to_string(),
+            retention_days: 90,
+            sample_interval_ms: 0,
+            retention_overrides: vec![RetentionOverride {
+                pattern: "*/meter/*".to_string(),
+                days: 7 * 365,
+            }],
+            purge_batch_size: 2,
+            ..Default::default()
+        };
+
+        let actor = HistoryActor::spawn(HistoryActor::new(config));
+        let _ = actor.ask(ServiceMsg::Start).await.unwrap();
+
+        // Zone temps and a meter, 1 day, 100 days and 400 days old
+        for point in ["bldg/zone-1/temp", "bldg/zone-2/temp", "bldg/meter/kwh"] {
+            for days in [1, 100, 400] {
+                let event = Event::PointValueChanged {
+                    point: point.to_string(),
+                    value: PropertyValue::Real(days as f32),
+                    quality: PointQuality::Good,
+                    units: None,
+                    timestamp: std::time::Instant::now(),
+                    timestamp_utc: Utc::now() - chrono::Duration::days(days),
+                };
+                let _ = actor.ask(ServiceMsg::OnEvent { event }).await;
+            }
+        }
+
+        // Bounded batches: 4 expired zone samples take 2 batches + a final pass
+        let mut batches = 0;
+        let mut deleted = 0;
+        loop {
+            batches += 1;
+            match actor.ask(HistoryMsg::PurgeBatch).await.unwrap() {
+                HistoryReply::Purged { deleted: n, done } => {
+                    assert!(n <= 2);
+                    deleted += n;
+                    if done {
+                        break;
+                    }
+                }
+                other => panic!("Unexpected reply: {:?}", other),
+            }
+        }
+        assert_eq!(deleted, 4);
+        assert!(batches >= 2);
+
+        let count = |point: &'static str| {
+            let actor = actor.clone();
+            async move {
+                let (reply_tx, reply_rx) = oneshot::channel();
+                let _ = actor
+                    .ask(HistoryMsg::QueryHistory {
+                        point: point.to_string(),
+                        start: Utc::now() - chrono::Duration::days(1000),
+                        end: Utc::now(),
+                        limit: None,
+                        units: None,
+                        downsample: None,
+                        reply: reply_tx,
+                    })
+                    .await;
+                reply_rx.await.unwrap().unwrap().len()
+            }
+        };
+        assert_eq!(count("bldg/zone-1/temp").await, 1);
+        assert_eq!(count("bldg/zone-2/temp").await, 1);
+        assert_eq!(count("bldg/meter/kwh").await, 3);
+
+        let reply = actor.ask(ServiceMsg::GetStatus).await.unwrap();
+        let ServiceReply::Status { extra: Some(extra), .. } = reply else {
+            panic!("Expected Status reply");
+        };
+        assert_eq!(extra["purge"]["runs"], 1);
+        assert_eq!(extra["purge"]["total_deleted"], 4);
+
+        let (reply_tx, reply_rx) = oneshot::channel();
+        let _ = actor.ask(HistoryMsg::Compact { reply: reply_tx }).await;
+        assert!(reply_rx.await.unwrap().is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_history_write_throughput() {
+        // 200 points x 100 samples, committed in batches
+        const POINTS: usize = 200;
+        const SAMPLES: usize = 100;
+
+        let dir = tempdir().unwrap();
+        let mut actor = HistoryActor::new(HistoryConfig {
+            db_path: dir.path().join("test_history.redb").to_string_lossy().to_string(),
+            sample_interval_ms: 0,
+            ..Default::default()
+        });
+        actor.do_start().unwrap();
+
+        let points: Vec<String> = (0..POINTS).map(|i| format!("net/dev-{}/AI:{}", i / 10, i)).collect();
+        let base = Utc::now() - chrono::Duration::hours(1);
+
+        let started = Instant::now();
+        for n in 0..SAMPLES {
+            let timestamp = base + chrono::Duration::seconds(n as i64);
+            for point in &points {
+                actor
+                    .store_sample(
+                        point,
+                        &PropertyValue::Real(n as f32),
+                        PointQuality::Good,
+                        Some(EngineeringUnit::DegreesFahrenheit),
+                        timestamp,
+                    )
+                    .unwrap();
+            }
+        }
+        actor.flush().unwrap();
+        let elapsed = started.elapsed();
+
+        let total = POINTS * SAMPLES;
+        let rate = total as f64 / elapsed.as_secs_f64();
+        tracing::info!(
+            "History write throughput: {} samples in {:?} ({:.0} samples/s, {} commits)",
+            total, elapsed, rate, actor.write_stats.commits
+        );
+
+        assert_eq!(actor.write_stats.samples_written, total as u64);
+        assert_eq!(actor.write_stats.commits as usize, total.div_ceil(actor.config.batch_size));
+
+        let samples = actor
+            .query_samples(&points[42], base, Utc::now(), None, None)
+            .unwrap();
+        assert_eq!(samples.len(), SAMPLES);
+    }
+
+    #[tokio::test]
+    async fn test_history_aggregate_and_downsample() {
+        let dir = tempdir().unwrap();
+        let config = HistoryConfig {
+            db_path: dir.path().join("test_history.redb").to_string_lossy().to_string(),
+            sample_interval_ms: 0,
+            ..Default::default()
+        };
+        let actor = HistoryActor::spawn(HistoryActor::new(config));
+        let _ = actor.ask(ServiceMsg::Start).await.unwrap();
+
+        // One sample per minute for 3 hours: 32°F, 50°F, 68°F by hour
+        let start = DateTime::from_timestamp(1_700_000_000 / 3600 * 3600, 0).unwrap();
+        for minute in 0..180 {
+            let event = Event::PointValueChanged {
+                point: "bldg/oat".to_string(),
+                value: PropertyValue::Real(32.0 + 18.0 * (minute / 60) as f32),
+                quality: PointQuality::Good,
+                units: Some(EngineeringUnit::DegreesFahrenheit),
+                timestamp: std::time::Instant::now(),
+                timestamp_utc: start + chrono::Duration::minutes(minute),
+            };
+            let _ = actor.ask(ServiceMsg::OnEvent { event }).await;
+        }
+        let end = start + chrono::Duration::hours(3);
+
+        let (reply_tx, reply_rx) = oneshot::channel();
+        let _ = actor
+            .ask(HistoryMsg::AggregateHistory {
+                point: "bldg/oat".to_string(),
+                start,
+                end,
+                interval: "1h".to_string(),
+                functions: vec![Aggregation::Avg, Aggregation::Count],
+                units: Some(EngineeringUnit::DegreesCelsius),
+                reply: reply_tx,
+            })
+            .await;
+        let buckets = reply_rx.await.unwrap().unwrap();
+        assert_eq!(buckets.len(), 3);
+        assert_eq!(buckets[1].start, start + chrono::Duration::hours(1));
+        assert_eq!(buckets[1].count, 60);
+        assert!((buckets[1].avg.unwrap() - 10.0).abs() < 0.01);
+        assert_eq!(buckets[1].max, None);
+        assert_eq!(buckets[1].units, Some(EngineeringUnit::DegreesCelsius));
+
+        let (reply_tx, reply_rx) = oneshot::channel();
+        let _ = actor
+            .ask(HistoryMsg::QueryHistory {
+                point: "bldg/oat".to_string(),
+                start,
+                end,
+                limit: None,
+                units: None,
+                downsample: Some(20),
+                reply: reply_tx,
+            })
+            .await;
+        let samples = reply_rx.await.unwrap().unwrap();
+        assert_eq!(samples.len(), 20);
+        assert_eq!(samples[0].timestamp, start);
+    }
+
+    #[tokio::test]
+    async fn test_history_rollups_match_raw_and_outlive_purge() {
+        let dir = tempdir().unwrap();
+        let config = HistoryConfig {
+            db_path: dir.path().join("test_history.redb").to_string_lossy().to_string(),
+            retention_days: 30,
+            sample_interval_ms: 0,
+            ..Default::default()
+        };
+        let actor = HistoryActor::spawn(HistoryActor::new(config));
+        let _ = actor.ask(ServiceMsg::Start).await.unwrap();
+
+        // Every 10 minutes for 2 days, starting at UTC midnight 40 days ago
+        let day = (Utc::now() - chrono::Duration::days(40))
+            .date_naive()
+            .and_hms_opt(0, 0, 0)
+            .unwrap()
+            .and_utc();
+        for i in 0..288 {
+            let event = Event::PointValueChanged {
+                point: "bldg/meter/kw".to_string(),
+                value: PropertyValue::Real((i % 37) as f32),
+                quality: PointQuality::Good,
+                units: Some(EngineeringUnit::Kilowatts),
+                timestamp: std::time::Instant::now(),
+                timestamp_utc: day + chrono::Duration::minutes(10 * i),
+            };
+            let _ = actor.ask(ServiceMsg::OnEvent { event }).await;
+        }
+
+        let aggregate = |start: DateTime<Utc>,
+                         end: DateTime<Utc>,
+                         interval: &'static str,
+                         functions: Vec<Aggregation>| {
+            let actor = actor.clone();
+            async move {
+                let (reply_tx, reply_rx) = oneshot::channel();
+                let _ = actor
+                    .ask(HistoryMsg::AggregateHistory {
+                        point: "bldg/meter/kw".to_string(),
+                        start,
+                        end,
+                        interval: interval.to_string(),
+                        functions,
+                        units: None,
+                        reply: reply_tx,
+                    })
+                    .await;
+                reply_rx.await.unwrap().unwrap()
+            }
+        };
+
+        // A range with partial hours at both ends: rollups in the middle, raw at
+        // the edges, same result as a raw-only query (First forces raw)
+        let start = day + chrono::Duration::minutes(35);
+        let end = day + chrono::Duration::hours(30) + chrono::Duration::minutes(15);
+        let functions = vec![
+            Aggregation::Min,
+            Aggregation::Max,
+            Aggregation::Avg,
+            Aggregation::Count,
+        ];
+        let planned = aggregate(start, end, "1h", functions.clone()).await;
+        let mut raw_functions = functions;
+        raw_functions.push(Aggregation::First);
+        let raw = aggregate(start, end, "1h", raw_functions).await;
+
+        assert_eq!(planned.len(), 31);
+        assert_eq!(planned.len(), raw.len());
+        for (p, r) in planned.iter().zip(&raw) {
+            assert_eq!(p.start, r.start);
+            assert_eq!(p.count, r.count);
+            assert_eq!(p.min, r.min);
+            assert_eq!(p.max, r.max);
+            assert!((p.avg.unwrap() - r.avg.unwrap()).abs() < 1e-9);
+            assert_eq!(p.units, Some(EngineeringUnit::Kilowatts));
+        }
+        assert_eq!(planned[0].count, 2);
+
+        // Raw samples expire; hourly and daily rollups are retained
+        loop {
+            match actor.ask(HistoryMsg::PurgeBatch).await.unwrap() {
+                HistoryReply::Purged { done: true, .. } => break,
+                HistoryReply::Purged { .. } => {}
+                other => panic!("Unexpected reply: {:?}", other),
+            }
+        }
+        let (reply_tx, reply_rx) = oneshot::channel();
+        let _ = actor
+            .ask(HistoryMsg::QueryHistory {
+                point: "bldg/meter/kw".to_string(),
+                start: day,
+                end: day + chrono::Duration::days(2),
+                limit: None,
+                units: None,
+                downsample: None,
+                reply: reply_tx,
+            })
+            .await;
+        assert!(reply_rx.await.unwrap().unwrap().is_empty());
+
+        let two_days = day + chrono::Duration::days(2);
+        let daily = aggregate(day, two_days, "1d", vec![Aggregation::Count, Aggregation::Max]).await;
+        assert_eq!(daily.len(), 2);
+        assert_eq!(daily[0].count, 144);
+        assert_eq!(daily[0].max, Some(36.0));
+        let hourly = aggregate(day, two_days, "1h", vec![Aggregation::Last]).await;
+        assert_eq!(hourly.len(), 48);
+    }
+
+    #[tokio::test]
+    async fn test_history_logging_policies() {
+        let dir = tempdir().unwrap();
+        let config: HistoryConfig = serde_json::from_value(serde_json::json!({
+            "db_path": dir.path().join("test_history.redb").to_string_lossy(),
+            "retention_days": 0,
+            "sample_interval_ms": 0,
+            "policies": [
+                {"pattern": "*/debug/*", "mode": "disabled"},
+                {"pattern": "*/temp", "mode": "cov", "deadband": {"absolute": 0.5}, "max_gap_secs": 3600},
+                {"pattern": "*/meter/*", "mode": "interval", "interval_secs": 900}
+            ]
+        }))
+        .unwrap();
+        let mut actor = HistoryActor::new(config);
+        actor.do_start().unwrap();
+
+        let t0 = DateTime::from_timestamp(1_699_999_200, 0).unwrap();
+        let at = |secs: i64| t0 + chrono::Duration::seconds(secs);
+        let mut value = |point: &str, value: f32, secs: i64| {
+            let value = PropertyValue::Real(value);
+            actor.on_point_value(point, value, PointQuality::Good, None, at(secs)).unwrap();
+        };
+        for (v, secs) in [(70.0, 0), (70.2, 60), (70.6, 120), (70.7, 180)] {
+            value("bldg/zone-1/temp", v, secs);
+        }
+        value("bldg/debug/counter", 1.0, 0);
+        value("bldg/meter/kwh", 5.0, 10);
+        value("bldg/meter/kwh", 6.0, 500);
+
+        // The meter is sampled at its 900s slot; the temp heartbeat is due
+        // an hour after its last recorded change
+        assert_eq!(actor.sample_policies(at(905)).unwrap(), 1);
+        assert_eq!(actor.sample_policies(at(910)).unwrap(), 0);
+        assert_eq!(actor.sample_policies(at(3800)).unwrap(), 2);
+        actor.flush().unwrap();
+
+        let samples = |point: &str| {
+            actor
+                .query_samples(point, t0, at(7200), None, None)
+                .unwrap()
+                .into_iter()
+                .map(|s| (s.timestamp, s.value))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            samples("bldg/zone-1/temp"),
+            vec![
+                (at(0), PropertyValue::Real(70.0)),
+                (at(120), PropertyValue::Real(70.6)),
+                (at(3800), PropertyValue::Real(70.7)),
+            ]
+        );
+        assert_eq!(
+            samples("bldg/meter/kwh"),
+            vec![
+                (at(900), PropertyValue::Real(6.0)),
+                (at(3600), PropertyValue::Real(6.0)),
+            ]
+        );
+        assert!(samples("bldg/debug/counter").is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_history_gaps_and_stale_events() {
+        let dir = tempdir().unwrap();
+        let config: HistoryConfig = serde_json::from_value(serde_json::json!({
+            "db_path": dir.path().join("test_history.redb").to_string_lossy(),
+            "retention_days": 0,
+            "sample_interval_ms": 0,
+            "policies": [
+                {"pattern": "*/sat", "mode": "cov", "max_gap_secs": 900, "stale_after_secs": 1800}
+            ]
+        }))
+        .unwrap();
+        let mut actor = HistoryActor::new(config);
+        actor.do_start().unwrap();
+
+        let t0 = DateTime::from_timestamp(1_699_999_200, 0).unwrap();
+        let at = |secs: i64| t0 + chrono::Duration::seconds(secs);
+        actor.started_at = Some(t0);
+        let value = |actor: &mut HistoryActor, value: f32, quality: PointQuality, secs: i64| {
+            let value = PropertyValue::Real(value);
+            actor.on_point_value("ahu-1/sat", value, quality, None, at(secs)).unwrap();
+        };
+        for (v, secs) in [(55.0, 0), (55.5, 300), (56.0, 600)] {
+            value(&mut actor, v, PointQuality::Good, secs);
+        }
+
+        // Nothing recorded for 30 minutes after the last sample
+        assert_eq!(actor.check_stale(at(2400)), 0);
+        assert_eq!(actor.check_stale(at(2401)), 1);
+        assert_eq!(actor.check_stale(at(2500)), 0);
+        value(&mut actor, 57.0, PointQuality::Good, 3000);
+        value(&mut actor, 0.0, PointQuality::Bad, 3300);
+        value(&mut actor, 57.5, PointQuality::Good, 3600);
+
+        let events: Vec<_> = actor
+            .events
+            .iter()
+            .map(|event| match event {
+                Event::HistoryStale {
+                    point,
+                    last_logged,
+                    ..
+                } => (point.as_str(), "stale", *last_logged),
+                Event::HistoryResumed {
+                    point,
+                    timestamp_utc,
+                    ..
+                } => (point.as_str(), "resumed", Some(*timestamp_utc)),
+                other => panic!("Unexpected event {:?}", other),
+            })
+            .collect();
+        assert_eq!(
+            events,
+            vec![
+                ("ahu-1/sat", "stale", Some(at(600))),
+                ("ahu-1/sat", "resumed", Some(at(3000))),
+            ]
+        );
+
+        actor.flush().unwrap();
+        let reports = actor
+            .gap_reports(vec!["ahu-1/sat".to_string()], t0, at(3900), None, at(100_000))
+            .unwrap();
+        let report = &reports[0];
+        assert_eq!(report.expected_interval_secs, 900);
+        assert_eq!(report.samples, 6);
+        let gaps: Vec<_> = report.gaps.iter().map(|g| (g.start, g.end)).collect();
+        assert_eq!(gaps, vec![(at(600), at(3000))]);
+        let bad: Vec<_> = report
+            .bad_quality
+            .iter()
+            .map(|p| (p.start, p.end, p.quality))
+            .collect();
+        assert_eq!(bad, vec![(at(3300), at(3600), PointQuality::Bad)]);
+        let coverage = 2100.0 / 3900.0 * 100.0;
+        assert!((report.coverage_percent - coverage).abs() < 1e-9);
+
+        // An explicit expected interval overrides the policy's, and the range
+        // stops at the current time
+        let reports = actor
+            .gap_reports(vec!["ahu-1/sat".to_string()], t0, at(7200), Some("5m"), at(3900))
+            .unwrap();
+        assert_eq!(reports[0].end, at(3900));
+        assert_eq!(reports[0].gaps.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_history_table_and_export() {
+        let dir = tempdir().unwrap();
+        let config = HistoryConfig {
+            db_path: dir.path().join("test_history.redb").to_string_lossy().to_string(),
+            retention_days: 0,
+            sample_interval_ms: 0,
+            export_dir: dir.path().join("exports").to_string_lossy().to_string(),
+            ..Default::default()
+        };
+        let actor = HistoryActor::spawn(HistoryActor::new(config));
+        let _ = actor.ask(ServiceMsg::Start).await.unwrap();
+
+        let t0 = DateTime::from_timestamp(1_699_999_800, 0).unwrap();
+        let minutes = |m: i64| t0 + chrono::Duration::minutes(m);
+        let samples = [("ahu/sat", 10.0, 0), ("ahu/sat", 20.0, 10), ("ahu/fan", 1.0, 5)];
+        for (point, value, m) in samples {
+            let event = Event::PointValueChanged {
+                point: point.to_string(),
+                value: PropertyValue::Real(value),
+                quality: PointQuality::Good,
+                units: None,
+                timestamp: std::time::Instant::now(),
+                timestamp_utc: minutes(m),
+            };
+            let _ = actor.ask(ServiceMsg::OnEvent { event }).await;
+        }
+        let points = vec!["ahu/sat".to_string(), "ahu/fan".to_string()];
+
+        let table = |interval: Option<&str>, interpolation| {
+            let actor = actor.clone();
+            let points = points.clone();
+            let interval = interval.map(str::to_string);
+            async move {
+                let (reply_tx, reply_rx) = oneshot::channel();
+                let _ = actor
+                    .ask(HistoryMsg::QueryTable {
+                        points,
+                        start: t0,
+                        end: t0 + chrono::Duration::minutes(10),
+                        interval,
+                        interpolation,
+                        units: None,
+                        limit: None,
+                        reply: reply_tx,
+                    })
+                    .await;
+                let table = reply_rx.await.unwrap().unwrap();
+                table.rows.into_iter().map(|r| r.values).collect::<Vec<_>>()
+            }
+        };
+
+        assert_eq!(
+            table(Some("5m"), Interpolation::Previous).await,
+            vec![
+                vec![Some(10.0), None],
+                vec![Some(10.0), Some(1.0)],
+                vec![Some(20.0), Some(1.0)],
+            ]
+        );
+        assert_eq!(
+            table(Some("5m"), Interpolation::Linear).await,
+            vec![
+                vec![Some(10.0), None],
+                vec![Some(15.0), Some(1.0)],
+                vec![Some(20.0), None],
+            ]
+        );
+        // Without an interval, a row per sample timestamp
+        assert_eq!(
+            table(None, Interpolation::None).await,
+            vec![
+                vec![Some(10.0), None],
+                vec![None, Some(1.0)],
+                vec![Some(20.0), None],
+            ]
+        );
+
+        let export = |format, file_name: &str| {
+            let actor = actor.clone();
+            let request = ServiceRequest::ExportHistory {
+                points: points.clone(),
+                start: t0,
+                end: t0 + chrono::Duration::minutes(10),
+                interval: Some("5m".to_string()),
+                interpolation: Interpolation::Previous,
+                units: None,
+                format,
+                file_name: file_name.to_string(),
+            };
+            async move {
+                let (reply_tx, reply_rx) = oneshot::channel();
+                let _ = actor.ask(ServiceMsg::HandleRequest { request, reply: reply_tx }).await;
+                reply_rx.await.unwrap()
+            }
+        };
+
+        let ServiceResponse::HistoryExported { path, rows } =
+            export(ExportFormat::Csv, "ahu.csv").await
+        else {
+            panic!("Expected HistoryExported response");
+        };
+        assert_eq!(rows, 3);
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "timestamp,ahu/sat,ahu/fan\n\
+             2023-11-14T22:10:00Z,10,\n\
+             2023-11-14T22:15:00Z,10,1\n\
+             2023-11-14T22:20:00Z,20,1\n"
+        );
+
+        let ServiceResponse::HistoryExported { path, rows } =
+            export(ExportFormat::Parquet, "ahu.parquet").await
+        else {
+            panic!("Expected HistoryExported response");
+        };
+        assert_eq!(rows, 3);
+        let bytes = std::fs::read(&path).unwrap();
+        assert_eq!(&bytes[..4], b"PAR1");
+        assert_eq!(&bytes[bytes.len() - 4..], b"PAR1");
+
+        // Export files stay inside the export directory
+        assert!(matches!(
+            export(ExportFormat::Csv, "../escape.csv").await,
+            ServiceResponse::Error { .. }
+        ));
+
+        // A sample in units that do not convert is skipped, not a failed query
+        let event = Event::PointValueChanged {
+            point: "ahu/sat".to_string(),
+            value: PropertyValue::Real(50.0),
+            quality: PointQuality::Good,
+            units: Some(EngineeringUnit::Percent),
+            timestamp: std::time::Instant::now(),
+            timestamp_utc: minutes(12),
+        };
+        let _ = actor.ask(ServiceMsg::OnEvent { event }).await;
+        let (reply_tx, reply_rx) = oneshot::channel();
+        let _ = actor
+            .ask(HistoryMsg::QueryTable {
+                points: points.clone(),
+                start: t0,
+                end: minutes(15),
+                interval: None,
+                interpolation: Interpolation::None,
+                units: Some(EngineeringUnit::DegreesFahrenheit),
+                limit: None,
+                reply: reply_tx,
+            })
+            .await;
+        let rows = reply_rx.await.unwrap().unwrap().rows;
+        assert_eq!(rows.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_history_subscription_backfill_then_live() {
+        let dir = tempdir().unwrap();
+        let config = HistoryConfig {
+            db_path: dir.path().join("test_history.redb").to_string_lossy().to_string(),
+            retention_days: 0,
+            sample_interval_ms: 0,
+            ..Default::default()
+        };
+        let actor = HistoryActor::spawn(HistoryActor::new(config));
+        let _ = actor.ask(ServiceMsg::Start).await.unwrap();
+
+        let now = Utc::now();
+        let at = |secs: i64| now + chrono::Duration::seconds(secs);
+        let send = |point: &str, value: f32, timestamp_utc: DateTime<Utc>| {
+            let actor = actor.clone();
+            let event = Event::PointValueChanged {
+                point: point.to_string(),
+                value: PropertyValue::Real(value),
+                quality: PointQuality::Good,
+                units: None,
+                timestamp: std::time::Instant::now(),
+                timestamp_utc,
+            };
+            async move {
+                let _ = actor.ask(ServiceMsg::OnEvent { event }).await;
+            }
+        };
+        send("ahu-1/sat", 55.0, at(-7200)).await;
+        send("ahu-1/sat", 55.5, at(-1800)).await;
+        send("ahu-1/sat", 56.0, at(-60)).await;
+        send("ahu-1/rat", 72.0, at(-60)).await;
+
+        let (sender, mut receiver) = mpsc::channel(16);
+        let (reply_tx, reply_rx) = oneshot::channel();
+        let _ = actor
+            .ask(HistoryMsg::Subscribe {
+                points: vec!["ahu-1/sat".to_string()],
+                start: at(-3600),
+                units: None,
+                sender,
+                reply: reply_tx,
+            })
+            .await;
+        let subscription_id = reply_rx.await.unwrap().unwrap();
+
+        let Some(HistoryStreamItem::Backfill { point, samples }) = receiver.recv().await else {
+            panic!("Expected backfill");
+        };
+        assert_eq!(point, "ahu-1/sat");
+        let values: Vec<_> = samples.into_iter().map(|s| s.value).collect();
+        assert_eq!(values, vec![PropertyValue::Real(55.5), PropertyValue::Real(56.0)]);
+        assert!(matches!(
+            receiver.recv().await,
+            Some(HistoryStreamItem::BackfillComplete { .. })
+        ));
+
+        // Other points and late samples are not streamed
+        send("ahu-1/rat", 72.5, at(0)).await;
+        send("ahu-1/sat", 55.8, at(-120)).await;
+        send("ahu-1/sat", 56.5, at(0)).await;
+        let Some(HistoryStreamItem::Live { point, sample }) = receiver.recv().await else {
+            panic!("Expected live sample");
+        };
+        assert_eq!((point.as_str(), sample.value), ("ahu-1/sat", PropertyValue::Real(56.5)));
+        assert_eq!(sample.timestamp, at(0));
+
+        let reply = actor.ask(HistoryMsg::Unsubscribe { subscription_id }).await.unwrap();
+        assert!(matches!(reply, HistoryReply::Unsubscribed { found: true }));
+        assert!(receiver.recv().await.is_none());
+
+        // A subscriber that does not keep up is closed rather than buffered for
+        let (sender, mut receiver) = mpsc::channel(2);
+        let (reply_tx, reply_rx) = oneshot::channel();
+        let _ = actor
+            .ask(HistoryMsg::Subscribe {
+                points: vec!["ahu-1/sat".to_string()],
+                start: at(-3600),
+                units: None,
+                sender,
+                reply: reply_tx,
+            })
+            .await;
+        let subscription_id = reply_rx.await.unwrap().unwrap();
+        send("ahu-1/sat", 57.0, at(1)).await;
+        send("ahu-1/sat", 57.5, at(2)).await;
+        let reply = actor.ask(HistoryMsg::Unsubscribe { subscription_id }).await.unwrap();
+        assert!(matches!(reply, HistoryReply::Unsubscribed { found: false }));
+        assert!(matches!(receiver.recv().await, Some(HistoryStreamItem::Backfill { .. })));
+        assert!(matches!(
+            receiver.recv().await,
+            Some(HistoryStreamItem::BackfillComplete { .. })
+        ));
+        assert!(receiver.recv().await.is_none());
+    }
+
+    /// Run one forwarding pass to completion, returning the samples forwarded
+    async fn forward(actor: &mut HistoryActor) -> usize {
+        let mut forwarded = 0;
+        for job in actor.forward_jobs() {
+            let outcome = job.run().await;
+            forwarded += actor.forward_done(outcome);
+        }
+        forwarded
+    }
+
+    #[tokio::test]
+    async fn test_history_forwards_to_backends() {
+        use tokio::io::AsyncBufReadExt;
+
+        let dir = tempdir().unwrap();
+        // An address nothing listens on yet
+        let address = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let line_file = dir.path().join("influx/history.lp");
+        let config: HistoryConfig = serde_json::from_value(serde_json::json!({
+            "db_path": dir.path().join("test_history.redb").to_string_lossy(),
+            "retention_days": 0,
+            "sample_interval_ms": 0,
+            "forward_batch_size": 2,
+            "backends": [
+                {"name": "file", "type": "influx_file", "path": line_file.to_string_lossy()},
+                {"name": "telegraf", "type": "influx_tcp", "address": address.to_string()}
+            ]
+        }))
+        .unwrap();
+        let mut actor = HistoryActor::new(config);
+        actor.do_start().unwrap();
+
+        let t0 = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        for (secs, value) in [(0, 55.0), (60, 55.5), (120, 56.0)] {
+            let at = t0 + chrono::Duration::seconds(secs);
+            let value = PropertyValue::Real(value);
+            actor.on_point_value("ahu-1/sat", value, PointQuality::Good, None, at).unwrap();
+        }
+        actor.flush().unwrap();
+
+        // The file takes a batch of two; the socket is down, so its samples stay queued.
+        // A backend gets no further batch while one is being written.
+        let jobs = actor.forward_jobs();
+        assert_eq!(jobs.len(), 2);
+        assert!(actor.forward_jobs().is_empty());
+        let mut forwarded = 0;
+        for job in jobs {
+            let outcome = job.run().await;
+            forwarded += actor.forward_done(outcome);
+        }
+        assert_eq!(forwarded, 2);
+        assert!(actor.backends[1].last_error.is_some());
+        assert_eq!(forward(&mut actor).await, 1);
+        assert_eq!(forward(&mut actor).await, 0);
+        let lines = std::fs::read_to_string(&line_file).unwrap();
+        assert_eq!(
+            lines.lines().collect::<Vec<_>>(),
+            vec![
+                "history,point=ahu-1/sat,quality=good value=55.0 1700000000000000000",
+                "history,point=ahu-1/sat,quality=good value=55.5 1700000060000000000",
+                "history,point=ahu-1/sat,quality=good value=56.0 1700000120000000000",
+            ]
+        );
+
+        // Once the listener is up the backlog drains in order
+        let listener = tokio::net::TcpListener::bind(address).await.unwrap();
+        assert_eq!(forward(&mut actor).await, 2);
+        assert!(actor.backends[1].last_error.is_none());
+        assert_eq!(forward(&mut actor).await, 1);
+        assert_eq!(forward(&mut actor).await, 0);
+
+        let (socket, _) = listener.accept().await.unwrap();
+        let mut received = tokio::io::BufReader::new(socket).lines();
+        for secs in [0, 60, 120] {
+            let line = received.next_line().await.unwrap().unwrap();
+            assert!(line.ends_with(&format!(" {}", (t0.timestamp() + secs) * 1_000_000_000)));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_history_import() {
+        let dir = tempdir().unwrap();
+        let import_dir = dir.path().join("imports");
+        let config = HistoryConfig {
+            db_path: dir.path().join("test_history.redb").to_string_lossy().to_string(),
+            retention_days: 0,
+            sample_interval_ms: 60_000,
+            import_dir: import_dir.to_string_lossy().to_string(),
+            ..Default::default()
+        };
+        let actor = HistoryActor::spawn(HistoryActor::new(config));
+        let _ = actor.ask(ServiceMsg::Start).await.unwrap();
+
+        // Already recorded: the import must not overwrite it
+        let at = |text: &str| DateTime::parse_from_rfc3339(text).unwrap().with_timezone(&Utc);
+        let event = Event::PointValueChanged {
+            point: "ahu-1/sat".to_string(),
+            value: PropertyValue::Real(99.0),
+            quality: PointQuality::Good,
+            units: None,
+            timestamp: std::time::Instant::now(),
+            timestamp_utc: at("2024-03-10T07:45:00Z"),
+        };
+        let _ = actor.ask(ServiceMsg::OnEvent { event }).await;
+
+        std::fs::create_dir_all(&import_dir).unwrap();
+        std::fs::write(
+            import_dir.join("trends.csv"),
+            "Timestamp,Point,Value,Quality\n\
+             03/10/2024 1:45 AM,AHU1.SAT,55.2,good\n\
+             03/10/2024 1:46 AM,AHU1.SAT,55.4,good\n\
+             03/10/2024 1:46:30 AM,AHU1.SAT,55.5,good\n\
+             03/10/2024 1:47 AM,AHU1.SAT,n/a,bad\n\
+             03/10/2024 1:47 AM,OLD.UNUSED,1,good\n",
+        )
+        .unwrap();
+        std::fs::write(
+            import_dir.join("trends.jsonl"),
+            "{\"timestamp\": 1710056820, \"point\": \"ahu-1/sat\", \"value\": 56}\n",
+        )
+        .unwrap();
+
+        let import = |file_name: &str, format, timezone: Option<&str>| {
+            let actor = actor.clone();
+            let request = ServiceRequest::ImportHistory {
+                file_name: file_name.to_string(),
+                format,
+                point_map: HashMap::from([("AHU1.SAT".to_string(), "ahu-1/sat".to_string())]),
+                mapped_only: format == ImportFormat::Csv,
+                timezone: timezone.map(str::to_string),
+                timestamp_format: None,
+            };
+            async move {
+                let (reply_tx, reply_rx) = oneshot::channel();
+                let _ = actor.ask(ServiceMsg::HandleRequest { request, reply: reply_tx }).await;
+                let ServiceResponse::ImportProgress { progress } = reply_rx.await.unwrap() else {
+                    panic!("Expected ImportProgress response");
+                };
+
+                // Wait for the import to finish
+                for _ in 0..200 {
+                    let (reply_tx, reply_rx) = oneshot::channel();
+                    let request = ServiceRequest::GetImportProgress {
+                        import_id: progress.import_id,
+                    };
+                    let _ = actor.ask(ServiceMsg::HandleRequest { request, reply: reply_tx }).await;
+                    let ServiceResponse::ImportProgress { progress } =
+                        reply_rx.await.unwrap()
+                    else {
+                        panic!("Expected ImportProgress response");
+                    };
+                    if progress.state != ImportState::Running {
+                        return progress;
+                    }
+                    tokio::time::sleep(Duration::from_millis(10)).await;
+                }
+                panic!("Import did not finish");
+            }
+        };
+
+        let progress = import("trends.csv", ImportFormat::Csv, Some("America/Chicago")).await;
+        assert_eq!(progress.state, ImportState::Completed);
+        assert_eq!(progress.rows_read, 5);
+        assert_eq!((progress.imported, progress.duplicates, progress.skipped), (2, 1, 2));
+        assert_eq!(progress.errors, vec!["line 5: Invalid value 'n/a'".to_string()]);
+        assert_eq!(progress.bytes_read, progress.total_bytes);
+
+        // Running it again only finds duplicates
+        let progress = import("trends.csv", ImportFormat::Csv, Some("America/Chicago")).await;
+        assert_eq!((progress.imported, progress.duplicates), (0, 3));
+
+        let progress = import("trends.jsonl", ImportFormat::JsonLines, None).await;
+        assert_eq!((progress.imported, progress.duplicates), (1, 0));
+
+        let (reply_tx, reply_rx) = oneshot::channel();
+        let _ = actor
+            .ask(HistoryMsg::QueryHistory {
+                point: "ahu-1/sat".to_string(),
+                start: at("2024-03-10T07:00:00Z"),
+                end: at("2024-03-10T08:00:00Z"),
+                limit: None,
+                units: None,
+                downsample: None,
+                reply: reply_tx,
+            })
+            .await;
+        let samples: Vec<_> = reply_rx
+            .await
+            .unwrap()
+            .unwrap()
+            .into_iter()
+            .map(|s| (s.timestamp, s.value))
+            .collect();
+        assert_eq!(
+            samples,
+            vec![
+                (at("2024-03-10T07:45:00Z"), PropertyValue::Real(99.0)),
+                (at("2024-03-10T07:46:00Z"), PropertyValue::Real(55.4)),
+                (at("2024-03-10T07:46:30Z"), PropertyValue::Real(55.5)),
+                (at("2024-03-10T07:47:00Z"), PropertyValue::Real(56.0)),
+            ]
+        );
+
+        // Unknown time zones are rejected before the import starts
+        let (reply_tx, reply_rx) = oneshot::channel();
+        let request = ServiceRequest::ImportHistory {
+            file_name: "trends.csv".to_string(),
+            format: ImportFormat::Csv,
+            point_map: HashMap::new(),
+            mapped_only: false,
+            timezone: Some("Mars/Olympus".to_string()),
+            timestamp_format: None,
+        };
+        let _ = actor.ask(ServiceMsg::HandleRequest { request, reply: reply_tx }).await;
+        assert!(matches!(reply_rx.await.unwrap(), ServiceResponse::Error { .. }));
+    }
+}
@@ -0,0 +1,320 @@
+// History Logging Policies - Which point values are recorded, and when
+//
+// Each point is logged under the first policy whose pattern and tag filter
+// match it. Points without a policy record every change, subject to the
+// global `sample_interval_ms` rate limit.
+//
+//   disabled   nothing is recorded
+//   cov        changes beyond the deadband (absolute, or percent of the last
+//              recorded value) and quality changes are recorded
+//   interval   the current value is recorded every `interval_secs`, aligned
+//              to the clock (every 900s = :00, :15, :30, :45), changed or not
+//
+// `max_gap_secs` adds a heartbeat: the current value is recorded again when
+// nothing has been recorded for that long, so a flat line still has samples
+// and a gap in the data means the value really was missing.
+//
+// `stale_after_secs` watches for points that stop logging altogether: the
+// history service publishes HistoryStale once nothing has been recorded for
+// that long, and HistoryResumed when the point logs again. The policy's
+// expected interval (`interval_secs`, else `max_gap_secs`) is what gap
+// reports measure against. Durations too long to represent are rejected when
+// the history config is loaded.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use wildmatch::WildMatch;
+
+use super::aggregate::numeric_value;
+use crate::points::{EngineeringUnit, TagFilter, TagStore};
+use crate::types::{Error, PointQuality, PropertyValue, Result};
+
+/// History logging policy for points matching a pattern and tag filter
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoggingPolicy {
+    /// Glob pattern to match point paths (e.g., "*/meter/*")
+    #[serde(default = "default_pattern")]
+    pub pattern: String,
+    /// Tag filter points must also match (e.g., "elec and meter")
+    #[serde(default)]
+    pub tag_filter: Option<String>,
+    /// How values are recorded
+    #[serde(flatten)]
+    pub mode: LoggingMode,
+    /// Record the current value again after this many seconds without a sample
+    #[serde(default)]
+    pub max_gap_secs: Option<u64>,
+    /// Publish HistoryStale after this many seconds without a sample
+    #[serde(default)]
+    pub stale_after_secs: Option<u64>,
+}
+
+fn default_pattern() -> String {
+    "*".to_string()
+}
+
+/// How a policy records values
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum LoggingMode {
+    /// Record nothing
+    Disabled,
+    /// Record changes of value beyond the deadband (any change when None)
+    Cov {
+        #[serde(default)]
+        deadband: Option<Deadband>,
+    },
+    /// Record the current value at fixed, clock-aligned intervals
+    Interval { interval_secs: u64 },
+}
+
+/// Change-of-value deadband
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Deadband {
+    /// Change in the point's units (e.g., 0.5 for ±0.5°F)
+    Absolute(f64),
+    /// Change as a percentage of the last recorded value
+    Percent(f64),
+}
+
+impl Deadband {
+    /// Whether the change from `last` to `value` exceeds the deadband
+    fn exceeded(&self, last: f64, value: f64) -> bool {
+        let change = (value - last).abs();
+        match self {
+            Self::Absolute(band) => change > *band,
+            Self::Percent(percent) => change > last.abs() * percent / 100.0,
+        }
+    }
+}
+
+/// A point value as observed or recorded
+#[derive(Debug, Clone)]
+pub(super) struct Observed {
+    pub value: PropertyValue,
+    pub quality: PointQuality,
+    pub units: Option<EngineeringUnit>,
+    pub at: DateTime<Utc>,
+}
+
+/// Logging state for one point
+#[derive(Debug, Default)]
+pub(super) struct PointLog {
+    /// Latest value seen, recorded or not
+    pub current: Option<Observed>,
+    /// Last value recorded
+    pub recorded: Option<Observed>,
+    /// When the last rate-limited sample was accepted
+    pub last_accepted: Option<std::time::Instant>,
+    /// HistoryStale has been published and logging has not resumed
+    pub stale: bool,
+}
+
+/// A number of seconds as a duration, if it can be represented
+fn seconds(secs: u64) -> Option<Duration> {
+    i64::try_from(secs).ok().and_then(Duration::try_seconds)
+}
+
+impl LoggingPolicy {
+    /// Check that the policy's durations can be represented
+    pub fn validate(&self) -> Result<()> {
+        let interval_secs = match self.mode {
+            LoggingMode::Interval { interval_secs } => Some(interval_secs),
+            _ => None,
+        };
+        let durations = [
+            ("interval_secs", interval_secs),
+            ("max_gap_secs", self.max_gap_secs),
+            ("stale_after_secs", self.stale_after_secs),
+        ];
+        for (name, secs) in durations {
+            if let Some(secs) = secs.filter(|secs| seconds(*secs).is_none()) {
+                return Err(Error::Config(format!(
+                    "History policy '{}': {} of {} is too long",
+                    self.pattern, name, secs
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether a point matches the policy's pattern and tag filter
+    pub(super) fn matches(
+        &self,
+        point: &str,
+        tag_store: Option<&TagStore>,
+        tag_filters: &mut HashMap<String, Option<TagFilter>>,
+    ) -> bool {
+        if !WildMatch::new(&self.pattern).matches(point) {
+            return false;
+        }
+        let Some(filter_text) = &self.tag_filter else {
+            return true;
+        };
+        let Some(store) = tag_store else {
+            return false;
+        };
+
+        let filter = tag_filters
+            .entry(filter_text.clone())
+            .or_insert_with(|| match TagFilter::parse(filter_text) {
+                Ok(filter) => Some(filter),
+                Err(e) => {
+                    tracing::warn!(
+                        "History policy '{}' has an invalid tag filter: {}",
+                        self.pattern,
+                        e
+                    );
+                    None
+                }
+            });
+
+        filter.as_ref().is_some_and(|f| store.matches(point, f))
+    }
+
+    /// Longest expected time between samples, in seconds
+    pub(super) fn expected_interval_secs(&self) -> Option<u64> {
+        match self.mode {
+            LoggingMode::Interval { interval_secs } => Some(interval_secs),
+            _ => self.max_gap_secs,
+        }
+    }
+
+    /// Whether a point last recorded at `last` is stale at `now`
+    pub(super) fn is_stale(&self, last: DateTime<Utc>, now: DateTime<Utc>) -> bool {
+        self.stale_after_secs
+            .and_then(seconds)
+            .is_some_and(|after| now - last > after)
+    }
+
+    /// Whether a new value should be recorded as it arrives
+    pub(super) fn records_change(&self, recorded: Option<&Observed>, value: &Observed) -> bool {
+        let LoggingMode::Cov { deadband } = &self.mode else {
+            return false;
+        };
+        let Some(recorded) = recorded else {
+            return true;
+        };
+        if recorded.quality != value.quality || recorded.units != value.units {
+            return true;
+        }
+
+        match (
+            deadband,
+            numeric_value(&recorded.value),
+            numeric_value(&value.value),
+        ) {
+            (Some(deadband), Some(last), Some(new)) => deadband.exceeded(last, new),
+            _ => recorded.value != value.value,
+        }
+    }
+
+    /// Timestamp to record the current value at, if a sample is due at `now`
+    ///
+    /// Interval samples are due at each clock-aligned slot once the value was
+    /// known at that slot; heartbeats are due `max_gap_secs` after the last
+    /// recorded sample.
+    pub(super) fn due(&self, log: &PointLog, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let current = log.current.as_ref()?;
+        let last_recorded = log.recorded.as_ref().map(|r| r.at);
+
+        if let LoggingMode::Interval { interval_secs } = self.mode {
+            let width = interval_secs.max(1) as i64;
+            let slot = DateTime::from_timestamp(now.timestamp().div_euclid(width) * width, 0)?;
+            if current.at <= slot && last_recorded.is_none_or(|at| at < slot) {
+                return Some(slot);
+            }
+        }
+
+        match (self.mode == LoggingMode::Disabled, self.max_gap_secs, last_recorded) {
+            (false, Some(gap), Some(at)) if seconds(gap).is_some_and(|gap| now - at >= gap) => {
+                Some(now)
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn observed(value: f32, quality: PointQuality, secs: i64) -> Observed {
+        Observed {
+            value: PropertyValue::Real(value),
+            quality,
+            units: None,
+            at: DateTime::from_timestamp(secs, 0).unwrap(),
+        }
+    }
+
+    fn policy(json: serde_json::Value) -> LoggingPolicy {
+        serde_json::from_value(json).unwrap()
+    }
+
+    #[test]
+    fn test_cov_deadband() {
+        let absolute = policy(serde_json::json!({"mode": "cov", "deadband": {"absolute": 0.5}}));
+        let percent = policy(serde_json::json!({"mode": "cov", "deadband": {"percent": 10.0}}));
+        let any = policy(serde_json::json!({"mode": "cov"}));
+        let last = observed(70.0, PointQuality::Good, 0);
+
+        assert!(absolute.records_change(None, &last));
+        assert!(!absolute.records_change(Some(&last), &observed(70.4, PointQuality::Good, 1)));
+        assert!(absolute.records_change(Some(&last), &observed(69.4, PointQuality::Good, 1)));
+        assert!(!percent.records_change(Some(&last), &observed(76.0, PointQuality::Good, 1)));
+        assert!(percent.records_change(Some(&last), &observed(78.0, PointQuality::Good, 1)));
+        assert!(any.records_change(Some(&last), &observed(70.1, PointQuality::Good, 1)));
+        assert!(!any.records_change(Some(&last), &observed(70.0, PointQuality::Good, 1)));
+
+        // Quality changes are always recorded
+        assert!(absolute.records_change(Some(&last), &observed(70.0, PointQuality::Bad, 1)));
+    }
+
+    #[test]
+    fn test_interval_and_heartbeat_due() {
+        let interval = policy(serde_json::json!({"mode": "interval", "interval_secs": 900}));
+        let heartbeat = policy(serde_json::json!({"mode": "cov", "max_gap_secs": 3600}));
+        let at = |secs| DateTime::from_timestamp(secs, 0).unwrap();
+
+        let mut log = PointLog {
+            current: Some(observed(1.0, PointQuality::Good, 100)),
+            ..Default::default()
+        };
+        // Value first seen after the 0s slot: due at the 900s slot
+        assert_eq!(interval.due(&log, at(850)), None);
+        assert_eq!(interval.due(&log, at(905)), Some(at(900)));
+        log.recorded = Some(observed(1.0, PointQuality::Good, 900));
+        assert_eq!(interval.due(&log, at(1700)), None);
+        assert_eq!(interval.due(&log, at(1800)), Some(at(1800)));
+
+        assert_eq!(heartbeat.due(&log, at(4000)), None);
+        assert_eq!(heartbeat.due(&log, at(4500)), Some(at(4500)));
+    }
+
+    #[test]
+    fn test_policy_durations_validated() {
+        let stale = policy(serde_json::json!({"mode": "cov", "stale_after_secs": 300}));
+        assert!(stale.validate().is_ok());
+
+        // Too long to represent: rejected on load, and never due or stale
+        let gap = policy(serde_json::json!({"mode": "cov", "max_gap_secs": u64::MAX}));
+        assert!(matches!(gap.validate(), Err(Error::Config(_))));
+        let stale = policy(serde_json::json!({"mode": "cov", "stale_after_secs": u64::MAX}));
+        assert!(matches!(stale.validate(), Err(Error::Config(_))));
+        let interval = policy(serde_json::json!({"mode": "interval", "interval_secs": u64::MAX}));
+        assert!(matches!(interval.validate(), Err(Error::Config(_))));
+
+        let at = |secs| DateTime::from_timestamp(secs, 0).unwrap();
+        let log = PointLog {
+            current: Some(observed(1.0, PointQuality::Good, 0)),
+            recorded: Some(observed(1.0, PointQuality::Good, 0)),
+            ..Default::default()
+        };
+        assert_eq!(gap.due(&log, at(1_000_000)), None);
+        assert!(!stale.is_stale(at(0), at(1_000_000)));
+    }
+}
@@ -0,0 +1,341 @@
+// History Rollups - Pre-computed hourly and daily aggregates
+//
+// Each committed numeric sample also updates the hourly and daily rollup row
+// for its point, in the same write transaction as the sample. A rollup row
+// holds count, min, max, sum and the last value of its bucket, so long-range
+// aggregate queries read one row per hour or day instead of every sample, and
+// the trend survives after raw samples are purged.
+//
+// Rows are keyed by (point id, bucket start micros), with buckets aligned to
+// the Unix epoch like query-time aggregation (daily buckets start at UTC
+// midnight). Bad quality and non-numeric samples are not rolled up. A row
+// keeps the units of its first sample: later samples are converted to them,
+// and samples that cannot be (the point's units changed, or were added or
+// removed) are left out of the row rather than mixed into it.
+//
+//   bytes 0-7    count (u64)
+//   bytes 8-39   min, max, sum, last (f64)
+//   bytes 40-47  timestamp of the last value (i64 micros)
+//   bytes 48-49  engineering unit code (see codec)
+
+use std::collections::HashMap;
+
+use redb::{ReadableTable, TableDefinition, WriteTransaction};
+
+use super::codec;
+use crate::points::EngineeringUnit;
+use crate::services::messages::Aggregation;
+use crate::types::{Error, Result};
+
+// Hourly rollups: key is (point id, hour start micros), value is a rollup record
+const HOURLY_TABLE: TableDefinition<(u32, i64), &[u8]> = TableDefinition::new("history_rollup_hourly");
+
+// Daily rollups: key is (point id, day start micros), value is a rollup record
+const DAILY_TABLE: TableDefinition<(u32, i64), &[u8]> = TableDefinition::new("history_rollup_daily");
+
+const RECORD_LEN: usize = 50;
+
+/// Aggregates that can be answered from rollups
+pub(super) const ROLLUP_FUNCTIONS: [Aggregation; 6] = [
+    Aggregation::Min,
+    Aggregation::Max,
+    Aggregation::Avg,
+    Aggregation::Count,
+    Aggregation::Sum,
+    Aggregation::Last,
+];
+
+/// Rollup granularity
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(super) enum RollupLevel {
+    Hourly,
+    Daily,
+}
+
+impl RollupLevel {
+    /// All levels, finest first
+    pub const ALL: [Self; 2] = [Self::Hourly, Self::Daily];
+
+    /// Bucket width in microseconds
+    pub fn width(self) -> i64 {
+        match self {
+            Self::Hourly => 3_600_000_000,
+            Self::Daily => 86_400_000_000,
+        }
+    }
+
+    /// Start of the bucket containing a timestamp
+    pub fn bucket_start(self, ts: i64) -> i64 {
+        ts.div_euclid(self.width()) * self.width()
+    }
+
+    pub fn table(self) -> TableDefinition<'static, (u32, i64), &'static [u8]> {
+        match self {
+            Self::Hourly => HOURLY_TABLE,
+            Self::Daily => DAILY_TABLE,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Hourly => "hourly",
+            Self::Daily => "daily",
+        }
+    }
+}
+
+/// Aggregates for one point and bucket
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) struct RollupRecord {
+    pub count: u64,
+    pub min: f64,
+    pub max: f64,
+    pub sum: f64,
+    pub last: f64,
+    /// Timestamp of `last` in microseconds
+    pub last_ts: i64,
+    pub units: Option<EngineeringUnit>,
+}
+
+impl RollupRecord {
+    fn new(ts: i64, value: f64, units: Option<EngineeringUnit>) -> Self {
+        Self {
+            count: 1,
+            min: value,
+            max: value,
+            sum: value,
+            last: value,
+            last_ts: ts,
+            units,
+        }
+    }
+
+    /// Add a sample, converting it to the record's units when they differ
+    ///
+    /// Samples that cannot be converted are skipped.
+    fn add(&mut self, ts: i64, value: f64, units: Option<EngineeringUnit>) {
+        let value = match (units, self.units) {
+            (from, to) if from == to => value,
+            (Some(from), Some(to)) => match from.convert(value, to) {
+                Ok(value) => value,
+                Err(e) => {
+                    tracing::debug!("Skipping rollup of sample at {}: {}", ts, e);
+                    return;
+                }
+            },
+            (from, to) => {
+                tracing::debug!(
+                    "Skipping rollup of sample at {}: units {:?} differ from {:?}",
+                    ts, from, to
+                );
+                return;
+            }
+        };
+        self.count += 1;
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+        self.sum += value;
+        if ts >= self.last_ts {
+            self.last = value;
+            self.last_ts = ts;
+        }
+    }
+
+    /// Merge another record for the same bucket into this one
+    ///
+    /// A record that cannot be converted to this one's units is skipped.
+    fn merge(&mut self, other: &RollupRecord) {
+        let other = match (other.units, self.units) {
+            (from, to) if from == to => *other,
+            (Some(_), Some(_)) => match other.convert(self.units) {
+                Ok(other) => other,
+                Err(e) => {
+                    tracing::debug!("Skipping rollup merge at {}: {}", other.last_ts, e);
+                    return;
+                }
+            },
+            (from, to) => {
+                tracing::debug!(
+                    "Skipping rollup merge at {}: units {:?} differ from {:?}",
+                    other.last_ts, from, to
+                );
+                return;
+            }
+        };
+        self.count += other.count;
+        self.min = self.min.min(other.min);
+        self.max = self.max.max(other.max);
+        self.sum += other.sum;
+        if other.last_ts >= self.last_ts {
+            self.last = other.last;
+            self.last_ts = other.last_ts;
+        }
+    }
+
+    /// Convert to other units (records without units are returned as-is)
+    pub fn convert(&self, units: Option<EngineeringUnit>) -> Result<Self> {
+        let (Some(from), Some(to)) = (self.units, units) else {
+            return Ok(*self);
+        };
+        if from == to {
+            return Ok(*self);
+        }
+
+        let convert = |value: f64| from.convert(value, to).map_err(|e| Error::Service(e.to_string()));
+        let avg = convert(self.sum / self.count.max(1) as f64)?;
+        Ok(Self {
+            count: self.count,
+            min: convert(self.min)?,
+            max: convert(self.max)?,
+            // Conversions may have an offset, so the sum follows the average
+            sum: avg * self.count as f64,
+            last: convert(self.last)?,
+            last_ts: self.last_ts,
+            units: Some(to),
+        })
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(RECORD_LEN);
+        bytes.extend_from_slice(&self.count.to_le_bytes());
+        for value in [self.min, self.max, self.sum, self.last] {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        bytes.extend_from_slice(&self.last_ts.to_le_bytes());
+        bytes.extend_from_slice(&codec::units_code(self.units).to_le_bytes());
+        bytes
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() != RECORD_LEN {
+            return Err(Error::Database(format!(
+                "Invalid rollup record length {}",
+                bytes.len()
+            )));
+        }
+        let word = |at: usize| {
+            let mut word = [0u8; 8];
+            word.copy_from_slice(&bytes[at..at + 8]);
+            word
+        };
+
+        Ok(Self {
+            count: u64::from_le_bytes(word(0)),
+            min: f64::from_le_bytes(word(8)),
+            max: f64::from_le_bytes(word(16)),
+            sum: f64::from_le_bytes(word(24)),
+            last: f64::from_le_bytes(word(32)),
+            last_ts: i64::from_le_bytes(word(40)),
+            units: codec::units_from_code(u16::from_le_bytes([bytes[48], bytes[49]])),
+        })
+    }
+}
+
+/// Rollup updates for a batch of samples, keyed by (level, point id, bucket start)
+#[derive(Debug, Default)]
+pub(super) struct RollupUpdates {
+    records: HashMap<(RollupLevel, u32, i64), RollupRecord>,
+}
+
+impl RollupUpdates {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a numeric sample to every rollup level
+    pub fn add(&mut self, id: u32, ts: i64, value: f64, units: Option<EngineeringUnit>) {
+        for level in RollupLevel::ALL {
+            self.records
+                .entry((level, id, level.bucket_start(ts)))
+                .and_modify(|record| record.add(ts, value, units))
+                .or_insert_with(|| RollupRecord::new(ts, value, units));
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    /// Merge the updates into the rollup tables
+    pub fn write(&self, txn: &WriteTransaction) -> Result<()> {
+        for level in RollupLevel::ALL {
+            let mut table = txn
+                .open_table(level.table())
+                .map_err(|e| Error::Database(e.to_string()))?;
+
+            for ((_, id, start), record) in self.records.iter().filter(|((l, _, _), _)| *l == level) {
+                let existing = table
+                    .get((*id, *start))
+                    .map_err(|e| Error::Database(e.to_string()))?
+                    .map(|stored| RollupRecord::decode(stored.value()))
+                    .transpose()?;
+                let merged = match existing {
+                    Some(mut existing) => {
+                        existing.merge(record);
+                        existing
+                    }
+                    None => *record,
+                };
+                table
+                    .insert((*id, *start), merged.encode().as_slice())
+                    .map_err(|e| Error::Database(e.to_string()))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOUR: i64 = 3_600_000_000;
+
+    #[test]
+    fn test_rollup_records() {
+        let units = Some(EngineeringUnit::DegreesFahrenheit);
+        let mut updates = RollupUpdates::new();
+        updates.add(1, 10 * HOUR + 5, 50.0, units);
+        updates.add(1, 10 * HOUR + 1, 68.0, units);
+        updates.add(1, 11 * HOUR, 32.0, units);
+
+        let hourly = updates.records[&(RollupLevel::Hourly, 1, 10 * HOUR)];
+        assert_eq!(hourly.count, 2);
+        assert_eq!((hourly.min, hourly.max, hourly.sum), (50.0, 68.0, 118.0));
+        // Last is by timestamp, not arrival order
+        assert_eq!(hourly.last, 50.0);
+
+        let daily = updates.records[&(RollupLevel::Daily, 1, 0)];
+        assert_eq!(daily.count, 3);
+        assert_eq!(daily.last, 32.0);
+
+        assert_eq!(RollupRecord::decode(&daily.encode()).unwrap(), daily);
+        assert!(RollupRecord::decode(&[0; 8]).is_err());
+
+        let celsius = daily.convert(Some(EngineeringUnit::DegreesCelsius)).unwrap();
+        assert!((celsius.min - 0.0).abs() < 1e-9);
+        assert!((celsius.max - 20.0).abs() < 1e-9);
+        assert!((celsius.sum / 3.0 - 10.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_rollup_skips_unconvertible_units() {
+        let mut updates = RollupUpdates::new();
+        updates.add(1, 10 * HOUR, 68.0, Some(EngineeringUnit::DegreesFahrenheit));
+        updates.add(1, 10 * HOUR + 1, 20.0, Some(EngineeringUnit::DegreesCelsius));
+        updates.add(1, 10 * HOUR + 2, 50.0, Some(EngineeringUnit::Percent));
+        updates.add(1, 10 * HOUR + 3, 7.0, None);
+
+        // Only the converted Celsius sample joins the Fahrenheit bucket
+        let hourly = updates.records[&(RollupLevel::Hourly, 1, 10 * HOUR)];
+        assert_eq!(hourly.count, 2);
+        assert!((hourly.min - 68.0).abs() < 1e-9);
+        assert!((hourly.max - 68.0).abs() < 1e-9);
+        assert_eq!(hourly.last_ts, 10 * HOUR + 1);
+
+        let mut merged = hourly;
+        merged.merge(&RollupRecord::new(10 * HOUR + 4, 99.0, Some(EngineeringUnit::Percent)));
+        merged.merge(&RollupRecord::new(10 * HOUR + 5, 99.0, None));
+        assert_eq!(merged, hourly);
+    }
+}
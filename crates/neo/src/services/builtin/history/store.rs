@@ -0,0 +1,873 @@
+// History Store - Where committed samples are kept
+//
+// The history service buffers samples, applies logging policies and answers
+// queries; a store keeps what it commits. A store writes batches of samples,
+// scans the samples of a point over a range and purges samples past their
+// retention. It also keeps what is derived from samples in step with them,
+// committed in the same transaction: hourly and daily rollups (see rollup) and
+// the outbox of each forwarding backend (see backend).
+//
+// RedbStore, an embedded redb file, is the default store. Points get a
+// numeric id on first sample; samples are keyed by (point id, timestamp) and
+// stored in a compact binary format (see codec). Stores written by older
+// versions (JSON samples under "point:timestamp" keys) are migrated on open.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use redb::{Database, ReadableTable, Table, TableDefinition, TableError};
+use serde::{Deserialize, Serialize};
+
+use super::aggregate::numeric_value;
+use super::align::{AlignSpec, align};
+use super::codec::{self, SampleRecord};
+use super::export::{self, TableSink};
+use super::rollup::{RollupLevel, RollupRecord, RollupUpdates};
+use crate::points::EngineeringUnit;
+use crate::services::messages::{ExportFormat, HistorySample};
+use crate::types::{Error, PointQuality, PropertyValue, Result};
+
+// Point ids: key is the point path, value is its numeric id
+const POINTS_TABLE: TableDefinition<&str, u32> = TableDefinition::new("history_points");
+
+// Samples: key is (point id, timestamp_micros), value is a binary sample (see codec)
+const SAMPLES_TABLE: TableDefinition<(u32, i64), &[u8]> = TableDefinition::new("history_samples");
+
+// Forwarding outbox: key is (backend name, sequence), value is a SAMPLES_TABLE key
+const OUTBOX_TABLE: TableDefinition<(&str, u64), (u32, i64)> =
+    TableDefinition::new("history_outbox");
+
+// Legacy samples: key is "point_name:timestamp_micros", value is JSON (migrated on open)
+const LEGACY_TABLE: TableDefinition<&str, &[u8]> = TableDefinition::new("history");
+
+/// Legacy samples moved per migration transaction
+const MIGRATION_BATCH: usize = 10_000;
+
+/// An export to run on a blocking thread, returning the number of rows written
+pub(super) type ExportJob = Box<dyn FnOnce() -> Result<u64> + Send>;
+
+/// Storage for committed history
+pub(super) trait HistoryStore: Send + Sync {
+    /// Paths of the points with stored history
+    fn points(&self) -> Vec<String>;
+
+    /// Commit samples (with their rollups, if enabled) in one transaction,
+    /// queueing each for the named backends
+    ///
+    /// With `skip_existing`, samples whose point and timestamp are already
+    /// stored are left out. Returns the number of samples written.
+    fn write(
+        &mut self,
+        samples: &[PendingSample],
+        skip_existing: bool,
+        forward_to: &[String],
+    ) -> Result<usize>;
+
+    /// Visit the samples of a point between two timestamps (micros, inclusive)
+    /// in time order until the visitor returns false
+    fn scan(
+        &self,
+        point: &str,
+        start: i64,
+        end: i64,
+        visit: &mut dyn FnMut(HistorySample) -> Result<bool>,
+    ) -> Result<()>;
+
+    /// Last sample of a point before a timestamp (micros), as (timestamp, quality)
+    fn sample_before(&self, point: &str, before: i64) -> Result<Option<(i64, PointQuality)>>;
+
+    /// Visit the rollup rows of a point with bucket starts in [start, end)
+    fn scan_rollups(
+        &self,
+        point: &str,
+        level: RollupLevel,
+        start: i64,
+        end: i64,
+        visit: &mut dyn FnMut(i64, RollupRecord) -> Result<()>,
+    ) -> Result<()>;
+
+    /// Delete up to `limit` rows older than their cutoff, continuing a sweep
+    /// from `cursor`
+    ///
+    /// `cutoff` gives the cutoff (micros) of a point's rows in a table, or
+    /// None to keep them. Returns the number of rows deleted and whether the
+    /// sweep is complete.
+    fn purge(
+        &mut self,
+        cursor: &mut PurgeCursor,
+        limit: usize,
+        cutoff: &dyn Fn(PurgeTable, &str) -> Option<i64>,
+    ) -> Result<(usize, bool)>;
+
+    /// Reclaim space freed by purging, returning whether any was reclaimed
+    fn compact(&mut self) -> Result<bool>;
+
+    /// The oldest queued samples of a backend as (sequence, sample)
+    ///
+    /// Samples purged since they were queued are None.
+    fn outbox_batch(
+        &self,
+        name: &str,
+        limit: usize,
+    ) -> Result<Vec<(u64, Option<(String, HistorySample)>)>>;
+
+    /// Remove entries from a backend's outbox
+    fn remove_from_outbox(&mut self, name: &str, sequences: &[u64]) -> Result<()>;
+
+    /// Stream several points aligned on a common time grid to a sink
+    fn query_table(&self, spec: &AlignSpec, sink: &mut TableSink) -> Result<()>;
+
+    /// Prepare an export of aligned history to a file
+    ///
+    /// The export reads a snapshot taken when it runs, so samples committed
+    /// while it runs are not included.
+    fn export(&self, spec: AlignSpec, format: ExportFormat, path: PathBuf) -> Result<ExportJob>;
+}
+
+/// A table swept by the purge
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum PurgeTable {
+    Samples,
+    Rollup(RollupLevel),
+}
+
+impl PurgeTable {
+    /// Tables in sweep order
+    const ALL: [Self; 3] = [
+        Self::Samples,
+        Self::Rollup(RollupLevel::Hourly),
+        Self::Rollup(RollupLevel::Daily),
+    ];
+
+    fn definition(self) -> TableDefinition<'static, (u32, i64), &'static [u8]> {
+        match self {
+            Self::Samples => SAMPLES_TABLE,
+            Self::Rollup(level) => level.table(),
+        }
+    }
+}
+
+/// Where a purge sweep resumes
+#[derive(Debug, Clone, Copy, Default)]
+pub(super) struct PurgeCursor {
+    /// Index into `PurgeTable::ALL` of the table being swept
+    table: usize,
+    /// Next point id to examine
+    point: u32,
+}
+
+/// A sample accepted but not yet committed
+pub(super) struct PendingSample {
+    point: String,
+    ts: i64,
+    bytes: Vec<u8>,
+    /// Value to roll up (None for Bad quality and non-numeric samples)
+    numeric: Option<f64>,
+    units: Option<EngineeringUnit>,
+}
+
+impl PendingSample {
+    pub fn new(
+        point: &str,
+        value: &PropertyValue,
+        quality: PointQuality,
+        units: Option<EngineeringUnit>,
+        timestamp: DateTime<Utc>,
+    ) -> Result<Self> {
+        let bytes = codec::encode(&SampleRecord {
+            value: value.clone(),
+            quality,
+            units,
+        })?;
+        Ok(Self {
+            point: point.to_string(),
+            ts: timestamp.timestamp_micros(),
+            bytes,
+            numeric: numeric_value(value).filter(|_| quality != PointQuality::Bad),
+            units,
+        })
+    }
+}
+
+/// Sample format of legacy stores
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct LegacySample {
+    /// Timestamp in microseconds since epoch
+    ts: i64,
+    /// Point value
+    v: PropertyValue,
+    /// Quality indicator
+    q: PointQuality,
+    /// Engineering units (omitted when unknown)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    u: Option<EngineeringUnit>,
+}
+
+/// Get the id of a point, allocating and recording a new one if needed
+fn point_id(
+    point_ids: &mut HashMap<String, u32>,
+    next_point_id: &mut u32,
+    table: &mut Table<&'static str, u32>,
+    point: &str,
+) -> Result<u32> {
+    if let Some(id) = point_ids.get(point) {
+        return Ok(*id);
+    }
+    let id = *next_point_id;
+    table
+        .insert(point, id)
+        .map_err(|e| Error::Database(e.to_string()))?;
+    *next_point_id += 1;
+    point_ids.insert(point.to_string(), id);
+    Ok(id)
+}
+
+/// Write a batch of samples (and their rollups, if enabled) in one transaction
+fn write_batch(
+    db: &Database,
+    point_ids: &mut HashMap<String, u32>,
+    next_point_id: &mut u32,
+    pending: &[PendingSample],
+    rollups: bool,
+    skip_existing: bool,
+    forward_to: &[String],
+) -> Result<usize> {
+    let write_txn = db.begin_write().map_err(|e| Error::Database(e.to_string()))?;
+    let mut updates = RollupUpdates::new();
+    let mut written = 0;
+    {
+        let mut points = write_txn
+            .open_table(POINTS_TABLE)
+            .map_err(|e| Error::Database(e.to_string()))?;
+        let mut samples = write_txn
+            .open_table(SAMPLES_TABLE)
+            .map_err(|e| Error::Database(e.to_string()))?;
+        let mut outbox = write_txn
+            .open_table(OUTBOX_TABLE)
+            .map_err(|e| Error::Database(e.to_string()))?;
+
+        // Next outbox sequence per backend
+        let mut sequences = Vec::with_capacity(forward_to.len());
+        for name in forward_to {
+            let last = outbox
+                .range((name.as_str(), 0)..=(name.as_str(), u64::MAX))
+                .map_err(|e| Error::Database(e.to_string()))?
+                .next_back()
+                .transpose()
+                .map_err(|e| Error::Database(e.to_string()))?;
+            sequences.push(last.map(|(key, _)| key.value().1 + 1).unwrap_or(0));
+        }
+
+        for sample in pending {
+            let id = point_id(point_ids, next_point_id, &mut points, &sample.point)?;
+            if skip_existing
+                && samples
+                    .get((id, sample.ts))
+                    .map_err(|e| Error::Database(e.to_string()))?
+                    .is_some()
+            {
+                continue;
+            }
+            samples
+                .insert((id, sample.ts), sample.bytes.as_slice())
+                .map_err(|e| Error::Database(e.to_string()))?;
+            if let (true, Some(value)) = (rollups, sample.numeric) {
+                updates.add(id, sample.ts, value, sample.units);
+            }
+            for (name, sequence) in forward_to.iter().zip(sequences.iter_mut()) {
+                outbox
+                    .insert((name.as_str(), *sequence), (id, sample.ts))
+                    .map_err(|e| Error::Database(e.to_string()))?;
+                *sequence += 1;
+            }
+            written += 1;
+        }
+    }
+    if !updates.is_empty() {
+        updates.write(&write_txn)?;
+    }
+    write_txn
+        .commit()
+        .map_err(|e| Error::Database(e.to_string()))?;
+    Ok(written)
+}
+
+/// Whether the rollup tables have been created
+fn rollup_tables_exist(db: &Database) -> Result<bool> {
+    let read_txn = db.begin_read().map_err(|e| Error::Database(e.to_string()))?;
+    for level in RollupLevel::ALL {
+        match read_txn.open_table(level.table()) {
+            Ok(_) => {}
+            Err(TableError::TableDoesNotExist(_)) => return Ok(false),
+            Err(e) => return Err(Error::Database(e.to_string())),
+        }
+    }
+    Ok(true)
+}
+
+/// History kept in an embedded redb database
+pub(super) struct RedbStore {
+    /// Shared with export threads
+    db: Arc<Database>,
+    rollups: bool,
+    /// Numeric ids of known points
+    point_ids: HashMap<String, u32>,
+    next_point_id: u32,
+}
+
+impl RedbStore {
+    /// Open or create a store, migrating legacy samples, and building the
+    /// rollups from raw samples when `rollups` is newly enabled
+    pub fn open(path: &Path, rollups: bool) -> Result<Self> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(Error::Io)?;
+        }
+        let db = Database::create(path)
+            .map_err(|e| Error::Database(format!("Failed to open database: {}", e)))?;
+        let rebuild_rollups = rollups && !rollup_tables_exist(&db)?;
+
+        // Ensure tables exist
+        let write_txn = db.begin_write().map_err(|e| Error::Database(e.to_string()))?;
+        write_txn
+            .open_table(POINTS_TABLE)
+            .map_err(|e| Error::Database(e.to_string()))?;
+        write_txn
+            .open_table(SAMPLES_TABLE)
+            .map_err(|e| Error::Database(e.to_string()))?;
+        write_txn
+            .open_table(OUTBOX_TABLE)
+            .map_err(|e| Error::Database(e.to_string()))?;
+        if rollups {
+            for level in RollupLevel::ALL {
+                write_txn
+                    .open_table(level.table())
+                    .map_err(|e| Error::Database(e.to_string()))?;
+            }
+        }
+        write_txn
+            .commit()
+            .map_err(|e| Error::Database(e.to_string()))?;
+
+        let mut store = Self {
+            db: Arc::new(db),
+            rollups,
+            point_ids: HashMap::new(),
+            next_point_id: 0,
+        };
+        store.load_point_ids()?;
+        store.migrate_legacy()?;
+        if rebuild_rollups {
+            store.rebuild_rollups()?;
+        }
+        Ok(store)
+    }
+
+    /// Load the point id map from the database
+    fn load_point_ids(&mut self) -> Result<()> {
+        let read_txn = self.db.begin_read().map_err(|e| Error::Database(e.to_string()))?;
+        let table = read_txn
+            .open_table(POINTS_TABLE)
+            .map_err(|e| Error::Database(e.to_string()))?;
+
+        self.point_ids.clear();
+        for entry in table.iter().map_err(|e| Error::Database(e.to_string()))? {
+            let (path, id) = entry.map_err(|e| Error::Database(e.to_string()))?;
+            self.point_ids.insert(path.value().to_string(), id.value());
+        }
+        self.next_point_id = self.point_ids.values().max().map(|id| id + 1).unwrap_or(0);
+
+        Ok(())
+    }
+
+    /// Move samples from the legacy JSON table into the binary tables
+    fn migrate_legacy(&mut self) -> Result<usize> {
+        let db = &self.db;
+
+        {
+            let read_txn = db.begin_read().map_err(|e| Error::Database(e.to_string()))?;
+            match read_txn.open_table(LEGACY_TABLE) {
+                Ok(_) => {}
+                Err(TableError::TableDoesNotExist(_)) => return Ok(0),
+                Err(e) => return Err(Error::Database(e.to_string())),
+            }
+        }
+
+        tracing::info!("Migrating legacy history samples to binary format...");
+        let mut migrated = 0;
+        let mut skipped = 0;
+
+        loop {
+            let write_txn = db.begin_write().map_err(|e| Error::Database(e.to_string()))?;
+            let mut moved = 0;
+            {
+                let mut legacy = write_txn
+                    .open_table(LEGACY_TABLE)
+                    .map_err(|e| Error::Database(e.to_string()))?;
+                let mut points = write_txn
+                    .open_table(POINTS_TABLE)
+                    .map_err(|e| Error::Database(e.to_string()))?;
+                let mut samples = write_txn
+                    .open_table(SAMPLES_TABLE)
+                    .map_err(|e| Error::Database(e.to_string()))?;
+
+                while moved < MIGRATION_BATCH {
+                    let Some((key, value)) =
+                        legacy.pop_first().map_err(|e| Error::Database(e.to_string()))?
+                    else {
+                        break;
+                    };
+                    moved += 1;
+
+                    let key = key.value().to_string();
+                    let stored: LegacySample = match serde_json::from_slice(value.value()) {
+                        Ok(stored) => stored,
+                        Err(e) => {
+                            tracing::warn!("Skipping unreadable history sample '{}': {}", key, e);
+                            skipped += 1;
+                            continue;
+                        }
+                    };
+                    let Some((point, _)) = key.rsplit_once(':') else {
+                        skipped += 1;
+                        continue;
+                    };
+
+                    let id = point_id(
+                        &mut self.point_ids,
+                        &mut self.next_point_id,
+                        &mut points,
+                        point,
+                    )?;
+                    let bytes = codec::encode(&SampleRecord {
+                        value: stored.v,
+                        quality: stored.q,
+                        units: stored.u,
+                    })?;
+                    samples
+                        .insert((id, stored.ts), bytes.as_slice())
+                        .map_err(|e| Error::Database(e.to_string()))?;
+                    migrated += 1;
+                }
+            }
+
+            if moved == 0 {
+                write_txn
+                    .delete_table(LEGACY_TABLE)
+                    .map_err(|e| Error::Database(e.to_string()))?;
+                write_txn
+                    .commit()
+                    .map_err(|e| Error::Database(e.to_string()))?;
+                break;
+            }
+            write_txn
+                .commit()
+                .map_err(|e| Error::Database(e.to_string()))?;
+        }
+
+        tracing::info!(
+            "Migrated {} legacy history samples ({} skipped)",
+            migrated,
+            skipped
+        );
+        Ok(migrated)
+    }
+
+    /// Build the rollup tables from raw samples, one point per transaction
+    fn rebuild_rollups(&mut self) -> Result<usize> {
+        tracing::info!("Building history rollups from raw samples...");
+        let mut ids: Vec<u32> = self.point_ids.values().copied().collect();
+        ids.sort_unstable();
+        let mut rolled_up = 0;
+
+        for id in ids {
+            let mut updates = RollupUpdates::new();
+            {
+                let read_txn = self.db.begin_read().map_err(|e| Error::Database(e.to_string()))?;
+                let table = read_txn
+                    .open_table(SAMPLES_TABLE)
+                    .map_err(|e| Error::Database(e.to_string()))?;
+                let range = table
+                    .range((id, i64::MIN)..=(id, i64::MAX))
+                    .map_err(|e| Error::Database(e.to_string()))?;
+
+                for entry in range {
+                    let (key, value) = entry.map_err(|e| Error::Database(e.to_string()))?;
+                    let record = codec::decode(value.value())?;
+                    if record.quality == PointQuality::Bad {
+                        continue;
+                    }
+                    if let Some(numeric) = numeric_value(&record.value) {
+                        updates.add(id, key.value().1, numeric, record.units);
+                        rolled_up += 1;
+                    }
+                }
+            }
+
+            if !updates.is_empty() {
+                let write_txn = self.db.begin_write().map_err(|e| Error::Database(e.to_string()))?;
+                updates.write(&write_txn)?;
+                write_txn
+                    .commit()
+                    .map_err(|e| Error::Database(e.to_string()))?;
+            }
+        }
+
+        tracing::info!("Rolled up {} history samples", rolled_up);
+        Ok(rolled_up)
+    }
+
+    /// Ids of the points of an aligned query, in column order
+    fn align_ids(&self, spec: &AlignSpec) -> Vec<Option<u32>> {
+        spec.points.iter().map(|p| self.point_ids.get(p).copied()).collect()
+    }
+}
+
+impl HistoryStore for RedbStore {
+    fn points(&self) -> Vec<String> {
+        self.point_ids.keys().cloned().collect()
+    }
+
+    fn write(
+        &mut self,
+        samples: &[PendingSample],
+        skip_existing: bool,
+        forward_to: &[String],
+    ) -> Result<usize> {
+        let result = write_batch(
+            &self.db,
+            &mut self.point_ids,
+            &mut self.next_point_id,
+            samples,
+            self.rollups,
+            skip_existing,
+            forward_to,
+        );
+        if result.is_err() {
+            // Ids allocated in the failed transaction were not stored
+            if let Err(e) = self.load_point_ids() {
+                tracing::warn!("Failed to reload history point ids: {}", e);
+            }
+        }
+        result
+    }
+
+    fn scan(
+        &self,
+        point: &str,
+        start: i64,
+        end: i64,
+        visit: &mut dyn FnMut(HistorySample) -> Result<bool>,
+    ) -> Result<()> {
+        let Some(&id) = self.point_ids.get(point) else {
+            return Ok(());
+        };
+        if start > end {
+            return Ok(());
+        }
+
+        let read_txn = self.db.begin_read().map_err(|e| Error::Database(e.to_string()))?;
+        let table = read_txn
+            .open_table(SAMPLES_TABLE)
+            .map_err(|e| Error::Database(e.to_string()))?;
+
+        let range = table
+            .range((id, start)..=(id, end))
+            .map_err(|e| Error::Database(e.to_string()))?;
+
+        for result in range {
+            let (key, value) = result.map_err(|e| Error::Database(e.to_string()))?;
+            let (_, ts) = key.value();
+            let record = codec::decode(value.value())?;
+            let sample = HistorySample {
+                timestamp: DateTime::from_timestamp_micros(ts).unwrap_or_else(Utc::now),
+                value: record.value,
+                quality: record.quality,
+                units: record.units,
+            };
+            if !visit(sample)? {
+                break;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn sample_before(&self, point: &str, before: i64) -> Result<Option<(i64, PointQuality)>> {
+        let Some(&id) = self.point_ids.get(point) else {
+            return Ok(None);
+        };
+
+        let read_txn = self.db.begin_read().map_err(|e| Error::Database(e.to_string()))?;
+        let table = read_txn
+            .open_table(SAMPLES_TABLE)
+            .map_err(|e| Error::Database(e.to_string()))?;
+        let mut range = table
+            .range((id, i64::MIN)..(id, before))
+            .map_err(|e| Error::Database(e.to_string()))?;
+
+        match range.next_back() {
+            Some(entry) => {
+                let (key, value) = entry.map_err(|e| Error::Database(e.to_string()))?;
+                Ok(Some((key.value().1, codec::decode(value.value())?.quality)))
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn scan_rollups(
+        &self,
+        point: &str,
+        level: RollupLevel,
+        start: i64,
+        end: i64,
+        visit: &mut dyn FnMut(i64, RollupRecord) -> Result<()>,
+    ) -> Result<()> {
+        let Some(&id) = self.point_ids.get(point) else {
+            return Ok(());
+        };
+
+        let read_txn = self.db.begin_read().map_err(|e| Error::Database(e.to_string()))?;
+        let table = read_txn
+            .open_table(level.table())
+            .map_err(|e| Error::Database(e.to_string()))?;
+
+        for entry in table
+            .range((id, start)..(id, end))
+            .map_err(|e| Error::Database(e.to_string()))?
+        {
+            let (key, value) = entry.map_err(|e| Error::Database(e.to_string()))?;
+            visit(key.value().1, RollupRecord::decode(value.value())?)?;
+        }
+
+        Ok(())
+    }
+
+    fn purge(
+        &mut self,
+        cursor: &mut PurgeCursor,
+        limit: usize,
+        cutoff: &dyn Fn(PurgeTable, &str) -> Option<i64>,
+    ) -> Result<(usize, bool)> {
+        let limit = limit.max(1);
+        let mut expired: Vec<(PurgeTable, (u32, i64))> = Vec::new();
+        let mut done = true;
+        {
+            let read_txn = self.db.begin_read().map_err(|e| Error::Database(e.to_string()))?;
+
+            'tables: while let Some(&target) = PurgeTable::ALL.get(cursor.table) {
+                // Cutoffs of the points from the cursor on, in id order
+                let mut cutoffs: Vec<(u32, i64)> = match target {
+                    PurgeTable::Rollup(_) if !self.rollups => Vec::new(),
+                    _ => self
+                        .point_ids
+                        .iter()
+                        .filter(|(_, id)| **id >= cursor.point)
+                        .filter_map(|(point, id)| Some((*id, cutoff(target, point)?)))
+                        .collect(),
+                };
+                cutoffs.sort_unstable();
+
+                if !cutoffs.is_empty() {
+                    let table = read_txn
+                        .open_table(target.definition())
+                        .map_err(|e| Error::Database(e.to_string()))?;
+
+                    for (id, cutoff) in cutoffs {
+                        let range = table
+                            .range((id, i64::MIN)..(id, cutoff))
+                            .map_err(|e| Error::Database(e.to_string()))?;
+                        for entry in range.take(limit - expired.len()) {
+                            let (key, _) = entry.map_err(|e| Error::Database(e.to_string()))?;
+                            expired.push((target, key.value()));
+                        }
+
+                        if expired.len() >= limit {
+                            // This point may have more expired rows: resume here
+                            cursor.point = id;
+                            done = false;
+                            break 'tables;
+                        }
+                        cursor.point = id + 1;
+                    }
+                }
+                cursor.table += 1;
+                cursor.point = 0;
+            }
+        }
+
+        if !expired.is_empty() {
+            let write_txn = self.db.begin_write().map_err(|e| Error::Database(e.to_string()))?;
+            for target in PurgeTable::ALL {
+                let mut keys = expired.iter().filter(|(t, _)| *t == target).peekable();
+                if keys.peek().is_none() {
+                    continue;
+                }
+                let mut table = write_txn
+                    .open_table(target.definition())
+                    .map_err(|e| Error::Database(e.to_string()))?;
+                for (_, key) in keys {
+                    table
+                        .remove(*key)
+                        .map_err(|e| Error::Database(e.to_string()))?;
+                }
+            }
+            write_txn
+                .commit()
+                .map_err(|e| Error::Database(e.to_string()))?;
+        }
+
+        Ok((expired.len(), done))
+    }
+
+    fn compact(&mut self) -> Result<bool> {
+        let db = Arc::get_mut(&mut self.db)
+            .ok_or_else(|| Error::Service("Database is in use by an export".to_string()))?;
+        db.compact()
+            .map_err(|e| Error::Database(format!("Compaction failed: {}", e)))
+    }
+
+    fn outbox_batch(
+        &self,
+        name: &str,
+        limit: usize,
+    ) -> Result<Vec<(u64, Option<(String, HistorySample)>)>> {
+        let read_txn = self.db.begin_read().map_err(|e| Error::Database(e.to_string()))?;
+        let outbox = read_txn
+            .open_table(OUTBOX_TABLE)
+            .map_err(|e| Error::Database(e.to_string()))?;
+        let samples = read_txn
+            .open_table(SAMPLES_TABLE)
+            .map_err(|e| Error::Database(e.to_string()))?;
+        let points: HashMap<u32, &str> = self
+            .point_ids
+            .iter()
+            .map(|(point, id)| (*id, point.as_str()))
+            .collect();
+
+        let range = outbox
+            .range((name, 0)..=(name, u64::MAX))
+            .map_err(|e| Error::Database(e.to_string()))?;
+        let mut batch = Vec::new();
+        for entry in range.take(limit) {
+            let (key, value) = entry.map_err(|e| Error::Database(e.to_string()))?;
+            let (id, ts) = value.value();
+            let stored = samples
+                .get((id, ts))
+                .map_err(|e| Error::Database(e.to_string()))?;
+            let sample = match (points.get(&id), stored) {
+                (Some(point), Some(bytes)) => {
+                    let record = codec::decode(bytes.value())?;
+                    let sample = HistorySample {
+                        timestamp: DateTime::from_timestamp_micros(ts).unwrap_or_default(),
+                        value: record.value,
+                        quality: record.quality,
+                        units: record.units,
+                    };
+                    Some((point.to_string(), sample))
+                }
+                _ => None,
+            };
+            batch.push((key.value().1, sample));
+        }
+        Ok(batch)
+    }
+
+    fn remove_from_outbox(&mut self, name: &str, sequences: &[u64]) -> Result<()> {
+        let write_txn = self.db.begin_write().map_err(|e| Error::Database(e.to_string()))?;
+        {
+            let mut outbox = write_txn
+                .open_table(OUTBOX_TABLE)
+                .map_err(|e| Error::Database(e.to_string()))?;
+            for sequence in sequences {
+                outbox
+                    .remove((name, *sequence))
+                    .map_err(|e| Error::Database(e.to_string()))?;
+            }
+        }
+        write_txn
+            .commit()
+            .map_err(|e| Error::Database(e.to_string()))?;
+        Ok(())
+    }
+
+    fn query_table(&self, spec: &AlignSpec, sink: &mut TableSink) -> Result<()> {
+        let read_txn = self.db.begin_read().map_err(|e| Error::Database(e.to_string()))?;
+        let table = read_txn
+            .open_table(SAMPLES_TABLE)
+            .map_err(|e| Error::Database(e.to_string()))?;
+
+        align(&table, spec, &self.align_ids(spec), sink)?;
+        Ok(())
+    }
+
+    fn export(&self, spec: AlignSpec, format: ExportFormat, path: PathBuf) -> Result<ExportJob> {
+        let db = self.db.clone();
+        let ids = self.align_ids(&spec);
+
+        Ok(Box::new(move || {
+            let read_txn = db.begin_read().map_err(|e| Error::Database(e.to_string()))?;
+            let table = read_txn
+                .open_table(SAMPLES_TABLE)
+                .map_err(|e| Error::Database(e.to_string()))?;
+            export::export(&table, &spec, &ids, format, &path)
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_redb_store_migrates_legacy_json() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test_history.redb");
+        let now = Utc::now();
+
+        // A store written by an older version
+        {
+            let db = Database::create(&db_path).unwrap();
+            let write_txn = db.begin_write().unwrap();
+            {
+                let mut table = write_txn.open_table(LEGACY_TABLE).unwrap();
+                for (i, value) in [70.0f32, 71.5, 73.0].iter().enumerate() {
+                    let ts = (now - chrono::Duration::minutes(i as i64)).timestamp_micros();
+                    let sample = LegacySample {
+                        ts,
+                        v: PropertyValue::Real(*value),
+                        q: PointQuality::Good,
+                        u: None,
+                    };
+                    let key = format!("net/AHU-1/AI:1:{:020}", ts);
+                    table
+                        .insert(key.as_str(), serde_json::to_vec(&sample).unwrap().as_slice())
+                        .unwrap();
+                }
+            }
+            write_txn.commit().unwrap();
+        }
+
+        let store = RedbStore::open(&db_path, true).unwrap();
+
+        let mut samples = Vec::new();
+        let start = (now - chrono::Duration::hours(1)).timestamp_micros();
+        store
+            .scan("net/AHU-1/AI:1", start, now.timestamp_micros(), &mut |sample| {
+                samples.push(sample);
+                Ok(true)
+            })
+            .unwrap();
+        assert_eq!(samples.len(), 3);
+        assert_eq!(samples[2].value, PropertyValue::Real(70.0));
+
+        // The legacy table is gone after migration
+        let read_txn = store.db.begin_read().unwrap();
+        assert!(matches!(
+            read_txn.open_table(LEGACY_TABLE),
+            Err(TableError::TableDoesNotExist(_))
+        ));
+    }
+}
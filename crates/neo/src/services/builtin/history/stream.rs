@@ -0,0 +1,125 @@
+// History Streams - Stored samples followed by live ones
+//
+// A subscription first receives the stored samples of its points from the
+// start time on (backfill), then BackfillComplete, then every sample recorded
+// for those points afterwards. The actor handles the subscription and the
+// recording of samples one message at a time, so nothing recorded between
+// the backfill and the first live sample is missed.
+//
+// Live samples at or before the last sample already sent for a point (late
+// data) are skipped, so nothing is sent twice. Live samples are the recorded
+// ones: values held back by a logging policy or the rate limit are not
+// streamed, and imported samples are not streamed either.
+//
+// The backfill runs inside the actor, so it is capped at MAX_BACKFILL_SAMPLES:
+// a subscription whose start is too far back is refused rather than holding
+// up recording. Items go through the subscriber's bounded channel without
+// waiting, so the channel must hold the whole backfill, and a subscriber that
+// lets it fill up afterwards is closed rather than buffered for.
+//
+// A subscription ends when its receiver is dropped, when its channel is full,
+// when it is cancelled with Unsubscribe, or when the service stops.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use tokio::sync::mpsc;
+use tokio::sync::mpsc::error::TrySendError;
+
+use super::convert_sample;
+use crate::points::EngineeringUnit;
+use crate::services::messages::{HistorySample, HistoryStreamItem};
+
+/// Stored samples per Backfill item
+pub(super) const BACKFILL_CHUNK: usize = 1000;
+
+/// Most stored samples sent to one subscription
+pub(super) const MAX_BACKFILL_SAMPLES: usize = 100_000;
+
+/// A live history subscription
+pub(super) struct Subscription {
+    points: Vec<String>,
+    units: Option<EngineeringUnit>,
+    sender: mpsc::Sender<HistoryStreamItem>,
+    /// Timestamp (micros) of the last sample sent per point
+    last_sent: HashMap<String, i64>,
+}
+
+impl Subscription {
+    pub fn new(
+        points: Vec<String>,
+        units: Option<EngineeringUnit>,
+        sender: mpsc::Sender<HistoryStreamItem>,
+    ) -> Self {
+        Self {
+            points,
+            units,
+            sender,
+            last_sent: HashMap::new(),
+        }
+    }
+
+    pub fn points(&self) -> &[String] {
+        &self.points
+    }
+
+    pub fn units(&self) -> Option<EngineeringUnit> {
+        self.units
+    }
+
+    /// Send an item; false when the receiver is gone or not keeping up
+    fn send(&self, item: HistoryStreamItem) -> bool {
+        match self.sender.try_send(item) {
+            Ok(()) => true,
+            Err(TrySendError::Full(_)) => {
+                tracing::warn!(
+                    "History subscriber is not keeping up ({} items queued), closing it",
+                    self.sender.max_capacity()
+                );
+                false
+            }
+            Err(TrySendError::Closed(_)) => false,
+        }
+    }
+
+    /// Send stored samples of a point; false when the receiver is gone
+    pub fn backfill(&mut self, point: &str, samples: Vec<HistorySample>) -> bool {
+        let Some(last) = samples.last() else {
+            return true;
+        };
+        self.last_sent
+            .insert(point.to_string(), last.timestamp.timestamp_micros());
+        let item = HistoryStreamItem::Backfill {
+            point: point.to_string(),
+            samples,
+        };
+        self.send(item)
+    }
+
+    /// Mark the end of the backfill; false when the receiver is gone
+    pub fn backfill_complete(&self, live_from: DateTime<Utc>) -> bool {
+        self.send(HistoryStreamItem::BackfillComplete { live_from })
+    }
+
+    /// Send a recorded sample if it is for one of the subscription's points;
+    /// false when the receiver is gone
+    pub fn live(&mut self, point: &str, sample: &HistorySample) -> bool {
+        if !self.points.iter().any(|p| p == point) {
+            return true;
+        }
+        let ts = sample.timestamp.timestamp_micros();
+        if self.last_sent.get(point).is_some_and(|last| ts <= *last) {
+            return true;
+        }
+
+        let Some(sample) = convert_sample(sample.clone(), self.units) else {
+            return true;
+        };
+        self.last_sent.insert(point.to_string(), ts);
+        let item = HistoryStreamItem::Live {
+            point: point.to_string(),
+            sample,
+        };
+        self.send(item)
+    }
+}
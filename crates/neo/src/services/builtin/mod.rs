@@ -3,12 +3,44 @@
 // This module contains service actor implementations:
 // - HistoryActor: Time-series data storage and retrieval
 // - AlarmActor: Alarm management and condition monitoring
+// - VirtualPointActor: Software points owned by Neo
+// - CalculatedPointActor: Points computed from expressions over other points
+// - TagActor: Semantic tag queries over points
 
 pub mod alarm;
+pub mod calculated;
+pub mod expression;
 pub mod history;
+pub mod tags;
+pub mod virtual_points;
 
 // History service
-pub use history::{HistoryActor, HistoryConfig, HistoryMsg, HistoryReply};
+pub use history::{
+    BackendConfig, BackendTarget, Deadband, HistoryActor, HistoryConfig, HistoryMsg, HistoryReply,
+    LoggingMode, LoggingPolicy, PurgeStats, RetentionOverride, RollupConfig, WriteStats,
+};
 
 // Alarm service
-pub use alarm::{AlarmActor, AlarmCondition, AlarmConfig, AlarmMsg, AlarmReply};
+pub use alarm::{
+    AckPolicy, AckRule, AlarmActor, AlarmClock, AlarmCondition, AlarmConfig, AlarmHistoryPage,
+    AlarmMsg, AlarmReply, BulkAcknowledgement, DeliveryStatus, DeviceAlarmConfig, Inhibit,
+    NotificationClass, NotificationConfig, NotificationHours, NotificationRecord, Recipient,
+    RecipientGroup, Shelf, ShelveTarget, SmtpConfig, SuppressedAlarm, Suppression,
+    SuppressionWindow,
+};
+
+// Virtual point service
+pub use virtual_points::{
+    VirtualPointActor, VirtualPointConfig, VirtualPointMsg, VirtualPointReply,
+    VirtualPointStoreConfig, VirtualPointType, WriteAccess,
+};
+
+// Calculated point service
+pub use calculated::{
+    CalculatedPointActor, CalculatedPointConfig, CalculatedPointMsg, CalculatedPointReply,
+    CalculatedPointsConfig,
+};
+pub use expression::Expression;
+
+// Tag service
+pub use tags::{TagActor, TagMsg, TagReply};
@@ -0,0 +1,226 @@
+// Tag Service - Semantic tags for points
+//
+// Actor-based front end for the shared TagStore. Learns point paths from
+// PointValueChanged (so virtual and calculated points get rule-based tags
+// too) and answers tag filter queries from the API, plugins and blueprints.
+
+use kameo::message::{Context, Message};
+use tokio::sync::oneshot;
+
+use crate::messages::Event;
+use crate::points::{TagConfig, TagFilter, TagSet, TagStore};
+use crate::services::actor::{ServiceMsg, ServiceReply, ServiceStateTracker};
+use crate::services::messages::{ServiceRequest, ServiceResponse, TaggedPoint};
+use crate::types::{Result, ServiceState};
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Tag Messages
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Tag-specific messages (beyond common ServiceMsg)
+#[derive(Debug)]
+pub enum TagMsg {
+    /// Find point paths matching a tag filter
+    Query {
+        filter: String,
+        reply: oneshot::Sender<Result<Vec<String>>>,
+    },
+    /// Get the tags of a point
+    GetTags {
+        path: String,
+        reply: oneshot::Sender<Option<TagSet>>,
+    },
+    /// Add or replace tags on a point
+    SetTags { path: String, tags: TagSet },
+}
+
+/// Reply type for TagMsg
+#[derive(Debug, kameo::Reply)]
+pub enum TagReply {
+    /// Reply sent via oneshot (or nothing to reply)
+    Done,
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Tag Actor
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Actor-based Tag Service
+#[derive(kameo::Actor)]
+pub struct TagActor {
+    id: String,
+    name: String,
+    #[allow(dead_code)]
+    description: String,
+    state: ServiceStateTracker,
+    store: TagStore,
+}
+
+impl TagActor {
+    /// Create a new TagActor serving the given (shared) tag store
+    pub fn new(store: TagStore) -> Self {
+        Self {
+            id: "tags".to_string(),
+            name: "Tag Service".to_string(),
+            description: "Semantic tags for points, equipment and sites".to_string(),
+            state: ServiceStateTracker::new(),
+            store,
+        }
+    }
+
+    /// Points matching a filter, with their tags
+    fn query(&self, filter: &str) -> Result<Vec<TaggedPoint>> {
+        let filter = TagFilter::parse(filter)?;
+        Ok(self
+            .store
+            .query(&filter)
+            .into_iter()
+            .map(|path| TaggedPoint {
+                tags: self.store.point_tags(&path).unwrap_or_default(),
+                path,
+            })
+            .collect())
+    }
+
+    fn tagged_point(&self, path: String) -> ServiceResponse {
+        match self.store.point_tags(&path) {
+            Some(tags) => ServiceResponse::PointTags {
+                point: TaggedPoint { path, tags },
+            },
+            None => ServiceResponse::Error {
+                code: "NOT_FOUND".to_string(),
+                message: format!("Point '{}' has no tags", path),
+            },
+        }
+    }
+
+    fn status_extra(&self) -> serde_json::Value {
+        let config = self.store.config();
+        serde_json::json!({
+            "point_count": self.store.point_count(),
+            "entity_count": config.entities.len(),
+            "rule_count": config.rules.len(),
+        })
+    }
+}
+
+// Handle common ServiceMsg
+impl Message<ServiceMsg> for TagActor {
+    type Reply = ServiceReply;
+
+    async fn handle(
+        &mut self,
+        msg: ServiceMsg,
+        _ctx: &mut Context<Self, Self::Reply>,
+    ) -> Self::Reply {
+        match msg {
+            ServiceMsg::Start => {
+                self.state.set_starting();
+                self.state.set_running();
+                tracing::info!("Tag service started ({} points)", self.store.point_count());
+                ServiceReply::Started
+            }
+
+            ServiceMsg::Stop => {
+                self.state.set_stopping();
+                self.state.set_stopped();
+                tracing::info!("Tag service stopped");
+                ServiceReply::Stopped
+            }
+
+            ServiceMsg::GetStatus => ServiceReply::Status {
+                id: self.id.clone(),
+                name: self.name.clone(),
+                state: self.state.state(),
+                uptime_secs: self.state.uptime_secs(),
+                extra: Some(self.status_extra()),
+            },
+
+            ServiceMsg::GetConfig => ServiceReply::Config {
+                config: serde_json::to_value(self.store.config()).unwrap_or_default(),
+            },
+
+            ServiceMsg::SetConfig { config } => {
+                if let Ok(new_config) = serde_json::from_value::<TagConfig>(config) {
+                    self.store.set_config(new_config);
+                    ServiceReply::ConfigSet
+                } else {
+                    ServiceReply::Failed("Invalid configuration format".to_string())
+                }
+            }
+
+            ServiceMsg::OnEvent { event } => {
+                if self.state.state() != ServiceState::Running {
+                    return ServiceReply::EventHandled;
+                }
+
+                if let Event::PointValueChanged { point, .. } = event {
+                    self.store.ensure_point(&point);
+                }
+                ServiceReply::EventHandled
+            }
+
+            ServiceMsg::HandleRequest { request, reply } => {
+                let response = match request {
+                    ServiceRequest::GetStatus => ServiceResponse::Status {
+                        id: self.id.clone(),
+                        name: self.name.clone(),
+                        state: self.state.state(),
+                        uptime_seconds: self.state.uptime_secs(),
+                        extra: Some(self.status_extra()),
+                    },
+
+                    ServiceRequest::GetConfig => ServiceResponse::Config {
+                        config: serde_json::to_value(self.store.config()).unwrap_or_default(),
+                    },
+
+                    ServiceRequest::QueryTags { filter } => match self.query(&filter) {
+                        Ok(points) => ServiceResponse::TaggedPoints { points },
+                        Err(e) => ServiceResponse::Error {
+                            code: "INVALID_FILTER".to_string(),
+                            message: e.to_string(),
+                        },
+                    },
+
+                    ServiceRequest::GetPointTags { path } => self.tagged_point(path),
+
+                    ServiceRequest::SetPointTags { path, tags } => {
+                        self.store.set_point_tags(&path, &tags);
+                        self.tagged_point(path)
+                    }
+
+                    _ => ServiceResponse::Error {
+                        code: "UNSUPPORTED".to_string(),
+                        message: "Request not supported by Tag service".to_string(),
+                    },
+                };
+
+                let _ = reply.send(response);
+                ServiceReply::RequestHandled
+            }
+        }
+    }
+}
+
+// Handle tag-specific messages
+impl Message<TagMsg> for TagActor {
+    type Reply = TagReply;
+
+    async fn handle(&mut self, msg: TagMsg, _ctx: &mut Context<Self, Self::Reply>) -> Self::Reply {
+        match msg {
+            TagMsg::Query { filter, reply } => {
+                let result = TagFilter::parse(&filter).map(|f| self.store.query(&f));
+                let _ = reply.send(result);
+            }
+
+            TagMsg::GetTags { path, reply } => {
+                let _ = reply.send(self.store.point_tags(&path));
+            }
+
+            TagMsg::SetTags { path, tags } => {
+                self.store.set_point_tags(&path, &tags);
+            }
+        }
+        TagReply::Done
+    }
+}
@@ -0,0 +1,1024 @@
+// Virtual Point Service - Software points owned by Neo
+//
+// Actor-based store for points that no device owns (weather data from plugins,
+// operator setpoints, flags written by blueprints). Writes are published as
+// PointValueChanged so history, alarms and blueprints treat them exactly like
+// device points.
+//
+// Each point has a 16-level command priority array: writers command it at a
+// priority (optionally for a limited time) and the point holds the highest
+// priority command, or its default once every command is released.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use kameo::actor::ActorRef;
+use kameo::message::{Context, Message};
+use kameo_actors::pubsub::Publish;
+use redb::{Database, ReadableTable, TableDefinition};
+use serde::{Deserialize, Serialize};
+use tokio::sync::oneshot;
+use wildmatch::WildMatch;
+
+use crate::actors::PubSubBroker;
+use crate::messages::Event;
+use crate::points::{CommandPriority, EngineeringUnit, PointCommand, PriorityArray};
+use crate::services::actor::{ServiceMsg, ServiceReply, ServiceStateTracker};
+use crate::services::messages::{PointSnapshot, ServiceRequest, ServiceResponse};
+use crate::types::{Error, PointQuality, PropertyValue, Result, ServiceState};
+
+// Table definition: key is the point path, value is the JSON-serialized PersistedValue
+const VIRTUAL_POINTS_TABLE: TableDefinition<&str, &[u8]> = TableDefinition::new("virtual_points");
+
+/// Prefix used for all virtual point paths
+pub const VIRTUAL_POINT_PREFIX: &str = "virtual/";
+
+/// Value type accepted by a virtual point
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum VirtualPointType {
+    /// Numeric value (stored as Real, Unsigned writes are coerced)
+    Analog,
+    /// Boolean value
+    Binary,
+    /// Multi-state value (Unsigned or Enumerated)
+    MultiState,
+    /// Any value type is accepted as-is
+    Any,
+}
+
+impl VirtualPointType {
+    /// Coerce a written value into this type, or None if it is incompatible
+    fn coerce(&self, value: &PropertyValue) -> Option<PropertyValue> {
+        match (self, value) {
+            (VirtualPointType::Any, v) => Some(v.clone()),
+            (VirtualPointType::Analog, PropertyValue::Real(v)) => Some(PropertyValue::Real(*v)),
+            (VirtualPointType::Analog, PropertyValue::Unsigned(v)) => {
+                Some(PropertyValue::Real(*v as f32))
+            }
+            (VirtualPointType::Binary, PropertyValue::Boolean(v)) => {
+                Some(PropertyValue::Boolean(*v))
+            }
+            (VirtualPointType::MultiState, PropertyValue::Unsigned(_))
+            | (VirtualPointType::MultiState, PropertyValue::Enumerated(_)) => Some(value.clone()),
+            _ => None,
+        }
+    }
+}
+
+/// Who is allowed to write a virtual point
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum WriteAccess {
+    /// Any source may write the point
+    #[default]
+    Any,
+    /// The point only holds its default value and rejects writes
+    ReadOnly,
+    /// Only sources matching one of these glob patterns may write (e.g., "weather-service")
+    Sources { sources: Vec<String> },
+}
+
+impl WriteAccess {
+    /// Check whether a write source is allowed
+    fn allows(&self, source: &str) -> bool {
+        match self {
+            WriteAccess::Any => true,
+            WriteAccess::ReadOnly => false,
+            WriteAccess::Sources { sources } => {
+                sources.iter().any(|p| WildMatch::new(p).matches(source))
+            }
+        }
+    }
+}
+
+/// Definition of a single virtual point
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VirtualPointConfig {
+    /// Point path (must start with "virtual/", e.g., "virtual/weather/outdoor_temp")
+    pub path: String,
+    /// Value type accepted by the point
+    pub point_type: VirtualPointType,
+    /// Engineering units (e.g., "degrees-fahrenheit" or "°F")
+    #[serde(default)]
+    pub units: Option<EngineeringUnit>,
+    /// Human-readable description
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Value the point holds while no command is active (relinquish default)
+    #[serde(default)]
+    pub default: Option<PropertyValue>,
+    /// Whether active commands survive restarts
+    #[serde(default)]
+    pub persist: bool,
+    /// Write permissions
+    #[serde(default)]
+    pub write_access: WriteAccess,
+}
+
+impl VirtualPointConfig {
+    /// Validate the configuration
+    fn validate(&self) -> Result<()> {
+        if !self.path.starts_with(VIRTUAL_POINT_PREFIX) || self.path.len() <= VIRTUAL_POINT_PREFIX.len()
+        {
+            return Err(Error::Config(format!(
+                "Virtual point path '{}' must start with '{}'",
+                self.path, VIRTUAL_POINT_PREFIX
+            )));
+        }
+        if let Some(default) = &self.default {
+            if self.point_type.coerce(default).is_none() {
+                return Err(Error::Config(format!(
+                    "Default value {:?} is not valid for {:?} point '{}'",
+                    default, self.point_type, self.path
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Configuration for the Virtual Point Service
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VirtualPointStoreConfig {
+    /// Path to the database file used for persisted points
+    pub db_path: String,
+    /// Points created when the service starts
+    #[serde(default)]
+    pub points: Vec<VirtualPointConfig>,
+}
+
+impl Default for VirtualPointStoreConfig {
+    fn default() -> Self {
+        Self {
+            db_path: "./data/virtual_points.redb".to_string(),
+            points: Vec::new(),
+        }
+    }
+}
+
+/// Persisted value format
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct PersistedValue {
+    /// Timestamp in microseconds since epoch
+    ts: i64,
+    /// Point value
+    v: PropertyValue,
+    /// Active commands (absent in values written before priority arrays)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    c: Option<Vec<PointCommand>>,
+}
+
+/// Runtime state of a virtual point
+#[derive(Debug, Clone)]
+struct VirtualPoint {
+    config: VirtualPointConfig,
+    value: PropertyValue,
+    quality: PointQuality,
+    last_update_utc: DateTime<Utc>,
+    commands: PriorityArray,
+}
+
+impl VirtualPoint {
+    fn new(config: VirtualPointConfig) -> Self {
+        let mut point = Self {
+            config,
+            value: PropertyValue::Null,
+            quality: PointQuality::Uncertain,
+            last_update_utc: Utc::now(),
+            commands: PriorityArray::new(),
+        };
+        point.refresh();
+        point
+    }
+
+    /// Value held while no command is active
+    fn relinquish_default(&self) -> PropertyValue {
+        self.config
+            .default
+            .as_ref()
+            .and_then(|d| self.config.point_type.coerce(d))
+            .unwrap_or(PropertyValue::Null)
+    }
+
+    /// Re-evaluate the priority array, returning whether the value changed
+    fn refresh(&mut self) -> bool {
+        let value = self.commands.effective_value(&self.relinquish_default());
+        let changed = self.value != value;
+        self.value = value;
+        self.quality = if self.value == PropertyValue::Null {
+            PointQuality::Uncertain
+        } else {
+            PointQuality::Good
+        };
+        changed
+    }
+
+    fn snapshot(&self) -> PointSnapshot {
+        PointSnapshot {
+            path: self.config.path.clone(),
+            value: self.value.clone(),
+            quality: self.quality,
+            timestamp: self.last_update_utc,
+            units: self.config.units,
+        }
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Virtual Point Actor Messages
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Virtual point messages (beyond common ServiceMsg)
+#[derive(Debug)]
+pub enum VirtualPointMsg {
+    /// Create a virtual point at runtime
+    CreatePoint {
+        config: VirtualPointConfig,
+        reply: oneshot::Sender<Result<()>>,
+    },
+    /// Delete a virtual point
+    DeletePoint {
+        path: String,
+        reply: oneshot::Sender<bool>,
+    },
+    /// Command a value at a priority (Null releases the priority)
+    WritePoint {
+        path: String,
+        value: PropertyValue,
+        source: String,
+        priority: CommandPriority,
+        /// Release the command automatically at this time
+        expires_at: Option<DateTime<Utc>>,
+        reply: oneshot::Sender<Result<()>>,
+    },
+    /// Get the active commands of a point, highest priority first
+    GetCommands {
+        path: String,
+        reply: oneshot::Sender<Option<Vec<PointCommand>>>,
+    },
+    /// Release commands that have expired (sent periodically by the expiry task)
+    ExpireCommands,
+    /// Read the current value of a point
+    ReadPoint {
+        path: String,
+        reply: oneshot::Sender<Option<PointSnapshot>>,
+    },
+    /// List all virtual points
+    ListPoints {
+        reply: oneshot::Sender<Vec<PointSnapshot>>,
+    },
+    /// Get all virtual point definitions
+    GetConfigs {
+        reply: oneshot::Sender<Vec<VirtualPointConfig>>,
+    },
+}
+
+/// Reply type for VirtualPointMsg
+#[derive(Debug, kameo::Reply)]
+pub enum VirtualPointReply {
+    /// Operation completed (result sent via oneshot)
+    Done,
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Virtual Point Actor
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Actor-based Virtual Point Service
+#[derive(kameo::Actor)]
+pub struct VirtualPointActor {
+    id: String,
+    name: String,
+    #[allow(dead_code)]
+    description: String,
+    state: ServiceStateTracker,
+    config: VirtualPointStoreConfig,
+    /// Virtual points keyed by path
+    points: HashMap<String, VirtualPoint>,
+    db: Option<Database>,
+    pubsub: Option<ActorRef<PubSubBroker>>,
+}
+
+impl VirtualPointActor {
+    /// Create a new VirtualPointActor that publishes changes to the given broker
+    pub fn new(config: VirtualPointStoreConfig, pubsub: ActorRef<PubSubBroker>) -> Self {
+        Self {
+            pubsub: Some(pubsub),
+            ..Self::detached(config)
+        }
+    }
+
+    /// Create a VirtualPointActor without an event bus (changes are not published)
+    pub fn detached(config: VirtualPointStoreConfig) -> Self {
+        Self {
+            id: "virtual_points".to_string(),
+            name: "Virtual Point Service".to_string(),
+            description: "Stores software points that are not owned by any device".to_string(),
+            state: ServiceStateTracker::new(),
+            config,
+            points: HashMap::new(),
+            db: None,
+            pubsub: None,
+        }
+    }
+
+    /// Start the service (internal implementation)
+    async fn do_start(&mut self) -> Result<()> {
+        self.state.set_starting();
+
+        if self.config.points.iter().any(|p| p.persist) {
+            self.open_db().inspect_err(|_| self.state.set_failed())?;
+        }
+
+        for config in self.config.points.clone() {
+            if let Err(e) = self.create_point(config.clone()).await {
+                tracing::warn!("Skipping virtual point '{}': {}", config.path, e);
+            }
+        }
+
+        self.state.set_running();
+        tracing::info!(
+            "Virtual point service started ({} points)",
+            self.points.len()
+        );
+        Ok(())
+    }
+
+    /// Stop the service (internal implementation)
+    fn do_stop(&mut self) {
+        self.state.set_stopping();
+        self.points.clear();
+        self.db = None;
+        self.state.set_stopped();
+        tracing::info!("Virtual point service stopped");
+    }
+
+    /// Open the persistence database if it is not open yet
+    fn open_db(&mut self) -> Result<()> {
+        if self.db.is_some() {
+            return Ok(());
+        }
+
+        let db_path = PathBuf::from(&self.config.db_path);
+        if let Some(parent) = db_path.parent() {
+            std::fs::create_dir_all(parent).map_err(Error::Io)?;
+        }
+
+        let db = Database::create(&self.config.db_path)
+            .map_err(|e| Error::Database(format!("Failed to open database: {}", e)))?;
+
+        let write_txn = db.begin_write().map_err(|e| Error::Database(e.to_string()))?;
+        write_txn
+            .open_table(VIRTUAL_POINTS_TABLE)
+            .map_err(|e| Error::Database(e.to_string()))?;
+        write_txn
+            .commit()
+            .map_err(|e| Error::Database(e.to_string()))?;
+
+        self.db = Some(db);
+        Ok(())
+    }
+
+    /// Load the persisted value of a point, if any
+    fn load_persisted(&self, path: &str) -> Result<Option<PersistedValue>> {
+        let Some(db) = &self.db else {
+            return Ok(None);
+        };
+
+        let read_txn = db.begin_read().map_err(|e| Error::Database(e.to_string()))?;
+        let table = read_txn
+            .open_table(VIRTUAL_POINTS_TABLE)
+            .map_err(|e| Error::Database(e.to_string()))?;
+
+        match table.get(path).map_err(|e| Error::Database(e.to_string()))? {
+            Some(bytes) => serde_json::from_slice(bytes.value())
+                .map(Some)
+                .map_err(|e| Error::Service(e.to_string())),
+            None => Ok(None),
+        }
+    }
+
+    /// Persist the current value of a point
+    fn persist(&self, point: &VirtualPoint) -> Result<()> {
+        let Some(db) = &self.db else {
+            return Ok(());
+        };
+
+        let stored = PersistedValue {
+            ts: point.last_update_utc.timestamp_micros(),
+            v: point.value.clone(),
+            c: Some(point.commands.commands()),
+        };
+        let bytes = serde_json::to_vec(&stored).map_err(|e| Error::Service(e.to_string()))?;
+
+        let write_txn = db.begin_write().map_err(|e| Error::Database(e.to_string()))?;
+        {
+            let mut table = write_txn
+                .open_table(VIRTUAL_POINTS_TABLE)
+                .map_err(|e| Error::Database(e.to_string()))?;
+            table
+                .insert(point.config.path.as_str(), bytes.as_slice())
+                .map_err(|e| Error::Database(e.to_string()))?;
+        }
+        write_txn
+            .commit()
+            .map_err(|e| Error::Database(e.to_string()))?;
+
+        Ok(())
+    }
+
+    /// Create a virtual point and publish its initial value
+    async fn create_point(&mut self, config: VirtualPointConfig) -> Result<()> {
+        config.validate()?;
+
+        if self.points.contains_key(&config.path) {
+            return Err(Error::Config(format!(
+                "Virtual point '{}' already exists",
+                config.path
+            )));
+        }
+
+        if config.persist {
+            self.open_db()?;
+        }
+
+        let mut point = VirtualPoint::new(config);
+
+        if point.config.persist {
+            if let Some(stored) = self.load_persisted(&point.config.path)? {
+                point.commands = match stored.c {
+                    Some(commands) => PriorityArray::from_commands(commands, Utc::now()),
+                    // Older stores only kept the last written value
+                    None => {
+                        let mut commands = PriorityArray::new();
+                        if let Some(value) = point.config.point_type.coerce(&stored.v) {
+                            commands.write(CommandPriority::DEFAULT, value, "restored", None);
+                        }
+                        commands
+                    }
+                };
+                point.refresh();
+                point.last_update_utc =
+                    DateTime::from_timestamp_micros(stored.ts).unwrap_or_else(Utc::now);
+            }
+        }
+
+        let path = point.config.path.clone();
+        tracing::debug!("Virtual point created: {} = {:?}", path, point.value);
+        self.publish(&point).await;
+        self.points.insert(path, point);
+
+        Ok(())
+    }
+
+    /// Command (or, with Null, release) a virtual point at a priority
+    async fn write_point(
+        &mut self,
+        path: &str,
+        value: PropertyValue,
+        source: &str,
+        priority: CommandPriority,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> Result<()> {
+        let point = self
+            .points
+            .get_mut(path)
+            .ok_or_else(|| Error::NotFound(format!("Virtual point '{}'", path)))?;
+
+        if !point.config.write_access.allows(source) {
+            return Err(Error::Service(format!(
+                "Source '{}' is not allowed to write virtual point '{}'",
+                source, path
+            )));
+        }
+
+        // Null releases the priority; the next active command (or the default) takes over
+        let value = if value == PropertyValue::Null {
+            value
+        } else {
+            point.config.point_type.coerce(&value).ok_or_else(|| {
+                Error::Service(format!(
+                    "Value {:?} is not valid for {:?} point '{}'",
+                    value, point.config.point_type, path
+                ))
+            })?
+        };
+
+        point.commands.write(priority, value, source, expires_at);
+        point.last_update_utc = Utc::now();
+        let changed = point.refresh();
+
+        let point = point.clone();
+        self.commit(&point, changed).await;
+        Ok(())
+    }
+
+    /// Release expired commands on all points, returning how many were released
+    async fn expire_commands(&mut self, now: DateTime<Utc>) -> usize {
+        let mut expired = Vec::new();
+        for point in self.points.values_mut() {
+            let released = point.commands.expire(now);
+            if released.is_empty() {
+                continue;
+            }
+            for command in &released {
+                tracing::info!(
+                    "Command on {} at priority {} from '{}' expired",
+                    point.config.path,
+                    command.priority,
+                    command.source
+                );
+            }
+            point.last_update_utc = now;
+            let changed = point.refresh();
+            expired.push((point.clone(), changed, released.len()));
+        }
+
+        let mut count = 0;
+        for (point, changed, released) in expired {
+            self.commit(&point, changed).await;
+            count += released;
+        }
+        count
+    }
+
+    /// Persist a point after its commands changed and publish its value if it changed
+    async fn commit(&self, point: &VirtualPoint, changed: bool) {
+        if point.config.persist {
+            if let Err(e) = self.persist(point) {
+                tracing::warn!("Failed to persist virtual point '{}': {}", point.config.path, e);
+            }
+        }
+
+        if changed {
+            self.publish(point).await;
+        }
+    }
+
+    /// Start a background task that releases expired commands every second
+    pub fn start_expiry_task(actor_ref: ActorRef<Self>) -> tokio::task::JoinHandle<()> {
+        let weak_ref = actor_ref.downgrade();
+
+        tokio::spawn(async move {
+            let mut tick = tokio::time::interval(Duration::from_secs(1));
+            tick.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+
+            loop {
+                tick.tick().await;
+                let Some(actor_ref) = weak_ref.upgrade() else {
+                    tracing::debug!("Virtual point expiry task exiting");
+                    break;
+                };
+                if let Err(e) = actor_ref.tell(VirtualPointMsg::ExpireCommands).await {
+                    tracing::warn!("Failed to expire virtual point commands: {}", e);
+                }
+            }
+        })
+    }
+
+    /// Delete a virtual point (its persisted value is kept)
+    fn delete_point(&mut self, path: &str) -> bool {
+        self.points.remove(path).is_some()
+    }
+
+    /// Publish a point value to PubSub
+    async fn publish(&self, point: &VirtualPoint) {
+        if let Some(pubsub) = &self.pubsub {
+            let event = Event::PointValueChanged {
+                point: point.config.path.clone(),
+                value: point.value.clone(),
+                quality: point.quality,
+                units: point.config.units,
+                timestamp: Instant::now(),
+                timestamp_utc: point.last_update_utc,
+            };
+            if let Err(e) = pubsub.tell(Publish(event)).await {
+                tracing::warn!("Failed to publish virtual point change: {}", e);
+            }
+        }
+    }
+
+    fn list_points(&self) -> Vec<PointSnapshot> {
+        let mut points: Vec<PointSnapshot> = self.points.values().map(|p| p.snapshot()).collect();
+        points.sort_by(|a, b| a.path.cmp(&b.path));
+        points
+    }
+
+    fn status_extra(&self) -> serde_json::Value {
+        serde_json::json!({
+            "point_count": self.points.len(),
+            "persisted_count": self.points.values().filter(|p| p.config.persist).count(),
+            "db_path": self.config.db_path,
+        })
+    }
+}
+
+// Handle common ServiceMsg
+impl Message<ServiceMsg> for VirtualPointActor {
+    type Reply = ServiceReply;
+
+    async fn handle(
+        &mut self,
+        msg: ServiceMsg,
+        _ctx: &mut Context<Self, Self::Reply>,
+    ) -> Self::Reply {
+        match msg {
+            ServiceMsg::Start => match self.do_start().await {
+                Ok(_) => ServiceReply::Started,
+                Err(e) => ServiceReply::Failed(e.to_string()),
+            },
+
+            ServiceMsg::Stop => {
+                self.do_stop();
+                ServiceReply::Stopped
+            }
+
+            ServiceMsg::GetStatus => ServiceReply::Status {
+                id: self.id.clone(),
+                name: self.name.clone(),
+                state: self.state.state(),
+                uptime_secs: self.state.uptime_secs(),
+                extra: Some(self.status_extra()),
+            },
+
+            ServiceMsg::GetConfig => ServiceReply::Config {
+                config: serde_json::to_value(&self.config).unwrap_or_default(),
+            },
+
+            ServiceMsg::SetConfig { config } => {
+                if let Ok(new_config) = serde_json::from_value::<VirtualPointStoreConfig>(config) {
+                    self.config = new_config;
+                    ServiceReply::ConfigSet
+                } else {
+                    ServiceReply::Failed("Invalid configuration format".to_string())
+                }
+            }
+
+            // Virtual points are written, not driven by events
+            ServiceMsg::OnEvent { .. } => ServiceReply::EventHandled,
+
+            ServiceMsg::HandleRequest { request, reply } => {
+                let response = match request {
+                    ServiceRequest::GetStatus => ServiceResponse::Status {
+                        id: self.id.clone(),
+                        name: self.name.clone(),
+                        state: self.state.state(),
+                        uptime_seconds: self.state.uptime_secs(),
+                        extra: Some(self.status_extra()),
+                    },
+
+                    ServiceRequest::GetConfig => ServiceResponse::Config {
+                        config: serde_json::to_value(&self.config).unwrap_or_default(),
+                    },
+
+                    ServiceRequest::ReadPoint { path } => match self.points.get(&path) {
+                        Some(point) => ServiceResponse::PointValue {
+                            point: point.snapshot(),
+                        },
+                        None => ServiceResponse::Error {
+                            code: "NOT_FOUND".to_string(),
+                            message: format!("Virtual point '{}' not found", path),
+                        },
+                    },
+
+                    ServiceRequest::WritePoint {
+                        path,
+                        value,
+                        source,
+                        priority,
+                        duration_secs,
+                    } => {
+                        let source = source.unwrap_or_else(|| "request".to_string());
+                        let expires_at = duration_secs
+                            .map(|secs| {
+                                i64::try_from(secs)
+                                    .ok()
+                                    .and_then(chrono::Duration::try_seconds)
+                                    .and_then(|duration| Utc::now().checked_add_signed(duration))
+                                    .ok_or(secs)
+                            })
+                            .transpose();
+                        match expires_at {
+                            Ok(expires_at) => {
+                                let result = match priority.map(CommandPriority::new).transpose() {
+                                    Ok(priority) => {
+                                        let priority = priority.unwrap_or_default();
+                                        self.write_point(
+                                            &path, value, &source, priority, expires_at,
+                                        )
+                                        .await
+                                    }
+                                    Err(e) => Err(e),
+                                };
+                                match result {
+                                    Ok(_) => ServiceResponse::PointWritten { path },
+                                    Err(e) => ServiceResponse::Error {
+                                        code: "WRITE_FAILED".to_string(),
+                                        message: e.to_string(),
+                                    },
+                                }
+                            }
+                            Err(secs) => ServiceResponse::Error {
+                                code: "INVALID_REQUEST".to_string(),
+                                message: format!("Duration {}s is too long", secs),
+                            },
+                        }
+                    }
+
+                    ServiceRequest::GetPointCommands { path } => match self.points.get(&path) {
+                        Some(point) => ServiceResponse::PointCommands {
+                            commands: point.commands.commands(),
+                            path,
+                        },
+                        None => ServiceResponse::Error {
+                            code: "NOT_FOUND".to_string(),
+                            message: format!("Virtual point '{}' not found", path),
+                        },
+                    },
+
+                    ServiceRequest::ListPoints { pattern } => {
+                        let points = self
+                            .list_points()
+                            .into_iter()
+                            .filter(|p| {
+                                pattern
+                                    .as_ref()
+                                    .map(|f| WildMatch::new(f).matches(&p.path))
+                                    .unwrap_or(true)
+                            })
+                            .collect();
+                        ServiceResponse::Points { points }
+                    }
+
+                    _ => ServiceResponse::Error {
+                        code: "UNSUPPORTED".to_string(),
+                        message: "Request not supported by Virtual Point service".to_string(),
+                    },
+                };
+
+                let _ = reply.send(response);
+                ServiceReply::RequestHandled
+            }
+        }
+    }
+}
+
+// Handle virtual point messages
+impl Message<VirtualPointMsg> for VirtualPointActor {
+    type Reply = VirtualPointReply;
+
+    async fn handle(
+        &mut self,
+        msg: VirtualPointMsg,
+        _ctx: &mut Context<Self, Self::Reply>,
+    ) -> Self::Reply {
+        match msg {
+            VirtualPointMsg::CreatePoint { config, reply } => {
+                let result = self.create_point(config).await;
+                let _ = reply.send(result);
+            }
+
+            VirtualPointMsg::DeletePoint { path, reply } => {
+                let _ = reply.send(self.delete_point(&path));
+            }
+
+            VirtualPointMsg::WritePoint {
+                path,
+                value,
+                source,
+                priority,
+                expires_at,
+                reply,
+            } => {
+                let result = self
+                    .write_point(&path, value, &source, priority, expires_at)
+                    .await;
+                let _ = reply.send(result);
+            }
+
+            VirtualPointMsg::GetCommands { path, reply } => {
+                let _ = reply.send(self.points.get(&path).map(|p| p.commands.commands()));
+            }
+
+            VirtualPointMsg::ExpireCommands => {
+                self.expire_commands(Utc::now()).await;
+            }
+
+            VirtualPointMsg::ReadPoint { path, reply } => {
+                let _ = reply.send(self.points.get(&path).map(|p| p.snapshot()));
+            }
+
+            VirtualPointMsg::ListPoints { reply } => {
+                let _ = reply.send(self.list_points());
+            }
+
+            VirtualPointMsg::GetConfigs { reply } => {
+                let configs = self.points.values().map(|p| p.config.clone()).collect();
+                let _ = reply.send(configs);
+            }
+        }
+        VirtualPointReply::Done
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Tests
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kameo::actor::Spawn;
+    use tempfile::tempdir;
+
+    fn outdoor_temp(persist: bool) -> VirtualPointConfig {
+        VirtualPointConfig {
+            path: "virtual/weather/outdoor_temp".to_string(),
+            point_type: VirtualPointType::Analog,
+            units: Some(EngineeringUnit::DegreesFahrenheit),
+            description: None,
+            default: Some(PropertyValue::Real(70.0)),
+            persist,
+            write_access: WriteAccess::Sources {
+                sources: vec!["weather-*".to_string()],
+            },
+        }
+    }
+
+    async fn read(actor: &ActorRef<VirtualPointActor>, path: &str) -> Option<PointSnapshot> {
+        let (reply_tx, reply_rx) = oneshot::channel();
+        let _ = actor
+            .ask(VirtualPointMsg::ReadPoint {
+                path: path.to_string(),
+                reply: reply_tx,
+            })
+            .await;
+        reply_rx.await.unwrap()
+    }
+
+    async fn write(
+        actor: &ActorRef<VirtualPointActor>,
+        value: PropertyValue,
+        source: &str,
+    ) -> Result<()> {
+        command(actor, value, source, CommandPriority::DEFAULT, None).await
+    }
+
+    async fn command(
+        actor: &ActorRef<VirtualPointActor>,
+        value: PropertyValue,
+        source: &str,
+        priority: CommandPriority,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> Result<()> {
+        let (reply_tx, reply_rx) = oneshot::channel();
+        let _ = actor
+            .ask(VirtualPointMsg::WritePoint {
+                path: "virtual/weather/outdoor_temp".to_string(),
+                value,
+                source: source.to_string(),
+                priority,
+                expires_at,
+                reply: reply_tx,
+            })
+            .await;
+        reply_rx.await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_virtual_point_write_access_and_default() {
+        let dir = tempdir().unwrap();
+        let config = VirtualPointStoreConfig {
+            db_path: dir.path().join("vp.redb").to_string_lossy().to_string(),
+            points: vec![outdoor_temp(false)],
+        };
+
+        let actor = VirtualPointActor::spawn(VirtualPointActor::detached(config));
+        let reply = actor.ask(ServiceMsg::Start).await.unwrap();
+        assert!(matches!(reply, ServiceReply::Started));
+
+        let point = read(&actor, "virtual/weather/outdoor_temp").await.unwrap();
+        assert_eq!(point.value, PropertyValue::Real(70.0));
+
+        // Allowed writer, Unsigned is coerced to Real
+        write(&actor, PropertyValue::Unsigned(55), "weather-service")
+            .await
+            .unwrap();
+        let point = read(&actor, "virtual/weather/outdoor_temp").await.unwrap();
+        assert_eq!(point.value, PropertyValue::Real(55.0));
+
+        // Disallowed writer and wrong type are rejected
+        assert!(write(&actor, PropertyValue::Real(1.0), "random-service")
+            .await
+            .is_err());
+        assert!(write(&actor, PropertyValue::Boolean(true), "weather-service")
+            .await
+            .is_err());
+
+        // Null reverts to the default
+        write(&actor, PropertyValue::Null, "weather-service")
+            .await
+            .unwrap();
+        let point = read(&actor, "virtual/weather/outdoor_temp").await.unwrap();
+        assert_eq!(point.value, PropertyValue::Real(70.0));
+    }
+
+    #[tokio::test]
+    async fn test_virtual_point_command_priority_and_expiry() {
+        let dir = tempdir().unwrap();
+        let mut point = outdoor_temp(false);
+        point.write_access = WriteAccess::Any;
+        let config = VirtualPointStoreConfig {
+            db_path: dir.path().join("vp.redb").to_string_lossy().to_string(),
+            points: vec![point],
+        };
+
+        let actor = VirtualPointActor::spawn(VirtualPointActor::detached(config));
+        let _ = actor.ask(ServiceMsg::Start).await.unwrap();
+
+        command(&actor, PropertyValue::Real(72.0), "schedule", CommandPriority::SCHEDULED, None)
+            .await
+            .unwrap();
+        command(
+            &actor,
+            PropertyValue::Real(68.0),
+            "user:alice",
+            CommandPriority::MANUAL_OPERATOR,
+            Some(Utc::now() - chrono::Duration::seconds(1)),
+        )
+        .await
+        .unwrap();
+
+        // Lower priority writes do not take control
+        write(&actor, PropertyValue::Real(80.0), "plugin").await.unwrap();
+        let point = read(&actor, "virtual/weather/outdoor_temp").await.unwrap();
+        assert_eq!(point.value, PropertyValue::Real(68.0));
+
+        let (reply_tx, reply_rx) = oneshot::channel();
+        let _ = actor
+            .ask(VirtualPointMsg::GetCommands {
+                path: "virtual/weather/outdoor_temp".to_string(),
+                reply: reply_tx,
+            })
+            .await;
+        let commands = reply_rx.await.unwrap().unwrap();
+        assert_eq!(commands.len(), 3);
+        assert_eq!(commands[0].source, "user:alice");
+
+        // The expired override releases, reverting to the schedule
+        let _ = actor.ask(VirtualPointMsg::ExpireCommands).await;
+        let point = read(&actor, "virtual/weather/outdoor_temp").await.unwrap();
+        assert_eq!(point.value, PropertyValue::Real(72.0));
+
+        // Releasing the schedule reverts to the priority 16 write
+        command(&actor, PropertyValue::Null, "schedule", CommandPriority::SCHEDULED, None)
+            .await
+            .unwrap();
+        let point = read(&actor, "virtual/weather/outdoor_temp").await.unwrap();
+        assert_eq!(point.value, PropertyValue::Real(80.0));
+
+        // Durations too long to represent are refused, not wrapped or panicked on
+        let (reply_tx, reply_rx) = oneshot::channel();
+        let _ = actor
+            .ask(ServiceMsg::HandleRequest {
+                request: ServiceRequest::WritePoint {
+                    path: "virtual/weather/outdoor_temp".to_string(),
+                    value: PropertyValue::Real(60.0),
+                    source: None,
+                    priority: Some(8),
+                    duration_secs: Some(u64::MAX),
+                },
+                reply: reply_tx,
+            })
+            .await;
+        assert!(matches!(
+            reply_rx.await.unwrap(),
+            ServiceResponse::Error { code, .. } if code == "INVALID_REQUEST"
+        ));
+        let point = read(&actor, "virtual/weather/outdoor_temp").await.unwrap();
+        assert_eq!(point.value, PropertyValue::Real(80.0));
+    }
+
+    #[tokio::test]
+    async fn test_virtual_point_persistence() {
+        let dir = tempdir().unwrap();
+        let config = VirtualPointStoreConfig {
+            db_path: dir.path().join("vp.redb").to_string_lossy().to_string(),
+            points: vec![outdoor_temp(true)],
+        };
+
+        let actor = VirtualPointActor::spawn(VirtualPointActor::detached(config.clone()));
+        let _ = actor.ask(ServiceMsg::Start).await.unwrap();
+        write(&actor, PropertyValue::Real(42.5), "weather-service")
+            .await
+            .unwrap();
+        let _ = actor.ask(ServiceMsg::Stop).await.unwrap();
+
+        let actor = VirtualPointActor::spawn(VirtualPointActor::detached(config));
+        let _ = actor.ask(ServiceMsg::Start).await.unwrap();
+        let point = read(&actor, "virtual/weather/outdoor_temp").await.unwrap();
+        assert_eq!(point.value, PropertyValue::Real(42.5));
+    }
+}
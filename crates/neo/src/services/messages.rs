@@ -1,10 +1,13 @@
 // Service request and response types with TypeScript generation
 
+use std::collections::HashMap;
+
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use ts_rs::TS;
 use uuid::Uuid;
 
+use crate::points::{EngineeringUnit, PointCommand, TagSet};
 use crate::types::{AlarmSeverity, PointQuality, PropertyValue, ServiceState};
 
 // ─────────────────────────────────────────────────────────────────────────────
@@ -31,6 +34,63 @@ pub enum ServiceRequest {
         config: serde_json::Value,
     },
 
+    // ─────────────────────────────────────────────────────────────────────
+    // Point requests
+    // ─────────────────────────────────────────────────────────────────────
+
+    /// Read the current value of a point
+    ReadPoint {
+        path: String,
+    },
+
+    /// Write a value to a point
+    WritePoint {
+        path: String,
+        #[ts(type = "unknown")]
+        value: PropertyValue,
+        /// Identifies the writer (plugin, blueprint, user) for write permissions
+        #[serde(default)]
+        source: Option<String>,
+        /// Command priority 1-16 (default 16); a Null value releases this priority
+        #[serde(default)]
+        priority: Option<u8>,
+        /// Release the command automatically after this many seconds
+        #[serde(default)]
+        duration_secs: Option<u64>,
+    },
+
+    /// Get the active commands in a point's priority array
+    GetPointCommands {
+        path: String,
+    },
+
+    /// List points, optionally filtered by a glob pattern
+    ListPoints {
+        #[serde(default)]
+        pattern: Option<String>,
+    },
+
+    // ─────────────────────────────────────────────────────────────────────
+    // Tag service requests
+    // ─────────────────────────────────────────────────────────────────────
+
+    /// Find points matching a tag filter (e.g., "zone and air and temp and sensor")
+    QueryTags {
+        filter: String,
+    },
+
+    /// Get the tags of a point
+    GetPointTags {
+        path: String,
+    },
+
+    /// Add or replace tags on a point
+    SetPointTags {
+        path: String,
+        #[ts(type = "Record<string, unknown>")]
+        tags: TagSet,
+    },
+
     // ─────────────────────────────────────────────────────────────────────
     // History service requests
     // ─────────────────────────────────────────────────────────────────────
@@ -42,6 +102,116 @@ pub enum ServiceRequest {
         end: DateTime<Utc>,
         #[serde(default)]
         limit: Option<u32>,
+        /// Convert samples to these units (e.g., "degrees-celsius")
+        #[serde(default)]
+        #[ts(type = "string | null")]
+        units: Option<EngineeringUnit>,
+        /// Downsample to at most this many samples, preserving the trend shape (LTTB)
+        #[serde(default)]
+        downsample: Option<u32>,
+    },
+
+    /// Aggregate historical data for a point into fixed time buckets
+    AggregateHistory {
+        point: String,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        /// Bucket width (e.g., "1m", "15m", "1h", "1d")
+        interval: String,
+        /// Aggregates to compute (all when empty)
+        #[serde(default)]
+        functions: Vec<Aggregation>,
+        /// Convert values to these units before aggregating
+        #[serde(default)]
+        #[ts(type = "string | null")]
+        units: Option<EngineeringUnit>,
+    },
+
+    /// Query historical data for all points matching a tag filter
+    QueryHistoryByTags {
+        filter: String,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        #[serde(default)]
+        limit: Option<u32>,
+        /// Convert samples to these units (e.g., "degrees-celsius")
+        #[serde(default)]
+        #[ts(type = "string | null")]
+        units: Option<EngineeringUnit>,
+    },
+
+    /// Query several points aligned on a common time grid, one column per point
+    QueryHistoryTable {
+        points: Vec<String>,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        /// Row spacing (e.g., "15m"); a row at every sample timestamp when omitted
+        #[serde(default)]
+        interval: Option<String>,
+        #[serde(default)]
+        interpolation: Interpolation,
+        /// Convert values to these units (e.g., "degrees-celsius")
+        #[serde(default)]
+        #[ts(type = "string | null")]
+        units: Option<EngineeringUnit>,
+        #[serde(default)]
+        limit: Option<u32>,
+    },
+
+    /// Export several points, aligned as in QueryHistoryTable, to a file in
+    /// the history export directory
+    ExportHistory {
+        points: Vec<String>,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        #[serde(default)]
+        interval: Option<String>,
+        #[serde(default)]
+        interpolation: Interpolation,
+        #[serde(default)]
+        #[ts(type = "string | null")]
+        units: Option<EngineeringUnit>,
+        format: ExportFormat,
+        /// File name within the export directory (e.g., "ahu-1-march.csv")
+        file_name: String,
+    },
+
+    /// Import samples from a file in the history import directory, in the
+    /// background (see GetImportProgress)
+    ImportHistory {
+        /// File name within the import directory (e.g., "old-bas-trends.csv")
+        file_name: String,
+        format: ImportFormat,
+        /// Source point names to point paths (e.g., "AHU1.SAT" -> "ahu-1/sat")
+        #[serde(default)]
+        point_map: HashMap<String, String>,
+        /// Skip rows for points not in the point map
+        #[serde(default)]
+        mapped_only: bool,
+        /// IANA time zone of timestamps without an offset (e.g., "America/Chicago");
+        /// UTC when omitted
+        #[serde(default)]
+        timezone: Option<String>,
+        /// strftime format of timestamps (e.g., "%d.%m.%Y %H:%M"); common
+        /// layouts are recognized when omitted
+        #[serde(default)]
+        timestamp_format: Option<String>,
+    },
+
+    /// Get the progress of a history import
+    GetImportProgress {
+        import_id: Uuid,
+    },
+
+    /// Report gaps, bad-quality periods and coverage of points' history
+    GetHistoryGaps {
+        points: Vec<String>,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        /// Longest expected time between samples (e.g., "15m"); taken from
+        /// each point's logging policy when omitted
+        #[serde(default)]
+        expected_interval: Option<String>,
     },
 
     // ─────────────────────────────────────────────────────────────────────
@@ -51,19 +221,55 @@ pub enum ServiceRequest {
     /// Get all currently active alarms
     GetActiveAlarms,
 
-    /// Acknowledge an alarm
+    /// Acknowledge an alarm (some severities require a comment)
     AcknowledgeAlarm {
         alarm_id: Uuid,
         #[serde(default)]
+        acknowledged_by: Option<String>,
+        #[serde(default)]
+        comment: Option<String>,
+    },
+
+    /// Acknowledge every active alarm matching a filter
+    AcknowledgeAlarms {
+        filter: AlarmFilter,
+        #[serde(default)]
+        acknowledged_by: Option<String>,
+        #[serde(default)]
         comment: Option<String>,
     },
 
+    /// Add an operator comment to an alarm's timeline
+    CommentAlarm {
+        alarm_id: Uuid,
+        comment: String,
+        #[serde(default)]
+        author: Option<String>,
+    },
+
+    /// Get the timeline of an alarm (raised, acknowledged, commented, cleared)
+    GetAlarmTimeline {
+        alarm_id: Uuid,
+    },
+
     /// Get alarm history
     GetAlarmHistory {
         start: DateTime<Utc>,
         end: DateTime<Utc>,
         #[serde(default)]
         source_filter: Option<String>,
+        /// Alarms per page (newest first)
+        #[serde(default)]
+        limit: Option<u32>,
+        /// Continue after this alarm (the `next_cursor` of the previous page)
+        #[serde(default)]
+        cursor: Option<Uuid>,
+    },
+
+    /// Get operator alarm KPIs (ISA-18.2 metrics) over a time range
+    GetAlarmKpis {
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
     },
 
     // ─────────────────────────────────────────────────────────────────────
@@ -132,6 +338,45 @@ pub enum ServiceResponse {
     /// Generic success response
     Ok,
 
+    // ─────────────────────────────────────────────────────────────────────
+    // Point responses
+    // ─────────────────────────────────────────────────────────────────────
+
+    /// Current value of a single point
+    PointValue {
+        point: PointSnapshot,
+    },
+
+    /// Point was written
+    PointWritten {
+        path: String,
+    },
+
+    /// Active commands of a point, highest priority first
+    PointCommands {
+        path: String,
+        commands: Vec<PointCommand>,
+    },
+
+    /// List of points
+    Points {
+        points: Vec<PointSnapshot>,
+    },
+
+    // ─────────────────────────────────────────────────────────────────────
+    // Tag responses
+    // ─────────────────────────────────────────────────────────────────────
+
+    /// Tags of a single point
+    PointTags {
+        point: TaggedPoint,
+    },
+
+    /// Points matching a tag filter
+    TaggedPoints {
+        points: Vec<TaggedPoint>,
+    },
+
     // ─────────────────────────────────────────────────────────────────────
     // History responses
     // ─────────────────────────────────────────────────────────────────────
@@ -141,6 +386,37 @@ pub enum ServiceResponse {
         samples: Vec<HistorySample>,
     },
 
+    /// Historical data for several points
+    HistorySeries {
+        series: Vec<PointHistory>,
+    },
+
+    /// Aggregated historical data, one entry per non-empty bucket
+    HistoryBuckets {
+        buckets: Vec<HistoryBucket>,
+    },
+
+    /// Several points aligned on a common time grid
+    HistoryTable {
+        table: HistoryTable,
+    },
+
+    /// History export file written
+    HistoryExported {
+        path: String,
+        rows: u64,
+    },
+
+    /// Progress of a history import
+    ImportProgress {
+        progress: ImportProgress,
+    },
+
+    /// History gap reports, one per point
+    HistoryGaps {
+        reports: Vec<GapReport>,
+    },
+
     // ─────────────────────────────────────────────────────────────────────
     // Alarm responses
     // ─────────────────────────────────────────────────────────────────────
@@ -153,6 +429,8 @@ pub enum ServiceResponse {
     /// Alarm history
     AlarmHistory {
         alarms: Vec<Alarm>,
+        /// Cursor for the next page, when more alarms match
+        next_cursor: Option<Uuid>,
     },
 
     /// Alarm was acknowledged
@@ -160,6 +438,29 @@ pub enum ServiceResponse {
         alarm_id: Uuid,
     },
 
+    /// Alarms matching a filter were acknowledged
+    AlarmsAcknowledged {
+        acknowledged: Vec<Uuid>,
+        /// Matching alarms left unacknowledged, as they require a comment
+        skipped: Vec<Uuid>,
+    },
+
+    /// Comment was added to an alarm
+    AlarmCommented {
+        alarm_id: Uuid,
+    },
+
+    /// Timeline of an alarm, oldest first
+    AlarmTimeline {
+        alarm_id: Uuid,
+        entries: Vec<AlarmTimelineEntry>,
+    },
+
+    /// Alarm KPIs
+    AlarmKpis {
+        kpis: AlarmKpis,
+    },
+
     // ─────────────────────────────────────────────────────────────────────
     // Scheduler responses
     // ─────────────────────────────────────────────────────────────────────
@@ -204,6 +505,37 @@ pub enum ServiceResponse {
 // Supporting Types
 // ─────────────────────────────────────────────────────────────────────────────
 
+/// Current value of a point
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+pub struct PointSnapshot {
+    pub path: String,
+    #[ts(type = "unknown")]
+    pub value: PropertyValue,
+    pub quality: PointQuality,
+    pub timestamp: DateTime<Utc>,
+    #[serde(default)]
+    #[ts(type = "string | null")]
+    pub units: Option<EngineeringUnit>,
+}
+
+/// A point and its semantic tags
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+pub struct TaggedPoint {
+    pub path: String,
+    #[ts(type = "Record<string, unknown>")]
+    pub tags: TagSet,
+}
+
+/// Historical samples for one point
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+pub struct PointHistory {
+    pub point: String,
+    pub samples: Vec<HistorySample>,
+}
+
 /// A single historical data sample
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
 #[ts(export, export_to = "bindings/")]
@@ -212,6 +544,193 @@ pub struct HistorySample {
     #[ts(type = "unknown")]
     pub value: PropertyValue,
     pub quality: PointQuality,
+    #[serde(default)]
+    #[ts(type = "string | null")]
+    pub units: Option<EngineeringUnit>,
+}
+
+/// An item of a history subscription: stored samples first, then live ones
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+#[serde(tag = "type")]
+pub enum HistoryStreamItem {
+    /// Stored samples of a point, oldest first
+    Backfill {
+        point: String,
+        samples: Vec<HistorySample>,
+    },
+    /// All stored samples have been sent; samples from here on are live
+    BackfillComplete { live_from: DateTime<Utc> },
+    /// A sample recorded after the subscription started
+    Live { point: String, sample: HistorySample },
+}
+
+/// Aggregate function for bucketed history queries
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+#[serde(rename_all = "snake_case")]
+pub enum Aggregation {
+    Min,
+    Max,
+    Avg,
+    First,
+    Last,
+    Count,
+    Sum,
+    /// Average weighted by how long each value was held
+    TimeWeightedAvg,
+}
+
+/// Aggregated samples for one time bucket
+///
+/// Only the requested aggregates are set; numeric samples with Bad quality
+/// are excluded.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+pub struct HistoryBucket {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    /// Number of samples in the bucket
+    pub count: u64,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    pub avg: Option<f64>,
+    pub first: Option<f64>,
+    pub last: Option<f64>,
+    pub sum: Option<f64>,
+    pub time_weighted_avg: Option<f64>,
+    #[serde(default)]
+    #[ts(type = "string | null")]
+    pub units: Option<EngineeringUnit>,
+}
+
+/// How aligned history fills rows between samples
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+#[serde(rename_all = "snake_case")]
+pub enum Interpolation {
+    /// Hold the last sample at or before the row (step)
+    #[default]
+    Previous,
+    /// Interpolate linearly between the samples either side of the row
+    Linear,
+    /// Only samples exactly at the row's timestamp
+    None,
+}
+
+/// History export file format
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+#[serde(rename_all = "snake_case")]
+pub enum ExportFormat {
+    Csv,
+    Parquet,
+}
+
+/// History import file format
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+#[serde(rename_all = "snake_case")]
+pub enum ImportFormat {
+    /// Header row with timestamp, point, value and optional quality and units columns
+    Csv,
+    /// One JSON object per line with the same fields
+    JsonLines,
+}
+
+/// State of a history import
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+#[serde(rename_all = "snake_case")]
+pub enum ImportState {
+    Running,
+    Completed,
+    Failed,
+}
+
+/// Progress of a history import
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+pub struct ImportProgress {
+    pub import_id: Uuid,
+    pub file_name: String,
+    pub state: ImportState,
+    pub started_at: DateTime<Utc>,
+    #[serde(default)]
+    pub finished_at: Option<DateTime<Utc>>,
+    /// File size, and how much of it has been read
+    pub total_bytes: u64,
+    pub bytes_read: u64,
+    /// Data rows read
+    pub rows_read: u64,
+    /// Samples stored
+    pub imported: u64,
+    /// Samples already stored for the same point and timestamp
+    pub duplicates: u64,
+    /// Rows that could not be parsed, or whose point is not mapped
+    pub skipped: u64,
+    /// The first row errors (e.g., "line 12: Invalid value 'n/a'")
+    pub errors: Vec<String>,
+    /// Why the import stopped, when it failed
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+/// Several points aligned on a common time grid
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+pub struct HistoryTable {
+    /// Column order of each row's values
+    pub points: Vec<String>,
+    pub rows: Vec<HistoryRow>,
+}
+
+/// One row of aligned history (None where a point has no value)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+pub struct HistoryRow {
+    pub timestamp: DateTime<Utc>,
+    pub values: Vec<Option<f64>>,
+}
+
+/// Gaps and data quality of one point's history over a time range
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+pub struct GapReport {
+    pub point: String,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    /// Longest expected time between samples
+    pub expected_interval_secs: u64,
+    /// Samples in the range
+    pub samples: u64,
+    /// Periods longer than the expected interval without a sample
+    pub gaps: Vec<HistoryGap>,
+    /// Periods of Bad or Stale samples
+    pub bad_quality: Vec<QualityPeriod>,
+    /// Share of the range backed by good samples (0-100)
+    pub coverage_percent: f64,
+}
+
+/// A period without history samples
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+pub struct HistoryGap {
+    /// Last sample before the gap (or the range start)
+    pub start: DateTime<Utc>,
+    /// First sample after the gap (or the range end)
+    pub end: DateTime<Utc>,
+}
+
+/// A run of history samples of one bad quality
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+pub struct QualityPeriod {
+    pub start: DateTime<Utc>,
+    /// Next sample of a different quality (or the range end)
+    pub end: DateTime<Utc>,
+    pub quality: PointQuality,
+    pub samples: u64,
 }
 
 /// An alarm instance
@@ -219,6 +738,9 @@ pub struct HistorySample {
 #[ts(export, export_to = "bindings/")]
 pub struct Alarm {
     pub id: Uuid,
+    /// Alarm configuration that raised the alarm
+    #[serde(default)]
+    pub config_id: Option<Uuid>,
     pub source: String,
     pub message: String,
     pub severity: AlarmSeverity,
@@ -228,11 +750,24 @@ pub struct Alarm {
     pub acknowledged_at: Option<DateTime<Utc>>,
     #[serde(default)]
     pub acknowledged_by: Option<String>,
+    /// Comment given when the alarm was acknowledged
+    #[serde(default)]
+    pub ack_comment: Option<String>,
+    /// When the condition returned to normal while the alarm stays active
+    /// (a latching alarm awaiting acknowledgement)
+    #[serde(default)]
+    pub returned_to_normal_at: Option<DateTime<Utc>>,
     #[serde(default)]
     pub cleared_at: Option<DateTime<Utc>>,
     #[serde(default)]
     #[ts(type = "unknown")]
     pub value_at_trigger: Option<PropertyValue>,
+    /// Alarm flood the alarm was raised in, if any
+    #[serde(default)]
+    pub flood_id: Option<Uuid>,
+    /// Whether the alarm was the first of its flood (the likely root cause)
+    #[serde(default)]
+    pub first_out: bool,
 }
 
 /// Alarm lifecycle state
@@ -253,6 +788,97 @@ impl Default for AlarmState {
     }
 }
 
+/// Which active alarms a bulk operation applies to (every field that is set
+/// must match)
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+pub struct AlarmFilter {
+    /// Glob pattern of alarm sources (e.g., "net1/AHU-1/*")
+    #[serde(default)]
+    pub source_pattern: Option<String>,
+    /// Only alarms of this severity or higher
+    #[serde(default)]
+    pub min_severity: Option<AlarmSeverity>,
+    /// Only alarms raised by this alarm configuration
+    #[serde(default)]
+    pub config_id: Option<Uuid>,
+}
+
+/// A step in an alarm's lifecycle, or an operator comment
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+pub struct AlarmTimelineEntry {
+    pub at: DateTime<Utc>,
+    pub kind: AlarmTimelineKind,
+    /// Operator who acknowledged or commented
+    #[serde(default)]
+    pub user: Option<String>,
+    #[serde(default)]
+    pub comment: Option<String>,
+}
+
+/// Kind of alarm timeline entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+pub enum AlarmTimelineKind {
+    Raised,
+    Acknowledged,
+    Commented,
+    /// The condition is no longer met
+    ReturnedToNormal,
+    /// The alarm left the active list
+    Cleared,
+    /// The condition was met again before a latching alarm was acknowledged
+    ReRaised,
+}
+
+/// Operator alarm KPIs over a time range, following ISA-18.2
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+pub struct AlarmKpis {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    /// Alarms raised in the range
+    pub total_alarms: u32,
+    pub alarms_per_hour: f64,
+    /// Most frequent alarms ("bad actors"), most frequent first (up to 10)
+    pub top_alarms: Vec<AlarmCount>,
+    /// Alarms raised 3 or more times within 60 seconds
+    pub chattering_alarms: Vec<AlarmCount>,
+    /// Alarms active for more than 24 hours (as of now)
+    pub standing_alarms: Vec<Alarm>,
+    /// Mean seconds from raise to acknowledgement, over the alarms acknowledged
+    pub mean_time_to_acknowledge_secs: Option<f64>,
+    /// Alarm floods with alarms raised in the range
+    pub floods: Vec<AlarmFlood>,
+}
+
+/// How often an alarm (configuration and source) was raised
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+pub struct AlarmCount {
+    pub config_id: Option<Uuid>,
+    pub source: String,
+    /// Message of the latest occurrence
+    pub message: String,
+    pub count: u32,
+}
+
+/// A burst of alarms (more than the flood threshold within its window)
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "bindings/")]
+pub struct AlarmFlood {
+    pub id: Uuid,
+    /// When the first alarm of the flood was raised
+    pub started_at: DateTime<Utc>,
+    /// When the last alarm of the flood was raised
+    pub last_alarm_at: DateTime<Utc>,
+    pub alarm_count: u32,
+    /// First alarm of the flood (the likely root cause)
+    pub first_out: Uuid,
+    pub first_out_source: String,
+}
+
 /// A schedule definition
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
 #[ts(export, export_to = "bindings/")]
@@ -322,9 +948,32 @@ mod type_export {
 
         ServiceRequest::export().expect("Failed to export ServiceRequest");
         ServiceResponse::export().expect("Failed to export ServiceResponse");
+        PointSnapshot::export().expect("Failed to export PointSnapshot");
+        PointCommand::export().expect("Failed to export PointCommand");
+        TaggedPoint::export().expect("Failed to export TaggedPoint");
+        PointHistory::export().expect("Failed to export PointHistory");
         HistorySample::export().expect("Failed to export HistorySample");
+        HistoryStreamItem::export().expect("Failed to export HistoryStreamItem");
+        HistoryBucket::export().expect("Failed to export HistoryBucket");
+        Aggregation::export().expect("Failed to export Aggregation");
+        Interpolation::export().expect("Failed to export Interpolation");
+        ExportFormat::export().expect("Failed to export ExportFormat");
+        HistoryTable::export().expect("Failed to export HistoryTable");
+        HistoryRow::export().expect("Failed to export HistoryRow");
+        ImportFormat::export().expect("Failed to export ImportFormat");
+        ImportState::export().expect("Failed to export ImportState");
+        ImportProgress::export().expect("Failed to export ImportProgress");
+        GapReport::export().expect("Failed to export GapReport");
+        HistoryGap::export().expect("Failed to export HistoryGap");
+        QualityPeriod::export().expect("Failed to export QualityPeriod");
         Alarm::export().expect("Failed to export Alarm");
         AlarmState::export().expect("Failed to export AlarmState");
+        AlarmFilter::export().expect("Failed to export AlarmFilter");
+        AlarmTimelineEntry::export().expect("Failed to export AlarmTimelineEntry");
+        AlarmTimelineKind::export().expect("Failed to export AlarmTimelineKind");
+        AlarmKpis::export().expect("Failed to export AlarmKpis");
+        AlarmCount::export().expect("Failed to export AlarmCount");
+        AlarmFlood::export().expect("Failed to export AlarmFlood");
         Schedule::export().expect("Failed to export Schedule");
         ScheduleEntry::export().expect("Failed to export ScheduleEntry");
         ScheduleException::export().expect("Failed to export ScheduleException");
@@ -18,14 +18,33 @@ pub use actor::{
 
 // Re-exports - Common types
 pub use crate::types::ServiceState;
-pub use messages::{ServiceRequest, ServiceResponse};
+pub use messages::{
+    Aggregation, ExportFormat, GapReport, HistoryBucket, HistoryGap, HistoryRow, HistorySample,
+    HistoryStreamItem, HistoryTable, ImportFormat, ImportProgress, ImportState, Interpolation,
+    PointHistory, PointSnapshot, QualityPeriod, ServiceRequest, ServiceResponse, TaggedPoint,
+};
 
 // Re-exports - Registry
 pub use registry::{RegistryMsg, RegistryReply, ServiceInfo, ServiceRegistration, ServiceRegistry};
 
 // Re-exports - Built-in services
-pub use builtin::{AlarmActor, AlarmCondition, AlarmConfig, AlarmMsg, AlarmReply};
-pub use builtin::{HistoryActor, HistoryConfig, HistoryMsg, HistoryReply};
+pub use builtin::{
+    AckPolicy, AlarmActor, AlarmCondition, AlarmConfig, AlarmMsg, AlarmReply, DeviceAlarmConfig,
+    NotificationConfig,
+};
+pub use builtin::{
+    BackendConfig, BackendTarget, Deadband, HistoryActor, HistoryConfig, HistoryMsg, HistoryReply,
+    LoggingMode, LoggingPolicy, PurgeStats, RetentionOverride, RollupConfig, WriteStats,
+};
+pub use builtin::{
+    CalculatedPointActor, CalculatedPointConfig, CalculatedPointMsg, CalculatedPointReply,
+    CalculatedPointsConfig,
+};
+pub use builtin::{TagActor, TagMsg, TagReply};
+pub use builtin::{
+    VirtualPointActor, VirtualPointConfig, VirtualPointMsg, VirtualPointReply,
+    VirtualPointStoreConfig, VirtualPointType, WriteAccess,
+};
 
 // Re-exports - Plugin services
 pub use plugin::{
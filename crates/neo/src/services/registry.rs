@@ -267,10 +267,14 @@ impl ServiceRegistry {
         match event {
             Event::PointValueChanged { .. } => "PointValueChanged".to_string(),
             Event::AlarmRaised { .. } => "AlarmRaised".to_string(),
+            Event::AlarmAcknowledged { .. } => "AlarmAcknowledged".to_string(),
+            Event::AlarmReturnedToNormal { .. } => "AlarmReturnedToNormal".to_string(),
             Event::AlarmCleared { .. } => "AlarmCleared".to_string(),
             Event::DeviceStatusChanged { .. } => "DeviceStatusChanged".to_string(),
             Event::DeviceDiscovered { .. } => "DeviceDiscovered".to_string(),
             Event::ServiceStateChanged { .. } => "ServiceStateChanged".to_string(),
+            Event::HistoryStale { .. } => "HistoryStale".to_string(),
+            Event::HistoryResumed { .. } => "HistoryResumed".to_string(),
             Event::Custom { event_type, .. } => event_type.clone(),
         }
     }
@@ -3,6 +3,8 @@ use serde::{Deserialize, Serialize};
 use std::time::Instant;
 use ts_rs::TS;
 
+use crate::points::{EngineeringUnit, TagSet};
+
 // Re-export BACnet types from the library
 pub use bacnet::{BacnetError, ObjectIdentifier, ObjectType, PropertyIdentifier, PropertyValue};
 
@@ -29,8 +31,12 @@ pub struct BACnetPoint {
     // Metadata (discovered from device)
     pub object_name: Option<String>,
     pub description: Option<String>,
-    pub units: Option<String>,
+    pub units: Option<EngineeringUnit>,
     pub cov_increment: Option<f32>,
+
+    // Semantic tags (resolved from the tag store)
+    #[serde(default, skip_serializing_if = "TagSet::is_empty")]
+    pub tags: TagSet,
 }
 
 impl BACnetPoint {
@@ -45,6 +51,7 @@ impl BACnetPoint {
             description: None,
             units: None,
             cov_increment: None,
+            tags: TagSet::default(),
         }
     }
 }